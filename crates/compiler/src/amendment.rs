@@ -0,0 +1,193 @@
+//! Quote amendments
+//!
+//! Compiles a maker's English amendment ("extend expiry to 30 minutes and
+//! restrict to taker_bob") into a [`ConstraintDiff`] against the quote's
+//! current constraints, so an open quote can change terms without being
+//! cancelled and reposted under a new ID. Prices and sizes can't be
+//! amended: they fix the Local Law's debit and credit limits.
+
+use rfq_models::{percent_to_bps, QuoteConstraints};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{CompilerError, ConstraintError};
+
+/// Amended terms extracted by the LLM (None = unchanged)
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ParsedAmendment {
+    /// New expiry, in minutes from now
+    #[serde(default)]
+    pub expiry_minutes: Option<u64>,
+    /// The complete new taker allowlist (empty = any taker)
+    #[serde(default)]
+    pub allowed_takers: Option<Vec<String>>,
+    /// The complete new feed source allowlist
+    #[serde(default)]
+    pub allowed_sources: Option<Vec<String>>,
+    #[serde(default)]
+    pub max_staleness_seconds: Option<u64>,
+    #[serde(default)]
+    pub quorum_required: Option<u32>,
+    #[serde(default)]
+    pub quorum_tolerance_percent: Option<f64>,
+    #[serde(default)]
+    pub max_fills: Option<u32>,
+    #[serde(default)]
+    pub max_fills_per_taker: Option<u32>,
+    /// Questions for the maker when the amendment is vague
+    #[serde(default)]
+    pub clarifications: Vec<String>,
+}
+
+/// Constraint fields an amendment can change
+const AMENDABLE_FIELDS: [&str; 8] = [
+    "expiry_timestamp",
+    "allowed_takers",
+    "allowed_sources",
+    "max_staleness_secs",
+    "quorum_count",
+    "quorum_tolerance_bps",
+    "max_fills",
+    "max_fills_per_taker",
+];
+
+/// Changes to a quote's constraints (None = unchanged)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ConstraintDiff {
+    pub expiry_timestamp: Option<u64>,
+    pub allowed_takers: Option<Vec<String>>,
+    pub allowed_sources: Option<Vec<String>>,
+    pub max_staleness_secs: Option<u64>,
+    pub quorum_count: Option<u32>,
+    pub quorum_tolerance_bps: Option<u32>,
+    pub max_fills: Option<u32>,
+    pub max_fills_per_taker: Option<u32>,
+}
+
+impl ConstraintDiff {
+    /// Check if the diff changes nothing
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Apply the changes to `constraints`
+    pub fn apply(&self, constraints: &mut QuoteConstraints) {
+        if let Some(expiry) = self.expiry_timestamp {
+            constraints.expiry_timestamp = expiry;
+        }
+        if let Some(takers) = &self.allowed_takers {
+            constraints.allowed_takers = takers.clone();
+        }
+        if let Some(sources) = &self.allowed_sources {
+            constraints.allowed_sources = sources.clone();
+        }
+        if let Some(staleness) = self.max_staleness_secs {
+            constraints.max_staleness_secs = staleness;
+        }
+        if let Some(quorum) = self.quorum_count {
+            constraints.quorum_count = quorum;
+        }
+        if let Some(tolerance) = self.quorum_tolerance_bps {
+            constraints.quorum_tolerance_bps = tolerance;
+        }
+        if let Some(max_fills) = self.max_fills {
+            constraints.max_fills = max_fills;
+        }
+        if let Some(per_taker) = self.max_fills_per_taker {
+            constraints.max_fills_per_taker = Some(per_taker);
+        }
+    }
+
+    /// The changed fields, with their values before and after
+    pub fn changes(
+        &self,
+        original: &QuoteConstraints,
+    ) -> Vec<(&'static str, serde_json::Value, serde_json::Value)> {
+        let mut amended = original.clone();
+        self.apply(&mut amended);
+        let (before, after) = (json(original), json(&amended));
+        AMENDABLE_FIELDS
+            .iter()
+            .filter(|field| before[**field] != after[**field])
+            .map(|field| (*field, before[*field].clone(), after[*field].clone()))
+            .collect()
+    }
+
+    /// Reject changes that would leave the constraints nonsensical
+    fn validate(&self, original: &QuoteConstraints) -> Result<(), ConstraintError> {
+        let mut amended = original.clone();
+        self.apply(&mut amended);
+        if amended.quorum_count == 0 {
+            return Err(ConstraintError::ZeroQuorum);
+        }
+        let sources = amended.allowed_sources.len();
+        if sources > 0 && amended.quorum_count as usize > sources {
+            return Err(ConstraintError::QuorumExceedsSources {
+                quorum: amended.quorum_count,
+                sources,
+            });
+        }
+        if amended.quorum_tolerance_bps > 10_000 {
+            return Err(ConstraintError::InvalidTolerance(amended.quorum_tolerance_percent()));
+        }
+        if amended.max_fills == 0 {
+            return Err(ConstraintError::ZeroFills);
+        }
+        if amended.max_fills_per_taker == Some(0) {
+            return Err(ConstraintError::ZeroFillsPerTaker);
+        }
+        Ok(())
+    }
+}
+
+fn json(constraints: &QuoteConstraints) -> serde_json::Value {
+    serde_json::to_value(constraints).expect("constraints serialize to JSON")
+}
+
+/// `new`, unless it restates `old`
+fn changed<T: PartialEq>(new: Option<T>, old: &T) -> Option<T> {
+    new.filter(|new| new != old)
+}
+
+impl ParsedAmendment {
+    /// The changes this amendment makes to `original`, at unix time `now`
+    ///
+    /// Terms restated with their current value are left out of the diff.
+    pub fn into_diff(
+        self,
+        original: &QuoteConstraints,
+        now: u64,
+    ) -> Result<ConstraintDiff, CompilerError> {
+        if !self.clarifications.is_empty() {
+            return Err(CompilerError::NeedsClarification {
+                questions: self.clarifications,
+            });
+        }
+        if self.expiry_minutes == Some(0) {
+            return Err(ConstraintError::ZeroExpiry.into());
+        }
+        if let Some(tolerance) = self.quorum_tolerance_percent {
+            if !(0.0..=100.0).contains(&tolerance) {
+                return Err(ConstraintError::InvalidTolerance(tolerance).into());
+            }
+        }
+
+        let diff = ConstraintDiff {
+            expiry_timestamp: self.expiry_minutes.map(|minutes| now + minutes * 60),
+            allowed_takers: changed(self.allowed_takers, &original.allowed_takers),
+            allowed_sources: changed(self.allowed_sources, &original.allowed_sources),
+            max_staleness_secs: changed(self.max_staleness_seconds, &original.max_staleness_secs),
+            quorum_count: changed(self.quorum_required, &original.quorum_count),
+            quorum_tolerance_bps: changed(
+                self.quorum_tolerance_percent.map(percent_to_bps),
+                &original.quorum_tolerance_bps,
+            ),
+            max_fills: changed(self.max_fills, &original.max_fills),
+            max_fills_per_taker: self
+                .max_fills_per_taker
+                .filter(|n| Some(*n) != original.max_fills_per_taker),
+        };
+        diff.validate(original)?;
+        Ok(diff)
+    }
+}
@@ -0,0 +1,98 @@
+//! Asset registry
+//!
+//! Amounts in constraints and Local Laws input are integers in an asset's
+//! smallest unit. The [`AssetRegistry`] knows how many decimals each asset
+//! has (and which token settles it), so 2.5 of a 6-decimal stablecoin is
+//! 2,500,000 units while 2.5 of an 18-decimal token is 2.5e18. Assets that
+//! aren't registered use [`DEFAULT_DECIMALS`] and the native token.
+//!
+//! ```yaml
+//! assets:
+//!   USDC: { decimals: 6, token_id: "usdc" }
+//!   wETH: { decimals: 18, token_id: "weth" }
+//! ```
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use thiserror::Error;
+
+/// Decimals of unregistered assets (the native token's)
+pub const DEFAULT_DECIMALS: u8 = 9;
+
+/// Token ID of unregistered assets
+pub const NATIVE_TOKEN_ID: &str = "native";
+
+/// An amount that can't be expressed in an asset's units
+#[derive(Debug, Clone, PartialEq, Error)]
+#[error("{amount} {asset} doesn't fit in {decimals}-decimal units")]
+pub struct UnitsOverflow {
+    pub asset: String,
+    pub amount: f64,
+    pub decimals: u8,
+}
+
+/// How an asset is denominated and settled
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AssetInfo {
+    /// Decimal places of the smallest unit
+    pub decimals: u8,
+    /// Token ID of the asset on Delta
+    #[serde(default = "default_token_id")]
+    pub token_id: String,
+}
+
+fn default_token_id() -> String {
+    NATIVE_TOKEN_ID.to_string()
+}
+
+impl Default for AssetInfo {
+    fn default() -> Self {
+        Self {
+            decimals: DEFAULT_DECIMALS,
+            token_id: default_token_id(),
+        }
+    }
+}
+
+/// Asset symbol → decimals and token ID (symbols match case-insensitively)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct AssetRegistry {
+    assets: BTreeMap<String, AssetInfo>,
+}
+
+impl AssetRegistry {
+    /// Register an asset
+    pub fn with_asset(mut self, symbol: impl Into<String>, info: AssetInfo) -> Self {
+        self.assets.insert(symbol.into(), info);
+        self
+    }
+
+    /// An asset's info, or the native defaults if it isn't registered
+    pub fn get(&self, symbol: &str) -> AssetInfo {
+        self.assets
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(symbol))
+            .map(|(_, info)| info.clone())
+            .unwrap_or_default()
+    }
+
+    /// Smallest units per whole unit of an asset
+    pub fn scale(&self, symbol: &str) -> f64 {
+        10f64.powi(i32::from(self.get(symbol).decimals))
+    }
+
+    /// Convert an amount of an asset to its smallest units
+    pub fn to_units(&self, symbol: &str, amount: f64) -> Result<u64, UnitsOverflow> {
+        let units = (amount * self.scale(symbol)).round();
+        // u64::MAX as f64 rounds up to 2^64, which doesn't fit
+        if !(0.0..u64::MAX as f64).contains(&units) {
+            return Err(UnitsOverflow {
+                asset: symbol.to_string(),
+                amount,
+                decimals: self.get(symbol).decimals,
+            });
+        }
+        Ok(units as u64)
+    }
+}
@@ -0,0 +1,197 @@
+//! LLM token and cost budgets
+//!
+//! Caps the tokens (and what they cost) a single compilation and a whole
+//! UTC day may spend, so a client posting quotes in a loop can't run
+//! through the API budget. Once the daily limit is reached, no LLM call is
+//! made until the next day; a compilation over the per-request limit fails
+//! with the answer discarded. Calls whose provider doesn't report usage are
+//! charged an estimate of four characters per token.
+
+use chrono::{NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+
+use crate::{CompilerError, LlmExchange, TokenUsage};
+
+/// Token and cost limits (None = unlimited)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BudgetConfig {
+    /// Tokens (input and output) one compilation may spend, across retries,
+    /// fallbacks and the confidence pass
+    #[serde(default)]
+    pub max_request_tokens: Option<u64>,
+    /// Tokens all compilations may spend per UTC day
+    #[serde(default)]
+    pub max_daily_tokens: Option<u64>,
+    /// Cost one compilation may run up, in USD
+    #[serde(default)]
+    pub max_request_usd: Option<f64>,
+    /// Cost all compilations may run up per UTC day, in USD
+    #[serde(default)]
+    pub max_daily_usd: Option<f64>,
+    /// Price of input tokens, in USD per million
+    #[serde(default)]
+    pub input_usd_per_mtok: f64,
+    /// Price of output tokens, in USD per million
+    #[serde(default)]
+    pub output_usd_per_mtok: f64,
+}
+
+/// Tokens spent and what they cost
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Spend {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cost_usd: f64,
+}
+
+impl Spend {
+    pub fn tokens(&self) -> u64 {
+        self.input_tokens.saturating_add(self.output_tokens)
+    }
+
+    fn add(&mut self, other: Spend) {
+        self.input_tokens = self.input_tokens.saturating_add(other.input_tokens);
+        self.output_tokens = self.output_tokens.saturating_add(other.output_tokens);
+        self.cost_usd += other.cost_usd;
+    }
+}
+
+/// Usage counters for one UTC day
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BudgetUsage {
+    pub day: NaiveDate,
+    /// LLM calls charged to the budget
+    pub calls: u64,
+    /// Compilations refused or discarded for exceeding a limit
+    pub refused: u64,
+    #[serde(flatten)]
+    pub spent: Spend,
+}
+
+impl BudgetUsage {
+    fn new(day: NaiveDate) -> Self {
+        Self {
+            day,
+            calls: 0,
+            refused: 0,
+            spent: Spend::default(),
+        }
+    }
+}
+
+/// Enforces a [`BudgetConfig`] and keeps the day's usage counters
+#[derive(Debug)]
+pub struct TokenBudget {
+    config: BudgetConfig,
+    today: Mutex<BudgetUsage>,
+}
+
+impl TokenBudget {
+    pub fn new(config: BudgetConfig) -> Self {
+        Self {
+            config,
+            today: Mutex::new(BudgetUsage::new(Utc::now().date_naive())),
+        }
+    }
+
+    /// Usage so far today
+    pub fn usage(&self) -> BudgetUsage {
+        self.current().clone()
+    }
+
+    /// Refuse another LLM call once the daily limit is reached
+    pub(crate) fn check_daily(&self) -> Result<(), CompilerError> {
+        let mut today = self.current();
+        let spent = today.spent;
+        let reached = match (self.config.max_daily_tokens, self.config.max_daily_usd) {
+            (Some(max), _) if spent.tokens() >= max => {
+                Some(format!("daily limit of {} tokens reached", max))
+            }
+            (_, Some(max)) if spent.cost_usd >= max => {
+                Some(format!("daily limit of ${:.2} reached", max))
+            }
+            _ => None,
+        };
+        match reached {
+            Some(reason) => {
+                today.refused += 1;
+                Err(CompilerError::BudgetExceeded(reason))
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Charge a finished call to the day's usage
+    pub(crate) fn charge(&self, exchange: &LlmExchange) {
+        let spend = self.price(billed_usage(exchange));
+        let mut today = self.current();
+        today.calls += 1;
+        today.spent.add(spend);
+    }
+
+    /// Fail a compilation whose calls went over the per-request limit
+    pub(crate) fn check_request(&self, exchanges: &[LlmExchange]) -> Result<(), CompilerError> {
+        let mut spent = Spend::default();
+        for exchange in exchanges {
+            spent.add(self.price(billed_usage(exchange)));
+        }
+        let exceeded = match (self.config.max_request_tokens, self.config.max_request_usd) {
+            (Some(max), _) if spent.tokens() > max => Some(format!(
+                "compilation used {} tokens, over the limit of {}",
+                spent.tokens(),
+                max
+            )),
+            (_, Some(max)) if spent.cost_usd > max => Some(format!(
+                "compilation cost ${:.4}, over the limit of ${:.4}",
+                spent.cost_usd, max
+            )),
+            _ => None,
+        };
+        match exceeded {
+            Some(reason) => {
+                self.current().refused += 1;
+                Err(CompilerError::BudgetExceeded(reason))
+            }
+            None => Ok(()),
+        }
+    }
+
+    fn price(&self, usage: TokenUsage) -> Spend {
+        let (input, output) = (u64::from(usage.input_tokens), u64::from(usage.output_tokens));
+        Spend {
+            input_tokens: input,
+            output_tokens: output,
+            cost_usd: (input as f64 * self.config.input_usd_per_mtok
+                + output as f64 * self.config.output_usd_per_mtok)
+                / 1_000_000.0,
+        }
+    }
+
+    /// The counters, reset if the UTC day has rolled over
+    fn current(&self) -> std::sync::MutexGuard<'_, BudgetUsage> {
+        let mut today = self.today.lock().unwrap_or_else(|e| e.into_inner());
+        let day = Utc::now().date_naive();
+        if today.day != day {
+            *today = BudgetUsage::new(day);
+        }
+        today
+    }
+}
+
+/// The usage an exchange is charged: as reported, or estimated from the
+/// prompt and response length
+fn billed_usage(exchange: &LlmExchange) -> TokenUsage {
+    if let Some(usage) = exchange.usage {
+        return usage;
+    }
+    let Some(response) = &exchange.response else {
+        // Failed calls aren't billed
+        return TokenUsage::default();
+    };
+    let estimate = |text: &str| (text.len() as u32).div_ceil(4);
+    TokenUsage {
+        input_tokens: estimate(&exchange.prompt),
+        output_tokens: estimate(response),
+    }
+}
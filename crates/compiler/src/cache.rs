@@ -0,0 +1,175 @@
+//! Compilation cache
+//!
+//! Remembers the terms an LLM extracted for a quote text so identical texts
+//! (e.g. canned quotes posted over and over) don't hit the LLM twice.
+//! Entries are keyed on the whitespace-normalized text and the model that
+//! answered, evicted least-recently-used, and optionally persisted to a JSON
+//! file so they survive restarts.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use crate::ParsedQuote;
+
+/// Cache settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheConfig {
+    /// Maximum number of entries kept (0 disables the cache)
+    #[serde(default = "default_capacity")]
+    pub capacity: usize,
+    /// File the cache is loaded from and saved to (memory only if unset)
+    #[serde(default)]
+    pub path: Option<PathBuf>,
+}
+
+fn default_capacity() -> usize {
+    256
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            capacity: default_capacity(),
+            path: None,
+        }
+    }
+}
+
+/// (normalized text, model)
+type CacheKey = (String, String);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CacheEntry {
+    text: String,
+    model: String,
+    parsed: ParsedQuote,
+    /// Logical clock of the last hit or insert
+    last_used: u64,
+}
+
+#[derive(Debug, Default)]
+struct CacheState {
+    entries: HashMap<CacheKey, CacheEntry>,
+    clock: u64,
+}
+
+/// LRU cache of LLM compilations
+#[derive(Debug)]
+pub struct CompileCache {
+    config: CacheConfig,
+    state: Mutex<CacheState>,
+}
+
+impl CompileCache {
+    /// Create a cache, loading persisted entries from `config.path`
+    pub fn new(config: CacheConfig) -> Self {
+        let mut state = CacheState::default();
+        if let Some(path) = &config.path {
+            match std::fs::read(path) {
+                Ok(bytes) => match serde_json::from_slice::<Vec<CacheEntry>>(&bytes) {
+                    Ok(entries) => {
+                        for entry in entries {
+                            state.clock = state.clock.max(entry.last_used);
+                            state
+                                .entries
+                                .insert((entry.text.clone(), entry.model.clone()), entry);
+                        }
+                        tracing::info!(
+                            "Loaded {} cached compilations from {}",
+                            state.entries.len(),
+                            path.display()
+                        );
+                    }
+                    Err(e) => tracing::warn!("Ignoring unreadable compile cache {}: {}", path.display(), e),
+                },
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => tracing::warn!("Failed to read compile cache {}: {}", path.display(), e),
+            }
+        }
+        state.evict(config.capacity);
+
+        Self {
+            config,
+            state: Mutex::new(state),
+        }
+    }
+
+    /// Look up a compilation, marking it recently used
+    pub fn get(&self, text: &str, model: &str) -> Option<ParsedQuote> {
+        let mut state = self.state.lock().expect("cache lock poisoned");
+        state.clock += 1;
+        let clock = state.clock;
+        let entry = state.entries.get_mut(&(normalize(text), model.to_string()))?;
+        entry.last_used = clock;
+        Some(entry.parsed.clone())
+    }
+
+    /// Store a compilation, evicting the least recently used entry if full
+    pub fn insert(&self, text: &str, model: &str, parsed: &ParsedQuote) {
+        if self.config.capacity == 0 {
+            return;
+        }
+
+        let snapshot = {
+            let mut state = self.state.lock().expect("cache lock poisoned");
+            state.clock += 1;
+            let entry = CacheEntry {
+                text: normalize(text),
+                model: model.to_string(),
+                parsed: parsed.clone(),
+                last_used: state.clock,
+            };
+            state
+                .entries
+                .insert((entry.text.clone(), entry.model.clone()), entry);
+            state.evict(self.config.capacity);
+            self.config
+                .path
+                .as_ref()
+                .map(|_| state.entries.values().cloned().collect::<Vec<_>>())
+        };
+
+        if let (Some(path), Some(entries)) = (&self.config.path, snapshot) {
+            let result = serde_json::to_vec(&entries)
+                .map_err(std::io::Error::from)
+                .and_then(|bytes| std::fs::write(path, bytes));
+            if let Err(e) = result {
+                tracing::warn!("Failed to save compile cache {}: {}", path.display(), e);
+            }
+        }
+    }
+
+    /// Number of cached compilations
+    pub fn len(&self) -> usize {
+        self.state.lock().expect("cache lock poisoned").entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl CacheState {
+    fn evict(&mut self, capacity: usize) {
+        while self.entries.len() > capacity {
+            let Some(oldest) = self
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| key.clone())
+            else {
+                break;
+            };
+            self.entries.remove(&oldest);
+        }
+    }
+}
+
+/// Collapse runs of whitespace and trim
+///
+/// Case is kept: asset symbols like "dETH" are case-sensitive.
+fn normalize(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
@@ -0,0 +1,65 @@
+//! Incremental quote drafts
+//!
+//! A maker can write a quote over several messages ("sell 2 dETH", "no
+//! less than 2100 USDD", "good for 30 minutes"). [`Compiler::draft`]
+//! compiles whatever has been written so far into a [`QuoteDraft`]: the
+//! terms understood, the questions still open and, once none are, the
+//! full preview of the quote that would be posted.
+//!
+//! [`Compiler::draft`]: crate::Compiler::draft
+
+use serde::Serialize;
+
+use crate::{ConstraintError, ParsedQuote, QuotePreview};
+
+/// What a partly written quote compiles to
+#[derive(Debug, Clone, Serialize)]
+pub struct QuoteDraft {
+    /// Terms understood so far (terms not yet stated are zero or empty)
+    pub terms: ParsedQuote,
+    /// What the maker still has to answer, most essential first
+    pub questions: Vec<String>,
+    /// The quote that would be posted, once no questions are open
+    pub preview: Option<QuotePreview>,
+}
+
+impl QuoteDraft {
+    /// Whether the text can be posted as it is
+    pub fn is_complete(&self) -> bool {
+        self.preview.is_some()
+    }
+}
+
+/// Questions for the terms a quote can't be posted without
+///
+/// Every missing essential term gets its own question; once all are
+/// stated, the first invalid term (if any) is reported instead.
+pub(crate) fn questions(terms: &ParsedQuote) -> Vec<String> {
+    let mut questions = vec![];
+    if !matches!(terms.side.to_lowercase().as_str(), "buy" | "sell") {
+        questions.push("Do you want to buy or sell?".to_string());
+    }
+    if terms.asset.trim().is_empty() {
+        questions.push("Which asset do you want to trade?".to_string());
+    }
+    if !(terms.size > 0.0 && terms.size.is_finite()) {
+        questions.push("How much do you want to trade?".to_string());
+    }
+    if terms.currency.trim().is_empty() {
+        questions.push("Which currency should the price be in?".to_string());
+    }
+    if terms.expiry_minutes == 0 {
+        questions.push("How long should the quote stay open?".to_string());
+    }
+    if questions.is_empty() {
+        if let Err(e) = terms.validate() {
+            questions.push(question(&e));
+        }
+    }
+    questions
+}
+
+/// A question asking the maker to correct an invalid term
+pub(crate) fn question(error: &ConstraintError) -> String {
+    format!("The quote can't be posted as written: {}. What should it be?", error)
+}
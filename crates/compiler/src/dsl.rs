@@ -0,0 +1,431 @@
+//! Constraint DSL
+//!
+//! A `key = value` format that maps one-to-one onto quote terms, for makers
+//! who want reproducible quotes without LLM nondeterminism:
+//!
+//! ```text
+//! # Sell 5 dETH per fill, at least 1950 USDD
+//! side = sell
+//! asset = dETH
+//! size = 5
+//! price = 1950
+//! currency = USDD
+//! expires = 10m
+//! sources = FeedA, FeedB
+//! quorum = 2
+//! tolerance = 0.5%
+//! ```
+//!
+//! One key per line; blank lines and `#` comments are ignored, and keys are
+//! case-insensitive. Unknown or repeated keys are errors rather than being
+//! skipped, so a typo never silently drops a guardrail.
+//!
+//! | Key | Value |
+//! |-----|-------|
+//! | `side` | `buy` or `sell` (required) |
+//! | `asset` | Asset symbol, e.g. `dETH` (required) |
+//! | `size` | Size per fill, e.g. `5` or `5k` (required) |
+//! | `price` | Limit price: the max for buys, the min for sells |
+//! | `min_price`, `max_price` | Explicit price bounds |
+//! | `currency` | Quote currency, e.g. `USDD` (required) |
+//! | `expires` | Expiry (`s`, `m`, `h`, `d`; required) |
+//! | `sources` | Allowed feed sources |
+//! | `staleness` | Max feed age, e.g. `5s` |
+//! | `source_staleness` | Max feed age by source, overriding `staleness`, e.g. `FeedA:5s, FeedB:60s` |
+//! | `quorum` | Sources required |
+//! | `tolerance` | Max spread between sources, e.g. `0.5%` or `25bps` |
+//! | `quorum_policy` | How sources' prices combine: `spread`, `median`, `trimmed 1` or `weighted FeedA:2+FeedB:1` |
+//! | `takers` | Allowed takers |
+//! | `denied_takers` | Takers that may never fill |
+//! | `taker_shards` | Shards the taker's vault must be on, e.g. `1, 2` |
+//! | `max_fills` | Total fills allowed |
+//! | `max_fills_per_taker` | Fills allowed per taker |
+//! | `max_notional_per_taker` | Largest amount of the currency one taker may trade, e.g. `50k` |
+//! | `twap` | Settle at the TWAP over this window, e.g. `10m` |
+//! | `min_fill` | Smallest size per fill |
+//! | `display` | Size shown at a time, the rest of `size` hidden (iceberg) |
+//! | `legs` | Further assets of a basket, e.g. `100 dSOL, 2 dBTC` (`price` is then per package) |
+//! | `partial_fills` | `true` or `false` |
+//! | `settlement_window` | Settlement deadline after acceptance, e.g. `15m` |
+//! | `max_deviation` | Max distance of the fill price from the feed median, e.g. `1%` |
+//! | `max_fee` | Largest fee as a share of the notional, e.g. `5bps` |
+//! | `window` | Daily UTC hours fills are allowed in, e.g. `14:00-16:00` |
+//! | `no_side_payments` | `true` or `false` |
+//! | `atomic_dvp` | `true` or `false` |
+//! | `preset` | Named preset for the guardrails left unset |
+//!
+//! Lists are separated by `,` or `+`. Sizes and prices take `k`, `m`/`mm`
+//! and `b`/`bn` suffixes.
+
+use std::collections::BTreeMap;
+use thiserror::Error;
+
+use crate::grammar::{duration_secs, quorum_policy, trading_window};
+use crate::shorthand::{self, percent};
+use crate::{ParsedLeg, ParsedQuote};
+
+/// Why DSL text couldn't be parsed
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum DslError {
+    #[error("line {line}: expected `key = value`")]
+    Syntax { line: usize },
+    #[error("line {line}: unknown key `{key}`")]
+    UnknownKey { line: usize, key: String },
+    #[error("line {line}: `{key}` is set more than once")]
+    DuplicateKey { line: usize, key: String },
+    #[error("line {line}: invalid value `{value}` for `{key}`")]
+    InvalidValue {
+        line: usize,
+        key: String,
+        value: String,
+    },
+    #[error("`price` can't be combined with `min_price` or `max_price`")]
+    ConflictingPrices,
+    #[error("missing required key `{0}`")]
+    MissingKey(&'static str),
+    #[error("unknown preset `{0}`")]
+    UnknownPreset(String),
+}
+
+/// Parse DSL text into quote terms and the preset it names, if any
+pub fn parse(text: &str) -> Result<(ParsedQuote, Option<String>), DslError> {
+    let mut parsed = ParsedQuote {
+        asset: String::new(),
+        size: 0.0,
+        side: String::new(),
+        max_price: None,
+        min_price: None,
+        currency: String::new(),
+        expiry_minutes: 0,
+        allowed_sources: vec![],
+        max_staleness_seconds: None,
+        source_staleness_seconds: BTreeMap::new(),
+        quorum_required: None,
+        quorum_tolerance_percent: None,
+        quorum_policy: None,
+        allowed_takers: vec![],
+        denied_takers: vec![],
+        allowed_taker_shards: vec![],
+        no_side_payments: None,
+        atomic_dvp_only: None,
+        max_fills: None,
+        max_fills_per_taker: None,
+        max_notional_per_taker: None,
+        twap_window_minutes: None,
+        min_fill_size: None,
+        legs: vec![],
+        display_size: None,
+        partial_fills: None,
+        settlement_window_minutes: None,
+        max_price_deviation_percent: None,
+        max_fee_percent: None,
+        trading_window: None,
+        clarifications: vec![],
+        confidence: BTreeMap::new(),
+    };
+    let mut price = None;
+    let mut preset = None;
+    let mut seen: Vec<String> = vec![];
+
+    for (index, raw) in text.lines().enumerate() {
+        let line = index + 1;
+        let content = raw.split('#').next().unwrap_or_default().trim();
+        if content.is_empty() {
+            continue;
+        }
+        let (key, value) = content.split_once('=').ok_or(DslError::Syntax { line })?;
+        let key = key.trim().to_lowercase();
+        let value = value.trim();
+        if value.is_empty() {
+            return Err(DslError::Syntax { line });
+        }
+        if seen.contains(&key) {
+            return Err(DslError::DuplicateKey { line, key });
+        }
+
+        let invalid = || DslError::InvalidValue {
+            line,
+            key: key.clone(),
+            value: value.to_string(),
+        };
+        match key.as_str() {
+            "side" => {
+                let side = value.to_lowercase();
+                if side != "buy" && side != "sell" {
+                    return Err(invalid());
+                }
+                parsed.side = side;
+            }
+            "asset" => parsed.asset = value.to_string(),
+            "size" => parsed.size = number(value).ok_or_else(invalid)?,
+            "price" => price = Some(number(value).ok_or_else(invalid)?),
+            "min_price" => parsed.min_price = Some(number(value).ok_or_else(invalid)?),
+            "max_price" => parsed.max_price = Some(number(value).ok_or_else(invalid)?),
+            "currency" => parsed.currency = value.to_string(),
+            "expires" => {
+                parsed.expiry_minutes = duration_secs(value).ok_or_else(invalid)?.div_ceil(60)
+            }
+            "sources" => parsed.allowed_sources = list(value),
+            "staleness" => {
+                parsed.max_staleness_seconds = Some(duration_secs(value).ok_or_else(invalid)?)
+            }
+            "source_staleness" => {
+                for entry in list(value) {
+                    let (source, duration) = entry.split_once(':').ok_or_else(invalid)?;
+                    let secs = duration_secs(duration.trim()).ok_or_else(invalid)?;
+                    parsed.source_staleness_seconds.insert(source.trim().to_string(), secs);
+                }
+            }
+            "quorum" => parsed.quorum_required = Some(value.parse().map_err(|_| invalid())?),
+            "tolerance" => {
+                parsed.quorum_tolerance_percent = Some(percent(value).ok_or_else(invalid)?)
+            }
+            "quorum_policy" => {
+                quorum_policy(value).ok_or_else(invalid)?;
+                parsed.quorum_policy = Some(value.to_string())
+            }
+            "takers" => parsed.allowed_takers = list(value),
+            "denied_takers" => parsed.denied_takers = list(value),
+            "taker_shards" => {
+                parsed.allowed_taker_shards = list(value)
+                    .iter()
+                    .map(|shard| shard.parse().map_err(|_| invalid()))
+                    .collect::<Result<_, _>>()?
+            }
+            "max_fills" => parsed.max_fills = Some(value.parse().map_err(|_| invalid())?),
+            "max_fills_per_taker" => {
+                parsed.max_fills_per_taker = Some(value.parse().map_err(|_| invalid())?)
+            }
+            "max_notional_per_taker" => {
+                parsed.max_notional_per_taker = Some(number(value).ok_or_else(invalid)?)
+            }
+            "twap" => {
+                let secs = duration_secs(value).ok_or_else(invalid)?;
+                parsed.twap_window_minutes = Some(secs.div_ceil(60))
+            }
+            "min_fill" => parsed.min_fill_size = Some(number(value).ok_or_else(invalid)?),
+            "display" => parsed.display_size = Some(number(value).ok_or_else(invalid)?),
+            "legs" => {
+                parsed.legs = list(value)
+                    .iter()
+                    .map(|leg| {
+                        let (size, asset) = leg.split_once(char::is_whitespace)?;
+                        Some(ParsedLeg {
+                            asset: asset.trim().to_string(),
+                            size: number(size)?,
+                        })
+                    })
+                    .collect::<Option<_>>()
+                    .ok_or_else(invalid)?
+            }
+            "partial_fills" => parsed.partial_fills = Some(flag(value).ok_or_else(invalid)?),
+            "settlement_window" => {
+                let secs = duration_secs(value).ok_or_else(invalid)?;
+                parsed.settlement_window_minutes = Some(secs.div_ceil(60))
+            }
+            "max_deviation" => {
+                parsed.max_price_deviation_percent = Some(percent(value).ok_or_else(invalid)?)
+            }
+            "max_fee" => parsed.max_fee_percent = Some(percent(value).ok_or_else(invalid)?),
+            "window" => {
+                trading_window(value).ok_or_else(invalid)?;
+                parsed.trading_window = Some(value.to_string())
+            }
+            "no_side_payments" => parsed.no_side_payments = Some(flag(value).ok_or_else(invalid)?),
+            "atomic_dvp" => parsed.atomic_dvp_only = Some(flag(value).ok_or_else(invalid)?),
+            "preset" => preset = Some(value.to_string()),
+            _ => return Err(DslError::UnknownKey { line, key }),
+        }
+        seen.push(key);
+    }
+
+    for (key, missing) in [
+        ("side", parsed.side.is_empty()),
+        ("asset", parsed.asset.is_empty()),
+        ("size", !seen.iter().any(|k| k == "size")),
+        ("currency", parsed.currency.is_empty()),
+        ("expires", parsed.expiry_minutes == 0),
+    ] {
+        if missing {
+            return Err(DslError::MissingKey(key));
+        }
+    }
+
+    if let Some(price) = price {
+        if parsed.min_price.is_some() || parsed.max_price.is_some() {
+            return Err(DslError::ConflictingPrices);
+        }
+        match parsed.side.as_str() {
+            "buy" => parsed.max_price = Some(price),
+            _ => parsed.min_price = Some(price),
+        }
+    }
+
+    Ok((parsed, preset))
+}
+
+/// Parse a finite number, with trading shorthand like `5k` (range checks
+/// are left to validation)
+fn number(text: &str) -> Option<f64> {
+    shorthand::amount(text)
+}
+
+fn flag(text: &str) -> Option<bool> {
+    match text.to_lowercase().as_str() {
+        "true" | "yes" => Some(true),
+        "false" | "no" => Some(false),
+        _ => None,
+    }
+}
+
+/// Split a `,` or `+` separated list
+fn list(text: &str) -> Vec<String> {
+    text.split([',', '+'])
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grammar;
+
+    const BASE: &str = "\
+# Sell 5 dETH per fill, at least 1950 USDD
+side = sell
+asset = dETH
+size = 5
+price = 1950
+currency = USDD
+expires = 10m
+";
+
+    fn with(extra: &str) -> String {
+        format!("{}{}", BASE, extra)
+    }
+
+    #[test]
+    fn test_parse_quote() {
+        let (parsed, preset) = parse(&with("sources = FeedA, FeedB\nquorum = 2\n")).unwrap();
+        assert_eq!(parsed.side, "sell");
+        assert_eq!(parsed.asset, "dETH");
+        assert_eq!(parsed.size, 5.0);
+        assert_eq!(parsed.min_price, Some(1950.0));
+        assert_eq!(parsed.max_price, None);
+        assert_eq!(parsed.currency, "USDD");
+        assert_eq!(parsed.expiry_minutes, 10);
+        assert_eq!(parsed.allowed_sources, vec!["FeedA", "FeedB"]);
+        assert_eq!(parsed.quorum_required, Some(2));
+        assert_eq!(preset, None);
+    }
+
+    #[test]
+    fn test_execution_terms() {
+        // The DSL spelling of the README's `min fill 0.5, settle within 15m,
+        // deviation 1%, all or none`
+        let (parsed, _) = parse(&with(
+            "min_fill = 0.5\nsettlement_window = 15m\nmax_deviation = 1%\npartial_fills = false\n",
+        ))
+        .unwrap();
+        assert_eq!(parsed.min_fill_size, Some(0.5));
+        assert_eq!(parsed.settlement_window_minutes, Some(15));
+        assert_eq!(parsed.max_price_deviation_percent, Some(1.0));
+        assert_eq!(parsed.partial_fills, Some(false));
+
+        let shorthand = grammar::parse(
+            "SELL 5 dETH @ 1950 USDD, expires 10m, min fill 0.5, settle within 15m, \
+             deviation 1%, all or none",
+        )
+        .unwrap();
+        assert_eq!(shorthand.min_fill_size, parsed.min_fill_size);
+        assert_eq!(shorthand.settlement_window_minutes, parsed.settlement_window_minutes);
+        assert_eq!(shorthand.max_price_deviation_percent, parsed.max_price_deviation_percent);
+        assert_eq!(shorthand.partial_fills, parsed.partial_fills);
+    }
+
+    #[test]
+    fn test_shorthand_values() {
+        let (parsed, preset) = parse(&with(
+            "max_notional_per_taker = 50k\nmax_fee = 5bps\nsettlement_window = 90s\n\
+             partial_fills = yes\npreset = conservative\n",
+        ))
+        .unwrap();
+        assert_eq!(parsed.max_notional_per_taker, Some(50_000.0));
+        assert_eq!(parsed.max_fee_percent, Some(0.05));
+        // Windows round up to whole minutes
+        assert_eq!(parsed.settlement_window_minutes, Some(2));
+        assert_eq!(parsed.partial_fills, Some(true));
+        assert_eq!(preset.as_deref(), Some("conservative"));
+    }
+
+    #[test]
+    fn test_keys_are_case_insensitive() {
+        let (parsed, _) = parse(&with("MIN_FILL = 0.5  # dust guard\n")).unwrap();
+        assert_eq!(parsed.min_fill_size, Some(0.5));
+    }
+
+    #[test]
+    fn test_malformed_lines() {
+        // Line numbers count the comment on line 1
+        let error = |extra: &str| parse(&with(extra)).unwrap_err();
+        assert_eq!(error("min fill 0.5\n"), DslError::Syntax { line: 8 });
+        assert_eq!(error("min_fill =\n"), DslError::Syntax { line: 8 });
+        assert_eq!(
+            error("min_fil = 0.5\n"),
+            DslError::UnknownKey {
+                line: 8,
+                key: "min_fil".into()
+            }
+        );
+        assert_eq!(
+            error("size = 6\n"),
+            DslError::DuplicateKey {
+                line: 8,
+                key: "size".into()
+            }
+        );
+    }
+
+    #[test]
+    fn test_invalid_values() {
+        for (line, key, value) in [
+            ("min_fill = half", "min_fill", "half"),
+            ("settlement_window = 15", "settlement_window", "15"),
+            ("settlement_window = 15 minutes", "settlement_window", "15 minutes"),
+            ("max_deviation = one%", "max_deviation", "one%"),
+            ("partial_fills = maybe", "partial_fills", "maybe"),
+            ("window = 16:00-16:00", "window", "16:00-16:00"),
+        ] {
+            assert_eq!(
+                parse(&with(&format!("{}\n", line))).unwrap_err(),
+                DslError::InvalidValue {
+                    line: 8,
+                    key: key.into(),
+                    value: value.into()
+                },
+                "{}",
+                line
+            );
+        }
+        assert!(matches!(
+            parse(&BASE.replace("side = sell", "side = short")),
+            Err(DslError::InvalidValue { line: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn test_missing_and_conflicting_keys() {
+        let error = |text: &str| parse(text).unwrap_err();
+        assert_eq!(
+            error(&BASE.replace("expires = 10m\n", "")),
+            DslError::MissingKey("expires")
+        );
+        assert_eq!(
+            error(&BASE.replace("asset = dETH\n", "")),
+            DslError::MissingKey("asset")
+        );
+        assert_eq!(error(&with("max_price = 2000\n")), DslError::ConflictingPrices);
+    }
+}
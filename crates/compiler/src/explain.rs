@@ -0,0 +1,266 @@
+//! Constraint explanations
+//!
+//! [`explain_constraints`] describes each enforced constraint in a
+//! sentence and says where its value came from: a phrase of the quote
+//! text, the preset, the compiler's defaults, or a change made after
+//! compiling (an explicit request field or an amendment). Frontends and
+//! agents get one entry per constraint to show or reason about.
+
+use rfq_models::{AuditEntry, AuditEvent, QuorumPolicy, QuoteConstraints};
+use serde::{Deserialize, Serialize};
+
+use crate::{FieldOrigin, FieldSource};
+
+/// One enforced constraint, explained
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExplainedConstraint {
+    /// Constraint field (e.g. "expiry_timestamp")
+    pub field: String,
+    /// The value that will be enforced, as displayed
+    pub value: String,
+    /// The constraint in plain English
+    pub sentence: String,
+    pub origin: FieldOrigin,
+    /// Where the value came from, as displayed
+    /// (e.g. "from text: 'expires in 10 minutes'", "default")
+    pub source: String,
+    /// The quote phrase or amendment text the value was read from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub phrase: Option<String>,
+}
+
+/// The enforced constraints of a quote, explained
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ConstraintExplanation {
+    pub constraints: Vec<ExplainedConstraint>,
+}
+
+impl ConstraintExplanation {
+    fn push(&mut self, field: &str, value: String, sentence: String) {
+        self.constraints.push(ExplainedConstraint {
+            field: field.to_string(),
+            value,
+            sentence,
+            origin: FieldOrigin::Inferred,
+            source: String::new(),
+            phrase: None,
+        });
+    }
+
+    /// Record where a field's value came from
+    fn attribute(&mut self, field: &str, origin: FieldOrigin, phrase: Option<&str>) {
+        for entry in self.constraints.iter_mut().filter(|e| e.field == field) {
+            entry.origin = origin;
+            entry.phrase = phrase.map(str::to_string);
+        }
+    }
+}
+
+/// Explain `constraints`, given where the compiler found each field
+///
+/// `sources` are the fields traced back to the quote text (see
+/// [`QuotePreview::fields`](crate::QuotePreview::fields)) and `preset` the
+/// preset the text named. Changes in `audit_trail` take precedence over
+/// the compiled source. Fields no source accounts for are inferred.
+pub fn explain_constraints(
+    constraints: &QuoteConstraints,
+    sources: &[FieldSource],
+    preset: Option<&str>,
+    audit_trail: &[AuditEntry],
+) -> ConstraintExplanation {
+    let c = constraints;
+    let mut explanation = ConstraintExplanation::default();
+
+    if c.max_debit != u64::MAX {
+        let sentence = format!("The maker pays at most {} units per fill.", c.max_debit);
+        explanation.push("max_debit", c.max_debit.to_string(), sentence);
+    }
+    if let Some(min) = c.min_credit {
+        let sentence = format!("The maker receives at least {} units per fill.", min);
+        explanation.push("min_credit", min.to_string(), sentence);
+    }
+
+    let expiry = c.expiry_datetime().to_rfc3339();
+    let sentence = format!("The quote expires at {}.", expiry);
+    explanation.push("expiry_timestamp", expiry, sentence);
+
+    if !c.allowed_sources.is_empty() {
+        let sources = c.allowed_sources.join(", ");
+        let sentence = format!("Only prices from {} are accepted.", sources);
+        explanation.push("allowed_sources", sources, sentence);
+    }
+
+    let sentence = format!("Feed prices must be under {}s old.", c.max_staleness_secs);
+    explanation.push("max_staleness_secs", format!("{}s", c.max_staleness_secs), sentence);
+    for (source, secs) in &c.source_staleness_secs {
+        let sentence = format!("{}'s prices must be under {}s old.", source, secs);
+        explanation.push("source_staleness_secs", format!("{}: {}s", source, secs), sentence);
+    }
+
+    if c.quorum_count > 1 {
+        let sentence = format!("At least {} sources must report a price.", c.quorum_count);
+        explanation.push("quorum_count", c.quorum_count.to_string(), sentence);
+        let tolerance = c.quorum_tolerance_percent();
+        let sentence = format!("Their prices must agree within {}%.", tolerance);
+        explanation.push("quorum_tolerance_bps", format!("{}%", tolerance), sentence);
+    }
+
+    let quorum_price = match &c.quorum_policy {
+        QuorumPolicy::Spread => None,
+        QuorumPolicy::Median => Some("median of the sources' prices".to_string()),
+        QuorumPolicy::TrimmedMean { trim } => Some(format!(
+            "mean of the sources' prices without the {} lowest and {} highest",
+            trim, trim
+        )),
+        QuorumPolicy::Weighted { .. } => {
+            Some("mean of the sources' prices, weighted by source".to_string())
+        }
+    };
+    if let Some(quorum_price) = quorum_price {
+        let sentence = format!(
+            "The quorum price is the {}; sources further from it than the tolerance don't count.",
+            quorum_price
+        );
+        explanation.push("quorum_policy", c.quorum_policy.to_string(), sentence);
+    }
+
+    if !c.allowed_takers.is_empty() {
+        let takers = c.allowed_takers.join(", ");
+        let sentence = format!("Only {} may fill the quote.", takers);
+        explanation.push("allowed_takers", takers, sentence);
+    }
+
+    if !c.denied_takers.is_empty() {
+        let takers = c.denied_takers.join(", ");
+        let sentence = format!("{} may never fill the quote.", takers);
+        explanation.push("denied_takers", takers, sentence);
+    }
+
+    if !c.allowed_taker_shards.is_empty() {
+        let shards = c.allowed_taker_shards.iter().map(u64::to_string).collect::<Vec<_>>();
+        let shards = shards.join(", ");
+        let sentence = format!("Only takers whose vault is on shard {} may fill.", shards);
+        explanation.push("allowed_taker_shards", shards, sentence);
+    }
+
+    // Icebergs are limited by their total size rather than a fill count,
+    // as are partially fillable quotes with no cap of their own
+    if c.max_fills > 1 && c.max_fills != u32::MAX && c.iceberg.is_none() {
+        let sentence = format!("The quote can be filled up to {} times.", c.max_fills);
+        explanation.push("max_fills", c.max_fills.to_string(), sentence);
+    }
+    if let Some(per_taker) = c.max_fills_per_taker {
+        let sentence = format!("Fills per taker are capped at {}.", per_taker);
+        explanation.push("max_fills_per_taker", per_taker.to_string(), sentence);
+    }
+    if let Some(notional) = c.max_notional_per_taker {
+        let sentence = format!("No taker may trade more than {} units in total.", notional);
+        explanation.push("max_notional_per_taker", notional.to_string(), sentence);
+    }
+
+    if let Some(benchmark) = &c.benchmark {
+        let sentence = format!(
+            "Fills settle at the {}-minute TWAP after acceptance.",
+            benchmark.window_secs / 60
+        );
+        let value = format!("TWAP over {}s", benchmark.window_secs);
+        explanation.push("benchmark", value, sentence);
+    }
+
+    if let Some(min) = c.min_fill_size {
+        let sentence = format!("Fills must be at least {} units.", min);
+        explanation.push("min_fill_size", min.to_string(), sentence);
+    }
+
+    if !c.leg_max_fill_sizes.is_empty() {
+        let legs = c
+            .leg_max_fill_sizes
+            .iter()
+            .map(|(asset, size)| format!("{} units of {}", size, asset))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let sentence = format!(
+            "Traded as a package: a full fill also moves {}, and a smaller fill its share.",
+            legs
+        );
+        explanation.push("legs", legs, sentence);
+    }
+
+    if let Some(iceberg) = &c.iceberg {
+        let sentence = format!(
+            "Shows {} units at a time, of {} in total.",
+            c.max_fill_size, iceberg.total_size
+        );
+        explanation.push("iceberg_total_size", iceberg.total_size.to_string(), sentence);
+    }
+
+    if c.allow_partial_fills {
+        let sentence = if c.iceberg.is_none() {
+            "Fills may take part of the size; the rest stays open.".to_string()
+        } else {
+            "Fills may take part of the size.".to_string()
+        };
+        explanation.push("allow_partial_fills", "true".to_string(), sentence);
+    }
+
+    if let Some(window) = c.trading_window {
+        let sentence = format!("Fills are only accepted {}.", window);
+        explanation.push("trading_window", window.to_string(), sentence);
+    }
+
+    if let Some(window) = c.settlement_window_secs {
+        let sentence = if c.benchmark.is_some() {
+            format!("The benchmark window must close within {}s of acceptance.", window)
+        } else {
+            format!("Fills must settle within {}s of acceptance.", window)
+        };
+        explanation.push("settlement_window_secs", format!("{}s", window), sentence);
+    }
+
+    if let Some(deviation) = c.max_price_deviation_percent() {
+        let sentence = format!("The fill price must be within {}% of the feed median.", deviation);
+        explanation.push("max_price_deviation_bps", format!("{}%", deviation), sentence);
+    }
+
+    if let Some(fee) = c.max_fee_percent() {
+        let sentence = format!("A fee leg may be at most {}% of the fill price.", fee);
+        explanation.push("max_fee_bps", format!("{}%", fee), sentence);
+    }
+
+    if c.require_atomic_dvp {
+        let sentence = "Both legs settle atomically (DvP).".to_string();
+        explanation.push("require_atomic_dvp", "true".to_string(), sentence);
+    }
+
+    if c.no_side_payments {
+        let sentence = "No side-payments are allowed.".to_string();
+        explanation.push("no_side_payments", "true".to_string(), sentence);
+    }
+
+    for source in sources {
+        explanation.attribute(&source.field, source.origin, source.phrase.as_deref());
+    }
+    for entry in audit_trail {
+        match &entry.event {
+            AuditEvent::ConstraintOverride { field, .. } => {
+                explanation.attribute(field, FieldOrigin::Request, None);
+            }
+            AuditEvent::Amendment { text, field, .. } => {
+                explanation.attribute(field, FieldOrigin::Amendment, Some(text));
+            }
+        }
+    }
+
+    for entry in &mut explanation.constraints {
+        entry.source = match (entry.origin, &entry.phrase, preset) {
+            (FieldOrigin::Text, Some(phrase), _) => format!("from text: '{}'", phrase),
+            (FieldOrigin::Amendment, Some(text), _) => format!("from amendment: '{}'", text),
+            (FieldOrigin::Preset, _, Some(name)) => format!("from preset '{}'", name),
+            (FieldOrigin::Preset, ..) => "from preset".to_string(),
+            (FieldOrigin::Default, ..) => "default".to_string(),
+            (FieldOrigin::Request, ..) => "set in the request".to_string(),
+            _ => "inferred by the compiler".to_string(),
+        };
+    }
+    explanation
+}
@@ -0,0 +1,253 @@
+//! Deterministic quote grammar
+//!
+//! Parses a constrained quote syntax without calling an LLM, e.g.
+//!
+//! ```text
+//! SELL 5 dETH @ 1950 USDD, expires 10m, feeds FeedA+FeedB
+//! BUY 2 dETH @ 2000 USDD, expires 1h, quorum 2, tolerance 0.5%, staleness 5s
+//! ```
+//!
+//! The head is `<BUY|SELL> <size> <asset> @ <price> <currency>`, followed by
+//! comma-separated clauses (keywords are case-insensitive). Sizes and prices
+//! take `k`, `m`/`mm` and `b`/`bn` suffixes (`5k` = 5000):
+//!
+//! | Clause | Meaning |
+//! |--------|---------|
+//! | `expires 10m` | Expiry (`s`, `m`, `h`, `d`; required) |
+//! | `feeds FeedA+FeedB` | Allowed sources |
+//! | `staleness 5s` | Max feed age |
+//! | `staleness FeedB 60s` | Max feed age of one source, overriding the above |
+//! | `quorum 2` | Sources required |
+//! | `tolerance 0.5%` | Max spread between sources (or `25bps`) |
+//! | `quorum median`, `quorum trimmed 1`, `quorum weighted FeedA:2+FeedB:1` | How sources' prices combine (default `spread`) |
+//! | `takers alice+bob` | Allowed takers |
+//! | `deny takers mallory+trent` | Takers that may never fill |
+//! | `taker shards 1+2` | Shards the taker's vault must be on |
+//! | `max fills 3` | Total fills allowed |
+//! | `max fills per taker 1` | Fills allowed per taker |
+//! | `max notional per taker 50k` | Largest amount of the currency one taker may trade |
+//! | `twap 10m` | Settle at the TWAP over the window |
+//! | `min fill 0.5` | Smallest size per fill |
+//! | `display 1` | Show only this much of the size at a time (iceberg) |
+//! | `plus 100 dSOL` | Another asset in the package (basket); the price is then per package |
+//! | `partial fills`, `all or none` | Whether fills may take part of the size |
+//! | `settle within 15m` | Settlement deadline after acceptance |
+//! | `deviation 1%` | Max distance of the fill price from the feed median |
+//! | `max fee 5bps` | Largest fee, as a share of the notional |
+//! | `window 14:00-16:00`, `between 14:00 and 16:00 UTC` | Daily UTC hours fills are allowed in |
+//! | `no side payments`, `atomic dvp` | Transfer pattern guardrails |
+//! | `<preset> guardrails` | Named preset (applied by the compiler) |
+//!
+//! Text that doesn't match the grammar exactly is left to the LLM.
+
+use std::collections::BTreeMap;
+
+use rfq_models::{QuorumPolicy, TradingWindow};
+
+use crate::{shorthand, ParsedLeg, ParsedQuote};
+
+/// Parse quote text in the deterministic grammar
+///
+/// Returns None if the text doesn't match, so the caller can fall back to
+/// the LLM.
+pub fn parse(text: &str) -> Option<ParsedQuote> {
+    let normalized = text.trim().trim_end_matches('.').replace('@', " @ ");
+    let mut clauses = normalized.split(',').map(str::trim);
+
+    let head: Vec<&str> = clauses.next()?.split_whitespace().collect();
+    let [side, size, asset, "@", price, currency] = head.as_slice() else {
+        return None;
+    };
+    let side = side.to_lowercase();
+    let size = shorthand::amount(size).filter(|s| *s > 0.0)?;
+    let price = shorthand::amount(price).filter(|p| *p > 0.0)?;
+    let (max_price, min_price) = match side.as_str() {
+        "buy" => (Some(price), None),
+        "sell" => (None, Some(price)),
+        _ => return None,
+    };
+
+    let mut parsed = ParsedQuote {
+        asset: asset.to_string(),
+        size,
+        side,
+        max_price,
+        min_price,
+        currency: currency.to_string(),
+        expiry_minutes: 0,
+        allowed_sources: vec![],
+        max_staleness_seconds: None,
+        source_staleness_seconds: BTreeMap::new(),
+        quorum_required: None,
+        quorum_tolerance_percent: None,
+        quorum_policy: None,
+        allowed_takers: vec![],
+        denied_takers: vec![],
+        allowed_taker_shards: vec![],
+        no_side_payments: None,
+        atomic_dvp_only: None,
+        max_fills: None,
+        max_fills_per_taker: None,
+        max_notional_per_taker: None,
+        twap_window_minutes: None,
+        min_fill_size: None,
+        legs: vec![],
+        display_size: None,
+        partial_fills: None,
+        settlement_window_minutes: None,
+        max_price_deviation_percent: None,
+        max_fee_percent: None,
+        trading_window: None,
+        clarifications: vec![],
+        confidence: BTreeMap::new(),
+    };
+
+    let mut has_expiry = false;
+    for clause in clauses.filter(|c| !c.is_empty()) {
+        let lower = clause.to_lowercase();
+        let words: Vec<&str> = clause.split_whitespace().collect();
+        let keywords: Vec<&str> = lower.split_whitespace().collect();
+
+        match keywords.as_slice() {
+            ["expires", duration] | ["expires", "in", duration] => {
+                parsed.expiry_minutes = duration_secs(duration)?.div_ceil(60);
+                has_expiry = true;
+            }
+            ["feeds" | "feed", _] => parsed.allowed_sources = list(words[1]),
+            ["staleness", duration] => parsed.max_staleness_seconds = Some(duration_secs(duration)?),
+            ["staleness", _, duration] => {
+                let secs = duration_secs(duration)?;
+                parsed.source_staleness_seconds.insert(words[1].to_string(), secs);
+            }
+            ["quorum", "spread" | "median" | "trimmed" | "weighted", ..] => {
+                let policy = words[1..].join(" ");
+                quorum_policy(&policy)?;
+                parsed.quorum_policy = Some(policy)
+            }
+            ["quorum", count] => parsed.quorum_required = Some(count.parse().ok()?),
+            ["tolerance", percent] => {
+                parsed.quorum_tolerance_percent = Some(shorthand::percent(percent)?)
+            }
+            ["takers" | "taker", _] => parsed.allowed_takers = list(words[1]),
+            ["deny" | "denied", "takers" | "taker", _] => parsed.denied_takers = list(words[2]),
+            ["taker", "shards" | "shard", _] => parsed.allowed_taker_shards = shards(words[2])?,
+            ["max", "fills", count] => parsed.max_fills = Some(count.parse().ok()?),
+            ["max", "fills", "per", "taker", count] => {
+                parsed.max_fills_per_taker = Some(count.parse().ok()?)
+            }
+            ["max", "notional", "per", "taker", notional] => {
+                parsed.max_notional_per_taker = Some(shorthand::amount(notional)?)
+            }
+            ["twap", duration] => parsed.twap_window_minutes = Some(duration_secs(duration)?.div_ceil(60)),
+            ["min", "fill", size] => parsed.min_fill_size = Some(shorthand::amount(size)?),
+            ["display" | "show", size] => parsed.display_size = Some(shorthand::amount(size)?),
+            ["plus", size, _] => parsed.legs.push(ParsedLeg {
+                asset: words[2].to_string(),
+                size: shorthand::amount(size)?,
+            }),
+            ["partial", "fills"] => parsed.partial_fills = Some(true),
+            ["all", "or", "none"] => parsed.partial_fills = Some(false),
+            ["settle", "within", duration] => {
+                parsed.settlement_window_minutes = Some(duration_secs(duration)?.div_ceil(60))
+            }
+            ["deviation", percent] => {
+                parsed.max_price_deviation_percent = Some(shorthand::percent(percent)?)
+            }
+            ["max", "fee", percent] => parsed.max_fee_percent = Some(shorthand::percent(percent)?),
+            ["window", range] | ["window", range, "utc"] => {
+                trading_window(range)?;
+                parsed.trading_window = Some(range.to_string())
+            }
+            ["between", from, "and", to] | ["between", from, "and", to, "utc"] => {
+                let range = format!("{}-{}", from, to);
+                trading_window(&range)?;
+                parsed.trading_window = Some(range)
+            }
+            ["no", "side", "payments"] => parsed.no_side_payments = Some(true),
+            ["atomic", "dvp"] => parsed.atomic_dvp_only = Some(true),
+            [_, "guardrails" | "preset" | "constraints"] => {}
+            _ => return None,
+        }
+    }
+
+    has_expiry.then_some(parsed)
+}
+
+/// Parse a duration like "30s", "10m", "2h" or "1d" into seconds
+pub(crate) fn duration_secs(text: &str) -> Option<u64> {
+    let split = text.find(|c: char| !c.is_ascii_digit())?;
+    let value: u64 = text[..split].parse().ok()?;
+    let unit = match &text[split..] {
+        "s" => 1,
+        "m" => 60,
+        "h" => 3600,
+        "d" => 86400,
+        _ => return None,
+    };
+    value.checked_mul(unit).filter(|secs| *secs > 0)
+}
+
+/// Parse a daily UTC window like "14:00-16:00" (a window closing before it
+/// opens runs past midnight)
+pub(crate) fn trading_window(text: &str) -> Option<TradingWindow> {
+    let text = text.trim();
+    let text = match text.len().checked_sub(3) {
+        Some(end) if text.is_char_boundary(end) && text[end..].eq_ignore_ascii_case("utc") => {
+            text[..end].trim_end()
+        }
+        _ => text,
+    };
+    let time = |hhmm: &str| {
+        let (hours, minutes) = hhmm.trim().split_once(':')?;
+        if hours.len() > 2 || minutes.len() != 2 {
+            return None;
+        }
+        let (hours, minutes): (u32, u32) = (hours.parse().ok()?, minutes.parse().ok()?);
+        (hours < 24 && minutes < 60).then_some(hours * 3600 + minutes * 60)
+    };
+    let (start, end) = text.split_once('-')?;
+    let window = TradingWindow {
+        start_secs: time(start)?,
+        end_secs: time(end)?,
+    };
+    (window.start_secs != window.end_secs).then_some(window)
+}
+
+/// Parse a quorum policy: "spread", "median", "trimmed 1" (drop the
+/// lowest and highest price) or "weighted FeedA:2+FeedB:1"
+pub(crate) fn quorum_policy(text: &str) -> Option<QuorumPolicy> {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let (keyword, args) = words.split_first()?;
+    match (keyword.to_lowercase().as_str(), args) {
+        ("spread", []) => Some(QuorumPolicy::Spread),
+        ("median", []) => Some(QuorumPolicy::Median),
+        ("trimmed", [trim]) => Some(QuorumPolicy::TrimmedMean {
+            trim: trim.parse().ok().filter(|trim| *trim > 0)?,
+        }),
+        ("weighted", [weights]) => {
+            let weights = list(weights)
+                .iter()
+                .map(|entry| {
+                    let (source, weight) = entry.split_once(':')?;
+                    Some((source.to_string(), weight.parse().ok()?))
+                })
+                .collect::<Option<BTreeMap<String, u32>>>()?;
+            (!weights.is_empty() && weights.values().any(|weight| *weight > 0))
+                .then_some(QuorumPolicy::Weighted { weights })
+        }
+        _ => None,
+    }
+}
+
+/// Parse a `+` or `/` separated list of shard numbers
+fn shards(text: &str) -> Option<Vec<u64>> {
+    list(text).iter().map(|shard| shard.parse().ok()).collect()
+}
+
+/// Split a `+` or `/` separated list
+fn list(text: &str) -> Vec<String> {
+    text.split(['+', '/'])
+        .filter(|item| !item.is_empty())
+        .map(str::to_string)
+        .collect()
+}
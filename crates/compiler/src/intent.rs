@@ -0,0 +1,113 @@
+//! Taker fill intents
+//!
+//! Compiles taker-side English ("fill quote X for 0.5 dETH at market using
+//! FeedA+FeedB") into a [`FillIntent`], from which an agent builds the
+//! [`FillRequest`] body once it has collected feed evidence.
+
+use rfq_models::{FeedEvidence, FillRequest, QuoteId};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{CompilerError, ConstraintError};
+
+/// Fill terms extracted by the LLM
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ParsedFillIntent {
+    /// ID of the quote to fill, as written
+    #[serde(default)]
+    pub quote_id: Option<String>,
+    #[serde(default)]
+    pub size: Option<f64>,
+    /// Price offered (None = at market)
+    #[serde(default)]
+    pub price: Option<f64>,
+    /// Feeds to take price evidence from (empty = the quote's sources)
+    #[serde(default)]
+    pub sources: Vec<String>,
+    /// Questions for the taker when essential terms are vague or missing
+    #[serde(default)]
+    pub clarifications: Vec<String>,
+}
+
+/// A taker's fill, compiled from English
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FillIntent {
+    /// Quote to fill (None if the text didn't name one)
+    pub quote_id: Option<QuoteId>,
+    pub size: f64,
+    /// Price offered (None = at market)
+    pub price: Option<f64>,
+    /// Feeds to take price evidence from (empty = the quote's sources)
+    pub sources: Vec<String>,
+}
+
+impl FillIntent {
+    /// Build the fill request body
+    ///
+    /// `market_price` is offered when the intent is at market, and
+    /// `feed_evidence` should come from the intent's sources.
+    pub fn to_request(
+        &self,
+        taker_owner_id: &str,
+        taker_shard: u64,
+        market_price: f64,
+        feed_evidence: Vec<FeedEvidence>,
+        taker_nonce: u64,
+    ) -> FillRequest {
+        FillRequest {
+            taker_owner_id: taker_owner_id.to_string(),
+            taker_shard,
+            size: self.size,
+            price: self.price.unwrap_or(market_price),
+            feed_evidence,
+            taker_nonce,
+            taker_signature: None,
+            extensions: Default::default(),
+            max_wait_ms: None,
+        }
+    }
+}
+
+impl TryFrom<ParsedFillIntent> for FillIntent {
+    type Error = CompilerError;
+
+    fn try_from(parsed: ParsedFillIntent) -> Result<Self, CompilerError> {
+        let mut questions = parsed.clarifications;
+        let quote_id = match &parsed.quote_id {
+            Some(id) => match QuoteId::parse_str(id.trim()) {
+                Ok(id) => Some(id),
+                Err(_) => {
+                    questions.push(format!("\"{}\" isn't a quote ID; which quote?", id));
+                    None
+                }
+            },
+            None => None,
+        };
+        if parsed.size.is_none() && questions.is_empty() {
+            questions.push("How much do you want to fill?".to_string());
+        }
+        if !questions.is_empty() {
+            return Err(CompilerError::NeedsClarification { questions });
+        }
+
+        let size = parsed.size.unwrap_or_default();
+        // Written so NaN fails too
+        if !(size > 0.0 && size.is_finite()) {
+            return Err(ConstraintError::NonPositiveSize(size).into());
+        }
+        if let Some(value) = parsed.price.filter(|p| !(*p > 0.0 && p.is_finite())) {
+            return Err(ConstraintError::NonPositivePrice {
+                field: "price",
+                value,
+            }
+            .into());
+        }
+
+        Ok(Self {
+            quote_id,
+            size,
+            price: parsed.price,
+            sources: parsed.sources,
+        })
+    }
+}
@@ -0,0 +1,125 @@
+//! Quote linting
+//!
+//! Flags compiled terms that are legal but risky (no price limit, a
+//! single-source quorum, a days-long expiry, any taker), so a maker can
+//! tighten the wording before posting a live quote. Warnings are ranked
+//! most risky first. [`lint_constraints`] checks compiled constraints on
+//! their own, without the quote spec.
+
+use rfq_models::{QuoteConstraints, QuoteSpec, Side};
+use serde::{Deserialize, Serialize};
+
+/// Expiries further out than this are flagged
+pub const LONG_EXPIRY_SECS: u64 = 24 * 60 * 60;
+
+/// How much a lint warning matters
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LintRisk {
+    Low,
+    Medium,
+    High,
+}
+
+/// A risky term in a compiled quote
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LintWarning {
+    /// Stable machine-readable code (e.g. "NO_PRICE_LIMIT")
+    pub code: String,
+    pub risk: LintRisk,
+    /// Constraint field the warning is about
+    pub field: String,
+    pub message: String,
+}
+
+impl LintWarning {
+    fn new(code: &str, risk: LintRisk, field: &str, message: String) -> Self {
+        Self {
+            code: code.to_string(),
+            risk,
+            field: field.to_string(),
+            message,
+        }
+    }
+}
+
+/// Lint a compiled quote at unix time `now`, most risky warnings first
+pub fn lint_quote(spec: &QuoteSpec, constraints: &QuoteConstraints, now: u64) -> Vec<LintWarning> {
+    let (unbounded, field, bound) = match spec.side {
+        Side::Buy => (constraints.max_debit == u64::MAX, "max_debit", "maximum"),
+        Side::Sell => (constraints.min_credit.is_none(), "min_credit", "minimum"),
+    };
+    let price = unbounded.then(|| {
+        LintWarning::new(
+            "NO_PRICE_LIMIT",
+            LintRisk::High,
+            field,
+            format!("No {} price: the quote fills at whatever price the feeds report", bound),
+        )
+    });
+    lint(constraints, now, price)
+}
+
+/// Lint compiled constraints as of now, most risky warnings first
+///
+/// Without the spec the side is unknown, so a missing price limit is only
+/// flagged when there is neither a maximum debit nor a minimum credit.
+pub fn lint_constraints(constraints: &QuoteConstraints) -> Vec<LintWarning> {
+    let unbounded = constraints.max_debit == u64::MAX && constraints.min_credit.is_none();
+    let price = unbounded.then(|| {
+        LintWarning::new(
+            "NO_PRICE_LIMIT",
+            LintRisk::High,
+            "max_debit",
+            "No price limit: the quote fills at whatever price the feeds report".to_string(),
+        )
+    });
+    let now = chrono::Utc::now().timestamp() as u64;
+    lint(constraints, now, price)
+}
+
+fn lint(constraints: &QuoteConstraints, now: u64, price: Option<LintWarning>) -> Vec<LintWarning> {
+    let mut warnings: Vec<LintWarning> = price.into_iter().collect();
+
+    if constraints.allowed_sources.is_empty() {
+        warnings.push(LintWarning::new(
+            "ANY_FEED_SOURCE",
+            LintRisk::High,
+            "allowed_sources",
+            "No price feeds named: fills can cite any source".to_string(),
+        ));
+    } else if constraints.quorum_count <= 1 {
+        warnings.push(LintWarning::new(
+            "SINGLE_SOURCE_QUORUM",
+            LintRisk::Medium,
+            "quorum_count",
+            "A quorum of 1 lets a single compromised or stale feed price the fill".to_string(),
+        ));
+    }
+
+    let lifetime = constraints.expiry_timestamp.saturating_sub(now);
+    if lifetime > LONG_EXPIRY_SECS {
+        warnings.push(LintWarning::new(
+            "LONG_EXPIRY",
+            LintRisk::Medium,
+            "expiry_timestamp",
+            format!(
+                "Expires in {:.1} hours; a long-lived quote can be filled after the market moves",
+                lifetime as f64 / 3600.0
+            ),
+        ));
+    }
+
+    if constraints.allowed_takers.is_empty() {
+        warnings.push(LintWarning::new(
+            "ANY_TAKER",
+            LintRisk::Low,
+            "allowed_takers",
+            "Any taker can fill the quote".to_string(),
+        ));
+    }
+
+    // Stable sort keeps the order above within a risk level
+    warnings.sort_by_key(|warning| std::cmp::Reverse(warning.risk));
+    warnings
+}
@@ -0,0 +1,487 @@
+//! Compile-only previews
+//!
+//! [`Compiler::preview`](crate::Compiler::preview) compiles quote text
+//! without binding it to a quote ID, lints it, and traces each
+//! enforced field back to the phrase of the text it came from. Frontends
+//! show makers exactly what will be enforced, and where each term came
+//! from, before anything is posted. Terms no phrase accounts for are
+//! attributed to the preset, the compiler's defaults, or (if neither
+//! explains them) the compiler's own reading of the text.
+
+use rfq_models::{QuorumPolicy, QuoteConstraints, QuoteSpec, Side};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::{
+    explain_constraints, shorthand, AssetRegistry, CompileMethod, ConstraintExplanation,
+    ConstraintPreset, LintWarning,
+};
+
+/// Words either side of a keyword its value may be written at
+const KEYWORD_WINDOW: usize = 3;
+
+/// Words that qualify a price limit ("at most 2000", "no more than 2000")
+const PRICE_QUALIFIERS: [&str; 13] = [
+    "at", "most", "least", "no", "more", "less", "than", "max", "min", "under", "below", "above",
+    "over",
+];
+
+/// Where an enforced value came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FieldOrigin {
+    /// Written in the text (see [`FieldSource::phrase`])
+    Text,
+    /// Filled in by the preset the text names
+    Preset,
+    /// The compiler's default for a term the text doesn't state
+    Default,
+    /// Not written in the text, but not a default either: check it
+    Inferred,
+    /// Set by an explicit field of the create request, over the compiled value
+    Request,
+    /// Changed by an amendment to the open quote
+    Amendment,
+}
+
+/// An enforced field traced back to the quote text
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldSource {
+    /// Spec or constraint field (e.g. "max_debit")
+    pub field: String,
+    /// The value that will be enforced, as displayed
+    pub value: String,
+    pub origin: FieldOrigin,
+    /// The phrase of the text the value was read from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub phrase: Option<String>,
+    /// Byte range of the phrase in the text
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub span: Option<(usize, usize)>,
+}
+
+/// What a quote text would compile to, without posting it
+#[derive(Debug, Clone, Serialize)]
+pub struct QuotePreview {
+    /// What the maker wants to trade
+    pub spec: QuoteSpec,
+    /// The guardrails that would be enforced (zero quote ID)
+    pub constraints: QuoteConstraints,
+    /// The preset referenced by the text, if any
+    pub preset: Option<String>,
+    /// Critical fields the LLM was unsure of, with their confidence
+    pub low_confidence: BTreeMap<String, f64>,
+    /// Risky terms, most risky first
+    pub warnings: Vec<LintWarning>,
+    /// Every enforced field and where it came from
+    pub fields: Vec<FieldSource>,
+    /// How the text was compiled
+    pub method: CompileMethod,
+}
+
+impl QuotePreview {
+    /// Explain the constraints that would be enforced
+    pub fn explanation(&self) -> ConstraintExplanation {
+        explain_constraints(&self.constraints, &self.fields, self.preset.as_deref(), &[])
+    }
+}
+
+/// A word of the text and its byte range
+struct Word<'a> {
+    text: &'a str,
+    start: usize,
+    end: usize,
+}
+
+/// Words of `text`, split at whitespace and punctuation
+///
+/// Commas between digits ("1,950") and decimal points stay in the word;
+/// a sentence's final period doesn't.
+fn words(text: &str) -> Vec<Word<'_>> {
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+    let digit = |j: Option<usize>| {
+        j.and_then(|j| chars.get(j))
+            .is_some_and(|(_, c)| c.is_ascii_digit())
+    };
+    let mut words = vec![];
+    let mut start = None;
+    for (k, &(i, c)) in chars.iter().enumerate() {
+        let part = c.is_alphanumeric()
+            || matches!(c, '.' | '%' | '$' | '-' | '_')
+            || (c == ',' && digit(k.checked_sub(1)) && digit(Some(k + 1)));
+        match (part, start) {
+            (true, None) => start = Some(i),
+            (false, Some(from)) => {
+                words.push((from, i));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(from) = start {
+        words.push((from, text.len()));
+    }
+    words
+        .into_iter()
+        .filter_map(|(start, end)| {
+            let word = text[start..end].trim_end_matches(['.', '-']);
+            (!word.is_empty()).then_some(Word {
+                text: word,
+                start,
+                end: start + word.len(),
+            })
+        })
+        .collect()
+}
+
+/// An amount written as a word ("1,950.50", "$2k")
+fn amount(word: &str) -> Option<f64> {
+    shorthand::amount(&word.trim_start_matches('$').replace(',', ""))
+}
+
+/// A percentage written as a word ("0.5%", "25bps"), not a bare number
+fn percent(word: &str) -> Option<f64> {
+    let lower = word.to_lowercase();
+    (lower.ends_with('%') || lower.ends_with("bp") || lower.ends_with("bps"))
+        .then(|| shorthand::percent(word))
+        .flatten()
+}
+
+/// Seconds in a duration unit
+fn unit_secs(unit: &str) -> Option<u64> {
+    match unit.to_lowercase().as_str() {
+        "s" | "sec" | "secs" | "second" | "seconds" => Some(1),
+        "m" | "min" | "mins" | "minute" | "minutes" => Some(60),
+        "h" | "hr" | "hrs" | "hour" | "hours" => Some(3600),
+        "d" | "day" | "days" => Some(86400),
+        _ => None,
+    }
+}
+
+fn same(a: f64, b: f64) -> bool {
+    (a - b).abs() <= b.abs() * 1e-9
+}
+
+/// Traces compiled fields back to the words of a text
+struct Tracer<'a> {
+    text: &'a str,
+    words: Vec<Word<'a>>,
+    preset: Option<&'a ConstraintPreset>,
+    defaults: QuoteConstraints,
+    fields: Vec<FieldSource>,
+}
+
+impl<'a> Tracer<'a> {
+    fn is(&self, i: usize, candidates: &[&str]) -> bool {
+        let word = self.words[i].text;
+        candidates.iter().any(|c| word.eq_ignore_ascii_case(c))
+    }
+
+    /// First word equal to one of `candidates`
+    fn word(&self, candidates: &[&str]) -> Option<(usize, usize)> {
+        (0..self.words.len())
+            .find(|&i| self.is(i, candidates))
+            .map(|i| (i, i))
+    }
+
+    /// First word holding `value`
+    fn number(&self, value: f64) -> Option<usize> {
+        (0..self.words.len()).find(|&i| amount(self.words[i].text).is_some_and(|n| same(n, value)))
+    }
+
+    /// A word matching `matches` within [`KEYWORD_WINDOW`] words of a keyword
+    ///
+    /// Keywords are tried in order, so the most specific should come first.
+    fn near(&self, keywords: &[&str], matches: impl Fn(&str) -> bool) -> Option<(usize, usize)> {
+        keywords
+            .iter()
+            .flat_map(|keyword| (0..self.words.len()).filter(|&k| self.is(k, &[keyword])))
+            .find_map(|k| {
+                let from = k.saturating_sub(KEYWORD_WINDOW);
+                let to = (k + KEYWORD_WINDOW).min(self.words.len() - 1);
+                (from..=to)
+                    .filter(|&i| i != k)
+                    .find(|&i| matches(self.words[i].text))
+                    .map(|i| (k.min(i), k.max(i)))
+            })
+    }
+
+    /// A duration of `secs` ("10m", "5 seconds")
+    fn duration(&self, secs: u64) -> Option<(usize, usize)> {
+        (0..self.words.len()).find_map(|i| {
+            let word = self.words[i].text;
+            let split = word
+                .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+                .unwrap_or(word.len());
+            let (number, unit) = word.split_at(split);
+            let number: f64 = number.parse().ok()?;
+            let (unit, end) = if unit.is_empty() {
+                (self.words.get(i + 1)?.text, i + 1)
+            } else {
+                (unit, i)
+            };
+            let total = number * unit_secs(unit)? as f64;
+            (total.round() as u64 == secs).then_some((i, end))
+        })
+    }
+
+    /// Words naming every one of `names`, from the first to the last
+    fn all(&self, names: &[String]) -> Option<(usize, usize)> {
+        let found: Option<Vec<usize>> = names
+            .iter()
+            .map(|name| (0..self.words.len()).find(|&i| self.is(i, &[name.as_str()])))
+            .collect();
+        let found = found.filter(|found| !found.is_empty())?;
+        Some((*found.iter().min()?, *found.iter().max()?))
+    }
+
+    /// Record a field found at `span` words, or attribute it elsewhere
+    fn push(
+        &mut self,
+        field: &str,
+        value: String,
+        span: Option<(usize, usize)>,
+        preset: bool,
+        default: bool,
+    ) {
+        let (origin, phrase, span) = match span {
+            Some((first, last)) => {
+                let (start, end) = (self.words[first].start, self.words[last].end);
+                (FieldOrigin::Text, Some(self.text[start..end].to_string()), Some((start, end)))
+            }
+            None if preset => (FieldOrigin::Preset, None, None),
+            None if default => (FieldOrigin::Default, None, None),
+            None => (FieldOrigin::Inferred, None, None),
+        };
+        self.fields.push(FieldSource {
+            field: field.to_string(),
+            value,
+            origin,
+            phrase,
+            span,
+        });
+    }
+}
+
+/// Trace the fields of a compilation of `text` back to its phrases
+///
+/// `now` is the unix time the text was compiled at, which the expiry is
+/// relative to.
+pub(crate) fn trace(
+    text: &str,
+    spec: &QuoteSpec,
+    constraints: &QuoteConstraints,
+    preset: Option<&ConstraintPreset>,
+    assets: &AssetRegistry,
+    now: u64,
+) -> Vec<FieldSource> {
+    let mut t = Tracer {
+        text,
+        words: words(text),
+        preset,
+        defaults: QuoteConstraints::new(constraints.quote_id),
+        fields: vec![],
+    };
+    let c = constraints;
+    let named = t.preset;
+    let preset = |f: fn(&ConstraintPreset) -> bool| named.is_some_and(f);
+
+    let (side, side_words): (_, &[&str]) = match spec.side {
+        Side::Buy => ("buy", &["buy", "buying", "bid", "bids", "purchase", "long"]),
+        Side::Sell => ("sell", &["sell", "selling", "offer", "offering", "ask", "short"]),
+    };
+    let span = t.word(side_words);
+    t.push("side", side.to_string(), span, false, false);
+
+    let span = t.word(&[spec.asset.as_str()]);
+    t.push("asset", spec.asset.clone(), span, false, false);
+    // The text may give the total rather than the size per fill
+    let span = t
+        .number(spec.size)
+        .or_else(|| t.number(spec.size * f64::from(c.max_fills)))
+        .map(|i| (i, i));
+    t.push("size", spec.size.to_string(), span, false, false);
+    if !spec.legs.is_empty() {
+        let assets: Vec<String> = spec.legs.iter().map(|leg| leg.asset.clone()).collect();
+        let span = t.all(&assets);
+        let value = spec
+            .legs
+            .iter()
+            .map(|leg| format!("{} {}", leg.size, leg.asset))
+            .collect::<Vec<_>>()
+            .join(", ");
+        t.push("legs", value, span, false, false);
+    }
+    if let Some(iceberg) = &c.iceberg {
+        let total = iceberg.total_size as f64 / assets.scale(&spec.asset);
+        let span = t.number(total).map(|i| (i, i));
+        t.push("iceberg_total_size", total.to_string(), span, false, false);
+    }
+    let span = t.word(&[spec.currency.as_str()]);
+    t.push("currency", spec.currency.clone(), span, false, false);
+
+    let field = match spec.side {
+        Side::Buy => "max_debit",
+        Side::Sell => "min_credit",
+    };
+    match spec.limit_price {
+        Some(price) => {
+            // Take in the words qualifying the price ("at most 2000")
+            let span = t.number(price).map(|i| {
+                let qualifiers = (0..i)
+                    .rev()
+                    .take(3)
+                    .take_while(|&j| t.is(j, &PRICE_QUALIFIERS))
+                    .count();
+                (i - qualifiers, i)
+            });
+            let value = format!("{} {} per {}", price, spec.currency, spec.asset);
+            t.push(field, value, span, false, false);
+        }
+        None => t.push(field, "none".to_string(), None, false, true),
+    }
+
+    let lifetime = c.expiry_timestamp.saturating_sub(now);
+    // Expiries are whole minutes; allow for the time since compiling
+    let span = t.duration(lifetime.div_ceil(60) * 60);
+    let value = c.expiry_datetime().to_rfc3339();
+    t.push("expiry_timestamp", value, span, false, false);
+
+    let span = t.all(&c.allowed_sources);
+    let value = c.allowed_sources.join(", ");
+    let from_preset = preset(|p| p.allowed_sources.is_some());
+    let empty = c.allowed_sources.is_empty();
+    t.push("allowed_sources", value, span, from_preset, empty);
+
+    let quorum = c.quorum_count as f64;
+    let span = t.near(&["quorum", "of", "agree", "agreeing", "sources", "feeds"], |w| {
+        amount(w).is_some_and(|n| same(n, quorum))
+    });
+    let from_preset = preset(|p| p.quorum_count.is_some());
+    let default = c.quorum_count == t.defaults.quorum_count;
+    t.push("quorum_count", c.quorum_count.to_string(), span, from_preset, default);
+
+    let tolerance = c.quorum_tolerance_percent();
+    let span = t.near(&["tolerance", "within", "spread", "agree", "agreeing"], |w| {
+        percent(w).is_some_and(|p| same(p, tolerance))
+    });
+    let from_preset = preset(|p| p.quorum_tolerance_percent.is_some());
+    let default = c.quorum_tolerance_bps == t.defaults.quorum_tolerance_bps;
+    let value = format!("{}%", tolerance);
+    t.push("quorum_tolerance_bps", value, span, from_preset, default);
+
+    if c.quorum_policy != QuorumPolicy::Spread {
+        let span = t.word(&["median", "trimmed", "weighted", "weights"]);
+        t.push("quorum_policy", c.quorum_policy.to_string(), span, false, false);
+    }
+
+    let span = t.duration(c.max_staleness_secs);
+    let from_preset = preset(|p| p.max_staleness_secs.is_some());
+    let default = c.max_staleness_secs == t.defaults.max_staleness_secs;
+    let value = format!("{}s", c.max_staleness_secs);
+    t.push("max_staleness_secs", value, span, from_preset, default);
+    for (source, secs) in &c.source_staleness_secs {
+        let span = t.duration(*secs);
+        let value = format!("{}: {}s", source, secs);
+        t.push("source_staleness_secs", value, span, false, false);
+    }
+
+    let span = t.all(&c.allowed_takers);
+    let empty = c.allowed_takers.is_empty();
+    let value = if empty {
+        "any".to_string()
+    } else {
+        c.allowed_takers.join(", ")
+    };
+    t.push("allowed_takers", value, span, false, empty);
+
+    if !c.denied_takers.is_empty() {
+        let span = t.all(&c.denied_takers);
+        t.push("denied_takers", c.denied_takers.join(", "), span, false, false);
+    }
+
+    if !c.allowed_taker_shards.is_empty() {
+        let shards = &c.allowed_taker_shards;
+        let span = t.near(&["shard", "shards", "taker_shards"], |w| {
+            w.split(['+', '/', ','])
+                .any(|s| s.parse().is_ok_and(|n: u64| shards.contains(&n)))
+        });
+        let value = shards.iter().map(u64::to_string).collect::<Vec<_>>().join(", ");
+        t.push("allowed_taker_shards", value, span, false, false);
+    }
+
+    let fills = c.max_fills as f64;
+    let span = t.near(&["fills", "fill", "times", "max_fills"], |w| {
+        amount(w).is_some_and(|n| same(n, fills))
+    });
+    let default = c.max_fills == t.defaults.max_fills;
+    t.push("max_fills", c.max_fills.to_string(), span, false, default);
+
+    if let Some(per_taker) = c.max_fills_per_taker {
+        let count = per_taker as f64;
+        let keywords = ["taker", "each", "max_fills_per_taker"];
+        let span = t.near(&keywords, |w| amount(w).is_some_and(|n| same(n, count)));
+        t.push("max_fills_per_taker", per_taker.to_string(), span, false, false);
+    }
+    if let Some(units) = c.max_notional_per_taker {
+        let notional = units as f64 / assets.scale(&spec.currency);
+        let span = t.near(&["notional", "taker", "max_notional_per_taker"], |w| {
+            amount(w).is_some_and(|n| same(n, notional))
+        });
+        t.push("max_notional_per_taker", notional.to_string(), span, false, false);
+    }
+    if let Some(benchmark) = &c.benchmark {
+        let span = t.word(&["twap"]);
+        let value = format!("TWAP over {}s", benchmark.window_secs);
+        t.push("benchmark", value, span, false, false);
+    }
+
+    if let Some(units) = c.min_fill_size {
+        let size = units as f64 / assets.scale(&spec.asset);
+        let span = t.near(&["min", "minimum", "least", "min_fill"], |w| {
+            amount(w).is_some_and(|n| same(n, size))
+        });
+        t.push("min_fill_size", size.to_string(), span, false, false);
+    }
+    if c.allow_partial_fills {
+        // A minimum fill size allows partial fills unless the text says otherwise
+        let span = t.word(&["partial", "partially"]);
+        let implied = c.min_fill_size.is_some();
+        t.push("allow_partial_fills", "true".to_string(), span, false, implied);
+    }
+    if let Some(window) = c.trading_window {
+        let span = t.near(&["window", "between", "from"], |w| w.contains(':'));
+        t.push("trading_window", window.to_string(), span, false, false);
+    }
+    if let Some(window) = c.settlement_window_secs {
+        let span = t.duration(window);
+        t.push("settlement_window_secs", format!("{}s", window), span, false, false);
+    }
+    if let Some(deviation) = c.max_price_deviation_percent() {
+        let span = t.near(&["deviation", "deviate", "median", "from"], |w| {
+            percent(w).is_some_and(|p| same(p, deviation))
+        });
+        let value = format!("{}%", deviation);
+        t.push("max_price_deviation_bps", value, span, false, false);
+    }
+    if let Some(fee) = c.max_fee_percent() {
+        let span = t.near(&["fee", "fees"], |w| percent(w).is_some_and(|p| same(p, fee)));
+        t.push("max_fee_bps", format!("{}%", fee), span, false, false);
+    }
+
+    let span = t.word(&["atomic", "dvp"]);
+    let from_preset = preset(|p| p.require_atomic_dvp.is_some());
+    let default = c.require_atomic_dvp == t.defaults.require_atomic_dvp;
+    let value = c.require_atomic_dvp.to_string();
+    t.push("require_atomic_dvp", value, span, from_preset, default);
+
+    let span = t
+        .word(&["side-payments", "side-payment"])
+        .or_else(|| t.near(&["side"], |w| w.to_lowercase().starts_with("payment")));
+    let from_preset = preset(|p| p.no_side_payments.is_some());
+    let default = c.no_side_payments == t.defaults.no_side_payments;
+    let value = c.no_side_payments.to_string();
+    t.push("no_side_payments", value, span, from_preset, default);
+
+    t.fields
+}
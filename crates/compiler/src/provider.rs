@@ -0,0 +1,829 @@
+//! LLM providers
+//!
+//! The compiler talks to LLMs through the [`LlmProvider`] trait. Claude,
+//! Gemini, Azure OpenAI and OpenAI-compatible endpoints (OpenAI, Ollama,
+//! vLLM, ...) are built in, as is an offline [`MockLlmProvider`] for tests;
+//! implement the trait to plug in anything else via
+//! [`Compiler::with_provider`].
+//!
+//! [`Compiler::with_provider`]: crate::Compiler::with_provider
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::CompilerError;
+
+/// JSON schema the model's answer must conform to
+#[derive(Debug, Clone)]
+pub struct OutputSchema {
+    /// Tool/function name presented to the model
+    pub name: String,
+    /// What the tool does, shown to the model
+    pub description: String,
+    /// JSON schema of the tool input
+    pub schema: serde_json::Value,
+}
+
+/// Tokens billed for one completion
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenUsage {
+    pub input_tokens: u32,
+    pub output_tokens: u32,
+}
+
+/// A model's answer to a prompt
+#[derive(Debug, Clone)]
+pub struct Completion {
+    pub text: String,
+    /// Tokens used, if the provider reports them
+    pub usage: Option<TokenUsage>,
+}
+
+impl From<String> for Completion {
+    fn from(text: String) -> Self {
+        Self { text, usage: None }
+    }
+}
+
+/// Longest answer asked of APIs that need a limit (Anthropic's)
+pub const DEFAULT_MAX_TOKENS: u32 = 1024;
+
+/// How a model samples its answer
+///
+/// Unset parameters are left to the provider's defaults. Not every API
+/// takes every parameter (Anthropic's has no seed); a provider reports
+/// the ones it actually sends through [`LlmProvider::sampling`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct SamplingParams {
+    #[serde(default)]
+    pub temperature: Option<f64>,
+    #[serde(default)]
+    pub top_p: Option<f64>,
+    /// Seed for providers that sample reproducibly given one
+    #[serde(default)]
+    pub seed: Option<u64>,
+    /// Longest answer, in tokens
+    #[serde(default)]
+    pub max_tokens: Option<u32>,
+}
+
+impl SamplingParams {
+    /// Write the set parameters into `object` under the API's names for
+    /// temperature, top-p, seed and max tokens
+    fn apply(&self, object: &mut serde_json::Value, names: [&str; 4]) {
+        let values = [
+            self.temperature.map(Into::into),
+            self.top_p.map(Into::into),
+            self.seed.map(Into::into),
+            self.max_tokens.map(Into::into),
+        ];
+        for (name, value) in names.into_iter().zip(values) {
+            if let Some(value) = value {
+                object[name] = value;
+            }
+        }
+    }
+}
+
+/// A text completion backend
+#[async_trait]
+pub trait LlmProvider: Send + Sync {
+    /// Short provider name for logs (e.g. "claude")
+    fn name(&self) -> &str;
+
+    /// Model answering prompts, used to key cached compilations
+    ///
+    /// Defaults to the provider name.
+    fn model(&self) -> &str {
+        self.name()
+    }
+
+    /// Sampling parameters sent with every prompt, recorded with each call
+    ///
+    /// None if the provider doesn't report them.
+    fn sampling(&self) -> Option<SamplingParams> {
+        None
+    }
+
+    /// Complete a single-turn prompt, returning the model's text
+    async fn complete(&self, prompt: &str) -> Result<Completion, CompilerError>;
+
+    /// Complete a prompt with output constrained to `schema`, returning JSON
+    ///
+    /// Providers with native structured output (tool use, function calling)
+    /// override this; the default falls back to [`LlmProvider::complete`].
+    async fn complete_structured(
+        &self,
+        prompt: &str,
+        schema: &OutputSchema,
+    ) -> Result<Completion, CompilerError> {
+        let _ = schema;
+        self.complete(prompt).await
+    }
+}
+
+/// Map an unsuccessful HTTP status to an error
+///
+/// 408, 429 and 5xx (including Anthropic's 529 "overloaded") are retryable.
+fn status_error(provider: &str, status: reqwest::StatusCode, body: String) -> CompilerError {
+    let message = format!("{} API error ({}): {}", provider, status, body);
+    if status.as_u16() == 408 || status.as_u16() == 429 || status.is_server_error() {
+        CompilerError::ProviderUnavailable(message)
+    } else {
+        CompilerError::ApiError(message)
+    }
+}
+
+/// Anthropic Messages API provider
+pub struct ClaudeProvider {
+    client: reqwest::Client,
+    api_key: String,
+    model: String,
+    sampling: SamplingParams,
+}
+
+impl ClaudeProvider {
+    pub fn new(api_key: impl Into<String>, model: impl Into<String>) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            api_key: api_key.into(),
+            model: model.into(),
+            sampling: SamplingParams::default(),
+        }
+        .with_sampling(SamplingParams::default())
+    }
+
+    /// Sample with `sampling`
+    ///
+    /// The Messages API takes no seed and requires a token limit, so the
+    /// seed is dropped and the limit defaults to [`DEFAULT_MAX_TOKENS`].
+    pub fn with_sampling(mut self, sampling: SamplingParams) -> Self {
+        self.sampling = SamplingParams {
+            seed: None,
+            max_tokens: Some(sampling.max_tokens.unwrap_or(DEFAULT_MAX_TOKENS)),
+            ..sampling
+        };
+        self
+    }
+
+    /// Send a Messages API request, returning the response body
+    async fn send(&self, mut request: serde_json::Value) -> Result<String, CompilerError> {
+        self.sampling
+            .apply(&mut request, ["temperature", "top_p", "seed", "max_tokens"]);
+
+        let response = self
+            .client
+            .post("https://api.anthropic.com/v1/messages")
+            .header("x-api-key", &self.api_key)
+            .header("anthropic-version", "2023-06-01")
+            .header("content-type", "application/json")
+            .json(&request)
+            .send()
+            .await
+            .map_err(|e| CompilerError::ProviderUnavailable(e.to_string()))?;
+
+        let status = response.status();
+        let body = response
+            .text()
+            .await
+            .map_err(|e| CompilerError::ProviderUnavailable(format!("Failed to read response: {}", e)))?;
+
+        if !status.is_success() {
+            return Err(status_error("Claude", status, body));
+        }
+        Ok(body)
+    }
+}
+
+#[async_trait]
+impl LlmProvider for ClaudeProvider {
+    fn name(&self) -> &str {
+        "claude"
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    fn sampling(&self) -> Option<SamplingParams> {
+        Some(self.sampling)
+    }
+
+    async fn complete(&self, prompt: &str) -> Result<Completion, CompilerError> {
+        #[derive(Deserialize)]
+        struct ClaudeResponse {
+            content: Vec<ClaudeContent>,
+            #[serde(default)]
+            usage: Option<TokenUsage>,
+        }
+
+        #[derive(Deserialize)]
+        struct ClaudeContent {
+            text: String,
+        }
+
+        let body = self
+            .send(serde_json::json!({
+                "model": self.model,
+                "messages": [{ "role": "user", "content": prompt }],
+            }))
+            .await?;
+
+        let claude_response: ClaudeResponse = serde_json::from_str(&body)
+            .map_err(|e| CompilerError::ApiError(format!("Failed to parse response: {} - body: {}", e, body)))?;
+
+        let text = claude_response
+            .content
+            .first()
+            .map(|c| c.text.clone())
+            .ok_or_else(|| CompilerError::ApiError("Empty response".to_string()))?;
+        Ok(Completion {
+            text,
+            usage: claude_response.usage,
+        })
+    }
+
+    /// Forces a call to a single tool whose input schema is `schema`
+    async fn complete_structured(
+        &self,
+        prompt: &str,
+        schema: &OutputSchema,
+    ) -> Result<Completion, CompilerError> {
+        #[derive(Deserialize)]
+        struct ClaudeResponse {
+            content: Vec<ClaudeBlock>,
+            #[serde(default)]
+            usage: Option<TokenUsage>,
+        }
+
+        #[derive(Deserialize)]
+        struct ClaudeBlock {
+            #[serde(rename = "type")]
+            kind: String,
+            #[serde(default)]
+            input: serde_json::Value,
+        }
+
+        let body = self
+            .send(serde_json::json!({
+                "model": self.model,
+                "messages": [{ "role": "user", "content": prompt }],
+                "tools": [{
+                    "name": schema.name,
+                    "description": schema.description,
+                    "input_schema": schema.schema,
+                }],
+                "tool_choice": { "type": "tool", "name": schema.name },
+            }))
+            .await?;
+
+        let claude_response: ClaudeResponse = serde_json::from_str(&body)
+            .map_err(|e| CompilerError::ApiError(format!("Failed to parse response: {} - body: {}", e, body)))?;
+
+        let text = claude_response
+            .content
+            .into_iter()
+            .find(|block| block.kind == "tool_use")
+            .map(|block| block.input.to_string())
+            .ok_or_else(|| CompilerError::ApiError("Response contained no tool call".to_string()))?;
+        Ok(Completion {
+            text,
+            usage: claude_response.usage,
+        })
+    }
+}
+
+/// Token counts as reported by OpenAI-compatible APIs
+#[derive(Deserialize)]
+struct GptUsage {
+    prompt_tokens: u32,
+    completion_tokens: u32,
+}
+
+impl From<GptUsage> for TokenUsage {
+    fn from(usage: GptUsage) -> Self {
+        Self {
+            input_tokens: usage.prompt_tokens,
+            output_tokens: usage.completion_tokens,
+        }
+    }
+}
+
+/// Default OpenAI API base URL
+pub const OPENAI_BASE_URL: &str = "https://api.openai.com/v1";
+
+/// Default base URL of a local Ollama server's OpenAI-compatible API
+pub const OLLAMA_BASE_URL: &str = "http://localhost:11434/v1";
+
+/// Azure OpenAI API version used when none is configured
+pub const AZURE_API_VERSION: &str = "2024-10-21";
+
+/// How requests to an OpenAI-compatible endpoint are authenticated
+enum OpenAiAuth {
+    /// `Authorization: Bearer <key>` (none if the key is empty)
+    Bearer,
+    /// Azure's `api-key: <key>` header
+    AzureKey,
+}
+
+/// OpenAI Chat Completions API provider
+///
+/// Also serves any OpenAI-compatible endpoint, such as a local Ollama
+/// server, via [`OpenAiProvider::with_base_url`], and Azure OpenAI
+/// deployments via [`OpenAiProvider::azure`].
+pub struct OpenAiProvider {
+    client: reqwest::Client,
+    name: String,
+    /// Chat completions URL
+    url: String,
+    auth: OpenAiAuth,
+    api_key: String,
+    model: String,
+    sampling: SamplingParams,
+}
+
+impl OpenAiProvider {
+    pub fn new(api_key: impl Into<String>, model: impl Into<String>) -> Self {
+        Self::with_base_url("gpt", OPENAI_BASE_URL, api_key, model)
+    }
+
+    /// Provider for an OpenAI-compatible endpoint at `base_url`
+    ///
+    /// `name` is used in logs; an empty `api_key` sends no Authorization header.
+    pub fn with_base_url(
+        name: impl Into<String>,
+        base_url: impl Into<String>,
+        api_key: impl Into<String>,
+        model: impl Into<String>,
+    ) -> Self {
+        let base_url = base_url.into();
+        Self {
+            client: reqwest::Client::new(),
+            name: name.into(),
+            url: format!("{}/chat/completions", base_url.trim_end_matches('/')),
+            auth: OpenAiAuth::Bearer,
+            api_key: api_key.into(),
+            model: model.into(),
+            sampling: SamplingParams::default(),
+        }
+    }
+
+    /// Provider for an Azure OpenAI deployment
+    ///
+    /// `endpoint` is the resource URL (e.g. `https://my-org.openai.azure.com`)
+    /// and `api_version` the REST API version sent with every request. The
+    /// deployment name stands in for the model in logs and cache keys.
+    pub fn azure(
+        endpoint: impl Into<String>,
+        deployment: impl Into<String>,
+        api_version: impl Into<String>,
+        api_key: impl Into<String>,
+    ) -> Self {
+        let (endpoint, deployment) = (endpoint.into(), deployment.into());
+        Self {
+            client: reqwest::Client::new(),
+            name: "azure".to_string(),
+            url: format!(
+                "{}/openai/deployments/{}/chat/completions?api-version={}",
+                endpoint.trim_end_matches('/'),
+                deployment,
+                api_version.into()
+            ),
+            auth: OpenAiAuth::AzureKey,
+            api_key: api_key.into(),
+            model: deployment,
+            sampling: SamplingParams::default(),
+        }
+    }
+
+    /// Sample with `sampling`
+    pub fn with_sampling(mut self, sampling: SamplingParams) -> Self {
+        self.sampling = sampling;
+        self
+    }
+
+    /// Start a chat completions request with `body`, authenticated if a
+    /// key is set
+    fn request(&self, mut body: serde_json::Value) -> reqwest::RequestBuilder {
+        self.sampling
+            .apply(&mut body, ["temperature", "top_p", "seed", "max_tokens"]);
+        let request = self
+            .client
+            .post(&self.url)
+            .header("Content-Type", "application/json")
+            .json(&body);
+        match self.auth {
+            _ if self.api_key.is_empty() => request,
+            OpenAiAuth::Bearer => {
+                request.header("Authorization", format!("Bearer {}", self.api_key))
+            }
+            OpenAiAuth::AzureKey => request.header("api-key", &self.api_key),
+        }
+    }
+}
+
+#[async_trait]
+impl LlmProvider for OpenAiProvider {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    fn sampling(&self) -> Option<SamplingParams> {
+        Some(self.sampling)
+    }
+
+    async fn complete(&self, prompt: &str) -> Result<Completion, CompilerError> {
+        #[derive(Deserialize)]
+        struct GptMessage {
+            content: String,
+        }
+
+        #[derive(Deserialize)]
+        struct GptResponse {
+            choices: Vec<GptChoice>,
+            #[serde(default)]
+            usage: Option<GptUsage>,
+        }
+
+        #[derive(Deserialize)]
+        struct GptChoice {
+            message: GptMessage,
+        }
+
+        let request = serde_json::json!({
+            "model": self.model,
+            "messages": [{ "role": "user", "content": prompt }],
+        });
+
+        let response = self
+            .request(request)
+            .send()
+            .await
+            .map_err(|e| CompilerError::ProviderUnavailable(e.to_string()))?;
+
+        let status = response.status();
+        let body = response
+            .text()
+            .await
+            .map_err(|e| CompilerError::ProviderUnavailable(format!("Failed to read response: {}", e)))?;
+
+        if !status.is_success() {
+            return Err(status_error(&self.name, status, body));
+        }
+
+        let gpt_response: GptResponse = serde_json::from_str(&body)
+            .map_err(|e| CompilerError::ApiError(format!("Failed to parse response: {} - body: {}", e, body)))?;
+
+        let text = gpt_response
+            .choices
+            .first()
+            .map(|c| c.message.content.clone())
+            .ok_or_else(|| CompilerError::ApiError("Empty response".to_string()))?;
+        Ok(Completion {
+            text,
+            usage: gpt_response.usage.map(TokenUsage::from),
+        })
+    }
+
+    /// Forces a call to a single function whose parameters are `schema`
+    async fn complete_structured(
+        &self,
+        prompt: &str,
+        schema: &OutputSchema,
+    ) -> Result<Completion, CompilerError> {
+        #[derive(Deserialize)]
+        struct GptResponse {
+            choices: Vec<GptChoice>,
+            #[serde(default)]
+            usage: Option<GptUsage>,
+        }
+
+        #[derive(Deserialize)]
+        struct GptChoice {
+            message: GptToolMessage,
+        }
+
+        #[derive(Deserialize)]
+        struct GptToolMessage {
+            #[serde(default)]
+            tool_calls: Vec<GptToolCall>,
+        }
+
+        #[derive(Deserialize)]
+        struct GptToolCall {
+            function: GptFunctionCall,
+        }
+
+        #[derive(Deserialize)]
+        struct GptFunctionCall {
+            arguments: String,
+        }
+
+        let request = serde_json::json!({
+            "model": self.model,
+            "messages": [{ "role": "user", "content": prompt }],
+            "tools": [{
+                "type": "function",
+                "function": {
+                    "name": schema.name,
+                    "description": schema.description,
+                    "parameters": schema.schema,
+                },
+            }],
+            "tool_choice": { "type": "function", "function": { "name": schema.name } },
+        });
+
+        let response = self
+            .request(request)
+            .send()
+            .await
+            .map_err(|e| CompilerError::ProviderUnavailable(e.to_string()))?;
+
+        let status = response.status();
+        let body = response
+            .text()
+            .await
+            .map_err(|e| CompilerError::ProviderUnavailable(format!("Failed to read response: {}", e)))?;
+
+        if !status.is_success() {
+            return Err(status_error(&self.name, status, body));
+        }
+
+        let gpt_response: GptResponse = serde_json::from_str(&body)
+            .map_err(|e| CompilerError::ApiError(format!("Failed to parse response: {} - body: {}", e, body)))?;
+
+        let text = gpt_response
+            .choices
+            .into_iter()
+            .next()
+            .and_then(|c| c.message.tool_calls.into_iter().next())
+            .map(|call| call.function.arguments)
+            .ok_or_else(|| CompilerError::ApiError("Response contained no tool call".to_string()))?;
+        Ok(Completion {
+            text,
+            usage: gpt_response.usage.map(TokenUsage::from),
+        })
+    }
+}
+
+/// Default Gemini API base URL
+pub const GEMINI_BASE_URL: &str = "https://generativelanguage.googleapis.com/v1beta";
+
+/// Google Gemini `generateContent` API provider
+pub struct GeminiProvider {
+    client: reqwest::Client,
+    base_url: String,
+    api_key: String,
+    model: String,
+    sampling: SamplingParams,
+}
+
+impl GeminiProvider {
+    pub fn new(api_key: impl Into<String>, model: impl Into<String>) -> Self {
+        Self::with_base_url(GEMINI_BASE_URL, api_key, model)
+    }
+
+    /// Provider for a Gemini API at `base_url` (e.g. a regional proxy)
+    pub fn with_base_url(
+        base_url: impl Into<String>,
+        api_key: impl Into<String>,
+        model: impl Into<String>,
+    ) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            api_key: api_key.into(),
+            model: model.into(),
+            sampling: SamplingParams::default(),
+        }
+    }
+
+    /// Sample with `sampling`
+    pub fn with_sampling(mut self, sampling: SamplingParams) -> Self {
+        self.sampling = sampling;
+        self
+    }
+
+    /// Send a `generateContent` request and return the first candidate's text
+    async fn generate(&self, mut request: serde_json::Value) -> Result<Completion, CompilerError> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct GeminiResponse {
+            #[serde(default)]
+            candidates: Vec<GeminiCandidate>,
+            #[serde(default)]
+            usage_metadata: Option<GeminiUsage>,
+        }
+
+        #[derive(Deserialize)]
+        struct GeminiCandidate {
+            content: GeminiContent,
+        }
+
+        #[derive(Deserialize)]
+        struct GeminiContent {
+            #[serde(default)]
+            parts: Vec<GeminiPart>,
+        }
+
+        #[derive(Deserialize)]
+        struct GeminiPart {
+            #[serde(default)]
+            text: Option<String>,
+        }
+
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct GeminiUsage {
+            #[serde(default)]
+            prompt_token_count: u32,
+            #[serde(default)]
+            candidates_token_count: u32,
+        }
+
+        if self.sampling != SamplingParams::default() {
+            self.sampling.apply(
+                &mut request["generationConfig"],
+                ["temperature", "topP", "seed", "maxOutputTokens"],
+            );
+        }
+
+        let response = self
+            .client
+            .post(format!("{}/models/{}:generateContent", self.base_url, self.model))
+            .header("x-goog-api-key", &self.api_key)
+            .header("Content-Type", "application/json")
+            .json(&request)
+            .send()
+            .await
+            .map_err(|e| CompilerError::ProviderUnavailable(e.to_string()))?;
+
+        let status = response.status();
+        let body = response
+            .text()
+            .await
+            .map_err(|e| CompilerError::ProviderUnavailable(format!("Failed to read response: {}", e)))?;
+
+        if !status.is_success() {
+            return Err(status_error("Gemini", status, body));
+        }
+
+        let gemini_response: GeminiResponse = serde_json::from_str(&body)
+            .map_err(|e| CompilerError::ApiError(format!("Failed to parse response: {} - body: {}", e, body)))?;
+
+        let text = gemini_response
+            .candidates
+            .into_iter()
+            .next()
+            .and_then(|c| c.content.parts.into_iter().find_map(|part| part.text))
+            .ok_or_else(|| CompilerError::ApiError("Empty response".to_string()))?;
+        Ok(Completion {
+            text,
+            usage: gemini_response.usage_metadata.map(|usage| TokenUsage {
+                input_tokens: usage.prompt_token_count,
+                output_tokens: usage.candidates_token_count,
+            }),
+        })
+    }
+}
+
+#[async_trait]
+impl LlmProvider for GeminiProvider {
+    fn name(&self) -> &str {
+        "gemini"
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    fn sampling(&self) -> Option<SamplingParams> {
+        Some(self.sampling)
+    }
+
+    async fn complete(&self, prompt: &str) -> Result<Completion, CompilerError> {
+        self.generate(serde_json::json!({
+            "contents": [{ "role": "user", "parts": [{ "text": prompt }] }],
+        }))
+        .await
+    }
+
+    /// Asks for a JSON answer conforming to `schema`
+    async fn complete_structured(
+        &self,
+        prompt: &str,
+        schema: &OutputSchema,
+    ) -> Result<Completion, CompilerError> {
+        self.generate(serde_json::json!({
+            "contents": [{ "role": "user", "parts": [{ "text": prompt }] }],
+            "generationConfig": {
+                "responseMimeType": "application/json",
+                "responseJsonSchema": schema.schema,
+            },
+        }))
+        .await
+    }
+}
+
+/// A canned answer for prompts mentioning a pattern
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MockResponse {
+    /// Case-insensitive substring of the quote (or request) text
+    pub pattern: String,
+    /// The JSON answered, e.g. a `ParsedQuote`
+    pub response: serde_json::Value,
+}
+
+/// Offline provider answering prompts with canned JSON
+///
+/// Lets tests and demos compile quotes without an API key. Responses are
+/// matched in order against the text the prompt is about (the first
+/// `Label: "..."` line, e.g. `Quote: "Buy 1 dETH ..."`); the first match
+/// wins. [`MockLlmProvider::default`] knows a buy and a sell `dETH` quote.
+#[derive(Debug, Clone)]
+pub struct MockLlmProvider {
+    responses: Vec<MockResponse>,
+}
+
+impl MockLlmProvider {
+    /// A mock with no canned responses
+    pub fn new() -> Self {
+        Self { responses: vec![] }
+    }
+
+    /// Answer prompts about text containing `pattern` with `response`
+    ///
+    /// Responses added earlier take precedence.
+    pub fn with_response(
+        mut self,
+        pattern: impl Into<String>,
+        response: serde_json::Value,
+    ) -> Self {
+        self.responses.push(MockResponse {
+            pattern: pattern.into(),
+            response,
+        });
+        self
+    }
+
+    /// The text a prompt is about
+    fn subject(prompt: &str) -> &str {
+        prompt
+            .lines()
+            .find_map(|line| {
+                let (label, rest) = line.split_once(": \"")?;
+                let single_word = !label.is_empty() && label.chars().all(char::is_alphabetic);
+                rest.strip_suffix('"').filter(|_| single_word)
+            })
+            .unwrap_or(prompt)
+    }
+}
+
+impl Default for MockLlmProvider {
+    fn default() -> Self {
+        let quote = |side: &str, price: (&str, f64)| {
+            let mut quote = serde_json::json!({
+                "asset": "dETH",
+                "size": 1.0,
+                "side": side,
+                "max_price": null,
+                "min_price": null,
+                "currency": "USDD",
+                "expiry_minutes": 10,
+                "allowed_sources": ["FeedA", "FeedB"],
+                "max_staleness_seconds": 5,
+                "quorum_required": 2,
+                "quorum_tolerance_percent": 0.5,
+                "allowed_takers": [],
+                "clarifications": [],
+            });
+            quote[price.0] = price.1.into();
+            quote
+        };
+        Self::new()
+            .with_response("sell", quote("sell", ("min_price", 1900.0)))
+            .with_response("buy", quote("buy", ("max_price", 2000.0)))
+    }
+}
+
+#[async_trait]
+impl LlmProvider for MockLlmProvider {
+    fn name(&self) -> &str {
+        "mock"
+    }
+
+    async fn complete(&self, prompt: &str) -> Result<Completion, CompilerError> {
+        let subject = Self::subject(prompt).to_lowercase();
+        self.responses
+            .iter()
+            .find(|r| subject.contains(&r.pattern.to_lowercase()))
+            .map(|r| Completion::from(r.response.to_string()))
+            .ok_or_else(|| {
+                CompilerError::ApiError(format!("Mock LLM has no response for \"{}\"", subject))
+            })
+    }
+}
@@ -0,0 +1,78 @@
+//! Recovery of JSON from malformed LLM answers
+//!
+//! Models sometimes wrap their answer in markdown code fences, add a
+//! sentence before or after it, or leave a stray backtick. [`parse_json`]
+//! falls back to the first JSON object in the text; answers that still
+//! aren't valid JSON are sent back to the model with [`repair_prompt`].
+
+use serde::de::DeserializeOwned;
+
+use crate::CompilerError;
+
+/// The first complete JSON object in `text`, skipping code fences and prose
+///
+/// Braces inside strings are ignored. Returns None if no object is closed.
+pub fn extract_json(text: &str) -> Option<&str> {
+    let start = text.find('{')?;
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut escaped = false;
+    for (i, c) in text[start..].char_indices() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(&text[start..=start + i]);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Parse an answer, falling back to the first JSON object in it
+pub fn parse_json<T: DeserializeOwned>(text: &str) -> Result<T, CompilerError> {
+    let error = match serde_json::from_str(text) {
+        Ok(value) => return Ok(value),
+        Err(e) => e,
+    };
+    match extract_json(text).filter(|json| json.len() < text.len()) {
+        Some(json) => serde_json::from_str(json),
+        None => Err(error),
+    }
+    .map_err(|e| CompilerError::ParseError(format!("JSON parse error: {}", e)))
+}
+
+/// Whether an answer has no syntactically valid JSON object at all
+///
+/// Answers that are valid JSON of the wrong shape aren't worth repairing.
+pub fn is_malformed(text: &str) -> bool {
+    extract_json(text).is_none_or(|json| {
+        serde_json::from_str::<serde_json::Value>(json).is_err()
+    })
+}
+
+/// Follow-up prompt asking the model to fix its malformed answer
+pub fn repair_prompt(answer: &str, error: &CompilerError) -> String {
+    format!(
+        r#"Your previous answer should have been a single JSON object, but it could not be parsed ({}).
+
+Previous answer:
+{}
+
+Fix this JSON. Keep every field and value as it was; only repair the syntax. Return ONLY the corrected JSON object, no markdown code blocks or commentary."#,
+        error, answer
+    )
+}
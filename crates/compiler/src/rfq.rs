@@ -0,0 +1,111 @@
+//! Taker requests for quotes
+//!
+//! Compiles taker-originated English ("looking to buy 10 dETH, settle
+//! USDD, need 2 feeds") into an [`RfqRequest`] that makers answer with
+//! quotes, the other half of the RFQ workflow.
+
+use rfq_models::{RfqRequest, Side};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{CompilerError, ConstraintError};
+
+/// Request terms extracted by the LLM
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ParsedRfqRequest {
+    /// "buy" or "sell", from the taker's point of view
+    #[serde(default)]
+    pub side: Option<String>,
+    #[serde(default)]
+    pub asset: Option<String>,
+    #[serde(default)]
+    pub size: Option<f64>,
+    /// Currency to settle in
+    #[serde(default)]
+    pub currency: Option<String>,
+    /// Worst acceptable price per unit (max for buys, min for sells)
+    #[serde(default)]
+    pub limit_price: Option<f64>,
+    /// Feeds named by the taker
+    #[serde(default)]
+    pub sources: Vec<String>,
+    /// Number of feeds the taker needs prices from
+    #[serde(default)]
+    pub min_sources: Option<u32>,
+    /// How long makers have to respond, in seconds
+    #[serde(default)]
+    pub response_window_secs: Option<u64>,
+    /// Questions for the taker when essential terms are vague or missing
+    #[serde(default)]
+    pub clarifications: Vec<String>,
+}
+
+impl TryFrom<ParsedRfqRequest> for RfqRequest {
+    type Error = CompilerError;
+
+    fn try_from(parsed: ParsedRfqRequest) -> Result<Self, CompilerError> {
+        let mut questions = parsed.clarifications;
+        let side = match parsed.side.as_deref().map(str::to_lowercase).as_deref() {
+            Some("buy") => Some(Side::Buy),
+            Some("sell") => Some(Side::Sell),
+            _ => None,
+        };
+        let stated = |value: Option<String>| {
+            value.map(|v| v.trim().to_string()).filter(|v| !v.is_empty())
+        };
+        let asset = stated(parsed.asset);
+        let currency = stated(parsed.currency);
+        if questions.is_empty() {
+            if side.is_none() {
+                questions.push("Are you looking to buy or to sell?".to_string());
+            }
+            if asset.is_none() {
+                questions.push("Which asset are you looking for?".to_string());
+            }
+            if parsed.size.is_none() {
+                questions.push("How much do you want to trade?".to_string());
+            }
+            if currency.is_none() {
+                questions.push("Which currency do you want to settle in?".to_string());
+            }
+        }
+        let (side, asset, size, currency) = match (side, asset, parsed.size, currency) {
+            (Some(side), Some(asset), Some(size), Some(currency)) if questions.is_empty() => {
+                (side, asset, size, currency)
+            }
+            _ => return Err(CompilerError::NeedsClarification { questions }),
+        };
+
+        // Written so NaN fails too
+        if !(size > 0.0 && size.is_finite()) {
+            return Err(ConstraintError::NonPositiveSize(size).into());
+        }
+        if let Some(value) = parsed.limit_price.filter(|p| !(*p > 0.0 && p.is_finite())) {
+            return Err(ConstraintError::NonPositivePrice {
+                field: "limit_price",
+                value,
+            }
+            .into());
+        }
+        if parsed.min_sources == Some(0) {
+            return Err(ConstraintError::ZeroQuorum.into());
+        }
+        if let Some(quorum) = parsed.min_sources {
+            let sources = parsed.sources.len();
+            if sources > 0 && quorum as usize > sources {
+                return Err(ConstraintError::QuorumExceedsSources { quorum, sources }.into());
+            }
+        }
+
+        Ok(Self {
+            side,
+            asset,
+            size,
+            currency,
+            limit_price: parsed.limit_price,
+            sources: parsed.sources,
+            min_sources: parsed.min_sources,
+            response_window_secs: parsed.response_window_secs,
+        })
+    }
+}
@@ -0,0 +1,155 @@
+//! Trading shorthand
+//!
+//! Desks write "5k dETH", "2mm USDD" and "within 25bps". The grammar and
+//! DSL expand these with [`amount`] and [`percent`]; LLM answers are checked
+//! against the shorthand in the text with [`normalize`], since a model that
+//! reads "1.5k" as 1.5 is off by a factor of a thousand and nothing else
+//! would notice before settlement.
+
+use crate::ParsedQuote;
+
+/// Multiplier of an amount suffix ("" for none)
+fn multiplier(suffix: &str) -> Option<f64> {
+    match suffix.to_lowercase().as_str() {
+        "" => Some(1.0),
+        "k" => Some(1e3),
+        "m" | "mm" => Some(1e6),
+        "b" | "bn" => Some(1e9),
+        _ => None,
+    }
+}
+
+/// Split "1.5k" into ("1.5", "k")
+fn split_number(text: &str) -> (&str, &str) {
+    let split = text
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(text.len());
+    text.split_at(split)
+}
+
+/// Parse a number with an optional `k`, `m`/`mm` or `b`/`bn` suffix
+pub fn amount(text: &str) -> Option<f64> {
+    let text = text.trim();
+    if let Ok(value) = text.parse::<f64>() {
+        return Some(value).filter(|v| v.is_finite());
+    }
+    let (number, suffix) = split_number(text);
+    let value = number.parse::<f64>().ok()? * multiplier(suffix)?;
+    Some(value).filter(|v| v.is_finite())
+}
+
+/// Parse a percentage: "0.5%", "0.5" or "25bps" (= 0.25%)
+pub fn percent(text: &str) -> Option<f64> {
+    let text = text.trim();
+    let lower = text.to_lowercase();
+    let value = if let Some(bps) = lower
+        .strip_suffix("bps")
+        .or_else(|| lower.strip_suffix("bp"))
+    {
+        bps.trim().parse::<f64>().ok()? / 100.0
+    } else {
+        text.trim_end_matches('%').trim().parse::<f64>().ok()?
+    };
+    Some(value).filter(|v| v.is_finite())
+}
+
+/// A shorthand number written in quote text
+#[derive(Debug, Clone, PartialEq)]
+enum Shorthand {
+    /// e.g. "1.5k": the bare number and its expansion
+    Amount {
+        word: String,
+        written: f64,
+        value: f64,
+    },
+    /// e.g. "25bps": the bare number and the percentage
+    Bps { written: f64, percent: f64 },
+}
+
+/// Shorthand numbers in `text`
+fn find(text: &str) -> Vec<Shorthand> {
+    text.split(|c: char| c.is_whitespace() || matches!(c, ',' | ';' | '(' | ')' | '@'))
+        .map(|word| word.trim_start_matches('$').trim_end_matches(['.', '!', '?']))
+        .filter_map(|word| {
+            let (number, suffix) = split_number(word);
+            let written: f64 = number.parse().ok()?;
+            match suffix.to_lowercase().as_str() {
+                // A bare "m" is as likely minutes ("expires 10m") as millions
+                "" | "m" => None,
+                "bp" | "bps" => Some(Shorthand::Bps {
+                    written,
+                    percent: written / 100.0,
+                }),
+                suffix => Some(Shorthand::Amount {
+                    word: word.to_string(),
+                    written,
+                    value: written * multiplier(suffix)?,
+                }),
+            }
+        })
+        .collect()
+}
+
+/// Expand shorthand the LLM took literally in `parsed`
+///
+/// Only unambiguous suffixes (`k`, `mm`, `b`/`bn`, `bps`) are checked. A
+/// field holding the bare number of a shorthand amount ("1.5k" read as 1.5)
+/// is expanded, unless another field already holds the expansion. If
+/// several fields hold the bare number, a clarification is asked instead of
+/// guessing. Returns a note per expanded field.
+pub fn normalize(text: &str, parsed: &mut ParsedQuote) -> Vec<String> {
+    let mut notes = vec![];
+    for shorthand in find(text) {
+        match shorthand {
+            Shorthand::Amount {
+                word,
+                written,
+                value,
+            } => {
+                let mut fields = [
+                    ("size", Some(&mut parsed.size)),
+                    ("min_price", parsed.min_price.as_mut()),
+                    ("max_price", parsed.max_price.as_mut()),
+                    ("min_fill_size", parsed.min_fill_size.as_mut()),
+                    ("display_size", parsed.display_size.as_mut()),
+                    ("max_notional_per_taker", parsed.max_notional_per_taker.as_mut()),
+                ];
+                if fields.iter().any(|(_, v)| v.as_deref() == Some(&value)) {
+                    continue;
+                }
+                let mut literal: Vec<_> = fields
+                    .iter_mut()
+                    .filter(|(_, v)| v.as_deref() == Some(&written))
+                    .collect();
+                match literal.as_mut_slice() {
+                    [] => {}
+                    [(name, Some(field))] => {
+                        **field = value;
+                        notes.push(format!("{} {} expanded to {}", name, written, value));
+                    }
+                    _ => parsed.clarifications.push(format!(
+                        "Does \"{}\" ({}) refer to the size or the price?",
+                        word, value
+                    )),
+                }
+            }
+            Shorthand::Bps { written, percent } => {
+                let fields = [
+                    ("quorum_tolerance_percent", &mut parsed.quorum_tolerance_percent),
+                    ("max_price_deviation_percent", &mut parsed.max_price_deviation_percent),
+                    ("max_fee_percent", &mut parsed.max_fee_percent),
+                ];
+                for (name, field) in fields {
+                    if written != percent && *field == Some(written) {
+                        *field = Some(percent);
+                        notes.push(format!(
+                            "{} {} expanded to {} ({}bps)",
+                            name, written, percent, written
+                        ));
+                    }
+                }
+            }
+        }
+    }
+    notes
+}
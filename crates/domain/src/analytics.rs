@@ -0,0 +1,230 @@
+//! Constraint effectiveness analytics
+//!
+//! Correlates rejection reasons with the constraint settings in force when
+//! fills were attempted, across all quotes. A periodic job aggregates the
+//! receipts into per-setting rejection rates; maker reports compare a
+//! maker's settings against the population and suggest looser settings
+//! where theirs reject far more fills (e.g. "tolerance 0.25% rejects 40% of
+//! fills; quotes at 0.5% reject 8%").
+
+use chrono::{DateTime, Utc};
+use rfq_models::{FillReceipt, QuoteConstraints, RejectionReason};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
+use uuid::Uuid;
+
+/// Attempts a setting needs before its rejection rate is trusted
+const MIN_ATTEMPTS: usize = 10;
+
+/// Rejection rate above which a setting is worth tuning
+const SUGGESTION_THRESHOLD: f64 = 0.2;
+
+/// A constraint whose rejections can be attributed to its setting
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TunableConstraint {
+    QuorumTolerancePercent,
+    MaxStalenessSecs,
+    QuorumCount,
+}
+
+impl TunableConstraint {
+    const ALL: [Self; 3] = [
+        Self::QuorumTolerancePercent,
+        Self::MaxStalenessSecs,
+        Self::QuorumCount,
+    ];
+
+    /// The setting's value in some constraints, as displayed
+    fn value(self, constraints: &QuoteConstraints) -> String {
+        match self {
+            Self::QuorumTolerancePercent => format!("{}%", constraints.quorum_tolerance_percent()),
+            Self::MaxStalenessSecs => format!("{}s", constraints.max_staleness_secs),
+            Self::QuorumCount => constraints.quorum_count.to_string(),
+        }
+    }
+
+    /// Whether a rejection was caused by this constraint
+    fn rejected(self, reason: &RejectionReason) -> bool {
+        matches!(
+            (self, reason),
+            (
+                Self::QuorumTolerancePercent,
+                RejectionReason::QuorumNotMet {
+                    price_spread_bps: Some(_),
+                    ..
+                },
+            ) | (
+                Self::QuorumCount,
+                RejectionReason::QuorumNotMet {
+                    price_spread_bps: None,
+                    ..
+                },
+            ) | (Self::MaxStalenessSecs, RejectionReason::StaleFeed { .. })
+        )
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::QuorumTolerancePercent => "tolerance",
+            Self::MaxStalenessSecs => "staleness",
+            Self::QuorumCount => "quorum",
+        }
+    }
+}
+
+/// How often one constraint setting rejected fills
+#[derive(Debug, Clone, Serialize)]
+pub struct SettingStats {
+    pub constraint: TunableConstraint,
+    /// The setting, e.g. "0.25%" or "5s"
+    pub value: String,
+    /// Quotes attempted with this setting
+    pub quotes: usize,
+    /// Decided fill attempts with this setting
+    pub attempts: usize,
+    /// Attempts rejected because of this constraint
+    pub rejections: usize,
+    pub rejection_rate: f64,
+}
+
+/// A suggested change to a maker's constraint setting
+#[derive(Debug, Clone, Serialize)]
+pub struct TuningSuggestion {
+    pub constraint: TunableConstraint,
+    pub current: String,
+    pub suggested: String,
+    pub current_rejection_rate: f64,
+    pub suggested_rejection_rate: f64,
+    pub message: String,
+}
+
+/// Population-wide rejection rates per constraint setting
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ConstraintAnalytics {
+    pub computed_at: Option<DateTime<Utc>>,
+    pub settings: Vec<SettingStats>,
+}
+
+impl ConstraintAnalytics {
+    /// Aggregate decided receipts by constraint setting
+    pub fn compute<'a>(receipts: impl IntoIterator<Item = &'a FillReceipt>) -> Self {
+        Self {
+            computed_at: Some(Utc::now()),
+            settings: setting_stats(receipts),
+        }
+    }
+
+    /// Suggest looser settings where a maker's reject far more than others
+    pub fn suggestions(&self, maker_settings: &[SettingStats]) -> Vec<TuningSuggestion> {
+        maker_settings
+            .iter()
+            .filter(|s| s.attempts >= MIN_ATTEMPTS && s.rejection_rate >= SUGGESTION_THRESHOLD)
+            .filter_map(|current| {
+                let best = self
+                    .settings
+                    .iter()
+                    .filter(|s| s.constraint == current.constraint && s.value != current.value)
+                    .filter(|s| s.attempts >= MIN_ATTEMPTS)
+                    .min_by(|a, b| a.rejection_rate.total_cmp(&b.rejection_rate))?;
+                (best.rejection_rate <= current.rejection_rate / 2.0).then(|| TuningSuggestion {
+                    constraint: current.constraint,
+                    current: current.value.clone(),
+                    suggested: best.value.clone(),
+                    current_rejection_rate: current.rejection_rate,
+                    suggested_rejection_rate: best.rejection_rate,
+                    message: format!(
+                        "{} {} rejects {:.0}% of fills; quotes at {} reject {:.0}%",
+                        current.constraint.label(),
+                        current.value,
+                        current.rejection_rate * 100.0,
+                        best.value,
+                        best.rejection_rate * 100.0
+                    ),
+                })
+            })
+            .collect()
+    }
+}
+
+/// A maker's fill outcomes and tuning suggestions
+#[derive(Debug, Clone, Serialize)]
+pub struct MakerReport {
+    pub maker_owner_id: String,
+    pub quotes: usize,
+    pub fill_attempts: usize,
+    pub accepted: usize,
+    pub rejected: usize,
+    /// Rejection counts by reason code
+    pub rejections_by_reason: BTreeMap<String, usize>,
+    /// Rejection rates of the maker's own constraint settings
+    pub constraints: Vec<SettingStats>,
+    pub suggestions: Vec<TuningSuggestion>,
+    /// When the population analytics were last computed
+    pub analytics_computed_at: Option<DateTime<Utc>>,
+}
+
+impl MakerReport {
+    /// Build a report from the maker's receipts
+    pub fn build(
+        maker_owner_id: &str,
+        quotes: usize,
+        receipts: &[FillReceipt],
+        analytics: &ConstraintAnalytics,
+    ) -> Self {
+        let decided: Vec<&FillReceipt> = receipts.iter().filter(|r| !r.is_pending()).collect();
+        let mut rejections_by_reason = BTreeMap::new();
+        for reason in decided.iter().filter_map(|r| r.rejection_reason()) {
+            *rejections_by_reason.entry(reason.code().to_string()).or_insert(0) += 1;
+        }
+        let constraints = setting_stats(decided.iter().copied());
+
+        Self {
+            maker_owner_id: maker_owner_id.to_string(),
+            quotes,
+            fill_attempts: decided.len(),
+            accepted: decided.iter().filter(|r| r.is_accepted()).count(),
+            rejected: rejections_by_reason.values().sum(),
+            suggestions: analytics.suggestions(&constraints),
+            rejections_by_reason,
+            constraints,
+            analytics_computed_at: analytics.computed_at,
+        }
+    }
+}
+
+/// Group decided receipts by each tunable constraint's setting
+fn setting_stats<'a>(receipts: impl IntoIterator<Item = &'a FillReceipt>) -> Vec<SettingStats> {
+    #[derive(Default)]
+    struct Bucket {
+        quotes: HashSet<Uuid>,
+        attempts: usize,
+        rejections: usize,
+    }
+
+    let mut buckets: BTreeMap<(TunableConstraint, String), Bucket> = BTreeMap::new();
+    for receipt in receipts.into_iter().filter(|r| !r.is_pending()) {
+        for constraint in TunableConstraint::ALL {
+            let bucket = buckets
+                .entry((constraint, constraint.value(&receipt.constraints)))
+                .or_default();
+            bucket.quotes.insert(receipt.quote.id);
+            bucket.attempts += 1;
+            if receipt.rejection_reason().is_some_and(|r| constraint.rejected(r)) {
+                bucket.rejections += 1;
+            }
+        }
+    }
+
+    buckets
+        .into_iter()
+        .map(|((constraint, value), bucket)| SettingStats {
+            constraint,
+            value,
+            quotes: bucket.quotes.len(),
+            attempts: bucket.attempts,
+            rejections: bucket.rejections,
+            rejection_rate: bucket.rejections as f64 / bucket.attempts as f64,
+        })
+        .collect()
+}
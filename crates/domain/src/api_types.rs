@@ -4,8 +4,19 @@
 //! LLM agents to understand and work with. They transform the internal
 //! rich models into concise API responses.
 
-use rfq_models::{FillReceipt, FillResult, Quote, QuoteConstraints, QuoteStatus, Side};
+use rfq_models::signing::to_hex;
+use rfq_models::{
+    AuditEntry, AutoRejectRules, CompilerStamp, DivergenceGuard, FeedEvidence, FillReceipt, FillResult, PricingBreakdown, QuorumPolicy, Quote, QuoteConstraints, QuoteLeg, QuoteStatus, RejectionReason, Side,
+};
+use rfq_compiler::{
+    CompilationRecord, ConstraintExplanation, FieldSource, LintWarning, ParsedQuote, QuotePreview,
+};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::drafts::DraftSession;
+use crate::events::{DivergenceTrigger, DomainEvent, EventEnvelope, MakerStats};
+use crate::settlement::ProofStatus;
 
 // ============================================================================
 // Quote Types (Flattened)
@@ -18,14 +29,22 @@ pub struct ApiQuote {
     pub id: String,
     /// Original English text
     pub text: String,
-    /// Current status: "active", "filled", "expired", "cancelled"
+    /// Current status: "pending_approval", "scheduled", "pending", "active", "filled",
+    /// "expired", "cancelled"
     pub status: String,
     /// Asset being traded (e.g., "dETH")
     pub asset: String,
     /// Trade direction: "buy" or "sell"
     pub direction: String,
-    /// Size of the trade
+    /// Size of the trade (for icebergs, the size on display; for partially
+    /// filled quotes, what is left)
     pub size: f64,
+    /// Whether more size is hidden behind `size`, refilled after each fill
+    #[serde(default)]
+    pub iceberg: bool,
+    /// Further assets traded with `asset` as a package, at their full sizes
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub legs: Vec<QuoteLeg>,
     /// Price limit (max for buys, min for sells)
     pub price_limit: Option<f64>,
     /// Settlement currency (e.g., "USDD")
@@ -34,12 +53,77 @@ pub struct ApiQuote {
     pub expires_at: i64,
     /// Creation time as unix timestamp (seconds)
     pub created_at: i64,
+    /// When a scheduled quote is posted, as unix timestamp (seconds)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub activates_at: Option<i64>,
+    /// Critical fields the compiler was unsure of, with their confidence
+    #[serde(skip_serializing_if = "BTreeMap::is_empty", default)]
+    pub low_confidence_fields: BTreeMap<String, f64>,
+    /// End of the publication embargo as unix timestamp (seconds), if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub embargo_until: Option<i64>,
     /// Maker's owner ID
     pub maker_owner_id: String,
     /// Maker's shard number
     pub maker_shard: u64,
+    /// Consensus feed price when the quote was created
+    pub reference_price: Option<f64>,
+    /// Price limit relative to the reference price, in basis points
+    pub spread_to_reference_bps: Option<f64>,
+    /// Number of accepted fills so far
+    pub fill_count: u32,
+    /// Constraint preset applied at compile time (e.g. "institutional")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub preset: Option<String>,
     /// The compiled constraints (Local Law)
     pub local_law: ApiLocalLaw,
+    /// Changes made after compilation (e.g. explicit constraint overrides)
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub audit_trail: Vec<AuditEntry>,
+    /// Maker filters checked before Local Laws
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auto_reject: Option<AutoRejectRules>,
+    /// Cancels or suspends the quote if the market moves away from its reference
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub divergence_guard: Option<DivergenceGuard>,
+    /// How reliably the maker settles and honours its quotes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub maker_reputation: Option<ApiMakerReputation>,
+}
+
+/// A maker's reliability record
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiMakerReputation {
+    /// 0 to 1, higher is better (null until the maker has a track record)
+    pub score: Option<f64>,
+    /// Share of settled fills whose proof failed or timed out
+    pub settlement_failure_rate: Option<f64>,
+    /// Amendments to already-filled quotes per quote posted
+    pub amendment_after_fill_rate: f64,
+    /// Fills whose proof reached a final status
+    pub settled_fills: usize,
+    /// Quotes posted
+    pub quotes: usize,
+}
+
+impl From<&MakerStats> for ApiMakerReputation {
+    fn from(stats: &MakerStats) -> Self {
+        Self {
+            score: stats.score(),
+            settlement_failure_rate: stats.settlement_failure_rate(),
+            amendment_after_fill_rate: stats.amendment_after_fill_rate(),
+            settled_fills: stats.settled,
+            quotes: stats.quotes,
+        }
+    }
+}
+
+impl ApiQuote {
+    /// Attach the maker's reliability record
+    pub fn with_maker_reputation(mut self, stats: Option<&MakerStats>) -> Self {
+        self.maker_reputation = stats.map(ApiMakerReputation::from);
+        self
+    }
 }
 
 /// Flattened Local Law (constraints) for API responses
@@ -49,18 +133,61 @@ pub struct ApiLocalLaw {
     pub max_debit: u64,
     /// Expiry timestamp
     pub expiry_timestamp: u64,
+    /// When the quote becomes fillable (null = at once)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub valid_from_timestamp: Option<u64>,
+    /// Daily UTC hours fills are allowed in, e.g. "14:00-16:00 UTC"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trading_window: Option<String>,
     /// Allowed price feed sources
     pub allowed_sources: Vec<String>,
     /// Maximum staleness for price feeds (seconds)
     pub max_staleness_secs: u64,
+    /// Maximum staleness of particular sources, overriding the above (seconds)
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub source_staleness_secs: BTreeMap<String, u64>,
     /// Minimum number of sources required
     pub quorum_count: u32,
     /// Maximum price spread tolerance (percentage)
     pub quorum_tolerance_percent: f64,
+    /// How the sources' prices combine, e.g. "median" (null = spread)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quorum_policy: Option<String>,
     /// Require atomic delivery vs payment
     pub require_atomic_dvp: bool,
     /// Disallow extra transfers
     pub no_side_payments: bool,
+    /// Owner ID (hex) credited by the one fee leg the settlement may pay
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fee_recipient: Option<String>,
+    /// Largest fee that leg may pay (in plancks)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_fee_amount: Option<u64>,
+    /// Total number of fills allowed
+    pub max_fills: u32,
+    /// Fills allowed per taker (null = no per-taker cap)
+    pub max_fills_per_taker: Option<u32>,
+    /// Largest amount one taker may trade across fills (in plancks of the
+    /// currency; null = uncapped)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_notional_per_taker: Option<u64>,
+    /// Smallest size one fill may take (in plancks; null = no minimum)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_fill_size: Option<u64>,
+    /// Whether a fill may take part of the size
+    pub allow_partial_fills: bool,
+    /// Seconds after acceptance within which a fill must settle
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub settlement_window_secs: Option<u64>,
+    /// Max distance of the fill price from the feed median (percentage)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_price_deviation_percent: Option<f64>,
+    /// Largest tolerated fee (percentage of the notional)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_fee_percent: Option<f64>,
+    /// Compiler version, model and prompt hash that produced the guardrails
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub compiled_by: Option<CompilerStamp>,
 }
 
 impl From<&Quote> for ApiQuote {
@@ -79,14 +206,27 @@ impl From<&Quote> for ApiQuote {
             status: status_to_string(q.status),
             asset: q.spec.asset.clone(),
             direction: side_to_string(q.spec.side),
-            size: q.spec.size,
+            size: q.visible_size().unwrap_or(q.spec.size),
+            iceberg: q.constraints.iceberg.is_some(),
+            legs: q.spec.legs.clone(),
             price_limit: q.spec.limit_price,
             currency: q.spec.currency.clone(),
             expires_at: q.expires_at.timestamp(),
             created_at: q.created_at.timestamp(),
+            activates_at: q.activates_at.map(|t| t.timestamp()),
+            low_confidence_fields: q.low_confidence_fields.clone(),
+            embargo_until: q.embargo_until.map(|t| t.timestamp()),
             maker_owner_id: q.maker_owner_id.clone(),
             maker_shard,
+            reference_price: q.reference_price.as_ref().map(|r| r.price),
+            spread_to_reference_bps: q.spread_to_reference_bps(),
+            fill_count: q.fill_history.len() as u32,
+            preset: q.preset.clone(),
             local_law: ApiLocalLaw::from(&q.constraints),
+            audit_trail: q.audit_trail.clone(),
+            auto_reject: q.auto_reject.clone(),
+            divergence_guard: q.divergence_guard,
+            maker_reputation: None,
         }
     }
 }
@@ -96,26 +236,46 @@ impl From<&QuoteConstraints> for ApiLocalLaw {
         Self {
             max_debit: c.max_debit,
             expiry_timestamp: c.expiry_timestamp,
+            valid_from_timestamp: c.valid_from_timestamp,
+            trading_window: c.trading_window.map(|window| window.to_string()),
             allowed_sources: c.allowed_sources.clone(),
             max_staleness_secs: c.max_staleness_secs,
+            source_staleness_secs: c.source_staleness_secs.clone(),
             quorum_count: c.quorum_count,
-            quorum_tolerance_percent: c.quorum_tolerance_percent,
+            quorum_tolerance_percent: c.quorum_tolerance_percent(),
+            quorum_policy: (c.quorum_policy != QuorumPolicy::Spread)
+                .then(|| c.quorum_policy.to_string()),
             require_atomic_dvp: c.require_atomic_dvp,
             no_side_payments: c.no_side_payments,
+            fee_recipient: c.allowed_fee.map(|fee| to_hex(&fee.recipient)),
+            max_fee_amount: c.allowed_fee.map(|fee| fee.max_amount),
+            max_fills: c.max_fills,
+            max_fills_per_taker: c.max_fills_per_taker,
+            max_notional_per_taker: c.max_notional_per_taker,
+            min_fill_size: c.min_fill_size,
+            allow_partial_fills: c.allow_partial_fills,
+            settlement_window_secs: c.settlement_window_secs,
+            max_price_deviation_percent: c.max_price_deviation_percent(),
+            max_fee_percent: c.max_fee_percent(),
+            compiled_by: c.compiled_by.clone(),
         }
     }
 }
 
-fn status_to_string(status: QuoteStatus) -> String {
+pub fn status_to_string(status: QuoteStatus) -> String {
     match status {
+        QuoteStatus::PendingApproval => "pending_approval".to_string(),
+        QuoteStatus::Scheduled => "scheduled".to_string(),
+        QuoteStatus::Pending => "pending".to_string(),
         QuoteStatus::Active => "active".to_string(),
         QuoteStatus::Filled => "filled".to_string(),
         QuoteStatus::Expired => "expired".to_string(),
         QuoteStatus::Cancelled => "cancelled".to_string(),
+        QuoteStatus::Suspended => "suspended".to_string(),
     }
 }
 
-fn side_to_string(side: Side) -> String {
+pub fn side_to_string(side: Side) -> String {
     match side {
         Side::Buy => "buy".to_string(),
         Side::Sell => "sell".to_string(),
@@ -132,10 +292,119 @@ pub struct ApiCreateQuoteResponse {
     /// The created quote (flattened)
     #[serde(flatten)]
     pub quote: ApiQuote,
-    /// Human-readable summary of constraints
-    pub constraints_summary: String,
+    /// Each enforced constraint in a sentence, and where its value came from
+    pub explanation: ConstraintExplanation,
     /// Success message
     pub message: String,
+    /// Non-fatal issues with the quote (e.g. a likely duplicate)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+    /// Risky compiled terms, most risky first (see POST /quotes/lint)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub lint_warnings: Vec<LintWarning>,
+}
+
+/// Response to linting quote text
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiLintResponse {
+    /// Asset being traded (e.g., "dETH")
+    pub asset: String,
+    /// Trade direction: "buy" or "sell"
+    pub direction: String,
+    /// Size of the trade
+    pub size: f64,
+    /// Price limit (max for buys, min for sells)
+    pub price_limit: Option<f64>,
+    /// Settlement currency (e.g., "USDD")
+    pub currency: String,
+    /// The Local Law the quote would be posted with
+    pub local_law: ApiLocalLaw,
+    /// Each constraint in a sentence, and where its value came from
+    pub explanation: ConstraintExplanation,
+    /// Constraint preset referenced by the text
+    pub preset: Option<String>,
+    /// Critical fields the compiler was unsure of, with their confidence
+    pub low_confidence_fields: BTreeMap<String, f64>,
+    /// Risky terms, most risky first
+    pub warnings: Vec<LintWarning>,
+    /// Each enforced field and the phrase of the text it came from
+    pub fields: Vec<FieldSource>,
+}
+
+impl From<QuotePreview> for ApiLintResponse {
+    fn from(preview: QuotePreview) -> Self {
+        let spec = &preview.spec;
+        Self {
+            asset: spec.asset.clone(),
+            direction: side_to_string(spec.side),
+            size: spec.size,
+            price_limit: spec.limit_price,
+            currency: spec.currency.clone(),
+            local_law: ApiLocalLaw::from(&preview.constraints),
+            explanation: preview.explanation(),
+            preset: preview.preset,
+            low_confidence_fields: preview.low_confidence,
+            warnings: preview.warnings,
+            fields: preview.fields,
+        }
+    }
+}
+
+/// How a quote was compiled (GET /quotes/:id/compilation)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiCompilationResponse {
+    pub quote_id: String,
+    /// Compiler version, method, model and prompt hash stamped on the Local Law
+    pub compiled_by: Option<CompilerStamp>,
+    /// The text, every LLM prompt and raw answer, and the post-processing steps
+    pub compilation: CompilationRecord,
+}
+
+/// A quote drafting session and what it compiles to so far
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiDraftResponse {
+    pub draft_id: String,
+    /// The maker's messages, oldest first
+    pub messages: Vec<String>,
+    /// The messages joined, to post with POST /quotes once ready
+    pub text: String,
+    /// Terms understood so far (None before the first message)
+    pub terms: Option<ParsedQuote>,
+    /// What the maker still has to answer, most essential first
+    pub questions: Vec<String>,
+    /// Whether the text can be posted as it is
+    pub ready: bool,
+    /// The quote that would be posted, once ready
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quote: Option<ApiLintResponse>,
+    /// Unix timestamps
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+impl From<DraftSession> for ApiDraftResponse {
+    fn from(session: DraftSession) -> Self {
+        let text = session.text();
+        let (terms, questions, quote) = match session.draft {
+            Some(draft) => (
+                Some(draft.terms),
+                draft.questions,
+                draft.preview.map(ApiLintResponse::from),
+            ),
+            None => (None, vec![], None),
+        };
+        Self {
+            draft_id: session.id.to_string(),
+            messages: session.messages,
+            text,
+            terms,
+            questions,
+            ready: quote.is_some(),
+            quote,
+            created_at: session.created_at.timestamp(),
+            updated_at: session.updated_at.timestamp(),
+        }
+    }
 }
 
 // ============================================================================
@@ -162,6 +431,20 @@ pub struct ApiFillResponse {
     /// Proof info if accepted
     #[serde(skip_serializing_if = "Option::is_none")]
     pub proof: Option<ApiProof>,
+    /// Set if the fill was answered before its settlement finished
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pending: Option<ApiPendingSettlement>,
+}
+
+/// A fill whose settlement continues after the response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiPendingSettlement {
+    /// Fill attempt ID
+    pub fill_id: String,
+    /// SDL hash whose proof is outstanding
+    pub sdl_hash: String,
+    /// Where the final proof status appears
+    pub receipts_url: String,
 }
 
 /// Error details for rejected fills
@@ -212,6 +495,9 @@ pub struct ApiSettlement {
     pub asset: String,
     /// Currency transferred
     pub currency: String,
+    /// How the currency amount was derived from the price
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pricing: Option<PricingBreakdown>,
 }
 
 /// Proof information
@@ -219,10 +505,54 @@ pub struct ApiSettlement {
 pub struct ApiProof {
     /// SDL hash from Delta
     pub sdl_hash: String,
-    /// Proof status
+    /// Proof status: "verified", "pending" (still waiting after the proof
+    /// timeout), "timed_out" or "failed"
     pub status: String,
 }
 
+impl ApiFillResponse {
+    /// Report the SDL's actual proof status
+    ///
+    /// A pending proof means the settlement outlived the wait (the proof
+    /// timeout or the request's `max_wait_ms`) and continues in the
+    /// background.
+    pub fn with_proof_status(mut self, status: Option<ProofStatus>) -> Self {
+        if let (Some(proof), Some(status)) = (self.proof.as_mut(), status) {
+            proof.status = status.as_str().to_string();
+            if status == ProofStatus::Pending {
+                self.message = "Fill accepted! Settlement is still running; \
+                    poll the receipts for its proof status."
+                    .to_string();
+                self.pending = Some(ApiPendingSettlement {
+                    fill_id: self.fill_id.clone(),
+                    sdl_hash: proof.sdl_hash.clone(),
+                    receipts_url: format!("/quotes/{}/receipts", self.quote_id),
+                });
+            }
+        }
+        self
+    }
+
+    /// Response for a fill stopped before validation, by screening or a
+    /// maker auto-reject rule (no receipt)
+    pub fn rejected_early(quote_id: uuid::Uuid, reason: &RejectionReason) -> Self {
+        Self {
+            success: false,
+            fill_id: String::new(),
+            quote_id: quote_id.to_string(),
+            message: format!("Fill rejected: {}", reason.message()),
+            error: Some(ApiFillError {
+                code: reason.code().to_string(),
+                message: reason.message(),
+                details: serde_json::to_value(reason).ok(),
+            }),
+            receipt: None,
+            proof: None,
+            pending: None,
+        }
+    }
+}
+
 impl From<&FillReceipt> for ApiFillResponse {
     fn from(receipt: &FillReceipt) -> Self {
         let fill_id = receipt.fill_attempt.id.to_string();
@@ -254,12 +584,36 @@ impl From<&FillReceipt> for ApiFillResponse {
                         taker_credit: settlement.taker_credit,
                         asset: settlement.asset.clone(),
                         currency: settlement.currency.clone(),
+                        pricing: settlement.pricing.clone(),
                     }),
                 }),
                 proof: Some(ApiProof {
                     sdl_hash: sdl_hash.clone(),
                     status: "verified".to_string(),
                 }),
+                pending: None,
+            },
+            FillResult::PendingSettlement { settles_at, .. } => Self {
+                success: true,
+                fill_id,
+                quote_id,
+                message: format!(
+                    "Fill accepted! It settles at the benchmark price when the window closes at {}.",
+                    settles_at
+                ),
+                error: None,
+                receipt: Some(ApiReceipt {
+                    id: receipt.receipt_id.to_string(),
+                    quote_id: receipt.quote.id.to_string(),
+                    taker_owner_id: receipt.fill_attempt.taker_owner_id.clone(),
+                    taker_shard: receipt.fill_attempt.taker_shard,
+                    size: receipt.fill_attempt.size,
+                    price: receipt.fill_attempt.price,
+                    filled_at: receipt.generated_at.timestamp(),
+                    settlement: None,
+                }),
+                proof: None,
+                pending: None,
             },
             FillResult::Rejected { reason, .. } => Self {
                 success: false,
@@ -273,6 +627,7 @@ impl From<&FillReceipt> for ApiFillResponse {
                 }),
                 receipt: None,
                 proof: None,
+                pending: None,
             },
         }
     }
@@ -291,7 +646,7 @@ pub struct ApiReceiptSummary {
     pub quote_id: String,
     /// Whether fill was accepted
     pub success: bool,
-    /// Status: "accepted" or "rejected"
+    /// Status: "accepted", "pending_settlement" or "rejected"
     pub status: String,
     /// Taker's owner ID
     pub taker_owner_id: String,
@@ -315,6 +670,12 @@ pub struct ApiReceiptSummary {
     /// Settlement details if accepted
     #[serde(skip_serializing_if = "Option::is_none")]
     pub settlement: Option<ApiSettlement>,
+    /// Proof status if accepted ("verified", "pending", "timed_out", "failed")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proof_status: Option<String>,
+    /// Feed evidence submitted with the fill, including retrieval provenance
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub feed_evidence: Vec<FeedEvidence>,
 }
 
 impl From<&FillReceipt> for ApiReceiptSummary {
@@ -333,8 +694,17 @@ impl From<&FillReceipt> for ApiReceiptSummary {
                     taker_credit: settlement.taker_credit,
                     asset: settlement.asset.clone(),
                     currency: settlement.currency.clone(),
+                    pricing: settlement.pricing.clone(),
                 }),
             ),
+            FillResult::PendingSettlement { .. } => (
+                true,
+                "pending_settlement".to_string(),
+                None,
+                None,
+                None,
+                None,
+            ),
             FillResult::Rejected { reason, .. } => (
                 false,
                 "rejected".to_string(),
@@ -359,6 +729,85 @@ impl From<&FillReceipt> for ApiReceiptSummary {
             error_message,
             sdl_hash,
             settlement,
+            proof_status: None,
+            feed_evidence: r.fill_attempt.feed_evidence.clone(),
+        }
+    }
+}
+
+// ============================================================================
+// Quote Timeline
+// ============================================================================
+
+/// One event in a quote's timeline
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiTimelineEntry {
+    /// Position in the domain event log
+    pub seq: u64,
+    /// When the event happened (unix timestamp)
+    pub at: i64,
+    /// "quote_compiled", "quote_created", "quote_updated", "fill_recorded"
+    /// or "proof_status_changed"
+    pub event: String,
+    /// Quote status after the event (quote events only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    /// Accepted fills after the event (quote events only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fill_count: Option<u32>,
+    /// The recorded fill (fill events only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub receipt: Option<ApiReceiptSummary>,
+    /// New proof status (proof events only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proof_status: Option<String>,
+    /// What set off the divergence guard (protective cancel/suspend only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub divergence: Option<DivergenceTrigger>,
+}
+
+impl From<&EventEnvelope> for ApiTimelineEntry {
+    fn from(e: &EventEnvelope) -> Self {
+        let mut entry = Self {
+            seq: e.seq,
+            at: e.at.timestamp(),
+            event: e.event.kind().to_string(),
+            status: None,
+            fill_count: None,
+            receipt: None,
+            proof_status: None,
+            divergence: None,
+        };
+        match &e.event {
+            DomainEvent::QuoteCreated { quote }
+            | DomainEvent::QuoteUpdated { quote }
+            | DomainEvent::QuoteActivated { quote }
+            | DomainEvent::QuoteApproved { quote }
+            | DomainEvent::QuoteAmended { quote }
+            | DomainEvent::QuoteCancelled { quote }
+            | DomainEvent::QuoteResumed { quote, .. } => {
+                entry.status = Some(status_to_string(quote.status));
+                entry.fill_count = Some(quote.fill_history.len() as u32);
+            }
+            DomainEvent::ProtectiveCancel { quote, trigger }
+            | DomainEvent::ProtectiveSuspend { quote, trigger } => {
+                entry.status = Some(status_to_string(quote.status));
+                entry.fill_count = Some(quote.fill_history.len() as u32);
+                entry.divergence = Some(*trigger);
+            }
+            DomainEvent::FillRecorded { receipt, .. } => {
+                entry.receipt = Some(ApiReceiptSummary::from(receipt.as_ref()));
+            }
+            DomainEvent::ProofStatusChanged { status, .. } => {
+                entry.proof_status = Some(status.as_str().to_string());
+            }
+            DomainEvent::QuoteCompiled { .. }
+            | DomainEvent::TakerNonceUsed { .. }
+            | DomainEvent::AnchorRecorded { .. }
+            | DomainEvent::AuctionOpened { .. }
+            | DomainEvent::AuctionResponded { .. }
+            | DomainEvent::AuctionAwarded { .. } => {}
         }
+        entry
     }
 }
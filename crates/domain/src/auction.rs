@@ -0,0 +1,288 @@
+//! RFQ auctions
+//!
+//! A taker opens an auction for a trade ("buy 10 dETH in USDD") with a
+//! response window, optionally compiled from English into an
+//! [`RfqRequest`](rfq_models::RfqRequest) with a price limit and feed
+//! requirements that responses must meet. Makers respond with a quote
+//! scheduled to activate when the window closes, so responses stay sealed: no
+//! taker or rival maker can see them until then. After the close the
+//! responses are revealed and ranked by price, ties going to the more
+//! reliable maker. The taker's acceptance fills the best response right away;
+//! the losing quotes are withdrawn and their makers get a [`RegretReceipt`]
+//! with the winning price.
+
+use chrono::{DateTime, Utc};
+use rfq_models::{Quote, QuoteId, QuoteStatus, Side};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::events::MakerStats;
+
+/// A taker's request for competing quotes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Auction {
+    pub id: Uuid,
+    pub taker_owner_id: String,
+    /// The taker's side of the trade
+    pub side: Side,
+    pub asset: String,
+    pub currency: String,
+    pub size: f64,
+    /// Worst price the taker accepts (max for buys, min for sells)
+    #[serde(default)]
+    pub limit_price: Option<f64>,
+    /// Feeds responses must price against (empty = any)
+    #[serde(default)]
+    pub sources: Vec<String>,
+    /// Fewest feeds a response's quorum may use
+    #[serde(default)]
+    pub min_sources: Option<u32>,
+    pub opened_at: DateTime<Utc>,
+    /// When responses are revealed
+    pub closes_at: DateTime<Utc>,
+    /// Sealed responses, in submission order
+    pub responses: Vec<AuctionResponse>,
+    /// Set once the taker accepts the winning response
+    pub award: Option<AuctionAward>,
+}
+
+/// A maker's sealed response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuctionResponse {
+    pub quote_id: QuoteId,
+    pub maker_owner_id: String,
+    pub submitted_at: DateTime<Utc>,
+}
+
+/// A revealed response with its rank (1 = best)
+#[derive(Debug, Clone, Serialize)]
+pub struct RankedResponse {
+    pub rank: usize,
+    pub quote_id: QuoteId,
+    pub maker_owner_id: String,
+    /// The quote's limit price
+    pub price: f64,
+    /// The maker's reputation score (see [`MakerStats::score`])
+    pub maker_reputation: Option<f64>,
+}
+
+/// Outcome of an accepted auction
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuctionAward {
+    pub winner: QuoteId,
+    pub winning_price: f64,
+    pub awarded_at: DateTime<Utc>,
+    pub regrets: Vec<RegretReceipt>,
+}
+
+/// Notice to a losing maker that the auction went to a better price
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegretReceipt {
+    pub auction_id: Uuid,
+    pub quote_id: QuoteId,
+    pub maker_owner_id: String,
+    pub rank: usize,
+    /// The maker's price
+    pub price: f64,
+    pub winning_price: f64,
+    pub issued_at: DateTime<Utc>,
+}
+
+/// Where an auction is in its lifecycle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuctionStatus {
+    /// Taking sealed responses
+    Open,
+    /// Responses revealed; waiting for the taker
+    Revealed,
+    /// The taker accepted the winning response
+    Awarded,
+}
+
+/// Why an auction request was refused
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum AuctionError {
+    #[error("Auction not found")]
+    UnknownAuction,
+    #[error("Quote not found")]
+    UnknownQuote,
+    #[error("Only the quote's maker can submit it")]
+    WrongMaker,
+    #[error("Only the auction's taker can accept it")]
+    WrongTaker,
+    #[error("The response window has closed")]
+    Closed,
+    #[error("Responses are sealed until the window closes")]
+    StillOpen,
+    #[error("The auction was already awarded")]
+    AlreadyAwarded,
+    #[error("The auction has no live responses")]
+    NoResponses,
+    #[error("The maker already responded")]
+    AlreadyResponded,
+    #[error("Responses must be quotes scheduled to activate when the window closes")]
+    NotSealed,
+    #[error("Quote doesn't match the auction: {0}")]
+    Mismatch(String),
+}
+
+impl AuctionError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::UnknownAuction | Self::UnknownQuote => "NOT_FOUND",
+            Self::WrongMaker | Self::WrongTaker => "FORBIDDEN",
+            Self::Closed => "AUCTION_CLOSED",
+            Self::StillOpen => "AUCTION_OPEN",
+            Self::AlreadyAwarded => "AUCTION_AWARDED",
+            Self::NoResponses => "NO_RESPONSES",
+            Self::AlreadyResponded => "ALREADY_RESPONDED",
+            Self::NotSealed => "RESPONSE_NOT_SEALED",
+            Self::Mismatch(_) => "RESPONSE_MISMATCH",
+        }
+    }
+}
+
+impl Auction {
+    pub fn status(&self, now: DateTime<Utc>) -> AuctionStatus {
+        if self.award.is_some() {
+            AuctionStatus::Awarded
+        } else if now < self.closes_at {
+            AuctionStatus::Open
+        } else {
+            AuctionStatus::Revealed
+        }
+    }
+
+    /// Check that `quote` is a valid sealed response from `maker_owner_id`
+    pub fn check_response(
+        &self,
+        quote: &Quote,
+        maker_owner_id: &str,
+        now: DateTime<Utc>,
+    ) -> Result<(), AuctionError> {
+        if now >= self.closes_at {
+            return Err(AuctionError::Closed);
+        }
+        if quote.maker_owner_id != maker_owner_id {
+            return Err(AuctionError::WrongMaker);
+        }
+        if self.responses.iter().any(|r| r.maker_owner_id == maker_owner_id) {
+            return Err(AuctionError::AlreadyResponded);
+        }
+        // Posting earlier would reveal the response
+        let sealed = quote.status == QuoteStatus::Scheduled
+            && quote.activates_at.is_some_and(|at| at >= self.closes_at);
+        if !sealed {
+            return Err(AuctionError::NotSealed);
+        }
+        let mismatch = |what: String| Err(AuctionError::Mismatch(what));
+        if quote.spec.side == self.side {
+            return mismatch(format!("the maker must take the other side of a {:?}", self.side));
+        }
+        if !quote.spec.asset.eq_ignore_ascii_case(&self.asset)
+            || !quote.spec.currency.eq_ignore_ascii_case(&self.currency)
+        {
+            return mismatch(format!("expected {} in {}", self.asset, self.currency));
+        }
+        if quote.spec.size < self.size {
+            return mismatch(format!("size {} is below the requested {}", quote.spec.size, self.size));
+        }
+        let Some(price) = quote.spec.limit_price else {
+            return mismatch("responses need a price limit to be ranked".to_string());
+        };
+        if let Some(limit) = self.limit_price {
+            let worse = match self.side {
+                Side::Buy => price > limit,
+                Side::Sell => price < limit,
+            };
+            if worse {
+                return mismatch(format!("price {} is worse than the limit {}", price, limit));
+            }
+        }
+        let quote_sources = &quote.constraints.allowed_sources;
+        if !self.sources.is_empty()
+            && (quote_sources.is_empty() || quote_sources.iter().any(|s| !self.sources.contains(s)))
+        {
+            return mismatch(format!("feeds must be among {}", self.sources.join(", ")));
+        }
+        if let Some(min_sources) = self.min_sources {
+            if quote.constraints.quorum_count < min_sources {
+                return mismatch(format!(
+                    "a quorum of {} feeds is required, the quote has {}",
+                    min_sources, quote.constraints.quorum_count
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Live responses ranked best first (cheapest ask for a buying taker,
+    /// highest bid for a selling one)
+    ///
+    /// Equal prices go to the maker with the better reputation score (makers
+    /// without one rank below those with one), then to the earlier response.
+    pub fn rank(
+        &self,
+        quotes: &HashMap<QuoteId, Quote>,
+        makers: &HashMap<String, MakerStats>,
+    ) -> Vec<RankedResponse> {
+        let mut live: Vec<(&AuctionResponse, f64, Option<f64>)> = self
+            .responses
+            .iter()
+            .filter_map(|response| {
+                let quote = quotes.get(&response.quote_id).filter(|q| q.is_open())?;
+                let reputation = makers
+                    .get(&response.maker_owner_id)
+                    .and_then(MakerStats::score);
+                Some((response, quote.spec.limit_price?, reputation))
+            })
+            .collect();
+        // Stable sort keeps submission order among equal prices and scores
+        live.sort_by(|(_, a, a_score), (_, b, b_score)| {
+            let by_price = match self.side {
+                Side::Buy => a.total_cmp(b),
+                Side::Sell => b.total_cmp(a),
+            };
+            let by_score = || {
+                let score = |s: &Option<f64>| s.unwrap_or(-1.0);
+                score(b_score).total_cmp(&score(a_score))
+            };
+            by_price.then_with(by_score)
+        });
+        live.into_iter()
+            .enumerate()
+            .map(|(index, (response, price, maker_reputation))| RankedResponse {
+                rank: index + 1,
+                quote_id: response.quote_id,
+                maker_owner_id: response.maker_owner_id.clone(),
+                price,
+                maker_reputation,
+            })
+            .collect()
+    }
+
+    /// The award for the best of `ranked`, with regret receipts for the rest
+    pub fn award(&self, ranked: &[RankedResponse], now: DateTime<Utc>) -> Option<AuctionAward> {
+        let (winner, losers) = ranked.split_first()?;
+        Some(AuctionAward {
+            winner: winner.quote_id,
+            winning_price: winner.price,
+            awarded_at: now,
+            regrets: losers
+                .iter()
+                .map(|loser| RegretReceipt {
+                    auction_id: self.id,
+                    quote_id: loser.quote_id,
+                    maker_owner_id: loser.maker_owner_id.clone(),
+                    rank: loser.rank,
+                    price: loser.price,
+                    winning_price: winner.price,
+                    issued_at: now,
+                })
+                .collect(),
+        })
+    }
+}
@@ -1,9 +1,23 @@
 //! Domain configuration
 
 use anyhow::{Context, Result};
+use rfq_compiler::{
+    AssetRegistry, BudgetConfig, CacheConfig, ConstraintPreset, ProviderSpec, RetryConfig,
+};
+
+use crate::duplicates::DuplicateGuardConfig;
+use crate::export::ExportConfig;
+use crate::limits::ApiLimitsConfig;
+use crate::markets::MarketConfig;
+use crate::notary::NotaryConfig;
+use crate::settlement::SettlementConfig;
+use crate::simulation::ProvingSimulation;
+use crate::watchdog::WatchdogConfig;
+use rfq_models::signing::{PublicKey, SchemeKind};
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 use std::env;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 /// Domain configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -13,27 +27,184 @@ pub struct DomainConfig {
     /// Path to keypair file (JSON format)
     #[serde(default = "default_keypair_path")]
     pub keypair_path: String,
+    /// Directory holding keys retired by rotation
+    #[serde(default = "default_retired_keys_dir")]
+    pub retired_keys_dir: String,
     /// RPC URL for delta testnet
     #[serde(default = "default_rpc_url")]
     pub rpc_url: String,
     /// HTTP port for API
     #[serde(default = "default_api_port")]
     pub api_port: u16,
-    /// LLM provider ("claude" or "openai")
+    /// LLM provider ("claude", "openai", "ollama" or "mock")
     #[serde(default = "default_llm_provider")]
     pub llm_provider: String,
-    /// LLM API key (optional in config, can be set via env)
+    /// LLM API key (optional in config, can be set via env; not needed for ollama)
     #[serde(default)]
     pub llm_api_key: String,
+    /// Model name (defaults per provider)
+    #[serde(default)]
+    pub llm_model: Option<String>,
+    /// Base URL of an OpenAI-compatible endpoint (e.g. an on-prem Ollama server),
+    /// or the Azure OpenAI resource endpoint
+    #[serde(default)]
+    pub llm_base_url: Option<String>,
+    /// Azure OpenAI deployment name (defaults to the model name)
+    #[serde(default)]
+    pub llm_deployment: Option<String>,
+    /// Azure OpenAI API version
+    #[serde(default)]
+    pub llm_api_version: Option<String>,
+    /// Providers tried in order when the primary one fails
+    /// (API keys default to the provider's environment variable)
+    #[serde(default)]
+    pub llm_fallbacks: Vec<ProviderSpec>,
+    /// Retry policy for rate-limited or unavailable providers
+    #[serde(default)]
+    pub llm_retry: RetryConfig,
+    /// Longest a single LLM call may take before it fails over
+    #[serde(default = "default_llm_timeout_secs")]
+    pub llm_timeout_secs: u64,
+    /// Use the provider's native structured output (tool use / function
+    /// calling) for quote compilation
+    #[serde(default = "default_llm_structured_output")]
+    pub llm_structured_output: bool,
+    /// Extract LLM-compiled quotes twice and distrust fields the passes
+    /// disagree on
+    #[serde(default)]
+    pub llm_confidence_pass: bool,
+    /// Quotes with a critical field (price, expiry, sources) the LLM is
+    /// less sure of than this aren't activated without the maker's say-so
+    #[serde(default = "default_min_field_confidence")]
+    pub min_field_confidence: f64,
+    /// LLM sampling temperature (provider default if unset)
+    #[serde(default)]
+    pub llm_temperature: Option<f64>,
+    /// LLM nucleus sampling cutoff (provider default if unset)
+    #[serde(default)]
+    pub llm_top_p: Option<f64>,
+    /// LLM sampling seed, for providers that take one
+    #[serde(default)]
+    pub llm_seed: Option<u64>,
+    /// Longest LLM answer, in tokens (provider default if unset)
+    #[serde(default)]
+    pub llm_max_tokens: Option<u32>,
+    /// Compile as reproducibly as the providers allow (temperature 0 and
+    /// a fixed seed), so auditors can replay compilations
+    #[serde(default)]
+    pub llm_deterministic: bool,
+    /// Cache of LLM compilations, keyed by quote text and model
+    #[serde(default)]
+    pub compile_cache: CacheConfig,
+    /// Per-request and per-day LLM token and cost limits
+    #[serde(default)]
+    pub llm_budget: BudgetConfig,
+    /// YAML or JSON file with the quote prompt template and few-shot
+    /// examples (built-in prompt if unset)
+    #[serde(default)]
+    pub llm_prompt_path: Option<PathBuf>,
     /// Whether to use mock mode (no real Delta connection)
     #[serde(default)]
     pub mock_mode: bool,
+    /// Largest amount one faucet request can credit, in plancks (mock mode)
+    #[serde(default = "default_faucet_max_plancks")]
+    pub faucet_max_plancks: u64,
+    /// Reject fills whose taker vault can't cover their leg
+    /// (default: on outside mock mode, where taker vaults aren't funded)
+    #[serde(default)]
+    pub check_taker_balance: Option<bool>,
+    /// Refuse fill requests without a taker signature (signed requests are
+    /// always verified)
+    #[serde(default)]
+    pub require_taker_signatures: bool,
+    /// How far feed clocks may be off from the domain's, in seconds; feed
+    /// evidence may be this much staler than a quote allows, or this much
+    /// in the future
+    #[serde(default = "default_clock_skew_secs")]
+    pub clock_skew_secs: u64,
+    /// Price feed base URLs by source name (used for reference price snapshots)
+    #[serde(default)]
+    pub feed_urls: HashMap<String, String>,
+    /// Public keys of feed sources, by source name; quotes carry the keys
+    /// of their allowed sources, and fills must bring evidence signed by
+    /// them
+    #[serde(default)]
+    pub feed_keys: BTreeMap<String, PublicKey>,
+    /// Let new quotes accept evidence from feeds without a key, unverified
+    /// (development against mock feeds only; sets the quotes'
+    /// `allow_unsigned_feeds`)
+    #[serde(default)]
+    pub allow_unsigned_feeds: bool,
+    /// Takers that may never fill (e.g. a sanctions list); new quotes carry
+    /// them in `denied_takers`
+    #[serde(default)]
+    pub denied_takers: Vec<String>,
+    /// File with more denied takers, one owner ID per line, read at startup
+    #[serde(default)]
+    pub denied_takers_path: Option<PathBuf>,
+    /// Named constraint presets makers can reference in quote text
+    #[serde(default = "default_presets")]
+    pub presets: BTreeMap<String, ConstraintPreset>,
+    /// Preset applied to quotes that name none
+    #[serde(default)]
+    pub default_preset: Option<String>,
+    /// Decimals and token IDs of quoted assets (unlisted ones have 9 decimals)
+    #[serde(default)]
+    pub assets: AssetRegistry,
+    /// Runtime health thresholds for suspending fills
+    #[serde(default)]
+    pub watchdog: WatchdogConfig,
+    /// Simulated proving latency and failures (mock mode only)
+    #[serde(default)]
+    pub proving_simulation: Option<ProvingSimulation>,
+    /// Receipt root anchoring on Delta
+    #[serde(default)]
+    pub notary: NotaryConfig,
+    /// Periodic Parquet export of quotes, fills and receipts
+    #[serde(default)]
+    pub export: ExportConfig,
+    /// Detection of quotes duplicating a maker's open quotes
+    #[serde(default)]
+    pub duplicate_guard: DuplicateGuardConfig,
+    /// Size ceilings per API key, enforced regardless of quote constraints
+    #[serde(default)]
+    pub api_limits: ApiLimitsConfig,
+    /// Tradable asset/currency pairs (any pair is accepted if empty)
+    #[serde(default)]
+    pub markets: Vec<MarketConfig>,
+    /// Key for operator endpoints such as market halts (disabled if unset)
+    #[serde(default)]
+    pub admin_key: Option<String>,
+    /// Proof timeout and late-proof compensation
+    #[serde(default)]
+    pub settlement: SettlementConfig,
+    /// How often constraint effectiveness analytics are recomputed, in seconds
+    #[serde(default = "default_analytics_interval_secs")]
+    pub analytics_interval_secs: u64,
+    /// How often scheduled quotes are checked for activation, in seconds
+    #[serde(default = "default_schedule_sweep_secs")]
+    pub schedule_sweep_secs: u64,
+    /// How often quotes' divergence guards are checked against the feeds, in seconds
+    #[serde(default = "default_divergence_sweep_secs")]
+    pub divergence_sweep_secs: u64,
+}
+
+fn default_min_field_confidence() -> f64 {
+    0.7
+}
+
+fn default_faucet_max_plancks() -> u64 {
+    1_000_000_000_000 // 1000 USDD equivalent
 }
 
 fn default_keypair_path() -> String {
     "keypair.json".to_string()
 }
 
+fn default_retired_keys_dir() -> String {
+    "retired_keys".to_string()
+}
+
 fn default_rpc_url() -> String {
     "http://164.92.69.96:9000".to_string()
 }
@@ -42,10 +213,70 @@ fn default_api_port() -> u16 {
     8080
 }
 
+fn default_clock_skew_secs() -> u64 {
+    2
+}
+
 fn default_llm_provider() -> String {
     "claude".to_string()
 }
 
+fn default_llm_timeout_secs() -> u64 {
+    30
+}
+
+fn default_analytics_interval_secs() -> u64 {
+    300
+}
+
+fn default_schedule_sweep_secs() -> u64 {
+    1
+}
+
+fn default_divergence_sweep_secs() -> u64 {
+    5
+}
+
+fn default_llm_structured_output() -> bool {
+    true
+}
+
+fn default_presets() -> BTreeMap<String, ConstraintPreset> {
+    BTreeMap::from([
+        (
+            "conservative".to_string(),
+            ConstraintPreset {
+                max_staleness_secs: Some(10),
+                quorum_count: Some(2),
+                quorum_tolerance_percent: Some(0.5),
+                require_atomic_dvp: Some(true),
+                no_side_payments: Some(true),
+                ..Default::default()
+            },
+        ),
+        (
+            "fast-settlement".to_string(),
+            ConstraintPreset {
+                max_staleness_secs: Some(120),
+                quorum_count: Some(1),
+                quorum_tolerance_percent: Some(2.0),
+                ..Default::default()
+            },
+        ),
+        (
+            "institutional".to_string(),
+            ConstraintPreset {
+                max_staleness_secs: Some(5),
+                quorum_count: Some(3),
+                quorum_tolerance_percent: Some(0.25),
+                require_atomic_dvp: Some(true),
+                no_side_payments: Some(true),
+                ..Default::default()
+            },
+        ),
+    ])
+}
+
 impl DomainConfig {
     /// Load configuration from a YAML file
     pub fn load_from<P: AsRef<Path>>(path: P) -> Result<Self> {
@@ -61,6 +292,21 @@ impl DomainConfig {
         Ok(config)
     }
 
+    /// Whether fills are checked against the taker's vault balance
+    pub fn checks_taker_balance(&self) -> bool {
+        self.check_taker_balance.unwrap_or(!self.mock_mode)
+    }
+
+    /// The feed keys a quote allowing `sources` carries (all of them if
+    /// any source is allowed)
+    pub fn feed_keys_for(&self, sources: &[String]) -> BTreeMap<String, PublicKey> {
+        self.feed_keys
+            .iter()
+            .filter(|(name, _)| sources.is_empty() || sources.contains(name))
+            .map(|(name, key)| (name.clone(), key.clone()))
+            .collect()
+    }
+
     /// Apply environment variable overrides
     fn apply_env_overrides(&mut self) {
         if let Ok(shard) = env::var("SHARD") {
@@ -71,6 +317,9 @@ impl DomainConfig {
         if let Ok(keypair) = env::var("KEYPAIR_PATH") {
             self.keypair_path = keypair;
         }
+        if let Ok(dir) = env::var("RETIRED_KEYS_DIR") {
+            self.retired_keys_dir = dir;
+        }
         if let Ok(rpc) = env::var("RPC_URL") {
             self.rpc_url = rpc;
         }
@@ -82,17 +331,86 @@ impl DomainConfig {
         if let Ok(provider) = env::var("LLM_PROVIDER") {
             self.llm_provider = provider;
         }
+        if let Ok(model) = env::var("LLM_MODEL") {
+            self.llm_model = Some(model);
+        }
+        if let Ok(base_url) = env::var("LLM_BASE_URL") {
+            self.llm_base_url = Some(base_url);
+        }
+        if let Ok(deployment) = env::var("LLM_DEPLOYMENT") {
+            self.llm_deployment = Some(deployment);
+        }
+        if let Ok(structured) = env::var("LLM_STRUCTURED_OUTPUT") {
+            self.llm_structured_output = structured == "1" || structured.to_lowercase() == "true";
+        }
+        if let Ok(deterministic) = env::var("LLM_DETERMINISTIC") {
+            self.llm_deterministic =
+                deterministic == "1" || deterministic.to_lowercase() == "true";
+        }
         if let Ok(mock) = env::var("MOCK_MODE") {
             self.mock_mode = mock == "1" || mock.to_lowercase() == "true";
         }
+        if let Ok(key) = env::var("ADMIN_KEY") {
+            self.admin_key = Some(key);
+        }
+        if let Ok(feeds) = env::var("FEED_URLS") {
+            // Format: "FeedA=http://localhost:9001,FeedB=http://localhost:9002"
+            self.feed_urls = feeds
+                .split(',')
+                .filter_map(|entry| entry.split_once('='))
+                .map(|(name, url)| (name.trim().to_string(), url.trim().to_string()))
+                .collect();
+        }
+        if let Ok(path) = env::var("DENIED_TAKERS_PATH") {
+            self.denied_takers_path = Some(PathBuf::from(path));
+        }
+        if let Ok(keys) = env::var("FEED_KEYS") {
+            // Format: "FeedA=<hex ed25519 key>,FeedB=secp256k1:<hex address>"
+            self.feed_keys = keys
+                .split(',')
+                .filter_map(|entry| entry.split_once('='))
+                .map(|(name, key)| {
+                    let (scheme, key) = match key.trim().split_once(':') {
+                        Some(("secp256k1", key)) => (SchemeKind::Secp256k1, key),
+                        Some(("ed25519", key)) => (SchemeKind::Ed25519, key),
+                        _ => (SchemeKind::Ed25519, key.trim()),
+                    };
+                    let key = PublicKey {
+                        scheme,
+                        key: key.to_string(),
+                    };
+                    (name.trim().to_string(), key)
+                })
+                .collect();
+        }
+
+        if let Ok(require) = env::var("REQUIRE_TAKER_SIGNATURES") {
+            self.require_taker_signatures = require == "1" || require.to_lowercase() == "true";
+        }
+        if let Ok(allow) = env::var("ALLOW_UNSIGNED_FEEDS") {
+            self.allow_unsigned_feeds = allow == "1" || allow.to_lowercase() == "true";
+        }
 
         // Always try to get API key from environment
         if self.llm_api_key.is_empty() {
-            self.llm_api_key = match self.llm_provider.as_str() {
-                "openai" | "gpt" => env::var("OPENAI_API_KEY").unwrap_or_default(),
-                _ => env::var("ANTHROPIC_API_KEY").unwrap_or_default(),
-            };
+            self.llm_api_key = api_key_from_env(&self.llm_provider);
         }
+        for fallback in &mut self.llm_fallbacks {
+            if fallback.api_key.is_empty() {
+                fallback.api_key = api_key_from_env(&fallback.llm);
+            }
+        }
+    }
+}
+
+/// The API key for an LLM provider from its environment variable
+fn api_key_from_env(provider: &str) -> String {
+    match provider {
+        "openai" | "gpt" => env::var("OPENAI_API_KEY").unwrap_or_default(),
+        "azure" => env::var("AZURE_OPENAI_API_KEY").unwrap_or_default(),
+        "gemini" => env::var("GEMINI_API_KEY").unwrap_or_default(),
+        "ollama" | "mock" => String::new(),
+        _ => env::var("ANTHROPIC_API_KEY").unwrap_or_default(),
     }
 }
 
@@ -101,11 +419,54 @@ impl Default for DomainConfig {
         let mut config = Self {
             shard: 9,
             keypair_path: default_keypair_path(),
+            retired_keys_dir: default_retired_keys_dir(),
             rpc_url: default_rpc_url(),
             api_port: default_api_port(),
             llm_provider: default_llm_provider(),
             llm_api_key: String::new(),
+            llm_model: None,
+            llm_base_url: None,
+            llm_deployment: None,
+            llm_api_version: None,
+            llm_fallbacks: vec![],
+            llm_retry: RetryConfig::default(),
+            llm_timeout_secs: default_llm_timeout_secs(),
+            llm_structured_output: default_llm_structured_output(),
+            llm_confidence_pass: false,
+            min_field_confidence: default_min_field_confidence(),
+            llm_temperature: None,
+            llm_top_p: None,
+            llm_seed: None,
+            llm_max_tokens: None,
+            llm_deterministic: false,
+            compile_cache: CacheConfig::default(),
+            llm_budget: BudgetConfig::default(),
+            llm_prompt_path: None,
             mock_mode: true, // Default to mock mode for safety
+            faucet_max_plancks: default_faucet_max_plancks(),
+            check_taker_balance: None,
+            require_taker_signatures: false,
+            clock_skew_secs: default_clock_skew_secs(),
+            feed_urls: HashMap::new(),
+            feed_keys: BTreeMap::new(),
+            allow_unsigned_feeds: false,
+            denied_takers: Vec::new(),
+            denied_takers_path: None,
+            presets: default_presets(),
+            default_preset: None,
+            assets: AssetRegistry::default(),
+            watchdog: WatchdogConfig::default(),
+            proving_simulation: None,
+            notary: NotaryConfig::default(),
+            export: ExportConfig::default(),
+            duplicate_guard: DuplicateGuardConfig::default(),
+            api_limits: ApiLimitsConfig::default(),
+            markets: vec![],
+            admin_key: None,
+            settlement: SettlementConfig::default(),
+            analytics_interval_secs: default_analytics_interval_secs(),
+            schedule_sweep_secs: default_schedule_sweep_secs(),
+            divergence_sweep_secs: default_divergence_sweep_secs(),
         };
         config.apply_env_overrides();
         config
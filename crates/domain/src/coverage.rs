@@ -0,0 +1,376 @@
+//! Constraint coverage of a quote
+//!
+//! Lists every constraint on a quote with how many of its fill attempts
+//! the constraint rejected and how many were checked against it, so a
+//! maker can spot guardrails doing all the work or doing nothing.
+//!
+//! Local Laws checks constraints in a fixed order and stops at the first
+//! failure, so an attempt exercised every constraint up to the one that
+//! rejected it, and an accepted attempt exercised them all. Rejections no
+//! constraint accounts for (insufficient balance, auto-reject rules)
+//! exercise none.
+
+use rfq_models::{
+    FillReceipt, QuorumPolicy, Quote, QuoteConstraints, QuoteId, RejectionReason, Side,
+};
+use serde::Serialize;
+
+/// A constraint Local Laws enforces, in the order it is checked
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CoveredConstraint {
+    DeniedTakers,
+    ExpiryTimestamp,
+    ValidFromTimestamp,
+    TradingWindow,
+    AllowedTakers,
+    AllowedTakerShards,
+    MaxFills,
+    MaxFillsPerTaker,
+    IcebergTotalSize,
+    MaxFillSize,
+    MinFillSize,
+    BasketLegs,
+    MaxDebit,
+    MinCredit,
+    IcebergTotalDebit,
+    MaxNotionalPerTaker,
+    QuorumCount,
+    AllowedSources,
+    AllowedAssets,
+    FeedKeys,
+    MaxStalenessSecs,
+    QuorumTolerancePercent,
+    MaxPriceDeviationPercent,
+    Benchmark,
+    SettlementWindowSecs,
+    RequireAtomicDvp,
+    NoSidePayments,
+    MaxFeePercent,
+    Nonce,
+}
+
+impl CoveredConstraint {
+    const ALL: [Self; 29] = [
+        Self::DeniedTakers,
+        Self::ExpiryTimestamp,
+        Self::ValidFromTimestamp,
+        Self::TradingWindow,
+        Self::AllowedTakers,
+        Self::AllowedTakerShards,
+        Self::MaxFills,
+        Self::MaxFillsPerTaker,
+        Self::IcebergTotalSize,
+        Self::MaxFillSize,
+        Self::MinFillSize,
+        Self::BasketLegs,
+        Self::MaxDebit,
+        Self::MinCredit,
+        Self::IcebergTotalDebit,
+        Self::MaxNotionalPerTaker,
+        Self::QuorumCount,
+        Self::AllowedSources,
+        Self::AllowedAssets,
+        Self::FeedKeys,
+        Self::MaxStalenessSecs,
+        Self::QuorumTolerancePercent,
+        Self::MaxPriceDeviationPercent,
+        Self::Benchmark,
+        Self::SettlementWindowSecs,
+        Self::RequireAtomicDvp,
+        Self::NoSidePayments,
+        Self::MaxFeePercent,
+        Self::Nonce,
+    ];
+
+    /// Whether the constraint is set on a quote (unset ones can't reject)
+    fn applies(self, constraints: &QuoteConstraints) -> bool {
+        match self {
+            Self::ValidFromTimestamp => constraints.valid_from_timestamp.is_some(),
+            Self::TradingWindow => constraints.trading_window.is_some(),
+            Self::DeniedTakers => !constraints.denied_takers.is_empty(),
+            Self::AllowedTakers => !constraints.allowed_takers.is_empty(),
+            Self::AllowedTakerShards => !constraints.allowed_taker_shards.is_empty(),
+            Self::MaxFillsPerTaker => constraints.max_fills_per_taker.is_some(),
+            Self::IcebergTotalSize => constraints.iceberg.is_some(),
+            Self::MinFillSize => constraints.min_fill_size.is_some(),
+            Self::BasketLegs => !constraints.leg_max_fill_sizes.is_empty(),
+            Self::MaxDebit => constraints.side == Side::Buy,
+            Self::MinCredit => constraints.side == Side::Sell && constraints.min_credit.is_some(),
+            Self::IcebergTotalDebit => {
+                constraints.iceberg.is_some_and(|i| i.total_debit.is_some())
+            }
+            Self::MaxNotionalPerTaker => constraints.max_notional_per_taker.is_some(),
+            Self::AllowedSources => !constraints.allowed_sources.is_empty(),
+            Self::AllowedAssets => !constraints.allowed_assets.is_empty(),
+            Self::FeedKeys => {
+                !constraints.allow_unsigned_feeds || !constraints.feed_keys.is_empty()
+            }
+            Self::MaxPriceDeviationPercent => {
+                constraints.max_price_deviation_bps.is_some() && constraints.benchmark.is_none()
+            }
+            Self::Benchmark => constraints.benchmark.is_some(),
+            Self::SettlementWindowSecs => constraints.settlement_window_secs.is_some(),
+            Self::MaxFeePercent => constraints.max_fee_bps.is_some(),
+            Self::RequireAtomicDvp => constraints.require_atomic_dvp,
+            Self::NoSidePayments => constraints.no_side_payments,
+            _ => true,
+        }
+    }
+
+    /// The constraint's setting, as displayed
+    fn value(self, constraints: &QuoteConstraints) -> String {
+        match self {
+            Self::ExpiryTimestamp => constraints.expiry_datetime().to_rfc3339(),
+            Self::ValidFromTimestamp => constraints
+                .valid_from_datetime()
+                .map(|valid_from| valid_from.to_rfc3339())
+                .unwrap_or_default(),
+            Self::TradingWindow => constraints
+                .trading_window
+                .map(|window| window.to_string())
+                .unwrap_or_default(),
+            Self::DeniedTakers => constraints.denied_takers.join(", "),
+            Self::AllowedTakers => constraints.allowed_takers.join(", "),
+            Self::AllowedTakerShards => {
+                let shards: Vec<String> =
+                    constraints.allowed_taker_shards.iter().map(u64::to_string).collect();
+                shards.join(", ")
+            }
+            Self::MaxFills => constraints.max_fills.to_string(),
+            Self::MaxFillsPerTaker => constraints
+                .max_fills_per_taker
+                .map(|n| n.to_string())
+                .unwrap_or_default(),
+            Self::IcebergTotalSize => constraints
+                .iceberg
+                .map(|i| i.total_size.to_string())
+                .unwrap_or_default(),
+            Self::MaxFillSize => constraints.max_fill_size.to_string(),
+            Self::MinFillSize => constraints
+                .min_fill_size
+                .map(|n| n.to_string())
+                .unwrap_or_default(),
+            Self::BasketLegs => constraints
+                .leg_max_fill_sizes
+                .iter()
+                .map(|(asset, size)| format!("{} {}", size, asset))
+                .collect::<Vec<_>>()
+                .join(", "),
+            Self::MaxDebit => constraints.max_debit.to_string(),
+            Self::MinCredit => constraints
+                .min_credit
+                .map(|credit| credit.to_string())
+                .unwrap_or_default(),
+            Self::IcebergTotalDebit => constraints
+                .iceberg
+                .and_then(|i| i.total_debit)
+                .map(|debit| debit.to_string())
+                .unwrap_or_default(),
+            Self::MaxNotionalPerTaker => constraints
+                .max_notional_per_taker
+                .map(|notional| notional.to_string())
+                .unwrap_or_default(),
+            Self::QuorumCount => constraints.quorum_count.to_string(),
+            Self::AllowedSources => constraints.allowed_sources.join(", "),
+            Self::AllowedAssets => constraints.allowed_assets.join(", "),
+            Self::FeedKeys => constraints
+                .feed_keys
+                .iter()
+                .map(|(source, key)| format!("{} ({})", source, key.scheme.as_str()))
+                .collect::<Vec<_>>()
+                .join(", "),
+            Self::MaxStalenessSecs => {
+                let mut value = format!("{}s", constraints.max_staleness_secs);
+                for (source, secs) in &constraints.source_staleness_secs {
+                    value.push_str(&format!(", {} {}s", source, secs));
+                }
+                value
+            }
+            Self::QuorumTolerancePercent => match &constraints.quorum_policy {
+                QuorumPolicy::Spread => format!("{}%", constraints.quorum_tolerance_percent()),
+                policy => format!("{}% ({})", constraints.quorum_tolerance_percent(), policy),
+            },
+            Self::MaxPriceDeviationPercent => constraints
+                .max_price_deviation_percent()
+                .map(|percent| format!("{}%", percent))
+                .unwrap_or_default(),
+            Self::Benchmark => constraints
+                .benchmark
+                .as_ref()
+                .map(|b| format!("TWAP over {}s", b.window_secs))
+                .unwrap_or_default(),
+            Self::SettlementWindowSecs => constraints
+                .settlement_window_secs
+                .map(|secs| format!("{}s", secs))
+                .unwrap_or_default(),
+            Self::MaxFeePercent => constraints
+                .max_fee_percent()
+                .map(|percent| format!("{}%", percent))
+                .unwrap_or_default(),
+            Self::RequireAtomicDvp | Self::NoSidePayments => "true".to_string(),
+            Self::Nonce => "fresh vault nonce".to_string(),
+        }
+    }
+
+    /// The constraint that caused a rejection, if any did
+    fn rejecting(reason: &RejectionReason) -> Option<Self> {
+        Some(match reason {
+            RejectionReason::QuoteExpired { .. } => Self::ExpiryTimestamp,
+            RejectionReason::QuoteNotYetValid { .. } => Self::ValidFromTimestamp,
+            RejectionReason::OutsideTradingWindow { .. } => Self::TradingWindow,
+            RejectionReason::DeniedTaker { .. } => Self::DeniedTakers,
+            RejectionReason::UnauthorizedTaker { .. } => Self::AllowedTakers,
+            RejectionReason::UnauthorizedShard { .. } => Self::AllowedTakerShards,
+            RejectionReason::FillLimitReached { .. } | RejectionReason::AlreadyFilled { .. } => {
+                Self::MaxFills
+            }
+            RejectionReason::TakerFillLimitReached { .. } => Self::MaxFillsPerTaker,
+            RejectionReason::IcebergSizeExhausted { .. } => Self::IcebergTotalSize,
+            RejectionReason::SizeExceedsMax { .. }
+            | RejectionReason::RemainingSizeExceeded { .. } => Self::MaxFillSize,
+            RejectionReason::SizeBelowMin { .. } => Self::MinFillSize,
+            RejectionReason::BasketLegMismatch { .. } => Self::BasketLegs,
+            RejectionReason::PriceExceedsLimit { .. } => Self::MaxDebit,
+            RejectionReason::PriceBelowLimit { .. } => Self::MinCredit,
+            RejectionReason::IcebergDebitExhausted { .. } => Self::IcebergTotalDebit,
+            RejectionReason::TakerNotionalExceeded { .. } => Self::MaxNotionalPerTaker,
+            RejectionReason::QuorumNotMet {
+                price_spread_bps: None,
+                ..
+            }
+            | RejectionReason::DuplicateFeedSource { .. } => Self::QuorumCount,
+            RejectionReason::QuorumNotMet { .. } => Self::QuorumTolerancePercent,
+            RejectionReason::UnauthorizedSource { .. } => Self::AllowedSources,
+            RejectionReason::FeedAssetMismatch { .. } => Self::AllowedAssets,
+            RejectionReason::InvalidFeedSignature { .. }
+            | RejectionReason::UnkeyedFeedSource { .. } => Self::FeedKeys,
+            RejectionReason::StaleFeed { .. } | RejectionReason::FutureFeed { .. } => {
+                Self::MaxStalenessSecs
+            }
+            RejectionReason::PriceDeviationExceeded { .. } => Self::MaxPriceDeviationPercent,
+            RejectionReason::InvalidBenchmarkEvidence { .. }
+            | RejectionReason::BenchmarkMismatch { .. } => Self::Benchmark,
+            RejectionReason::InvalidTransferPattern { .. } => Self::RequireAtomicDvp,
+            RejectionReason::SettlementWindowExceeded { .. } => Self::SettlementWindowSecs,
+            RejectionReason::SidePaymentDetected { .. } => Self::NoSidePayments,
+            RejectionReason::FeeExceedsLimit { .. } => Self::MaxFeePercent,
+            RejectionReason::ReplayDetected { .. }
+            | RejectionReason::UnknownVaultNonce { .. } => Self::Nonce,
+            _ => return None,
+        })
+    }
+
+    fn position(self) -> usize {
+        Self::ALL.iter().position(|c| *c == self).unwrap_or(0)
+    }
+}
+
+/// How a constraint has fared
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CoverageStatus {
+    /// No attempt got as far as the constraint
+    Unexercised,
+    /// Checked, but it never rejected an attempt
+    NeverRejected,
+    /// It rejected at least one attempt
+    Rejecting,
+}
+
+/// One constraint's record on a quote
+#[derive(Debug, Clone, Serialize)]
+pub struct ConstraintCoverage {
+    pub constraint: CoveredConstraint,
+    /// The quote's current setting
+    pub value: String,
+    /// Attempts checked against the constraint
+    pub exercised: usize,
+    /// Attempts it rejected
+    pub rejections: usize,
+    /// Its share of the rejections constraints account for
+    pub share_of_rejections: Option<f64>,
+    pub status: CoverageStatus,
+}
+
+/// Constraint coverage of one quote (GET /quotes/:id/coverage)
+#[derive(Debug, Clone, Serialize)]
+pub struct CoverageReport {
+    pub quote_id: QuoteId,
+    /// Decided fill attempts
+    pub fill_attempts: usize,
+    pub accepted: usize,
+    pub rejected: usize,
+    /// Rejections no constraint accounts for (e.g. insufficient balance)
+    pub other_rejections: usize,
+    /// The quote's constraints, in the order Local Laws checks them
+    pub constraints: Vec<ConstraintCoverage>,
+}
+
+impl CoverageReport {
+    /// Build the report from a quote's receipts
+    pub fn build(quote: &Quote, receipts: &[FillReceipt]) -> Self {
+        let all = CoveredConstraint::ALL;
+        let mut exercised = [0usize; CoveredConstraint::ALL.len()];
+        let mut rejections = [0usize; CoveredConstraint::ALL.len()];
+        let (mut accepted, mut rejected, mut other_rejections) = (0, 0, 0);
+
+        for receipt in receipts.iter().filter(|r| !r.is_pending()) {
+            let checked = match receipt.rejection_reason() {
+                None => {
+                    accepted += 1;
+                    all.len()
+                }
+                Some(reason) => {
+                    rejected += 1;
+                    match CoveredConstraint::rejecting(reason) {
+                        Some(constraint) => {
+                            rejections[constraint.position()] += 1;
+                            constraint.position() + 1
+                        }
+                        None => {
+                            other_rejections += 1;
+                            0
+                        }
+                    }
+                }
+            };
+            for (i, constraint) in all.iter().enumerate().take(checked) {
+                if constraint.applies(&receipt.constraints) {
+                    exercised[i] += 1;
+                }
+            }
+        }
+
+        let attributed = rejected - other_rejections;
+        let constraints = all
+            .iter()
+            .enumerate()
+            .filter(|(i, c)| c.applies(&quote.constraints) || rejections[*i] > 0)
+            .map(|(i, constraint)| ConstraintCoverage {
+                constraint: *constraint,
+                value: constraint.value(&quote.constraints),
+                exercised: exercised[i],
+                rejections: rejections[i],
+                share_of_rejections: (attributed > 0)
+                    .then(|| rejections[i] as f64 / attributed as f64),
+                status: if rejections[i] > 0 {
+                    CoverageStatus::Rejecting
+                } else if exercised[i] > 0 {
+                    CoverageStatus::NeverRejected
+                } else {
+                    CoverageStatus::Unexercised
+                },
+            })
+            .collect();
+
+        Self {
+            quote_id: quote.id,
+            fill_attempts: accepted + rejected,
+            accepted,
+            rejected,
+            other_rejections,
+            constraints,
+        }
+    }
+}
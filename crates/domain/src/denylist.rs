@@ -0,0 +1,38 @@
+//! Taker deny list
+//!
+//! Compliance can supply takers that must never fill (e.g. a sanctions
+//! list) as a plain file: owner IDs separated by newlines or commas, with
+//! `#` starting a comment. The domain reads it at startup and adds it to
+//! every new quote's `denied_takers`, so Local Laws block those takers even
+//! on quotes open to anyone.
+
+use anyhow::{Context, Result};
+use std::path::Path;
+
+/// Read the owner IDs in a deny list file
+pub fn load(path: &Path) -> Result<Vec<String>> {
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read deny list {}", path.display()))?;
+    Ok(parse(&contents))
+}
+
+/// Owner IDs in deny list text
+fn parse(contents: &str) -> Vec<String> {
+    contents
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or_default())
+        .flat_map(|line| line.split(','))
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Add `denied` to a deny list, skipping takers already on it
+pub fn merge(denied_takers: &mut Vec<String>, denied: &[String]) {
+    for taker in denied {
+        if !denied_takers.contains(taker) {
+            denied_takers.push(taker.clone());
+        }
+    }
+}
@@ -0,0 +1,190 @@
+//! Quote drafting sessions
+//!
+//! A maker writes a quote over several messages, chat style.
+//! `POST /quotes/drafts` opens a session (optionally with a first message)
+//! and `POST /quotes/drafts/:id/messages` adds one. After each message the
+//! whole draft is recompiled with [`Compiler::draft`], and the session
+//! keeps the terms understood so far and the questions still open. Once
+//! none are, the draft can be posted with `POST /quotes` and its text.
+//!
+//! Sessions are held in memory and dropped after [`DRAFT_IDLE_SECS`]
+//! without a message.
+//!
+//! [`Compiler::draft`]: rfq_compiler::Compiler::draft
+
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use chrono::{DateTime, Utc};
+use rfq_compiler::QuoteDraft;
+use std::collections::HashMap;
+use std::sync::RwLock;
+use thiserror::Error;
+use uuid::Uuid;
+
+/// Sessions idle this long are dropped
+pub const DRAFT_IDLE_SECS: i64 = 3600;
+
+/// Longest draft, in messages
+pub const MAX_DRAFT_MESSAGES: usize = 20;
+
+/// A quote being written
+#[derive(Debug, Clone)]
+pub struct DraftSession {
+    pub id: Uuid,
+    /// The maker's messages, oldest first
+    pub messages: Vec<String>,
+    /// What the messages compile to (None before the first message)
+    pub draft: Option<QuoteDraft>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl DraftSession {
+    /// The text the messages make up, as compiled and posted
+    pub fn text(&self) -> String {
+        join(&self.messages)
+    }
+}
+
+fn join(messages: &[String]) -> String {
+    messages.join("\n")
+}
+
+/// Why a draft message was refused
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum DraftError {
+    #[error("Unknown draft {0} (drafts expire after {DRAFT_IDLE_SECS}s without a message)")]
+    NotFound(Uuid),
+    #[error("Messages must not be empty")]
+    EmptyMessage,
+    #[error("Drafts are limited to {MAX_DRAFT_MESSAGES} messages")]
+    TooLong,
+    #[error("Another message was added to the draft meanwhile; resend this one")]
+    Conflict,
+}
+
+impl DraftError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::NotFound(_) => "DRAFT_NOT_FOUND",
+            Self::EmptyMessage => "EMPTY_MESSAGE",
+            Self::TooLong => "DRAFT_TOO_LONG",
+            Self::Conflict => "DRAFT_CONFLICT",
+        }
+    }
+
+    pub fn status(&self) -> StatusCode {
+        match self {
+            Self::NotFound(_) => StatusCode::NOT_FOUND,
+            Self::EmptyMessage | Self::TooLong => StatusCode::UNPROCESSABLE_ENTITY,
+            Self::Conflict => StatusCode::CONFLICT,
+        }
+    }
+}
+
+impl IntoResponse for DraftError {
+    fn into_response(self) -> Response {
+        let body = serde_json::json!({
+            "success": false,
+            "error": {
+                "code": self.code(),
+                "message": self.to_string(),
+            },
+        });
+        (self.status(), Json(body)).into_response()
+    }
+}
+
+/// A message ready to compile: the draft's text with it appended
+pub struct PendingMessage {
+    pub id: Uuid,
+    pub message: String,
+    pub text: String,
+    /// Messages in the draft when the text was taken
+    base: usize,
+}
+
+/// Open drafting sessions
+#[derive(Default)]
+pub struct Drafts {
+    sessions: RwLock<HashMap<Uuid, DraftSession>>,
+}
+
+impl Drafts {
+    /// Open an empty session, dropping idle ones
+    pub fn open(&self) -> DraftSession {
+        let now = Utc::now();
+        let session = DraftSession {
+            id: Uuid::new_v4(),
+            messages: vec![],
+            draft: None,
+            created_at: now,
+            updated_at: now,
+        };
+        let mut sessions = self.sessions.write().unwrap();
+        sessions.retain(|_, s| (now - s.updated_at).num_seconds() < DRAFT_IDLE_SECS);
+        sessions.insert(session.id, session.clone());
+        session
+    }
+
+    pub fn get(&self, id: &Uuid) -> Result<DraftSession, DraftError> {
+        self.sessions
+            .read()
+            .unwrap()
+            .get(id)
+            .cloned()
+            .ok_or(DraftError::NotFound(*id))
+    }
+
+    pub fn close(&self, id: &Uuid) -> Result<DraftSession, DraftError> {
+        self.sessions
+            .write()
+            .unwrap()
+            .remove(id)
+            .ok_or(DraftError::NotFound(*id))
+    }
+
+    /// Take the text to compile for a new message
+    pub fn prepare(&self, id: &Uuid, message: &str) -> Result<PendingMessage, DraftError> {
+        let message = message.trim();
+        if message.is_empty() {
+            return Err(DraftError::EmptyMessage);
+        }
+        let session = self.get(id)?;
+        if session.messages.len() >= MAX_DRAFT_MESSAGES {
+            return Err(DraftError::TooLong);
+        }
+        let mut messages = session.messages;
+        let base = messages.len();
+        messages.push(message.to_string());
+        Ok(PendingMessage {
+            id: *id,
+            message: message.to_string(),
+            text: join(&messages),
+            base,
+        })
+    }
+
+    /// Add a compiled message to its session
+    ///
+    /// Refused if another message was added since [`Drafts::prepare`], as
+    /// the draft was compiled without it.
+    pub fn commit(
+        &self,
+        pending: PendingMessage,
+        draft: QuoteDraft,
+    ) -> Result<DraftSession, DraftError> {
+        let mut sessions = self.sessions.write().unwrap();
+        let session = sessions
+            .get_mut(&pending.id)
+            .ok_or(DraftError::NotFound(pending.id))?;
+        if session.messages.len() != pending.base {
+            return Err(DraftError::Conflict);
+        }
+        session.messages.push(pending.message);
+        session.draft = Some(draft);
+        session.updated_at = Utc::now();
+        Ok(session.clone())
+    }
+}
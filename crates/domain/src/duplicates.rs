@@ -0,0 +1,138 @@
+//! Duplicate quote detection
+//!
+//! Flags quotes whose text is (nearly) identical to one the maker already
+//! has open, typically from a retried POST. Texts are normalized (case,
+//! punctuation, whitespace) and compared by hash first, then by the Dice
+//! coefficient of their character bigrams. Fuzzy matches must also quote
+//! the same numbers, so a changed size or price is never a duplicate.
+
+use rfq_models::{Quote, QuoteId};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+
+/// What to do when a maker posts a near-duplicate quote
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DuplicatePolicy {
+    /// Don't check
+    Off,
+    /// Create the quote but return a warning
+    #[default]
+    Warn,
+    /// Refuse the quote with 409 Conflict
+    Block,
+}
+
+/// Duplicate detection settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuplicateGuardConfig {
+    /// Policy for makers without an entry in `makers`
+    #[serde(default)]
+    pub default_policy: DuplicatePolicy,
+    /// Similarity (0.0-1.0) at or above which texts count as duplicates
+    #[serde(default = "default_similarity_threshold")]
+    pub similarity_threshold: f64,
+    /// Per-maker policy overrides, by maker owner ID
+    #[serde(default)]
+    pub makers: HashMap<String, DuplicatePolicy>,
+}
+
+fn default_similarity_threshold() -> f64 {
+    0.9
+}
+
+impl Default for DuplicateGuardConfig {
+    fn default() -> Self {
+        Self {
+            default_policy: DuplicatePolicy::default(),
+            similarity_threshold: default_similarity_threshold(),
+            makers: HashMap::new(),
+        }
+    }
+}
+
+impl DuplicateGuardConfig {
+    /// The policy for a maker
+    pub fn policy_for(&self, maker_owner_id: &str) -> DuplicatePolicy {
+        self.makers
+            .get(maker_owner_id)
+            .copied()
+            .unwrap_or(self.default_policy)
+    }
+}
+
+/// An open quote that a new quote text duplicates
+#[derive(Debug, Clone)]
+pub struct Duplicate {
+    pub quote_id: QuoteId,
+    /// 1.0 for identical normalized text
+    pub similarity: f64,
+}
+
+/// Find the open quote most similar to `text` at or above `threshold`
+pub fn find_duplicate<'a>(
+    text: &str,
+    open_quotes: impl IntoIterator<Item = &'a Quote>,
+    threshold: f64,
+) -> Option<Duplicate> {
+    let normalized = normalize(text);
+    let hash = text_hash(&normalized);
+
+    open_quotes
+        .into_iter()
+        .filter_map(|quote| {
+            let other = normalize(&quote.original_text);
+            let similarity = if text_hash(&other) == hash {
+                1.0
+            } else if numbers(&other) == numbers(&normalized) {
+                similarity(&normalized, &other)
+            } else {
+                0.0
+            };
+            (similarity >= threshold).then_some(Duplicate {
+                quote_id: quote.id,
+                similarity,
+            })
+        })
+        .max_by(|a, b| a.similarity.total_cmp(&b.similarity))
+}
+
+/// Lowercase, strip punctuation (keeping decimal points) and collapse whitespace
+pub fn normalize(text: &str) -> String {
+    text.to_lowercase()
+        .split(|c: char| !(c.is_alphanumeric() || c == '.' || c == '%'))
+        .map(|word| word.trim_matches('.'))
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Numeric tokens of normalized text, sorted
+fn numbers(normalized: &str) -> Vec<&str> {
+    let mut numbers: Vec<&str> = normalized
+        .split(' ')
+        .filter(|word| word.starts_with(|c: char| c.is_ascii_digit()))
+        .collect();
+    numbers.sort_unstable();
+    numbers
+}
+
+/// SHA-256 of normalized text
+fn text_hash(normalized: &str) -> [u8; 32] {
+    Sha256::digest(normalized.as_bytes()).into()
+}
+
+/// Dice coefficient of the character bigrams of two texts
+fn similarity(a: &str, b: &str) -> f64 {
+    fn bigrams(text: &str) -> HashSet<(char, char)> {
+        let chars: Vec<char> = text.chars().collect();
+        chars.windows(2).map(|w| (w[0], w[1])).collect()
+    }
+
+    let (a, b) = (bigrams(a), bigrams(b));
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    2.0 * a.intersection(&b).count() as f64 / (a.len() + b.len()) as f64
+}
@@ -0,0 +1,360 @@
+//! Domain events
+//!
+//! Every change to the domain state is recorded as an event in an
+//! append-only log. Quotes, their compilation records, receipts, proof
+//! statuses, taker nonces, taker and maker reputations, auctions and the
+//! notary log are projections folded from that log, and the timeline of any
+//! quote is read straight off it. The log lives in memory with the rest of
+//! the state; it isn't persisted or replayed.
+
+use chrono::{DateTime, Utc};
+use rfq_compiler::CompilationRecord;
+use rfq_models::{FillReceipt, Quote, QuoteId};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::auction::{Auction, AuctionAward, AuctionResponse};
+use crate::notary::{Anchor, ReceiptLog};
+use crate::settlement::ProofStatus;
+
+/// A change to the domain state
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DomainEvent {
+    /// A maker's quote text was compiled (prompts, answers and
+    /// post-processing), just before the quote was posted
+    QuoteCompiled {
+        quote_id: QuoteId,
+        record: Box<CompilationRecord>,
+    },
+    /// A maker posted a quote
+    QuoteCreated { quote: Box<Quote> },
+    /// A quote's status or fill history changed (full snapshot)
+    QuoteUpdated { quote: Box<Quote> },
+    /// A scheduled quote was posted (full snapshot)
+    QuoteActivated { quote: Box<Quote> },
+    /// A maker approved a quote held for approval (full snapshot)
+    QuoteApproved { quote: Box<Quote> },
+    /// A maker amended an open quote's constraints (full snapshot)
+    QuoteAmended { quote: Box<Quote> },
+    /// A maker cancelled a quote (full snapshot)
+    QuoteCancelled { quote: Box<Quote> },
+    /// A divergence guard cancelled a quote (full snapshot)
+    ProtectiveCancel {
+        quote: Box<Quote>,
+        trigger: DivergenceTrigger,
+    },
+    /// A divergence guard suspended a quote (full snapshot)
+    ProtectiveSuspend {
+        quote: Box<Quote>,
+        trigger: DivergenceTrigger,
+    },
+    /// The market came back within a suspended quote's threshold (full snapshot)
+    QuoteResumed {
+        quote: Box<Quote>,
+        market_price: f64,
+    },
+    /// A fill attempt was decided (or its benchmark settlement resolved)
+    FillRecorded {
+        quote_id: QuoteId,
+        receipt: Box<FillReceipt>,
+    },
+    /// An accepted fill's proof status changed
+    ProofStatusChanged {
+        quote_id: QuoteId,
+        sdl_hash: String,
+        status: ProofStatus,
+    },
+    /// A taker's fill request nonce was consumed
+    TakerNonceUsed { taker_owner_id: String, nonce: u64 },
+    /// A day's receipt root was anchored on Delta
+    AnchorRecorded { anchor: Anchor },
+    /// A taker opened an RFQ auction
+    AuctionOpened { auction: Box<Auction> },
+    /// A maker submitted a sealed response to an auction
+    AuctionResponded {
+        auction_id: Uuid,
+        response: AuctionResponse,
+    },
+    /// A taker accepted an auction's winning response
+    AuctionAwarded {
+        auction_id: Uuid,
+        award: Box<AuctionAward>,
+    },
+}
+
+impl DomainEvent {
+    /// The quote the event concerns, if any
+    pub fn quote_id(&self) -> Option<QuoteId> {
+        match self {
+            Self::QuoteCreated { quote }
+            | Self::QuoteUpdated { quote }
+            | Self::QuoteActivated { quote }
+            | Self::QuoteApproved { quote }
+            | Self::QuoteAmended { quote }
+            | Self::QuoteCancelled { quote }
+            | Self::ProtectiveCancel { quote, .. }
+            | Self::ProtectiveSuspend { quote, .. }
+            | Self::QuoteResumed { quote, .. } => Some(quote.id),
+            Self::QuoteCompiled { quote_id, .. }
+            | Self::FillRecorded { quote_id, .. }
+            | Self::ProofStatusChanged { quote_id, .. } => Some(*quote_id),
+            Self::AuctionResponded { response, .. } => Some(response.quote_id),
+            Self::TakerNonceUsed { .. }
+            | Self::AnchorRecorded { .. }
+            | Self::AuctionOpened { .. }
+            | Self::AuctionAwarded { .. } => None,
+        }
+    }
+
+    /// Short event name, e.g. "fill_recorded"
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::QuoteCompiled { .. } => "quote_compiled",
+            Self::QuoteCreated { .. } => "quote_created",
+            Self::QuoteUpdated { .. } => "quote_updated",
+            Self::QuoteActivated { .. } => "quote_activated",
+            Self::QuoteApproved { .. } => "quote_approved",
+            Self::QuoteAmended { .. } => "quote_amended",
+            Self::QuoteCancelled { .. } => "quote_cancelled",
+            Self::ProtectiveCancel { .. } => "protective_cancel",
+            Self::ProtectiveSuspend { .. } => "protective_suspend",
+            Self::QuoteResumed { .. } => "quote_resumed",
+            Self::FillRecorded { .. } => "fill_recorded",
+            Self::ProofStatusChanged { .. } => "proof_status_changed",
+            Self::TakerNonceUsed { .. } => "taker_nonce_used",
+            Self::AnchorRecorded { .. } => "anchor_recorded",
+            Self::AuctionOpened { .. } => "auction_opened",
+            Self::AuctionResponded { .. } => "auction_responded",
+            Self::AuctionAwarded { .. } => "auction_awarded",
+        }
+    }
+}
+
+/// Why a divergence guard acted on a quote
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct DivergenceTrigger {
+    /// The quote's reference price
+    pub reference_price: f64,
+    /// The feed consensus that breached the threshold
+    pub market_price: f64,
+    /// How far apart they were, in percent
+    pub move_percent: f64,
+    /// The guard's threshold, in percent
+    pub max_move_percent: f64,
+}
+
+/// An event with its position in the log
+#[derive(Debug, Clone, Serialize)]
+pub struct EventEnvelope {
+    /// Position in the log (1-based, gapless)
+    pub seq: u64,
+    /// When the event was appended
+    pub at: DateTime<Utc>,
+    pub event: DomainEvent,
+}
+
+/// Decided fills of one taker
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TakerStats {
+    pub accepted: usize,
+    pub decided: usize,
+}
+
+/// Reliability record of one maker
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MakerStats {
+    /// Quotes posted
+    pub quotes: usize,
+    /// Amendments to quotes takers had already filled against
+    pub amended_after_fill: usize,
+    /// Accepted fills whose proof reached a final status
+    pub settled: usize,
+    /// Of those, proofs that failed or timed out
+    pub settlement_failures: usize,
+}
+
+impl MakerStats {
+    /// Share of settled fills whose proof failed or timed out
+    pub fn settlement_failure_rate(&self) -> Option<f64> {
+        (self.settled > 0).then(|| self.settlement_failures as f64 / self.settled as f64)
+    }
+
+    /// Amendments after a fill per quote posted
+    pub fn amendment_after_fill_rate(&self) -> f64 {
+        if self.quotes == 0 {
+            return 0.0;
+        }
+        (self.amended_after_fill as f64 / self.quotes as f64).min(1.0)
+    }
+
+    /// Reliability from 0 to 1 (higher is better)
+    ///
+    /// The product of the settlement success rate and the share of quotes
+    /// not amended after a fill. None until the maker has settled a fill or
+    /// amended a filled quote.
+    pub fn score(&self) -> Option<f64> {
+        if self.settled == 0 && self.amended_after_fill == 0 {
+            return None;
+        }
+        let settlement = 1.0 - self.settlement_failure_rate().unwrap_or(0.0);
+        Some(settlement * (1.0 - self.amendment_after_fill_rate()))
+    }
+}
+
+/// State folded from the event log
+#[derive(Debug, Default)]
+pub struct Projections {
+    /// Quotes indexed by ID
+    pub quotes: HashMap<QuoteId, Quote>,
+    /// How each quote was compiled
+    pub compilations: HashMap<QuoteId, CompilationRecord>,
+    /// Fill receipts indexed by quote ID, in log order
+    pub receipts: HashMap<QuoteId, Vec<FillReceipt>>,
+    /// Last accepted fill request nonce per taker
+    pub taker_nonces: HashMap<String, u64>,
+    /// Proof status of accepted fills, by SDL hash
+    pub proofs: HashMap<String, ProofStatus>,
+    /// Decided fills per taker (pending benchmark fills count as neither)
+    pub reputations: HashMap<String, TakerStats>,
+    /// Reliability record per maker
+    pub maker_reputations: HashMap<String, MakerStats>,
+    /// Hash-chained log of all receipts, with their anchors
+    pub receipt_log: ReceiptLog,
+    /// RFQ auctions by ID
+    pub auctions: HashMap<Uuid, Auction>,
+}
+
+impl Projections {
+    /// Fold one event into the projections
+    pub fn apply(&mut self, event: &DomainEvent) {
+        match event {
+            DomainEvent::QuoteCompiled { quote_id, record } => {
+                self.compilations
+                    .insert(*quote_id, CompilationRecord::clone(record));
+            }
+            DomainEvent::QuoteCreated { quote } => {
+                self.maker_stats(&quote.maker_owner_id).quotes += 1;
+                self.quotes.insert(quote.id, Quote::clone(quote));
+            }
+            DomainEvent::QuoteAmended { quote } => {
+                if !quote.fill_history.is_empty() {
+                    self.maker_stats(&quote.maker_owner_id).amended_after_fill += 1;
+                }
+                self.quotes.insert(quote.id, Quote::clone(quote));
+            }
+            DomainEvent::QuoteUpdated { quote }
+            | DomainEvent::QuoteActivated { quote }
+            | DomainEvent::QuoteApproved { quote }
+            | DomainEvent::QuoteCancelled { quote }
+            | DomainEvent::ProtectiveCancel { quote, .. }
+            | DomainEvent::ProtectiveSuspend { quote, .. }
+            | DomainEvent::QuoteResumed { quote, .. } => {
+                self.quotes.insert(quote.id, Quote::clone(quote));
+            }
+            DomainEvent::FillRecorded { quote_id, receipt } => {
+                if !receipt.is_pending() {
+                    let stats = self
+                        .reputations
+                        .entry(receipt.fill_attempt.taker_owner_id.clone())
+                        .or_default();
+                    stats.accepted += receipt.is_accepted() as usize;
+                    stats.decided += 1;
+                }
+                self.receipt_log.append(receipt);
+                self.receipts
+                    .entry(*quote_id)
+                    .or_default()
+                    .push(FillReceipt::clone(receipt));
+            }
+            DomainEvent::ProofStatusChanged {
+                quote_id,
+                sdl_hash,
+                status,
+            } => {
+                // A pending proof is counted once it settles either way
+                let previous = self.proofs.insert(sdl_hash.clone(), *status);
+                let settled = |s: &ProofStatus| *s != ProofStatus::Pending;
+                let newly_settled = settled(status) && !previous.as_ref().is_some_and(settled);
+                let maker = self.quotes.get(quote_id).map(|q| q.maker_owner_id.clone());
+                if let Some(maker) = maker.filter(|_| newly_settled) {
+                    let stats = self.maker_stats(&maker);
+                    stats.settled += 1;
+                    stats.settlement_failures += (*status != ProofStatus::Verified) as usize;
+                }
+            }
+            DomainEvent::TakerNonceUsed {
+                taker_owner_id,
+                nonce,
+            } => {
+                self.taker_nonces.insert(taker_owner_id.clone(), *nonce);
+            }
+            DomainEvent::AnchorRecorded { anchor } => {
+                self.receipt_log.record_anchor(anchor.clone());
+            }
+            DomainEvent::AuctionOpened { auction } => {
+                self.auctions.insert(auction.id, Auction::clone(auction));
+            }
+            DomainEvent::AuctionResponded {
+                auction_id,
+                response,
+            } => {
+                if let Some(auction) = self.auctions.get_mut(auction_id) {
+                    auction.responses.push(response.clone());
+                }
+            }
+            DomainEvent::AuctionAwarded { auction_id, award } => {
+                if let Some(auction) = self.auctions.get_mut(auction_id) {
+                    auction.award = Some(AuctionAward::clone(award));
+                }
+            }
+        }
+    }
+
+    fn maker_stats(&mut self, maker_owner_id: &str) -> &mut MakerStats {
+        self.maker_reputations
+            .entry(maker_owner_id.to_string())
+            .or_default()
+    }
+}
+
+/// Append-only event log plus the projections folded from it
+#[derive(Debug, Default)]
+pub struct EventStore {
+    events: Vec<EventEnvelope>,
+    projections: Projections,
+}
+
+impl EventStore {
+    /// Append an event and fold it into the projections
+    pub fn append(&mut self, event: DomainEvent) -> &EventEnvelope {
+        self.projections.apply(&event);
+        self.events.push(EventEnvelope {
+            seq: self.events.len() as u64 + 1,
+            at: Utc::now(),
+            event,
+        });
+        self.events.last().expect("just pushed")
+    }
+
+    /// The current projections
+    pub fn projections(&self) -> &Projections {
+        &self.projections
+    }
+
+    /// Events after sequence number `after` (0 for all)
+    pub fn events_after(&self, after: u64) -> &[EventEnvelope] {
+        let start = (after as usize).min(self.events.len());
+        &self.events[start..]
+    }
+
+    /// Events concerning one quote, in order
+    pub fn quote_events(&self, quote_id: &QuoteId) -> Vec<EventEnvelope> {
+        self.events
+            .iter()
+            .filter(|e| e.event.quote_id().as_ref() == Some(quote_id))
+            .cloned()
+            .collect()
+    }
+}
@@ -0,0 +1,550 @@
+//! Data retention export to Parquet
+//!
+//! A periodic job writes closed quotes, fill attempts and receipts to
+//! Parquet files in a local directory or an S3-compatible bucket, so arena
+//! behavior can be analyzed with standard tooling instead of through the
+//! API. Files are partitioned by table and UTC day, Hive style:
+//! `<table>/date=<YYYY-MM-DD>/part-<run>.parquet`. Each record is written
+//! once: a quote when it can no longer be filled, a receipt (and the fill
+//! attempt behind it) when it is generated.
+//!
+//! What has been written is tracked per table in `_export_state.json` at
+//! the destination, saved around every file, so neither a restart nor a run
+//! that fails halfway writes records again. A file that was being written
+//! when the domain died is rewritten under the same name.
+//!
+//! ```yaml
+//! export:
+//!   enabled: true
+//!   interval_secs: 3600
+//!   destination: s3://arena-data/rfq    # or a local directory
+//!   s3_endpoint: http://localhost:9000  # for S3-compatible stores
+//! ```
+//!
+//! S3 credentials and region come from the usual `AWS_*` environment
+//! variables.
+
+use anyhow::{Context, Result};
+use arrow_array::{
+    ArrayRef, Float64Array, RecordBatch, StringArray, TimestampMillisecondArray, UInt32Array,
+    UInt64Array,
+};
+use arrow_schema::{DataType, Field, Schema, SchemaRef, TimeUnit};
+use chrono::{DateTime, NaiveDate, Utc};
+use object_store::aws::AmazonS3Builder;
+use object_store::local::LocalFileSystem;
+use object_store::path::Path;
+use object_store::ObjectStore;
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+use rfq_models::{FillReceipt, FillResult, Quote, QuoteStatus};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::api_types::{side_to_string, status_to_string};
+
+/// Parquet export settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportConfig {
+    /// Whether quotes, fills and receipts are exported
+    #[serde(default)]
+    pub enabled: bool,
+    /// How often new records are exported, in seconds
+    #[serde(default = "default_interval_secs")]
+    pub interval_secs: u64,
+    /// Local directory, or `s3://<bucket>/<prefix>`
+    #[serde(default = "default_destination")]
+    pub destination: String,
+    /// Endpoint of an S3-compatible store (AWS S3 if unset)
+    #[serde(default)]
+    pub s3_endpoint: Option<String>,
+}
+
+fn default_interval_secs() -> u64 {
+    3600
+}
+
+fn default_destination() -> String {
+    "exports".to_string()
+}
+
+impl Default for ExportConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_secs: default_interval_secs(),
+            destination: default_destination(),
+            s3_endpoint: None,
+        }
+    }
+}
+
+/// Rows written by one export run
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ExportSummary {
+    pub quotes: usize,
+    pub fills: usize,
+    pub receipts: usize,
+    pub files: usize,
+}
+
+/// Object at the destination recording what has been exported
+const STATE_FILE: &str = "_export_state.json";
+
+/// Records written so far, by table, and the file being written
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ExportState {
+    exported: HashMap<String, HashSet<Uuid>>,
+    #[serde(default)]
+    pending: Option<PendingFile>,
+}
+
+/// A file whose write may not have finished
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PendingFile {
+    table: String,
+    file: String,
+    ids: Vec<Uuid>,
+}
+
+/// Writes records to Parquet, remembering which have been written
+pub struct Exporter {
+    store: Arc<dyn ObjectStore>,
+    prefix: String,
+    /// Loaded from the destination on the first run
+    state: Option<ExportState>,
+}
+
+impl Exporter {
+    pub fn new(config: &ExportConfig) -> Result<Self> {
+        let (store, prefix): (Arc<dyn ObjectStore>, String) =
+            match config.destination.strip_prefix("s3://") {
+                Some(location) => {
+                    let (bucket, prefix) = location.split_once('/').unwrap_or((location, ""));
+                    let mut builder = AmazonS3Builder::from_env().with_bucket_name(bucket);
+                    if let Some(endpoint) = &config.s3_endpoint {
+                        builder = builder
+                            .with_endpoint(endpoint)
+                            .with_allow_http(endpoint.starts_with("http://"));
+                    }
+                    let store = builder.build().context("Failed to configure S3 export")?;
+                    (Arc::new(store), prefix.trim_matches('/').to_string())
+                }
+                None => {
+                    std::fs::create_dir_all(&config.destination).with_context(|| {
+                        format!("Failed to create export directory {}", config.destination)
+                    })?;
+                    let store = LocalFileSystem::new_with_prefix(&config.destination)?;
+                    (Arc::new(store), String::new())
+                }
+            };
+        Ok(Self {
+            store,
+            prefix,
+            state: None,
+        })
+    }
+
+    /// Export closed quotes and receipts that haven't been written yet
+    ///
+    /// Records are only marked as written once their file is stored, so a
+    /// failed run is retried on the next one.
+    pub async fn run(
+        &mut self,
+        quotes: &[Quote],
+        receipts: &[FillReceipt],
+    ) -> Result<ExportSummary> {
+        if self.state.is_none() {
+            self.state = Some(self.load_state().await?);
+        }
+        let mut summary = ExportSummary::default();
+        let closed: Vec<&Quote> = quotes.iter().filter(|q| !q.is_open()).collect();
+        let receipts: Vec<&FillReceipt> = receipts.iter().collect();
+
+        // Finish the file a previous run died writing, under the same name
+        if let Some(pending) = self.state().pending.clone() {
+            let wanted = |id: &Uuid| pending.ids.contains(id);
+            let quotes: Vec<&Quote> = closed.iter().copied().filter(|q| wanted(&q.id)).collect();
+            let receipts: Vec<&FillReceipt> =
+                receipts.iter().copied().filter(|r| wanted(&r.receipt_id)).collect();
+            let batch = match pending.table.as_str() {
+                _ if quotes.is_empty() && receipts.is_empty() => None,
+                "quotes" => Some(quote_batch(&quotes)?),
+                "fills" => Some(fill_batch(&receipts)?),
+                _ => Some(receipt_batch(&receipts)?),
+            };
+            // Records lost with the domain's memory can't be written again
+            if let Some(batch) = batch {
+                self.put(&pending.file, batch).await?;
+                summary.files += 1;
+            }
+            self.commit(&pending.table, pending.ids).await?;
+        }
+
+        let run = Utc::now().format("%Y%m%dT%H%M%S%.3fZ").to_string();
+        let new = closed.iter().copied().filter(|q| !self.exported("quotes", &q.id));
+        for (day, quotes) in by_day(new, |q| q.created_at) {
+            let ids = quotes.iter().map(|q| q.id).collect();
+            self.write("quotes", day, &run, ids, quote_batch(&quotes)?).await?;
+            summary.quotes += quotes.len();
+            summary.files += 1;
+        }
+
+        // Fills and receipts are tracked apart, so a run that fails between
+        // the two doesn't write the fills again
+        let new = receipts
+            .iter()
+            .copied()
+            .filter(|r| !self.exported("fills", &r.receipt_id));
+        for (day, receipts) in by_day(new, |r| r.generated_at) {
+            let ids = receipts.iter().map(|r| r.receipt_id).collect();
+            self.write("fills", day, &run, ids, fill_batch(&receipts)?).await?;
+            summary.fills += receipts.len();
+            summary.files += 1;
+        }
+        let new = receipts
+            .iter()
+            .copied()
+            .filter(|r| !self.exported("receipts", &r.receipt_id));
+        for (day, receipts) in by_day(new, |r| r.generated_at) {
+            let ids = receipts.iter().map(|r| r.receipt_id).collect();
+            self.write("receipts", day, &run, ids, receipt_batch(&receipts)?).await?;
+            summary.receipts += receipts.len();
+            summary.files += 1;
+        }
+
+        // Forget records the domain no longer holds, so the state stays the
+        // size of the domain's own
+        let live: HashSet<Uuid> = closed
+            .iter()
+            .map(|q| q.id)
+            .chain(receipts.iter().map(|r| r.receipt_id))
+            .collect();
+        let state = self.state_mut();
+        let before: usize = state.exported.values().map(HashSet::len).sum();
+        for ids in state.exported.values_mut() {
+            ids.retain(|id| live.contains(id));
+        }
+        if state.exported.values().map(HashSet::len).sum::<usize>() < before {
+            self.save_state().await?;
+        }
+
+        Ok(summary)
+    }
+
+    fn state(&self) -> &ExportState {
+        self.state.as_ref().expect("export state loaded")
+    }
+
+    fn state_mut(&mut self) -> &mut ExportState {
+        self.state.as_mut().expect("export state loaded")
+    }
+
+    fn exported(&self, table: &str, id: &Uuid) -> bool {
+        self.state().exported.get(table).is_some_and(|ids| ids.contains(id))
+    }
+
+    /// Path of an object under the destination's prefix
+    fn path(&self, file: &str) -> Path {
+        if self.prefix.is_empty() {
+            Path::from(file)
+        } else {
+            Path::from(format!("{}/{}", self.prefix, file))
+        }
+    }
+
+    async fn load_state(&self) -> Result<ExportState> {
+        match self.store.get(&self.path(STATE_FILE)).await {
+            Ok(object) => {
+                let bytes = object.bytes().await?;
+                serde_json::from_slice(&bytes)
+                    .with_context(|| format!("Corrupt export state {}", STATE_FILE))
+            }
+            Err(object_store::Error::NotFound { .. }) => Ok(ExportState::default()),
+            Err(e) => Err(e).with_context(|| format!("Failed to read {}", STATE_FILE)),
+        }
+    }
+
+    async fn save_state(&self) -> Result<()> {
+        let bytes = serde_json::to_vec(self.state())?;
+        self.store
+            .put(&self.path(STATE_FILE), bytes.into())
+            .await
+            .with_context(|| format!("Failed to write {}", STATE_FILE))?;
+        Ok(())
+    }
+
+    /// Mark a table's records as written and clear the pending file
+    async fn commit(&mut self, table: &str, ids: Vec<Uuid>) -> Result<()> {
+        let state = self.state_mut();
+        state.exported.entry(table.to_string()).or_default().extend(ids);
+        state.pending = None;
+        self.save_state().await
+    }
+
+    /// Write a table's records for a day, recording the file as pending
+    /// until it is stored
+    async fn write(
+        &mut self,
+        table: &str,
+        day: NaiveDate,
+        run: &str,
+        ids: Vec<Uuid>,
+        batch: RecordBatch,
+    ) -> Result<()> {
+        let file = format!("{}/date={}/part-{}.parquet", table, day, run);
+        self.state_mut().pending = Some(PendingFile {
+            table: table.to_string(),
+            file: file.clone(),
+            ids: ids.clone(),
+        });
+        self.save_state().await?;
+        self.put(&file, batch).await?;
+        self.commit(table, ids).await
+    }
+
+    async fn put(&self, file: &str, batch: RecordBatch) -> Result<()> {
+        let props = WriterProperties::builder()
+            .set_compression(Compression::SNAPPY)
+            .build();
+        let mut buffer = Vec::new();
+        let mut writer = ArrowWriter::try_new(&mut buffer, batch.schema(), Some(props))?;
+        writer.write(&batch)?;
+        writer.close()?;
+        self.store
+            .put(&self.path(file), buffer.into())
+            .await
+            .with_context(|| format!("Failed to write {}", file))?;
+        Ok(())
+    }
+}
+
+/// Group records by the UTC day of a timestamp
+fn by_day<'a, T>(
+    records: impl Iterator<Item = &'a T>,
+    at: impl Fn(&T) -> DateTime<Utc>,
+) -> BTreeMap<NaiveDate, Vec<&'a T>> {
+    let mut days: BTreeMap<NaiveDate, Vec<&T>> = BTreeMap::new();
+    for record in records {
+        days.entry(at(record).date_naive()).or_default().push(record);
+    }
+    days
+}
+
+fn timestamp(name: &str, nullable: bool) -> Field {
+    Field::new(
+        name,
+        DataType::Timestamp(TimeUnit::Millisecond, Some("UTC".into())),
+        nullable,
+    )
+}
+
+fn timestamps(values: Vec<Option<i64>>) -> ArrayRef {
+    Arc::new(TimestampMillisecondArray::from(values).with_timezone("UTC"))
+}
+
+fn strings<'a>(values: impl IntoIterator<Item = Option<&'a str>>) -> ArrayRef {
+    Arc::new(values.into_iter().collect::<StringArray>())
+}
+
+/// Schema of the `quotes` table
+pub fn quote_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("quote_id", DataType::Utf8, false),
+        Field::new("maker_owner_id", DataType::Utf8, false),
+        Field::new("asset", DataType::Utf8, false),
+        Field::new("currency", DataType::Utf8, false),
+        Field::new("side", DataType::Utf8, false),
+        Field::new("size", DataType::Float64, false),
+        Field::new("limit_price", DataType::Float64, true),
+        Field::new("status", DataType::Utf8, false),
+        timestamp("created_at", false),
+        timestamp("expires_at", false),
+        Field::new("fill_count", DataType::UInt32, false),
+        Field::new("max_debit", DataType::UInt64, true),
+        Field::new("min_credit", DataType::UInt64, true),
+        Field::new("allowed_sources", DataType::Utf8, false),
+        Field::new("quorum_count", DataType::UInt32, false),
+        Field::new("quorum_tolerance_percent", DataType::Float64, false),
+        Field::new("max_staleness_secs", DataType::UInt64, false),
+        Field::new("preset", DataType::Utf8, true),
+        Field::new("original_text", DataType::Utf8, false),
+    ]))
+}
+
+fn quote_batch(quotes: &[&Quote]) -> Result<RecordBatch> {
+    // A quote past its expiry may not have been marked expired yet
+    let status = |q: &Quote| match q.status {
+        QuoteStatus::Filled | QuoteStatus::Cancelled => status_to_string(q.status),
+        _ => status_to_string(QuoteStatus::Expired),
+    };
+    let statuses: Vec<String> = quotes.iter().map(|q| status(q)).collect();
+    let sources: Vec<String> = quotes
+        .iter()
+        .map(|q| q.constraints.allowed_sources.join(","))
+        .collect();
+    let ids: Vec<String> = quotes.iter().map(|q| q.id.to_string()).collect();
+    let sides: Vec<String> = quotes.iter().map(|q| side_to_string(q.spec.side)).collect();
+    let columns: Vec<ArrayRef> = vec![
+        strings(ids.iter().map(|s| Some(s.as_str()))),
+        strings(quotes.iter().map(|q| Some(q.maker_owner_id.as_str()))),
+        strings(quotes.iter().map(|q| Some(q.spec.asset.as_str()))),
+        strings(quotes.iter().map(|q| Some(q.spec.currency.as_str()))),
+        strings(sides.iter().map(|s| Some(s.as_str()))),
+        Arc::new(Float64Array::from_iter_values(quotes.iter().map(|q| q.spec.size))),
+        Arc::new(quotes.iter().map(|q| q.spec.limit_price).collect::<Float64Array>()),
+        strings(statuses.iter().map(|s| Some(s.as_str()))),
+        timestamps(quotes.iter().map(|q| Some(q.created_at.timestamp_millis())).collect()),
+        timestamps(quotes.iter().map(|q| Some(q.expires_at.timestamp_millis())).collect()),
+        Arc::new(UInt32Array::from_iter_values(
+            quotes.iter().map(|q| q.fill_history.len() as u32),
+        )),
+        Arc::new(
+            quotes
+                .iter()
+                .map(|q| Some(q.constraints.max_debit).filter(|&d| d != u64::MAX))
+                .collect::<UInt64Array>(),
+        ),
+        Arc::new(quotes.iter().map(|q| q.constraints.min_credit).collect::<UInt64Array>()),
+        strings(sources.iter().map(|s| Some(s.as_str()))),
+        Arc::new(UInt32Array::from_iter_values(
+            quotes.iter().map(|q| q.constraints.quorum_count),
+        )),
+        Arc::new(Float64Array::from_iter_values(
+            quotes.iter().map(|q| q.constraints.quorum_tolerance_percent()),
+        )),
+        Arc::new(UInt64Array::from_iter_values(
+            quotes.iter().map(|q| q.constraints.max_staleness_secs),
+        )),
+        strings(quotes.iter().map(|q| q.preset.as_deref())),
+        strings(quotes.iter().map(|q| Some(q.original_text.as_str()))),
+    ];
+    Ok(RecordBatch::try_new(quote_schema(), columns)?)
+}
+
+/// How a receipt's fill ended, as exported
+fn outcome(receipt: &FillReceipt) -> &'static str {
+    match receipt.result {
+        FillResult::Accepted { .. } => "accepted",
+        FillResult::PendingSettlement { .. } => "pending_settlement",
+        FillResult::Rejected { .. } => "rejected",
+    }
+}
+
+/// Schema of the `fills` table (one row per fill attempt)
+pub fn fill_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("fill_id", DataType::Utf8, false),
+        Field::new("receipt_id", DataType::Utf8, false),
+        Field::new("quote_id", DataType::Utf8, false),
+        Field::new("taker_owner_id", DataType::Utf8, false),
+        Field::new("taker_shard", DataType::UInt64, false),
+        Field::new("size", DataType::Float64, false),
+        Field::new("price", DataType::Float64, false),
+        Field::new("feed_sources", DataType::Utf8, false),
+        Field::new("outcome", DataType::Utf8, false),
+        timestamp("attempted_at", false),
+    ]))
+}
+
+fn fill_batch(receipts: &[&FillReceipt]) -> Result<RecordBatch> {
+    let attempts: Vec<_> = receipts.iter().map(|r| &r.fill_attempt).collect();
+    let fill_ids: Vec<String> = attempts.iter().map(|a| a.id.to_string()).collect();
+    let receipt_ids: Vec<String> = receipts.iter().map(|r| r.receipt_id.to_string()).collect();
+    let quote_ids: Vec<String> = attempts.iter().map(|a| a.quote_id.to_string()).collect();
+    let sources: Vec<String> = attempts
+        .iter()
+        .map(|a| {
+            let names: Vec<&str> = a.feed_evidence.iter().map(|e| e.source.as_str()).collect();
+            names.join(",")
+        })
+        .collect();
+    let columns: Vec<ArrayRef> = vec![
+        strings(fill_ids.iter().map(|s| Some(s.as_str()))),
+        strings(receipt_ids.iter().map(|s| Some(s.as_str()))),
+        strings(quote_ids.iter().map(|s| Some(s.as_str()))),
+        strings(attempts.iter().map(|a| Some(a.taker_owner_id.as_str()))),
+        Arc::new(UInt64Array::from_iter_values(attempts.iter().map(|a| a.taker_shard))),
+        Arc::new(Float64Array::from_iter_values(attempts.iter().map(|a| a.size))),
+        Arc::new(Float64Array::from_iter_values(attempts.iter().map(|a| a.price))),
+        strings(sources.iter().map(|s| Some(s.as_str()))),
+        strings(receipts.iter().map(|r| Some(outcome(r)))),
+        timestamps(attempts.iter().map(|a| Some(a.attempted_at.timestamp_millis())).collect()),
+    ];
+    Ok(RecordBatch::try_new(fill_schema(), columns)?)
+}
+
+/// Schema of the `receipts` table
+pub fn receipt_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("receipt_id", DataType::Utf8, false),
+        Field::new("quote_id", DataType::Utf8, false),
+        Field::new("fill_id", DataType::Utf8, false),
+        Field::new("outcome", DataType::Utf8, false),
+        Field::new("rejection_code", DataType::Utf8, true),
+        Field::new("rejection_message", DataType::Utf8, true),
+        Field::new("sdl_hash", DataType::Utf8, true),
+        Field::new("maker_debit", DataType::UInt64, true),
+        Field::new("maker_credit", DataType::UInt64, true),
+        Field::new("taker_debit", DataType::UInt64, true),
+        Field::new("taker_credit", DataType::UInt64, true),
+        timestamp("settled_at", true),
+        timestamp("generated_at", false),
+    ]))
+}
+
+fn receipt_batch(receipts: &[&FillReceipt]) -> Result<RecordBatch> {
+    let receipt_ids: Vec<String> = receipts.iter().map(|r| r.receipt_id.to_string()).collect();
+    let quote_ids: Vec<String> = receipts.iter().map(|r| r.quote.id.to_string()).collect();
+    let fill_ids: Vec<String> = receipts.iter().map(|r| r.fill_attempt.id.to_string()).collect();
+    let rejections: Vec<Option<(&str, String)>> = receipts
+        .iter()
+        .map(|r| match &r.result {
+            FillResult::Rejected { reason, .. } => Some((reason.code(), reason.message())),
+            _ => None,
+        })
+        .collect();
+    let settlements: Vec<_> = receipts
+        .iter()
+        .map(|r| match &r.result {
+            FillResult::Accepted {
+                sdl_hash,
+                settlement,
+                ..
+            } => Some((sdl_hash.as_str(), settlement)),
+            _ => None,
+        })
+        .collect();
+    let amount = |f: fn(&rfq_models::SettlementDetails) -> u64| -> ArrayRef {
+        Arc::new(
+            settlements
+                .iter()
+                .map(|s| s.map(|(_, details)| f(details)))
+                .collect::<UInt64Array>(),
+        )
+    };
+    let columns: Vec<ArrayRef> = vec![
+        strings(receipt_ids.iter().map(|s| Some(s.as_str()))),
+        strings(quote_ids.iter().map(|s| Some(s.as_str()))),
+        strings(fill_ids.iter().map(|s| Some(s.as_str()))),
+        strings(receipts.iter().map(|r| Some(outcome(r)))),
+        strings(rejections.iter().map(|r| r.as_ref().map(|(code, _)| *code))),
+        strings(rejections.iter().map(|r| r.as_ref().map(|(_, message)| message.as_str()))),
+        strings(settlements.iter().map(|s| s.map(|(hash, _)| hash))),
+        amount(|s| s.maker_debit),
+        amount(|s| s.maker_credit),
+        amount(|s| s.taker_debit),
+        amount(|s| s.taker_credit),
+        timestamps(
+            settlements
+                .iter()
+                .map(|s| s.map(|(_, details)| details.settled_at.timestamp_millis()))
+                .collect(),
+        ),
+        timestamps(receipts.iter().map(|r| Some(r.generated_at.timestamp_millis())).collect()),
+    ];
+    Ok(RecordBatch::try_new(receipt_schema(), columns)?)
+}
@@ -0,0 +1,236 @@
+//! Fill settlement journal
+//!
+//! An accepted fill settles in steps: validated, SDL submitted, proving,
+//! proven, proof submitted. Each step is written to the journal file before
+//! the next one starts, so after a crash the domain resumes every unfinished
+//! fill from its last durable step on restart instead of abandoning the
+//! settlement or submitting its transfers twice. Fills leave the journal
+//! once their proof is submitted or settlement definitively fails.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use rfq_models::{Quote, QuoteId, Side};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use uuid::Uuid;
+
+/// Context for submitting a fill to Delta
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FillContext {
+    /// Quote being filled
+    pub quote_id: QuoteId,
+    /// Maker's owner ID (base58 or arbitrary string)
+    pub maker_owner_id: String,
+    /// Taker's owner ID (base58 or arbitrary string)
+    pub taker_owner_id: String,
+    /// Amount the maker pays (in plancks): the fill price of a buy quote,
+    /// the fill size of a sell
+    pub maker_pays: u64,
+    /// Amount the taker pays (in plancks): the other leg
+    pub taker_pays: u64,
+    /// What the fill trades, so it can be booked after a restart (the
+    /// domain's quotes don't survive one); unset in older journal entries
+    #[serde(default)]
+    pub terms: Option<FillTerms>,
+}
+
+/// The quote side and symbols of a fill
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FillTerms {
+    pub side: Side,
+    pub asset: String,
+    pub currency: String,
+}
+
+impl FillContext {
+    /// Context of a fill of `quote` by `taker_owner_id`, with its legs
+    /// running the way the quote's side says
+    pub fn new(quote: &Quote, taker_owner_id: &str, fill_size: u64, fill_price: u64) -> Self {
+        let (maker_pays, taker_pays) = quote.constraints.side.legs(fill_size, fill_price);
+        Self {
+            quote_id: quote.id,
+            maker_owner_id: quote.maker_owner_id.clone(),
+            taker_owner_id: taker_owner_id.to_string(),
+            maker_pays,
+            taker_pays,
+            terms: Some(FillTerms {
+                side: quote.constraints.side,
+                asset: quote.spec.asset.clone(),
+                currency: quote.spec.currency.clone(),
+            }),
+        }
+    }
+}
+
+/// Last durable step of a fill's settlement
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FillStage {
+    /// Accepted by Local Laws; transfers not yet submitted
+    Validated,
+    /// Transfers submitted in an SDL
+    SdlSubmitted,
+    /// Proof generation started
+    Proving,
+    /// Proof generated and held by the runtime
+    Proven,
+    /// Proof submitted to the base layer
+    ProofSubmitted,
+}
+
+/// A fill whose settlement hasn't finished
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub fill_id: Uuid,
+    pub fill: FillContext,
+    pub stage: FillStage,
+    /// Local Laws input, as the JSON the prover reads
+    pub input: Vec<u8>,
+    /// Domain vault nonce of the fill's first transfer, once reserved
+    pub base_nonce: Option<u64>,
+    /// The runtime's SDL hash, once submitted
+    pub sdl_hash: Option<serde_json::Value>,
+    /// The SDL hash as shown in receipts
+    pub sdl_hash_display: Option<String>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Journal of unfinished fill settlements
+#[derive(Debug)]
+pub struct FillJournal {
+    /// File the journal is persisted to (memory only if unset)
+    path: Option<PathBuf>,
+    entries: Mutex<BTreeMap<Uuid, JournalEntry>>,
+}
+
+impl FillJournal {
+    /// Open the journal, loading unfinished fills from `path`
+    pub fn open(path: Option<PathBuf>) -> Result<Self> {
+        let mut entries = BTreeMap::new();
+        if let Some(path) = &path {
+            match std::fs::read(path) {
+                Ok(bytes) => {
+                    let loaded: Vec<JournalEntry> = serde_json::from_slice(&bytes)
+                        .with_context(|| format!("Corrupt fill journal {}", path.display()))?;
+                    entries.extend(loaded.into_iter().map(|e| (e.fill_id, e)));
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => {
+                    return Err(e)
+                        .with_context(|| format!("Failed to read fill journal {}", path.display()))
+                }
+            }
+        }
+
+        Ok(Self {
+            path,
+            entries: Mutex::new(entries),
+        })
+    }
+
+    /// Record a validated fill, returning its journal ID
+    ///
+    /// Fails, recording nothing, if the journal can't be persisted.
+    pub fn begin(&self, fill: FillContext, input: Vec<u8>) -> Result<Uuid> {
+        let entry = JournalEntry {
+            fill_id: Uuid::new_v4(),
+            fill,
+            stage: FillStage::Validated,
+            input,
+            base_nonce: None,
+            sdl_hash: None,
+            sdl_hash_display: None,
+            updated_at: Utc::now(),
+        };
+        let fill_id = entry.fill_id;
+        let mut entries = self.entries.lock().expect("journal lock poisoned");
+        entries.insert(fill_id, entry);
+        if let Err(e) = self.save(&entries) {
+            entries.remove(&fill_id);
+            return Err(e);
+        }
+        Ok(fill_id)
+    }
+
+    pub fn get(&self, fill_id: Uuid) -> Option<JournalEntry> {
+        let entries = self.entries.lock().expect("journal lock poisoned");
+        entries.get(&fill_id).cloned()
+    }
+
+    /// Update a fill's entry and persist it before returning
+    ///
+    /// If the journal can't be persisted the entry is left as it was.
+    pub fn update(&self, fill_id: Uuid, f: impl FnOnce(&mut JournalEntry)) -> Result<()> {
+        let mut entries = self.entries.lock().expect("journal lock poisoned");
+        let Some(entry) = entries.get_mut(&fill_id) else {
+            return Ok(());
+        };
+        let previous = entry.clone();
+        f(entry);
+        entry.updated_at = Utc::now();
+        if let Err(e) = self.save(&entries) {
+            entries.insert(fill_id, previous);
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    /// Advance a fill to `stage`
+    pub fn advance(&self, fill_id: Uuid, stage: FillStage) -> Result<()> {
+        self.update(fill_id, |entry| entry.stage = stage)
+    }
+
+    /// Drop a fill whose settlement is over
+    ///
+    /// If the journal can't be persisted the fill stays in it, and is
+    /// resumed on restart.
+    pub fn finish(&self, fill_id: Uuid) -> Result<()> {
+        let mut entries = self.entries.lock().expect("journal lock poisoned");
+        let Some(entry) = entries.remove(&fill_id) else {
+            return Ok(());
+        };
+        if let Err(e) = self.save(&entries) {
+            entries.insert(fill_id, entry);
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    /// Fills whose settlement hasn't finished, oldest update first
+    pub fn unfinished(&self) -> Vec<JournalEntry> {
+        let entries = self.entries.lock().expect("journal lock poisoned");
+        let mut unfinished: Vec<JournalEntry> = entries.values().cloned().collect();
+        unfinished.sort_by_key(|e| e.updated_at);
+        unfinished
+    }
+
+    /// Write the journal atomically and durably (synced temp file, then a
+    /// rename synced through the parent directory)
+    fn save(&self, entries: &BTreeMap<Uuid, JournalEntry>) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let tmp = path.with_extension("tmp");
+        let write = || -> std::io::Result<()> {
+            let bytes = serde_json::to_vec(&entries.values().collect::<Vec<_>>())?;
+            let mut file = std::fs::File::create(&tmp)?;
+            file.write_all(&bytes)?;
+            file.sync_all()?;
+            std::fs::rename(&tmp, path)?;
+            // Directories can't be opened for syncing on Windows
+            #[cfg(unix)]
+            {
+                let dir = match path.parent() {
+                    Some(dir) if !dir.as_os_str().is_empty() => dir,
+                    _ => std::path::Path::new("."),
+                };
+                std::fs::File::open(dir)?.sync_all()?;
+            }
+            Ok(())
+        };
+        write().with_context(|| format!("Failed to save fill journal {}", path.display()))
+    }
+}
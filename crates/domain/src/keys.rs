@@ -0,0 +1,330 @@
+//! Domain key management
+//!
+//! The domain operator key is loaded from the first available source:
+//! - `DOMAIN_PRIVATE_KEY` environment variable (base58, for injected secrets)
+//! - an encrypted key file (JSON envelope, unlocked with `KEYPAIR_PASSPHRASE`)
+//! - a plain base58 key file (the format of `keypair_9.json`)
+//!
+//! If none exists, a new key is generated and persisted to `keypair_path`.
+//!
+//! Rotation moves the active key file into `retired_keys_dir` and generates a
+//! fresh key in its place. Retired keys are kept as public identities only, so
+//! receipts produced before a rotation can still be attributed and verified.
+
+use anyhow::{bail, Context, Result};
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use delta_domain_sdk::base::crypto::ed25519::PrivKey;
+use delta_domain_sdk::base::crypto::OwnerId;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+
+use crate::config::DomainConfig;
+
+/// Environment variable holding a base58 private key
+const PRIVATE_KEY_ENV: &str = "DOMAIN_PRIVATE_KEY";
+
+/// Environment variable holding the key file passphrase
+const PASSPHRASE_ENV: &str = "KEYPAIR_PASSPHRASE";
+
+/// PBKDF2 iterations for deriving the key file encryption key
+const KDF_ITERATIONS: u32 = 100_000;
+
+/// Where the active key was loaded from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeySource {
+    /// Injected via `DOMAIN_PRIVATE_KEY`
+    Environment,
+    /// Decrypted from a passphrase-protected key file
+    EncryptedFile,
+    /// Read from a plain base58 key file
+    PlainFile,
+    /// Freshly generated on this start
+    Generated,
+}
+
+/// Public identity of a domain key
+#[derive(Debug, Clone)]
+pub struct KeyIdentity {
+    /// The owner ID derived from the key
+    pub owner: OwnerId,
+    /// Short hex fingerprint (first 8 bytes of SHA-256 of the owner ID)
+    pub fingerprint: String,
+}
+
+impl KeyIdentity {
+    fn of(key: &PrivKey) -> Self {
+        let owner = key.pub_key().owner();
+        Self {
+            owner,
+            fingerprint: fingerprint(owner),
+        }
+    }
+}
+
+/// Passphrase-encrypted key file envelope
+#[derive(Debug, Serialize, Deserialize)]
+struct EncryptedKeyFile {
+    /// Key derivation function ("pbkdf2-sha256")
+    kdf: String,
+    /// KDF iteration count
+    iterations: u32,
+    /// KDF salt (base58)
+    salt: String,
+    /// ChaCha20-Poly1305 nonce (base58)
+    nonce: String,
+    /// Encrypted 32-byte private key (base58)
+    ciphertext: String,
+}
+
+/// The active domain key plus the identities of all retired keys
+pub struct KeyRing {
+    active: PrivKey,
+    active_identity: KeyIdentity,
+    source: KeySource,
+    retired: Vec<KeyIdentity>,
+}
+
+impl KeyRing {
+    /// Load the active key and all retired keys
+    pub fn load(config: &DomainConfig) -> Result<Self> {
+        let passphrase = std::env::var(PASSPHRASE_ENV).ok();
+        let keypair_path = Path::new(&config.keypair_path);
+
+        let (active, source) = if let Ok(encoded) = std::env::var(PRIVATE_KEY_ENV) {
+            let key = decode_plain_key(&encoded)
+                .with_context(|| format!("Invalid key in {}", PRIVATE_KEY_ENV))?;
+            (key, KeySource::Environment)
+        } else if keypair_path.exists() {
+            read_key_file(keypair_path, passphrase.as_deref())?
+        } else {
+            tracing::warn!(
+                "Keypair file {:?} not found, generating and persisting a new keypair",
+                keypair_path
+            );
+            let key = generate_key_file(keypair_path, passphrase.as_deref())?;
+            (key, KeySource::Generated)
+        };
+
+        let retired = load_retired(Path::new(&config.retired_keys_dir), passphrase.as_deref())?;
+
+        Ok(Self {
+            active_identity: KeyIdentity::of(&active),
+            active,
+            source,
+            retired,
+        })
+    }
+
+    /// The key used for signing new transfers
+    pub fn active(&self) -> &PrivKey {
+        &self.active
+    }
+
+    /// Identity of the active key
+    pub fn active_identity(&self) -> &KeyIdentity {
+        &self.active_identity
+    }
+
+    /// Where the active key came from
+    pub fn source(&self) -> KeySource {
+        self.source
+    }
+
+    /// Identities of keys retired by previous rotations
+    pub fn retired(&self) -> &[KeyIdentity] {
+        &self.retired
+    }
+}
+
+/// Rotate the domain key
+///
+/// Moves the current key file into `retired_keys_dir` (named by its
+/// fingerprint) and generates a new key at `keypair_path`. Returns the
+/// identity of the new active key.
+pub fn rotate(config: &DomainConfig) -> Result<KeyIdentity> {
+    if std::env::var(PRIVATE_KEY_ENV).is_ok() {
+        bail!(
+            "Cannot rotate while {} is set; rotate the injected secret instead",
+            PRIVATE_KEY_ENV
+        );
+    }
+
+    let passphrase = std::env::var(PASSPHRASE_ENV).ok();
+    let keypair_path = Path::new(&config.keypair_path);
+    let retired_dir = Path::new(&config.retired_keys_dir);
+
+    if keypair_path.exists() {
+        let (old_key, _) = read_key_file(keypair_path, passphrase.as_deref())?;
+        let old = KeyIdentity::of(&old_key);
+
+        std::fs::create_dir_all(retired_dir)
+            .with_context(|| format!("Failed to create {:?}", retired_dir))?;
+        let retired_path = retired_dir.join(format!(
+            "{}-{}.json",
+            chrono::Utc::now().format("%Y%m%dT%H%M%SZ"),
+            old.fingerprint
+        ));
+        std::fs::rename(keypair_path, &retired_path)
+            .with_context(|| format!("Failed to retire key to {:?}", retired_path))?;
+
+        tracing::info!("Retired key {} to {:?}", old.fingerprint, retired_path);
+    }
+
+    let key = generate_key_file(keypair_path, passphrase.as_deref())?;
+    let identity = KeyIdentity::of(&key);
+    tracing::info!("Rotated to new key {}", identity.fingerprint);
+    Ok(identity)
+}
+
+/// Short hex fingerprint of an owner ID
+pub fn fingerprint(owner: OwnerId) -> String {
+    let bytes: [u8; 32] = owner.into();
+    let digest = Sha256::digest(bytes);
+    digest[..8].iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Read a plain or encrypted key file
+fn read_key_file(path: &Path, passphrase: Option<&str>) -> Result<(PrivKey, KeySource)> {
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read key file {:?}", path))?;
+
+    match serde_json::from_str::<EncryptedKeyFile>(&contents) {
+        Ok(envelope) => {
+            let passphrase = passphrase.with_context(|| {
+                format!("Key file {:?} is encrypted; set {}", path, PASSPHRASE_ENV)
+            })?;
+            let key = decrypt_key(&envelope, passphrase)
+                .with_context(|| format!("Failed to decrypt key file {:?}", path))?;
+            Ok((key, KeySource::EncryptedFile))
+        }
+        Err(_) => {
+            let key = decode_plain_key(&contents)
+                .with_context(|| format!("Failed to decode key file {:?}", path))?;
+            Ok((key, KeySource::PlainFile))
+        }
+    }
+}
+
+/// Decode a base58 private key (optionally JSON-quoted)
+fn decode_plain_key(encoded: &str) -> Result<PrivKey> {
+    let encoded = encoded.trim().trim_matches('"');
+    let key_bytes = bs58::decode(encoded)
+        .into_vec()
+        .context("Failed to decode base58 keypair")?;
+    let key_array: [u8; 32] = key_bytes
+        .try_into()
+        .map_err(|v: Vec<u8>| anyhow::anyhow!("Expected 32 bytes, got {}", v.len()))?;
+    Ok(PrivKey::from_bytes(&key_array))
+}
+
+/// Generate a new key and persist it, encrypted if a passphrase is set
+fn generate_key_file(path: &Path, passphrase: Option<&str>) -> Result<PrivKey> {
+    let mut key_bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut key_bytes);
+
+    let contents = match passphrase {
+        Some(passphrase) => serde_json::to_string_pretty(&encrypt_key(&key_bytes, passphrase)?)?,
+        None => format!("\"{}\"", bs58::encode(key_bytes).into_string()),
+    };
+
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {:?}", parent))?;
+    }
+    write_private(path, contents.as_bytes())
+        .with_context(|| format!("Failed to write key file {:?}", path))?;
+
+    Ok(PrivKey::from_bytes(&key_bytes))
+}
+
+/// Write a file only its owner can read (the key may be unencrypted)
+fn write_private(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    use std::io::Write;
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options.open(path)?.write_all(contents)
+}
+
+/// Load public identities of all keys in the retired directory
+fn load_retired(dir: &Path, passphrase: Option<&str>) -> Result<Vec<KeyIdentity>> {
+    if !dir.exists() {
+        return Ok(vec![]);
+    }
+
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(dir)
+        .with_context(|| format!("Failed to read retired keys dir {:?}", dir))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|p| p.is_file())
+        .collect();
+    paths.sort();
+
+    let mut retired = Vec::with_capacity(paths.len());
+    for path in paths {
+        match read_key_file(&path, passphrase) {
+            Ok((key, _)) => retired.push(KeyIdentity::of(&key)),
+            Err(e) => tracing::warn!("Skipping retired key {:?}: {:#}", path, e),
+        }
+    }
+    Ok(retired)
+}
+
+/// Derive the file encryption key from a passphrase
+fn derive_key(passphrase: &str, salt: &[u8], iterations: u32) -> Key {
+    let mut key = [0u8; 32];
+    pbkdf2::pbkdf2_hmac::<Sha256>(passphrase.as_bytes(), salt, iterations, &mut key);
+    Key::from(key)
+}
+
+fn encrypt_key(key_bytes: &[u8; 32], passphrase: &str) -> Result<EncryptedKeyFile> {
+    let mut salt = [0u8; 16];
+    let mut nonce = [0u8; 12];
+    rand::thread_rng().fill_bytes(&mut salt);
+    rand::thread_rng().fill_bytes(&mut nonce);
+
+    let cipher = ChaCha20Poly1305::new(&derive_key(passphrase, &salt, KDF_ITERATIONS));
+    let ciphertext = cipher
+        .encrypt(&Nonce::from(nonce), key_bytes.as_slice())
+        .map_err(|_| anyhow::anyhow!("Key encryption failed"))?;
+
+    Ok(EncryptedKeyFile {
+        kdf: "pbkdf2-sha256".to_string(),
+        iterations: KDF_ITERATIONS,
+        salt: bs58::encode(salt).into_string(),
+        nonce: bs58::encode(nonce).into_string(),
+        ciphertext: bs58::encode(ciphertext).into_string(),
+    })
+}
+
+fn decrypt_key(envelope: &EncryptedKeyFile, passphrase: &str) -> Result<PrivKey> {
+    if envelope.kdf != "pbkdf2-sha256" {
+        bail!("Unsupported key derivation function: {}", envelope.kdf);
+    }
+
+    let salt = bs58::decode(&envelope.salt).into_vec().context("Invalid salt")?;
+    let nonce: [u8; 12] = bs58::decode(&envelope.nonce)
+        .into_vec()
+        .context("Invalid nonce")?
+        .try_into()
+        .map_err(|v: Vec<u8>| anyhow::anyhow!("Expected 12-byte nonce, got {}", v.len()))?;
+    let ciphertext = bs58::decode(&envelope.ciphertext)
+        .into_vec()
+        .context("Invalid ciphertext")?;
+
+    let cipher = ChaCha20Poly1305::new(&derive_key(passphrase, &salt, envelope.iterations));
+    let plaintext = cipher
+        .decrypt(&Nonce::from(nonce), ciphertext.as_slice())
+        .map_err(|_| anyhow::anyhow!("Wrong passphrase or corrupted key file"))?;
+    let key_array: [u8; 32] = plaintext
+        .try_into()
+        .map_err(|v: Vec<u8>| anyhow::anyhow!("Expected 32 bytes, got {}", v.len()))?;
+    Ok(PrivKey::from_bytes(&key_array))
+}
@@ -0,0 +1,151 @@
+//! Per-API-key size limits
+//!
+//! Ceilings on quote and fill sizes enforced at the API layer, independent
+//! of quote constraints, so a compromised or runaway agent key can't post
+//! huge quotes or fills even when the constraints would allow them.
+//! Requests identify their key with the `X-API-Key` header.
+
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use thiserror::Error;
+
+/// Header carrying the caller's API key
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+/// Size and notional ceilings for one key
+///
+/// Sizes are in asset units, notionals in quote currency units. Unset
+/// ceilings are not enforced.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SizeLimits {
+    /// Largest total size a quote can trade (size per fill x max fills)
+    #[serde(default)]
+    pub max_quote_size: Option<f64>,
+    /// Largest total notional a quote can trade, at its limit price
+    #[serde(default)]
+    pub max_quote_notional: Option<f64>,
+    /// Largest single fill
+    #[serde(default)]
+    pub max_fill_size: Option<f64>,
+    /// Largest single fill notional (size x price)
+    #[serde(default)]
+    pub max_fill_notional: Option<f64>,
+}
+
+/// API key limits
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ApiLimitsConfig {
+    /// Reject quote and fill requests without a configured key
+    #[serde(default)]
+    pub require_key: bool,
+    /// Limits for requests without a key
+    #[serde(default)]
+    pub default_limits: SizeLimits,
+    /// Limits per API key
+    #[serde(default)]
+    pub keys: HashMap<String, SizeLimits>,
+}
+
+/// Why a request was refused by its key's limits
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum LimitError {
+    #[error("An API key is required (X-API-Key header)")]
+    MissingKey,
+    #[error("Unknown API key")]
+    UnknownKey,
+    #[error("Quote size {size} exceeds this key's limit of {limit}")]
+    QuoteSize { size: f64, limit: f64 },
+    #[error("Quote notional {notional} exceeds this key's limit of {limit}")]
+    QuoteNotional { notional: f64, limit: f64 },
+    #[error("Quote notional can't be bounded without a price limit (key limit {limit})")]
+    UnboundedNotional { limit: f64 },
+    #[error("Fill size {size} exceeds this key's limit of {limit}")]
+    FillSize { size: f64, limit: f64 },
+    #[error("Fill notional {notional} exceeds this key's limit of {limit}")]
+    FillNotional { notional: f64, limit: f64 },
+}
+
+impl LimitError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::MissingKey | Self::UnknownKey => "INVALID_API_KEY",
+            _ => "API_KEY_LIMIT_EXCEEDED",
+        }
+    }
+
+    pub fn status(&self) -> StatusCode {
+        match self {
+            Self::MissingKey | Self::UnknownKey => StatusCode::UNAUTHORIZED,
+            _ => StatusCode::FORBIDDEN,
+        }
+    }
+}
+
+impl IntoResponse for LimitError {
+    fn into_response(self) -> Response {
+        let body = serde_json::json!({
+            "success": false,
+            "error": {
+                "code": self.code(),
+                "message": self.to_string(),
+            },
+        });
+        (self.status(), Json(body)).into_response()
+    }
+}
+
+/// The API key a request was sent with
+pub fn api_key(headers: &HeaderMap) -> Option<&str> {
+    headers.get(API_KEY_HEADER).and_then(|v| v.to_str().ok())
+}
+
+impl ApiLimitsConfig {
+    /// The limits for a request's API key
+    pub fn limits_for(&self, key: Option<&str>) -> Result<&SizeLimits, LimitError> {
+        match key {
+            Some(key) => self.keys.get(key).ok_or(LimitError::UnknownKey),
+            None if self.require_key => Err(LimitError::MissingKey),
+            None => Ok(&self.default_limits),
+        }
+    }
+}
+
+impl SizeLimits {
+    /// Check a quote trading `size` per fill up to `max_fills` times
+    pub fn check_quote(
+        &self,
+        size: f64,
+        max_fills: u32,
+        limit_price: Option<f64>,
+    ) -> Result<(), LimitError> {
+        let size = size * max_fills.max(1) as f64;
+        if let Some(limit) = self.max_quote_size.filter(|limit| size > *limit) {
+            return Err(LimitError::QuoteSize { size, limit });
+        }
+        if let Some(limit) = self.max_quote_notional {
+            let Some(price) = limit_price else {
+                return Err(LimitError::UnboundedNotional { limit });
+            };
+            let notional = size * price;
+            if notional > limit {
+                return Err(LimitError::QuoteNotional { notional, limit });
+            }
+        }
+        Ok(())
+    }
+
+    /// Check a fill of `size` at `price`
+    pub fn check_fill(&self, size: f64, price: f64) -> Result<(), LimitError> {
+        if let Some(limit) = self.max_fill_size.filter(|limit| size > *limit) {
+            return Err(LimitError::FillSize { size, limit });
+        }
+        let notional = size * price;
+        if let Some(limit) = self.max_fill_notional.filter(|limit| notional > *limit) {
+            return Err(LimitError::FillNotional { notional, limit });
+        }
+        Ok(())
+    }
+}
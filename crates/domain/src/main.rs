@@ -20,19 +20,26 @@
 
 use anyhow::{Context, Result};
 use axum::{
-    extract::{Path, State},
-    http::StatusCode,
+    body::Body,
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
     routing::{get, post},
     Json, Router,
 };
 use clap::Parser;
 use delta_domain_sdk::base::crypto::ed25519::PrivKey;
-use delta_domain_sdk::base::vaults::{Address, TokenKind, Vault, WritableNativeBalance};
+use delta_domain_sdk::base::vaults::{
+    Address, ReadableNativeBalance, TokenKind, Vault, WritableNativeBalance,
+};
 use delta_domain_sdk::proving::mock;
 use delta_domain_sdk::{execution::default_execute, Runtime, SdlState};
 use delta_verifiable::types::debit_allowance::{AllowanceAmount, DebitAllowance, SignedDebitAllowance};
 use delta_verifiable::types::VerifiableType;
-use rfq_compiler::{Compiler, CompilerConfig};
+use rfq_compiler::{
+    CancellationToken, CompilationRecord, CompileMethod, CompileOptions, Compiler,
+    CompilerConfig, CompilerError, PromptTemplate,
+};
 use rfq_models::*;
 use std::collections::{BTreeMap, HashMap};
 use std::num::NonZero;
@@ -43,13 +50,52 @@ use tower_http::cors::{Any, CorsLayer};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 use uuid::Uuid;
 
+mod analytics;
 mod api_types;
+mod auction;
 mod config;
+mod coverage;
+mod denylist;
+mod drafts;
+mod duplicates;
+mod events;
+mod export;
+mod journal;
+mod keys;
+mod limits;
+mod markets;
+mod notary;
+mod reference;
+mod screening;
+mod settlement;
+mod simulation;
 mod state;
+mod watchdog;
 
-use api_types::{ApiCreateQuoteResponse, ApiFillResponse, ApiQuote, ApiReceiptSummary};
+use analytics::{ConstraintAnalytics, MakerReport};
+use auction::{Auction, AuctionError, AuctionStatus, RankedResponse};
+use api_types::{
+    ApiCompilationResponse, ApiCreateQuoteResponse, ApiDraftResponse, ApiFillResponse,
+    ApiLintResponse, ApiQuote, ApiReceiptSummary, ApiTimelineEntry,
+};
 use config::DomainConfig;
-use state::DomainState;
+use coverage::CoverageReport;
+use drafts::{DraftSession, Drafts};
+use duplicates::DuplicatePolicy;
+use events::EventEnvelope;
+use journal::{FillContext, FillJournal, FillStage, FillTerms, JournalEntry};
+use keys::KeyRing;
+use markets::{Market, MarketError, Markets};
+use export::Exporter;
+use notary::{Anchor, InclusionError, PendingAnchor};
+use reference::FeedClient;
+use settlement::{
+    settlement_id, ProofNotification, ProofReference, ProofStatus, SettlementLeg,
+    SettlementParty, SettlementRecord, Webhooks, SETTLEMENT_SCHEMA_VERSION,
+};
+use simulation::ProvingSimulator;
+use state::{DomainState, MakerActionError, ReceiptFilter};
+use watchdog::{RuntimeOutcome, Watchdog};
 
 /// CLI arguments
 #[derive(Parser)]
@@ -67,6 +113,10 @@ struct CliArgs {
     /// Run in mock mode (no Delta testnet connection)
     #[arg(long)]
     mock: bool,
+
+    /// Retire the current domain key and generate a new one before starting
+    #[arg(long)]
+    rotate_key: bool,
 }
 
 /// Type alias for our Runtime with mock proving
@@ -78,10 +128,26 @@ pub struct AppState {
     pub domain: Arc<DomainState>,
     /// Delta Runtime (for SDL submission and proving)
     pub runtime: Arc<RwLock<DeltaRuntime>>,
-    /// Domain operator keypair (for signing transfers)
-    pub keypair: Arc<PrivKey>,
+    /// Domain operator keys (active signing key + retired identities)
+    pub keys: Arc<KeyRing>,
     /// LLM compiler for quotes
     pub compiler: Compiler,
+    /// Price feed client for reference price snapshots
+    pub feeds: FeedClient,
+    /// Suspends fills while the runtime is unhealthy
+    pub watchdog: Watchdog,
+    /// Tradable markets and whether they are halted
+    pub markets: Markets,
+    /// Quotes being written over several messages
+    pub drafts: Drafts,
+    /// Latest constraint effectiveness analytics
+    pub analytics: RwLock<ConstraintAnalytics>,
+    /// Parties' webhooks for proof status changes
+    pub webhooks: Webhooks,
+    /// Settlement steps of unfinished fills, for resuming after a crash
+    pub journal: FillJournal,
+    /// Simulated proving latency and failures (mock mode only)
+    pub proving_sim: Option<ProvingSimulator>,
     /// Configuration
     pub config: DomainConfig,
 }
@@ -119,6 +185,13 @@ async fn main() -> Result<()> {
         config.mock_mode = true;
     }
 
+    // Add the externally provided deny list
+    if let Some(path) = &config.denied_takers_path {
+        let denied = denylist::load(path)?;
+        tracing::info!("Loaded {} denied takers from {}", denied.len(), path.display());
+        denylist::merge(&mut config.denied_takers, &denied);
+    }
+
     tracing::info!(
         "Configuration: shard={}, port={}, mock_mode={}",
         config.shard,
@@ -126,36 +199,140 @@ async fn main() -> Result<()> {
         config.mock_mode
     );
 
-    // Initialize LLM compiler
-    if config.llm_api_key.is_empty() {
-        tracing::error!("No LLM API key configured. Set ANTHROPIC_API_KEY or OPENAI_API_KEY");
-        std::process::exit(1);
-    }
-
-    tracing::info!("Using LLM compiler ({} provider)", config.llm_provider);
-    let compiler = Compiler::new(CompilerConfig {
+    // Initialize the quote compiler
+    let prompt = match &config.llm_prompt_path {
+        Some(path) => PromptTemplate::load(path)
+            .with_context(|| format!("Failed to load prompt template {}", path.display()))?,
+        None => PromptTemplate::default(),
+    };
+    let compiler_config = CompilerConfig {
         llm: config.llm_provider.clone(),
         api_key: config.llm_api_key.clone(),
-        model: if config.llm_provider == "claude" {
-            "claude-sonnet-4-20250514".to_string()
-        } else {
-            "gpt-4o-mini".to_string()
-        },
-    });
+        model: config.llm_model.clone().unwrap_or_else(|| {
+            match config.llm_provider.as_str() {
+                "claude" => "claude-sonnet-4-20250514",
+                "ollama" => "llama3.1",
+                "gemini" => "gemini-2.0-flash",
+                "mock" => "mock",
+                _ => "gpt-4o-mini",
+            }
+            .to_string()
+        }),
+        base_url: config.llm_base_url.clone(),
+        deployment: config.llm_deployment.clone(),
+        api_version: config.llm_api_version.clone(),
+        presets: config.presets.clone(),
+        default_preset: config.default_preset.clone(),
+        assets: config.assets.clone(),
+        structured_output: config.llm_structured_output,
+        fallbacks: config.llm_fallbacks.clone(),
+        retry: config.llm_retry.clone(),
+        cache: config.compile_cache.clone(),
+        confidence_pass: config.llm_confidence_pass,
+        min_confidence: config.min_field_confidence,
+        budget: config.llm_budget.clone(),
+        prompt,
+        batch_concurrency: rfq_compiler::DEFAULT_BATCH_CONCURRENCY,
+        call_timeout: std::time::Duration::from_secs(config.llm_timeout_secs),
+        temperature: config.llm_temperature,
+        top_p: config.llm_top_p,
+        seed: config.llm_seed,
+        max_tokens: config.llm_max_tokens,
+        deterministic: config.llm_deterministic,
+    };
+    let keyless = matches!(config.llm_provider.as_str(), "ollama" | "mock");
+    let compiler = if config.llm_api_key.is_empty() && !keyless {
+        tracing::warn!(
+            "No LLM API key configured (set ANTHROPIC_API_KEY, OPENAI_API_KEY, \
+             AZURE_OPENAI_API_KEY or GEMINI_API_KEY); \
+             only quotes in the deterministic grammar can be compiled"
+        );
+        Compiler::offline(compiler_config)
+    } else {
+        tracing::info!(
+            "Using LLM compiler ({} provider, {} fallbacks)",
+            config.llm_provider,
+            config.llm_fallbacks.len()
+        );
+        Compiler::new(compiler_config).context("Failed to initialize LLM compiler")?
+    };
+    let compiler = compiler.with_audit_hook(Arc::new(log_compilation));
+
+    // Load (and optionally rotate) the domain keys
+    if args.rotate_key {
+        keys::rotate(&config).context("Failed to rotate domain key")?;
+    }
+    let keys = KeyRing::load(&config).context("Failed to load domain keys")?;
+    tracing::info!(
+        "Using keypair: {} (fingerprint={}, source={:?}, retired={})",
+        keys.active_identity().owner,
+        keys.active_identity().fingerprint,
+        keys.source(),
+        keys.retired().len()
+    );
 
     // Initialize Delta Runtime
-    let (runtime, keypair) = init_runtime(&config).await?;
+    let runtime = init_runtime(&config, keys.active().clone()).await?;
     tracing::info!("Delta Runtime initialized (mock_mode={})", config.mock_mode);
+    let proving_sim = match &config.proving_simulation {
+        Some(simulation) if config.mock_mode => {
+            tracing::info!("Simulating proving backend: {:?}", simulation);
+            Some(ProvingSimulator::new(simulation.clone()))
+        }
+        Some(_) => {
+            tracing::warn!("Ignoring proving_simulation outside mock mode");
+            None
+        }
+        None => None,
+    };
 
     // Create application state
     let state = Arc::new(AppState {
-        domain: DomainState::new(),
+        domain: DomainState::new(config.assets.clone()),
         runtime: Arc::new(RwLock::new(runtime)),
-        keypair: Arc::new(keypair),
+        keys: Arc::new(keys),
         compiler,
+        feeds: FeedClient::new(config.feed_urls.clone()),
+        watchdog: Watchdog::new(config.watchdog.clone()),
+        markets: Markets::new(&config.markets, &config.assets),
+        drafts: Drafts::default(),
+        analytics: RwLock::new(ConstraintAnalytics::default()),
+        webhooks: Webhooks::new(
+            config.settlement.webhooks.clone(),
+            config.settlement.booking_webhooks.clone(),
+            config.settlement.booking_retry.clone(),
+        ),
+        journal: FillJournal::open(config.settlement.journal_path.clone())?,
+        proving_sim,
         config: config.clone(),
     });
 
+    // Pick up fill settlements interrupted by a crash
+    tokio::spawn(resume_fills(state.clone()));
+
+    // Probe a suspended runtime so fills resume once it recovers
+    tokio::spawn(probe_runtime_health(state.clone()));
+
+    // Periodically recompute constraint effectiveness analytics
+    tokio::spawn(refresh_analytics(state.clone()));
+
+    // Post scheduled quotes when their time comes
+    tokio::spawn(activate_scheduled_quotes(state.clone()));
+
+    // Cancel or suspend guarded quotes when their market moves away
+    tokio::spawn(guard_quote_divergence(state.clone()));
+
+    // Periodically anchor receipt roots on Delta
+    if config.notary.enabled {
+        tokio::spawn(anchor_receipts(state.clone()));
+    }
+
+    // Periodically export quotes, fills and receipts to Parquet
+    if config.export.enabled {
+        let exporter = Exporter::new(&config.export)?;
+        tokio::spawn(export_records(state.clone(), exporter));
+    }
+
     // Build router
     let app = Router::new()
         // Health check
@@ -163,16 +340,47 @@ async fn main() -> Result<()> {
         // Quote endpoints
         .route("/quotes", get(list_quotes))
         .route("/quotes", post(create_quote))
+        .route("/quotes/lint", post(lint_quote))
+        .route("/quotes/drafts", post(open_draft))
+        .route("/quotes/drafts/:id", get(get_draft).delete(close_draft))
+        .route("/quotes/drafts/:id/messages", post(add_draft_message))
         .route("/quotes/:id", get(get_quote))
         .route("/quotes/:id/fill", post(fill_quote))
+        .route("/quotes/:id/cancel", post(cancel_quote))
+        .route("/quotes/:id/approve", post(approve_quote))
+        .route("/quotes/:id/amend", post(amend_quote))
+        .route("/quotes/:id/timeline", get(get_quote_timeline))
+        .route("/quotes/:id/coverage", get(get_quote_coverage))
+        .route("/quotes/:id/compilation", get(get_quote_compilation))
         // Receipt endpoints
         .route("/quotes/:id/receipts", get(get_receipts))
+        .route("/auctions", get(list_auctions))
+        .route("/auctions", post(open_auction))
+        .route("/auctions/:id", get(get_auction))
+        .route("/auctions/:id/responses", post(respond_to_auction))
+        .route("/auctions/:id/accept", post(accept_auction))
+        .route("/receipts/:id", get(get_receipt))
+        .route("/receipts/:id/inclusion", get(get_inclusion_proof))
+        .route("/anchors", get(list_anchors))
+        // Markets
+        .route("/markets", get(list_markets))
+        .route("/markets/:id/halt", post(halt_market))
+        .route("/markets/:id/resume", post(resume_market))
+        // Event log
+        .route("/events", get(list_events))
+        // Maker endpoints
+        .route("/makers/:id/report", get(get_maker_report))
+        // Taker endpoints
+        .route("/takers/:id/nonce", get(get_taker_nonce))
+        // Test funding (mock mode only)
+        .route("/faucet", post(faucet))
         // CORS
         .layer(
             CorsLayer::new()
                 .allow_origin(Any)
                 .allow_methods(Any)
-                .allow_headers(Any),
+                .allow_headers(Any)
+                .expose_headers([header::HeaderName::from_static("x-next-cursor")]),
         )
         .with_state(state);
 
@@ -185,7 +393,18 @@ async fn main() -> Result<()> {
     tracing::info!("  POST /quotes              - Create quote from text");
     tracing::info!("  GET  /quotes/:id         - Get quote");
     tracing::info!("  POST /quotes/:id/fill    - Fill quote");
+    tracing::info!("  GET  /quotes/:id/timeline - Quote event timeline");
+    tracing::info!("  GET  /quotes/:id/coverage - Constraint coverage of a quote");
+    tracing::info!("  GET  /quotes/:id/compilation - How a quote was compiled");
     tracing::info!("  GET  /quotes/:id/receipts - Get receipts");
+    tracing::info!("  GET  /receipts/:id/inclusion - Receipt inclusion proof");
+    tracing::info!("  GET  /anchors             - Anchored receipt roots");
+    tracing::info!("  GET  /markets             - Supported markets and their status");
+    tracing::info!("  GET  /events              - Domain event log (admin)");
+    tracing::info!("  GET  /makers/:id/report   - Maker fill outcomes and tuning suggestions");
+    if config.mock_mode {
+        tracing::info!("  POST /faucet              - Fund a vault with test plancks");
+    }
 
     let listener = tokio::net::TcpListener::bind(&addr).await?;
     axum::serve(listener, app).await?;
@@ -193,36 +412,226 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
-/// Initialize the Delta Runtime
-/// Returns the runtime and the domain keypair
-async fn init_runtime(config: &DomainConfig) -> Result<(DeltaRuntime, PrivKey)> {
-    let shard = NonZero::new(config.shard).context("Invalid shard (cannot be 0)")?;
+/// Periodically probe the runtime while fills are suspended
+async fn probe_runtime_health(state: Arc<AppState>) {
+    let mut ticker = tokio::time::interval(state.watchdog.probe_interval());
+    loop {
+        ticker.tick().await;
+        if state.watchdog.suspension().is_none() {
+            continue;
+        }
 
-    // Load or generate keypair
-    let keypair = if std::path::Path::new(&config.keypair_path).exists() {
-        let key_str = std::fs::read_to_string(&config.keypair_path)?;
-        let key_str = key_str.trim().trim_matches('"');
-        let key_bytes = bs58::decode(key_str)
-            .into_vec()
-            .context("Failed to decode base58 keypair")?;
-        let key_array: [u8; 32] = key_bytes
-            .try_into()
-            .map_err(|v: Vec<u8>| anyhow::anyhow!("Expected 32 bytes, got {}", v.len()))?;
-        PrivKey::from_bytes(&key_array)
-    } else {
-        tracing::warn!("Keypair file not found, generating new keypair");
-        PrivKey::generate()
+        let domain_owner = state.keys.active().pub_key().owner();
+        let probe = state.runtime.read().await.domain_view().next_nonce(&domain_owner);
+        match probe {
+            Ok(_) => state.watchdog.resume(),
+            Err(e) => tracing::warn!("Runtime still unhealthy: {}", e),
+        }
+    }
+}
+
+/// Periodically recompute constraint effectiveness across all receipts
+async fn refresh_analytics(state: Arc<AppState>) {
+    let interval = std::time::Duration::from_secs(state.config.analytics_interval_secs.max(1));
+    let mut ticker = tokio::time::interval(interval);
+    loop {
+        ticker.tick().await;
+        let receipts = state.domain.all_receipts().await;
+        let analytics = ConstraintAnalytics::compute(&receipts);
+        tracing::debug!(
+            "Recomputed constraint analytics over {} receipts ({} settings)",
+            receipts.len(),
+            analytics.settings.len()
+        );
+        *state.analytics.write().await = analytics;
+    }
+}
+
+/// Post scheduled quotes once their activation time has come
+///
+/// The reference price is re-snapshotted at activation, since the one
+/// taken at creation may be long stale.
+async fn activate_scheduled_quotes(state: Arc<AppState>) {
+    let interval = std::time::Duration::from_secs(state.config.schedule_sweep_secs.max(1));
+    let mut ticker = tokio::time::interval(interval);
+    loop {
+        ticker.tick().await;
+        for id in state.domain.due_quotes().await {
+            activate_due_quote(&state, &id).await;
+        }
+    }
+}
+
+/// Post a due scheduled quote with a fresh reference price
+async fn activate_due_quote(state: &AppState, id: &QuoteId) {
+    let Some(quote) = state.domain.get_quote(id).await.filter(|q| q.is_due()) else {
+        return;
+    };
+    let reference_price = state
+        .feeds
+        .snapshot(
+            &quote.spec.asset,
+            &quote.spec.currency,
+            &quote.constraints.allowed_sources,
+        )
+        .await;
+    if let Some(quote) = state.domain.activate_quote(id, reference_price).await {
+        tracing::info!("Scheduled quote {} activated ({:?})", id, quote.status);
+    }
+}
+
+/// Periodically check guarded quotes' reference prices against the feeds
+async fn guard_quote_divergence(state: Arc<AppState>) {
+    let interval = std::time::Duration::from_secs(state.config.divergence_sweep_secs.max(1));
+    let mut ticker = tokio::time::interval(interval);
+    loop {
+        ticker.tick().await;
+        // One snapshot per pair and feed set per sweep
+        let mut prices: HashMap<(String, String, Vec<String>), Option<f64>> = HashMap::new();
+        for quote in state.domain.guarded_quotes().await {
+            let key = (
+                quote.spec.asset.clone(),
+                quote.spec.currency.clone(),
+                quote.constraints.allowed_sources.clone(),
+            );
+            let price = match prices.get(&key) {
+                Some(price) => *price,
+                None => {
+                    let snapshot = state.feeds.snapshot(&key.0, &key.1, &key.2).await;
+                    *prices.entry(key).or_insert(snapshot.map(|s| s.price))
+                }
+            };
+            // Without a consensus the quote is left as it is
+            let Some(price) = price else { continue };
+            if let Some(quote) = state.domain.guard_divergence(&quote.id, price).await {
+                tracing::warn!(
+                    "Divergence guard set quote {} {:?} at market price {}",
+                    quote.id,
+                    quote.status,
+                    price
+                );
+            }
+        }
+    }
+}
+
+/// Periodically anchor the roots of unanchored receipts on Delta
+async fn anchor_receipts(state: Arc<AppState>) {
+    let interval = std::time::Duration::from_secs(state.config.notary.interval_secs.max(1));
+    let mut ticker = tokio::time::interval(interval);
+    loop {
+        ticker.tick().await;
+        if state.watchdog.suspension().is_some() {
+            continue;
+        }
+
+        for pending in state.domain.pending_anchors().await {
+            match submit_anchor(&state, &pending).await {
+                Ok(anchor) => {
+                    tracing::info!(
+                        "Anchored {} receipts for {} in SDL {}",
+                        anchor.receipt_count,
+                        anchor.day,
+                        anchor.sdl_hash
+                    );
+                    state.domain.record_anchor(anchor).await;
+                }
+                Err(e) => tracing::warn!("Failed to anchor receipts for {}: {:#}", pending.day, e),
+            }
+        }
+    }
+}
+
+/// Periodically write new quotes, fills and receipts to Parquet
+async fn export_records(state: Arc<AppState>, mut exporter: Exporter) {
+    let interval = std::time::Duration::from_secs(state.config.export.interval_secs.max(1));
+    let mut ticker = tokio::time::interval(interval);
+    loop {
+        ticker.tick().await;
+        let quotes = state.domain.get_all_quotes().await;
+        let receipts = state.domain.all_receipts().await;
+        match exporter.run(&quotes, &receipts).await {
+            Ok(summary) if summary.files > 0 => tracing::info!(
+                "Exported {} quotes, {} fills and {} receipts to {} Parquet files",
+                summary.quotes,
+                summary.fills,
+                summary.receipts,
+                summary.files
+            ),
+            Ok(_) => {}
+            Err(e) => tracing::warn!("Parquet export failed: {:#}", e),
+        }
+    }
+}
+
+/// Commit a day root on Delta
+///
+/// The domain signs a 1-plank transfer to the vault whose owner ID is the
+/// root, so the root appears in the state diff of a domain-signed SDL.
+async fn submit_anchor(state: &AppState, pending: &PendingAnchor) -> Result<Anchor> {
+    let anchor_owner = delta_domain_sdk::base::crypto::OwnerId::from(pending.root);
+    let credited = Address::new(anchor_owner, state.config.shard);
+    let sdl_hash = submit_domain_transfer(state, credited, 1)
+        .await
+        .context("Failed to submit anchor transfer")?;
+
+    Ok(Anchor {
+        day: pending.day,
+        root: pending.root,
+        receipt_count: pending.receipt_count,
+        chain_head: pending.chain_head,
+        sdl_hash,
+        anchor_owner_id: bs58::encode(pending.root).into_string(),
+        anchored_at: chrono::Utc::now(),
+    })
+}
+
+/// Transfer `amount` plancks from the domain vault and submit the SDL
+///
+/// Returns the SDL hash.
+async fn submit_domain_transfer(
+    state: &AppState,
+    credited: Address,
+    amount: u64,
+) -> Result<String> {
+    let runtime = state.runtime.read().await;
+    let shard = state.config.shard;
+    let domain_owner = state.keys.active().pub_key().owner();
+
+    let nonce = runtime
+        .domain_view()
+        .next_nonce(&domain_owner)
+        .map_err(|e| anyhow::anyhow!("Failed to get domain nonce: {}", e))?;
+    let allowance = DebitAllowance {
+        credited,
+        allowances: BTreeMap::from([(TokenKind::Native, AllowanceAmount::Fungible(amount))]),
+        new_nonce: nonce,
+        debited_shard: shard,
     };
+    let signed = SignedDebitAllowance::sign(allowance, state.keys.active())
+        .map_err(|e| anyhow::anyhow!("Failed to sign transfer: {}", e))?;
+
+    runtime
+        .apply(default_execute(vec![VerifiableType::DebitAllowance(signed)]))
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to apply transfer: {}", e))?;
+    let sdl_hash = runtime
+        .submit()
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to submit SDL: {}", e))?
+        .context("Transfer produced no state changes")?;
+
+    Ok(format!("{:?}", sdl_hash))
+}
 
-    tracing::info!("Using keypair: {}", keypair.pub_key().owner());
+/// Initialize the Delta Runtime with the domain keypair
+async fn init_runtime(config: &DomainConfig, keypair: PrivKey) -> Result<DeltaRuntime> {
+    let shard = NonZero::new(config.shard).context("Invalid shard (cannot be 0)")?;
 
     // Create mock proving client with our local laws
     let proving_client = mock::Client::global_laws()
         .with_local_laws::<rfq_local_laws::RfqLocalLaws>();
 
-    // Clone keypair for return value (before it's moved into builder)
-    let keypair_clone = keypair.clone();
-
     // Build runtime
     let runtime = if config.mock_mode {
         // Mock mode: use mock RPC with pre-populated vaults
@@ -259,7 +668,7 @@ async fn init_runtime(config: &DomainConfig) -> Result<(DeltaRuntime, PrivKey)>
         }
     });
 
-    Ok((runtime, keypair_clone))
+    Ok(runtime)
 }
 
 // =============================================================================
@@ -268,27 +677,62 @@ async fn init_runtime(config: &DomainConfig) -> Result<(DeltaRuntime, PrivKey)>
 
 /// Health check endpoint
 async fn health_check(State(state): State<Arc<AppState>>) -> Json<serde_json::Value> {
+    let suspension = state.watchdog.suspension();
     Json(serde_json::json!({
-        "status": "ok",
+        "status": if suspension.is_some() { "degraded" } else { "ok" },
+        "fills_suspended": suspension,
         "shard": state.config.shard,
         "mock_mode": state.config.mock_mode,
+        "key_fingerprint": state.keys.active_identity().fingerprint,
+        "llm_usage": state.compiler.usage(),
+        "retired_key_fingerprints": state
+            .keys
+            .retired()
+            .iter()
+            .map(|k| k.fingerprint.as_str())
+            .collect::<Vec<_>>(),
     }))
 }
 
+/// Query parameters identifying the viewing taker
+#[derive(Debug, serde::Deserialize)]
+struct ViewerQuery {
+    /// Taker owner ID; embargoed quotes are only shown to their embargo takers
+    taker: Option<String>,
+    /// Maker owner ID; makers also see their own unposted and embargoed quotes
+    maker: Option<String>,
+}
+
+impl ViewerQuery {
+    fn can_see(&self, quote: &Quote) -> bool {
+        quote.is_visible_to(self.taker.as_deref())
+            || self.maker.as_deref() == Some(quote.maker_owner_id.as_str())
+    }
+}
+
 /// List all quotes (including filled and expired)
-async fn list_quotes(State(state): State<Arc<AppState>>) -> Json<Vec<ApiQuote>> {
+async fn list_quotes(
+    State(state): State<Arc<AppState>>,
+    Query(viewer): Query<ViewerQuery>,
+) -> Json<Vec<ApiQuote>> {
     let mut quotes = state.domain.get_all_quotes().await;
     
-    // Update status for expired quotes
+    // Update status for expired and newly published quotes
     for quote in &mut quotes {
-        if quote.status == QuoteStatus::Active && quote.is_expired() {
-            quote.status = QuoteStatus::Expired;
-            // Persist the updated status
-            state.domain.update_quote(quote.clone()).await;
+        if quote.clone().refresh_status() {
+            // Persist the updated status, against the quote as it stands
+            if let Some(current) = state.domain.refresh_quote(&quote.id).await {
+                *quote = current;
+            }
         }
     }
     
-    let api_quotes: Vec<ApiQuote> = quotes.iter().map(ApiQuote::from).collect();
+    let makers = state.domain.maker_reputations().await;
+    let api_quotes: Vec<ApiQuote> = quotes
+        .iter()
+        .filter(|q| viewer.can_see(q))
+        .map(|q| ApiQuote::from(q).with_maker_reputation(makers.get(&q.maker_owner_id)))
+        .collect();
     Json(api_quotes)
 }
 
@@ -296,22 +740,74 @@ async fn list_quotes(State(state): State<Arc<AppState>>) -> Json<Vec<ApiQuote>>
 async fn get_quote(
     State(state): State<Arc<AppState>>,
     Path(id): Path<Uuid>,
+    Query(viewer): Query<ViewerQuery>,
 ) -> Result<Json<ApiQuote>, StatusCode> {
-    state
+    let quote = state
         .domain
-        .get_quote(&id)
+        .refresh_quote(&id)
         .await
-        .map(|q| Json(ApiQuote::from(&q)))
-        .ok_or(StatusCode::NOT_FOUND)
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    // Hidden quotes are indistinguishable from missing ones
+    if !viewer.can_see(&quote) {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    let maker = state.domain.maker_reputation(&quote.maker_owner_id).await;
+    Ok(Json(ApiQuote::from(&quote).with_maker_reputation(maker.as_ref())))
 }
 
 /// Create a new quote from English text
 async fn create_quote(
     State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
     Json(request): Json<CreateQuoteRequest>,
-) -> Result<Json<ApiCreateQuoteResponse>, (StatusCode, String)> {
+) -> Result<Json<ApiCreateQuoteResponse>, Response> {
     tracing::info!("Creating quote from text: {}", request.text);
 
+    let limits = state
+        .config
+        .api_limits
+        .limits_for(limits::api_key(&headers))
+        .map_err(|e| (e.status(), e.to_string()).into_response())?;
+
+    // Catch retried or accidental re-posts of a quote the maker has open
+    let mut warnings = Vec::new();
+    let policy = state.config.duplicate_guard.policy_for(&request.maker_owner_id);
+    if policy != DuplicatePolicy::Off && !request.allow_duplicate {
+        let open_quotes: Vec<Quote> = state
+            .domain
+            .get_all_quotes()
+            .await
+            .into_iter()
+            .filter(|q| {
+                q.maker_owner_id == request.maker_owner_id
+                    && (q.is_active() || q.is_embargoed() || !q.is_posted())
+            })
+            .collect();
+        let duplicate = duplicates::find_duplicate(
+            &request.text,
+            &open_quotes,
+            state.config.duplicate_guard.similarity_threshold,
+        );
+        if let Some(duplicate) = duplicate {
+            let message = format!(
+                "Quote duplicates open quote {} ({:.0}% similar)",
+                duplicate.quote_id,
+                duplicate.similarity * 100.0
+            );
+            if policy == DuplicatePolicy::Block {
+                return Err((
+                    StatusCode::CONFLICT,
+                    format!("{}; set allow_duplicate to post it anyway", message),
+                )
+                    .into_response());
+            }
+            tracing::warn!("{}", message);
+            warnings.push(message);
+        }
+    }
+
     // Generate quote ID
     let quote_id = Uuid::new_v4();
     let quote_id_bytes: [u8; 32] = {
@@ -320,67 +816,487 @@ async fn create_quote(
         bytes
     };
 
-    // Get next nonce (simplified - in production, get from vault)
-    let nonce = 1u64;
+    // Compile the quote using LLM (or the DSL, deterministically)
+    let options = CompileOptions {
+        use_cache: !request.bypass_cache,
+        ..Default::default()
+    };
+    let compiled = if request.dsl {
+        state.compiler.compile_dsl(&request.text, quote_id_bytes)
+    } else {
+        state
+            .compiler
+            .compile_with_options(&request.text, quote_id_bytes, &options)
+            .await
+    };
+    let compiled = compiled.map_err(|e| compile_error_response(&request.text, e))?;
+    if let Some(preset) = &compiled.preset {
+        tracing::info!("Quote uses constraint preset '{}'", preset);
+    }
+
+    // Hold guardrails the LLM may have made up until the maker approves them
+    let mut low_confidence = compiled.low_confidence;
+    if request.expiry_secs.is_some() {
+        low_confidence.remove("expiry_minutes");
+    }
+    let needs_approval = !low_confidence.is_empty() && !request.accept_low_confidence;
+    let (spec, mut constraints) = (compiled.spec, compiled.constraints);
+
+    // Explicit request fields take precedence over what the LLM extracted
+    if request.expiry_secs == Some(0) || request.quorum_count == Some(0) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "expiry_secs and quorum_count must be positive".to_string(),
+        )
+            .into_response());
+    }
+    if let Some(guard) = &request.divergence_guard {
+        if !(guard.max_move_percent > 0.0 && guard.max_move_percent.is_finite()) {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                "divergence_guard.max_move_percent must be positive".to_string(),
+            )
+                .into_response());
+        }
+    }
+    let now = chrono::Utc::now();
+    let audit_trail: Vec<AuditEntry> = request
+        .apply_overrides(&mut constraints, now)
+        .into_iter()
+        .map(|event| AuditEntry { at: now, event })
+        .collect();
+    if !audit_trail.is_empty() {
+        tracing::info!("Applied {} explicit constraint overrides", audit_trail.len());
+    }
+    constraints.extensions = request.extensions.clone();
+
+    // Scheduled quotes are posted later; their lifetime counts from then
+    let activates_at = match request.activate_at {
+        Some(at) => {
+            let at = chrono::DateTime::from_timestamp(at, 0)
+                .filter(|at| *at > now)
+                .ok_or_else(|| {
+                    (
+                        StatusCode::BAD_REQUEST,
+                        "activate_at must be a future unix timestamp".to_string(),
+                    )
+                        .into_response()
+                })?;
+            constraints.expiry_timestamp = constraints
+                .expiry_timestamp
+                .saturating_add((at - now).num_seconds() as u64);
+            // The Local Law holds fills back until activation as well
+            constraints.valid_from_timestamp = Some(at.timestamp() as u64);
+            Some(at)
+        }
+        None => None,
+    };
 
-    // Compile the quote using LLM
-    let (spec, constraints) = state
-        .compiler
-        .compile(&request.text, quote_id_bytes, nonce)
-        .await
-        .map_err(|e| (StatusCode::BAD_REQUEST, format!("Failed to compile quote: {}", e)))?;
+    // Per-key ceilings apply whatever the constraints allow; an iceberg
+    // trades up to its hidden total, however many fills that takes
+    let (size, fills) = match constraints.iceberg {
+        Some(iceberg) if constraints.max_fill_size > 0 => (
+            spec.size * iceberg.total_size as f64 / constraints.max_fill_size as f64,
+            1,
+        ),
+        _ => (spec.size, constraints.max_fills),
+    };
+    limits
+        .check_quote(size, fills, spec.limit_price)
+        .map_err(|e| (e.status(), e.to_string()).into_response())?;
+
+    // The pair must be a listed, trading market, on its tick and lot grid
+    let market = state
+        .markets
+        .check_quote(&spec.asset, &spec.currency, spec.size, spec.limit_price)
+        .map_err(IntoResponse::into_response)?;
+    if let Some(market) = market.filter(|_| constraints.allowed_sources.is_empty()) {
+        constraints.allowed_sources = market.default_sources;
+    }
+    constraints.feed_keys = state.config.feed_keys_for(&constraints.allowed_sources);
+    constraints.allow_unsigned_feeds = state.config.allow_unsigned_feeds;
+    denylist::merge(&mut constraints.denied_takers, &state.config.denied_takers);
+
+    // Snapshot the consensus price the quote was created against
+    let reference_price = state
+        .feeds
+        .snapshot(&spec.asset, &spec.currency, &constraints.allowed_sources)
+        .await;
+    if reference_price.is_none() {
+        tracing::warn!("No reference price available for {}", spec.asset);
+        if request.divergence_guard.is_some() && activates_at.is_none() {
+            warnings.push(
+                "No reference price available: the divergence guard is inactive".to_string(),
+            );
+        }
+    }
+
+    // Embargoed quotes start unlisted, visible only to preferred takers
+    let embargo_until = request
+        .embargo_secs
+        .filter(|secs| *secs > 0)
+        .map(|secs| activates_at.unwrap_or(now) + chrono::Duration::seconds(secs as i64));
+    let embargo_takers = if request.embargo_takers.is_empty() {
+        constraints.allowed_takers.clone()
+    } else {
+        request.embargo_takers.clone()
+    };
+    if embargo_until.is_some() && embargo_takers.is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Embargo requires embargo_takers or an allowed taker list".to_string(),
+        )
+            .into_response());
+    }
 
     // Create the quote
-    let now = chrono::Utc::now();
     let quote = Quote {
         id: quote_id,
         spec: spec.clone(),
         constraints: constraints.clone(),
-        status: QuoteStatus::Active,
+        status: if needs_approval {
+            QuoteStatus::PendingApproval
+        } else if activates_at.is_some() {
+            QuoteStatus::Scheduled
+        } else if embargo_until.is_some() {
+            QuoteStatus::Pending
+        } else {
+            QuoteStatus::Active
+        },
         created_at: now,
         expires_at: constraints.expiry_datetime(),
         maker_owner_id: request.maker_owner_id.clone(),
         maker_vault_address: format!("{},{}", request.maker_owner_id, request.maker_shard),
         original_text: request.text.clone(),
+        activates_at,
+        low_confidence_fields: low_confidence,
+        embargo_until,
+        embargo_takers: if embargo_until.is_some() { embargo_takers } else { vec![] },
+        reference_price,
+        preset: compiled.preset,
+        fill_history: vec![],
+        audit_trail,
+        auto_reject: request.auto_reject.clone(),
+        divergence_guard: request.divergence_guard,
     };
 
     // Store the quote
-    state.domain.add_quote(quote.clone()).await;
-    tracing::info!("Quote created: {}", quote_id);
+    state.domain.add_quote(quote.clone(), compiled.record).await;
+    match activates_at {
+        Some(at) => tracing::info!("Quote scheduled: {} (activates at {})", quote_id, at),
+        None => tracing::info!("Quote created: {}", quote_id),
+    }
+    let message = if needs_approval {
+        let fields: Vec<&str> = quote.low_confidence_fields.keys().map(String::as_str).collect();
+        tracing::info!("Quote {} held for approval ({})", quote_id, fields.join(", "));
+        format!(
+            "Quote created but not posted: not confident the compiled {} match the quote text. \
+             Check them, then approve with POST /quotes/{}/approve (or cancel with \
+             POST /quotes/{}/cancel).",
+            fields.join(", "),
+            quote_id,
+            quote_id
+        )
+    } else {
+        "Quote created successfully. The Local Law has been compiled and will enforce your constraints cryptographically.".to_string()
+    };
 
-    // Generate summary
-    let summary = rfq_compiler::summarize_constraints(&constraints);
+    let explanation = state.compiler.explain(&quote);
+    let lint_warnings = rfq_compiler::lint_constraints(&constraints);
+    if !lint_warnings.is_empty() {
+        let codes: Vec<&str> = lint_warnings.iter().map(|w| w.code.as_str()).collect();
+        tracing::info!("Quote {} has risky terms: {}", quote_id, codes.join(", "));
+    }
 
     // Return flattened API response
     Ok(Json(ApiCreateQuoteResponse {
         quote: ApiQuote::from(&quote),
-        constraints_summary: summary,
-        message: "Quote created successfully. The Local Law has been compiled and will enforce your constraints cryptographically.".to_string(),
+        explanation,
+        message,
+        warnings,
+        lint_warnings,
     }))
 }
 
+/// Log the LLM exchanges behind a compilation, for post-incident review
+fn log_compilation(record: &CompilationRecord) {
+    if record.method != CompileMethod::Llm {
+        return;
+    }
+    let usage = record.usage();
+    let latency_ms: u64 = record.exchanges.iter().map(|e| e.latency_ms).sum();
+    tracing::info!(
+        "Compiled {:?} with {} LLM call(s) in {}ms ({} input / {} output tokens){}",
+        record.text,
+        record.exchanges.len(),
+        latency_ms,
+        usage.input_tokens,
+        usage.output_tokens,
+        record
+            .error
+            .as_ref()
+            .map(|e| format!(": {}", e))
+            .unwrap_or_default()
+    );
+    for exchange in &record.exchanges {
+        tracing::debug!(
+            "{}/{} answered in {}ms: {}",
+            exchange.provider,
+            exchange.model,
+            exchange.latency_ms,
+            exchange
+                .response
+                .as_deref()
+                .or(exchange.error.as_deref())
+                .unwrap_or_default()
+        );
+    }
+}
+
+/// Response for quote text that didn't compile
+fn compile_error_response(text: &str, e: CompilerError) -> Response {
+    match e {
+        CompilerError::NeedsClarification { questions } => clarification_response(
+            text,
+            "Quote text is ambiguous; answer the questions and post a revised quote",
+            questions,
+        ),
+        e @ CompilerError::BudgetExceeded(_) => {
+            (StatusCode::TOO_MANY_REQUESTS, e.to_string()).into_response()
+        }
+        e @ CompilerError::Timeout { .. } => {
+            (StatusCode::GATEWAY_TIMEOUT, e.to_string()).into_response()
+        }
+        // Possible prompt injection: refuse rather than post widened guardrails
+        e @ CompilerError::SuspiciousCompilation { .. } => {
+            (StatusCode::UNPROCESSABLE_ENTITY, e.to_string()).into_response()
+        }
+        e => (StatusCode::BAD_REQUEST, format!("Failed to compile quote: {}", e)).into_response(),
+    }
+}
+
+/// Request to lint quote text
+#[derive(Debug, serde::Deserialize)]
+struct LintQuoteRequest {
+    /// English quote text (or constraint DSL, see `dsl`)
+    text: String,
+    /// Compile `text` as constraint DSL instead of English
+    #[serde(default)]
+    dsl: bool,
+}
+
+/// Compile quote text without creating a quote and report its risky terms
+async fn lint_quote(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<LintQuoteRequest>,
+) -> Result<Json<ApiLintResponse>, Response> {
+    let preview = if request.dsl {
+        state.compiler.preview_dsl(&request.text)
+    } else {
+        state.compiler.preview(&request.text).await
+    };
+    let preview = preview.map_err(|e| compile_error_response(&request.text, e))?;
+    Ok(Json(ApiLintResponse::from(preview)))
+}
+
+/// Request to open a quote draft
+#[derive(Debug, serde::Deserialize)]
+struct OpenDraftRequest {
+    /// First message of the quote
+    #[serde(default)]
+    message: Option<String>,
+}
+
+/// Request to add a message to a quote draft
+#[derive(Debug, serde::Deserialize)]
+struct DraftMessageRequest {
+    message: String,
+}
+
+/// Open a quote drafting session, optionally with its first message
+async fn open_draft(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<OpenDraftRequest>,
+) -> Result<Json<ApiDraftResponse>, Response> {
+    let session = state.drafts.open();
+    let Some(message) = request.message else {
+        return Ok(Json(ApiDraftResponse::from(session)));
+    };
+    match compile_draft_message(&state, session.id, &message).await {
+        Ok(session) => Ok(Json(ApiDraftResponse::from(session))),
+        Err(response) => {
+            // The caller never learns the ID of a session that failed to open
+            let _ = state.drafts.close(&session.id);
+            Err(response)
+        }
+    }
+}
+
+/// Get a quote draft as last compiled
+async fn get_draft(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiDraftResponse>, Response> {
+    let session = state.drafts.get(&id).map_err(IntoResponse::into_response)?;
+    Ok(Json(ApiDraftResponse::from(session)))
+}
+
+/// Drop a quote draft
+async fn close_draft(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiDraftResponse>, Response> {
+    let session = state.drafts.close(&id).map_err(IntoResponse::into_response)?;
+    Ok(Json(ApiDraftResponse::from(session)))
+}
+
+/// Add a message to a quote draft and recompile it
+///
+/// A message that fails to compile (no LLM, budget exhausted) isn't added.
+async fn add_draft_message(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<DraftMessageRequest>,
+) -> Result<Json<ApiDraftResponse>, Response> {
+    let session = compile_draft_message(&state, id, &request.message).await?;
+    Ok(Json(ApiDraftResponse::from(session)))
+}
+
+async fn compile_draft_message(
+    state: &AppState,
+    id: Uuid,
+    message: &str,
+) -> Result<DraftSession, Response> {
+    let pending = state
+        .drafts
+        .prepare(&id, message)
+        .map_err(IntoResponse::into_response)?;
+    let draft = state
+        .compiler
+        .draft(&pending.text)
+        .await
+        .map_err(|e| compile_error_response(&pending.text, e))?;
+    tracing::debug!(
+        "Draft {}: {} questions open, ready={}",
+        id,
+        draft.questions.len(),
+        draft.is_complete()
+    );
+    state
+        .drafts
+        .commit(pending, draft)
+        .map_err(IntoResponse::into_response)
+}
+
+/// 422 response asking the maker to clarify ambiguous quote text
+fn clarification_response(text: &str, message: &str, questions: Vec<String>) -> Response {
+    let body = serde_json::json!({
+        "success": false,
+        "error": {
+            "code": "NEEDS_CLARIFICATION",
+            "message": message,
+            "questions": questions,
+            "original_text": text,
+        },
+    });
+    (StatusCode::UNPROCESSABLE_ENTITY, Json(body)).into_response()
+}
+
 /// Attempt to fill a quote
 async fn fill_quote(
     State(state): State<Arc<AppState>>,
     Path(id): Path<Uuid>,
+    headers: HeaderMap,
     Json(request): Json<FillRequest>,
-) -> Result<Json<ApiFillResponse>, (StatusCode, String)> {
+) -> Result<Json<ApiFillResponse>, Response> {
     tracing::info!(
         "Fill attempt for quote {}: taker={}",
         id,
         request.taker_owner_id
     );
 
+    // Per-key ceilings apply whatever the quote's constraints allow
+    state
+        .config
+        .api_limits
+        .limits_for(limits::api_key(&headers))
+        .and_then(|limits| limits.check_fill(request.size, request.price))
+        .map_err(IntoResponse::into_response)?;
+
+    // The domain's deny list also covers quotes created before a taker was
+    // listed
+    if state.config.denied_takers.contains(&request.taker_owner_id) {
+        let reason = RejectionReason::DeniedTaker {
+            taker: request.taker_owner_id.clone(),
+        };
+        tracing::info!("Fill rejected for quote {}: {}", id, reason.message());
+        return Ok(Json(ApiFillResponse::rejected_early(id, &reason)));
+    }
+
+    // Reject hopeless fills before any locks or Local Laws work
+    if let Some(reason) = state.domain.screen_fill(&id, &request) {
+        tracing::debug!("Fill screened out for quote {}: {}", id, reason.message());
+        return Ok(Json(ApiFillResponse::rejected_early(id, &reason)));
+    }
+
+    // No new fills while the runtime is unhealthy
+    if let Some(suspension) = state.watchdog.suspension() {
+        return Err(suspension.into_response());
+    }
+
+    // A signed request must be signed by the taker's own key
+    check_taker_signature(&state, &id, &request)
+        .map_err(|e| (StatusCode::UNAUTHORIZED, e).into_response())?;
+
+    // Reject replayed requests before touching the quote; the nonce is only
+    // consumed once the fill is accepted
+    if let Err(e) = state
+        .domain
+        .check_taker_nonce(&request.taker_owner_id, request.taker_nonce)
+        .await
+    {
+        return Err((StatusCode::CONFLICT, e.message(request.taker_nonce)).into_response());
+    }
+
     // Get the quote
     let mut quote = state
         .domain
-        .get_quote(&id)
+        .refresh_quote(&id)
         .await
-        .ok_or((StatusCode::NOT_FOUND, "Quote not found".to_string()))?;
+        .ok_or_else(|| (StatusCode::NOT_FOUND, "Quote not found").into_response())?;
+
+    // Scheduled and unapproved quotes aren't posted yet
+    if !quote.is_posted() {
+        return Err((StatusCode::NOT_FOUND, "Quote not found").into_response());
+    }
 
-    // Check if quote is still active
-    if !quote.is_active() {
-        let reason = if quote.is_expired() {
+    // Quotes held by their divergence guard are answered directly, without a receipt
+    if quote.status == QuoteStatus::Suspended {
+        let reason = RejectionReason::AutoRejected {
+            rule: "divergence_guard".to_string(),
+            detail: "Quote is suspended: the market moved away from its reference price"
+                .to_string(),
+        };
+        return Ok(Json(ApiFillResponse::rejected_early(id, &reason)));
+    }
+
+    // No fills on a halted market
+    state
+        .markets
+        .check_trading(&quote.spec.asset, &quote.spec.currency)
+        .map_err(IntoResponse::into_response)?;
+
+    // During the embargo only the preferred takers may fill
+    let early_rejection = if quote.is_embargoed() {
+        (!quote.is_visible_to(Some(&request.taker_owner_id))).then(|| {
+            RejectionReason::UnauthorizedTaker {
+                taker: request.taker_owner_id.clone(),
+                allowed_takers: quote.embargo_takers.clone(),
+            }
+        })
+    } else if !quote.is_active() {
+        // Check if quote is still active
+        Some(if quote.is_expired() {
             RejectionReason::QuoteExpired {
                 expired_at: quote.expires_at,
                 attempted_at: chrono::Utc::now(),
@@ -389,8 +1305,23 @@ async fn fill_quote(
             RejectionReason::AlreadyFilled {
                 filled_at: chrono::Utc::now(),
             }
-        };
+        })
+    } else {
+        None
+    };
 
+    // Maker filters are answered directly, without a receipt
+    if early_rejection.is_none() {
+        if let Some(rules) = &quote.auto_reject {
+            let reputation = state.domain.taker_reputation(&request.taker_owner_id).await;
+            if let Some(reason) = rules.check(request.size, reputation, chrono::Utc::now()) {
+                tracing::info!("Fill auto-rejected for quote {}: {}", id, reason.message());
+                return Ok(Json(ApiFillResponse::rejected_early(id, &reason)));
+            }
+        }
+    }
+
+    if let Some(reason) = early_rejection {
         let fill_attempt = FillAttempt {
             id: Uuid::new_v4(),
             quote_id: id,
@@ -428,50 +1359,150 @@ async fn fill_quote(
         attempted_at: chrono::Utc::now(),
     };
 
+    // Sizes are in the asset's smallest units, prices in the currency's
+    let assets = &state.config.assets;
+    let units = assets.to_units(&quote.spec.asset, request.size).and_then(|size| {
+        let notional = request.price * request.size;
+        Ok((size, assets.to_units(&quote.spec.currency, notional)?))
+    });
+    // Amounts too large for the Local Law's integers are answered directly,
+    // without a receipt
+    let (fill_size, fill_price) = match units {
+        Ok(units) => units,
+        Err(e) => {
+            tracing::info!("Fill rejected for quote {}: {}", id, e);
+            let reason = RejectionReason::NumericOverflow {
+                quantity: format!("{} amount", e.asset),
+            };
+            return Ok(Json(ApiFillResponse::rejected_early(id, &reason)));
+        }
+    };
+
     // Validate against local laws
     let current_timestamp = chrono::Utc::now().timestamp() as u64;
-    let local_laws_input = rfq_local_laws::RfqLocalLawsInput {
+    let input_for = |quote: &Quote| rfq_local_laws::RfqLocalLawsInput {
         constraints: quote.constraints.clone(),
         taker_owner_id: request.taker_owner_id.clone(),
-        fill_size: (request.size * 1_000_000_000.0) as u64,
-        fill_price: (request.price * request.size * 1_000_000_000.0) as u64,
+        taker_shard: request.taker_shard,
+        fill_size,
+        fill_price,
         feed_evidence: request.feed_evidence.clone(),
         current_timestamp,
+        clock_skew_secs: state.config.clock_skew_secs,
         transfer_leg_count: 2, // Assuming atomic DvP
         has_extra_transfers: false,
+        fill_history: quote.fill_history.clone(),
+        benchmark: None,
+        leg_fill_sizes: quote.constraints.leg_fill_sizes(fill_size),
+        filled_so_far: quote.filled_size(),
+        taker_filled_notional: FillRecord::taker_notional(
+            &quote.fill_history,
+            &request.taker_owner_id,
+        ),
+        counterparties: Some(rfq_local_laws::Counterparties {
+            maker: parse_or_derive_owner_id(&quote.maker_owner_id).into(),
+            taker: parse_or_derive_owner_id(&request.taker_owner_id).into(),
+        }),
+        extensions: request.extensions.clone(),
+    };
+
+    // Benchmark fills are accepted now and priced when their window closes
+    let validate = |input: &rfq_local_laws::RfqLocalLawsInput| {
+        if input.constraints.benchmark.is_some() {
+            rfq_local_laws::validate_acceptance(input)
+        } else {
+            rfq_local_laws::validate_fill(input)
+        }
+    };
+    let local_laws_input = input_for(&quote);
+    let result = validate(&local_laws_input);
+
+    // The taker's vault must cover their leg before the fill is recorded
+    let result = match result {
+        Ok(()) if state.config.checks_taker_balance() => {
+            let (_, taker_pays) = quote
+                .constraints
+                .side
+                .legs(local_laws_input.fill_size, local_laws_input.fill_price);
+            check_taker_balance(&state, &request.taker_owner_id, request.taker_shard, taker_pays)
+                .await
+        }
+        other => other,
     };
 
-    let result = rfq_local_laws::validate_fill(&local_laws_input);
+    // Record the fill against the quote as it stands now: fills accepted
+    // while this one was checked count towards its caps, and the proof is
+    // made from the input validated then
+    let result = match result {
+        Ok(()) => {
+            let fill = FillRecord {
+                taker_owner_id: request.taker_owner_id.clone(),
+                fill_size,
+                fill_price,
+            };
+            state
+                .domain
+                .reserve_fill(&id, fill, request.taker_nonce, |current| {
+                    let input = input_for(current);
+                    validate(&input).map(|()| input)
+                })
+                .await
+        }
+        Err(reason) => Err(reason),
+    };
 
     let fill_result = match result {
-        Ok(()) => {
-            // Fill accepted! Submit to Delta for proof
-            quote.status = QuoteStatus::Filled;
-            state.domain.update_quote(quote.clone()).await;
+        Ok((reserved, local_laws_input)) if local_laws_input.constraints.benchmark.is_some() => {
+            quote = reserved;
+            let window = quote.constraints.benchmark.map_or(0, |b| b.window_secs);
+            tokio::spawn(settle_benchmark_fill(
+                state.clone(),
+                quote.clone(),
+                fill_attempt.clone(),
+                local_laws_input,
+            ));
+
+            FillResult::PendingSettlement {
+                fill_id: fill_attempt.id,
+                settles_at: fill_attempt.attempted_at + chrono::Duration::seconds(window as i64),
+            }
+        }
+        Ok((reserved, local_laws_input)) => {
+            // Fill accepted and recorded (closing the quote once the fill
+            // cap or an iceberg's total is reached); submit to Delta for proof
+            quote = reserved;
 
             // Create fill context for transfer verifiables
-            let fill_ctx = FillContext {
-                maker_owner_id: quote.maker_owner_id.clone(),
-                taker_owner_id: request.taker_owner_id.clone(),
-                maker_pays: local_laws_input.fill_price,
-                taker_pays: local_laws_input.fill_size,
-            };
+            let fill_ctx = FillContext::new(
+                &quote,
+                &request.taker_owner_id,
+                local_laws_input.fill_size,
+                local_laws_input.fill_price,
+            );
 
             // Submit SDL to Delta Runtime with actual transfers
-            let sdl_hash = submit_fill_to_delta(&state, &local_laws_input, &fill_ctx).await;
+            let max_wait = request.max_wait_ms.map(std::time::Duration::from_millis);
+            let sdl_hash =
+                submit_fill_to_delta(&state, &local_laws_input, &fill_ctx, max_wait).await;
+            let pricing = pricing_breakdown(
+                &state,
+                &quote,
+                &fill_attempt,
+                request.price,
+                local_laws_input.fill_price,
+            );
 
             FillResult::Accepted {
                 fill_id: fill_attempt.id,
                 sdl_hash,
-                settlement: SettlementDetails {
-                    maker_debit: local_laws_input.fill_price,
-                    maker_credit: local_laws_input.fill_size,
-                    taker_debit: local_laws_input.fill_size,
-                    taker_credit: local_laws_input.fill_price,
-                    asset: quote.spec.asset.clone(),
-                    currency: quote.spec.currency.clone(),
-                    settled_at: chrono::Utc::now(),
-                },
+                settlement: SettlementDetails::dvp(
+                    quote.constraints.side,
+                    local_laws_input.fill_size,
+                    local_laws_input.fill_price,
+                    quote.spec.asset.clone(),
+                    quote.spec.currency.clone(),
+                    Some(pricing),
+                ),
             }
         }
         Err(reason) => FillResult::Rejected {
@@ -499,20 +1530,175 @@ async fn fill_quote(
         }
     );
 
-    Ok(Json(ApiFillResponse::from(&receipt)))
+    let proof_status = match receipt.sdl_hash() {
+        Some(sdl_hash) => state.domain.proof_status(sdl_hash).await,
+        None => None,
+    };
+    Ok(Json(ApiFillResponse::from(&receipt).with_proof_status(proof_status)))
 }
 
-/// Context for submitting a fill to Delta
-struct FillContext {
-    /// Maker's owner ID (base58 or arbitrary string)
-    maker_owner_id: String,
-    /// Taker's owner ID (base58 or arbitrary string)
-    taker_owner_id: String,
-    /// Amount maker pays (in plancks) - the price * size
-    maker_pays: u64,
-    /// Amount taker pays (in plancks) - the asset size
-    taker_pays: u64,
-}
+/// Longest gap between benchmark feed samples
+const MAX_BENCHMARK_SAMPLE_INTERVAL_SECS: u64 = 15;
+
+/// How a fill at `price` came to `amount` currency units
+fn pricing_breakdown(
+    state: &AppState,
+    quote: &Quote,
+    fill_attempt: &FillAttempt,
+    price: f64,
+    amount: u64,
+) -> PricingBreakdown {
+    let prices = fill_attempt.feed_evidence.iter().map(|e| from_fixed_price(e.price));
+    let median = reference::median_price(prices);
+    let scale = state.config.assets.scale(&quote.spec.currency);
+    PricingBreakdown::new(price, fill_attempt.size, scale, amount, median)
+}
+
+/// Settle an accepted benchmark fill once its window closes
+///
+/// Samples the quote's feeds throughout the window, prices the fill at the
+/// TWAP of the samples and runs the full Local Laws validation (which
+/// recomputes the benchmark from the same samples) before submitting the
+/// transfers. If settlement is rejected, the fill's slot is released.
+async fn settle_benchmark_fill(
+    state: Arc<AppState>,
+    quote: Quote,
+    fill_attempt: FillAttempt,
+    mut input: rfq_local_laws::RfqLocalLawsInput,
+) {
+    let Some(benchmark) = quote.constraints.benchmark else {
+        return;
+    };
+    let accepted_at = input.current_timestamp;
+    let window_end = accepted_at + benchmark.window_secs;
+
+    // Sample often enough to collect min_samples with room to spare
+    let interval_secs = (benchmark.window_secs / (2 * benchmark.min_samples.max(1) as u64))
+        .clamp(1, MAX_BENCHMARK_SAMPLE_INTERVAL_SECS);
+    let mut ticker = tokio::time::interval(std::time::Duration::from_secs(interval_secs));
+
+    let mut samples = Vec::new();
+    while (chrono::Utc::now().timestamp() as u64) <= window_end {
+        ticker.tick().await;
+        let updates = state
+            .feeds
+            .fetch(
+                &quote.spec.asset,
+                &quote.spec.currency,
+                &quote.constraints.allowed_sources,
+            )
+            .await;
+        samples.extend(
+            updates
+                .into_iter()
+                .filter(|e| e.timestamp >= accepted_at && e.timestamp <= window_end),
+        );
+    }
+
+    let benchmark_price = rfq_local_laws::twap(&samples, accepted_at, window_end);
+    tracing::info!(
+        "Benchmark window closed for fill {}: {} samples, TWAP {:?}",
+        fill_attempt.id,
+        samples.len(),
+        benchmark_price
+    );
+
+    input.fill_price = benchmark_price.map_or(0, |price| {
+        rfq_local_laws::benchmark_amount(&input.constraints, price, input.fill_size)
+    });
+    input.current_timestamp = chrono::Utc::now().timestamp() as u64;
+    input.benchmark = Some(rfq_local_laws::BenchmarkSettlement {
+        accepted_at,
+        samples,
+    });
+
+    let fill_result = match rfq_local_laws::validate_fill(&input) {
+        Ok(()) => {
+            let fill_ctx = FillContext::new(
+                &quote,
+                &fill_attempt.taker_owner_id,
+                input.fill_size,
+                input.fill_price,
+            );
+            // The taker was answered when the window opened; nobody waits
+            let sdl_hash = submit_fill_to_delta(&state, &input, &fill_ctx, None).await;
+            // Validation passed, so there is a benchmark price
+            let price = from_fixed_price(benchmark_price.unwrap_or_default());
+            let pricing = pricing_breakdown(&state, &quote, &fill_attempt, price, input.fill_price);
+
+            FillResult::Accepted {
+                fill_id: fill_attempt.id,
+                sdl_hash,
+                settlement: SettlementDetails::dvp(
+                    quote.constraints.side,
+                    input.fill_size,
+                    input.fill_price,
+                    quote.spec.asset.clone(),
+                    quote.spec.currency.clone(),
+                    Some(pricing),
+                ),
+            }
+        }
+        Err(reason) => {
+            // Release the slot the fill took at acceptance
+            let fill = FillRecord {
+                taker_owner_id: fill_attempt.taker_owner_id.clone(),
+                fill_size: input.fill_size,
+                fill_price: input.fill_price,
+            };
+            state.domain.release_fill(&quote.id, &fill).await;
+
+            FillResult::Rejected {
+                fill_id: fill_attempt.id,
+                reason,
+            }
+        }
+    };
+
+    let current = state.domain.get_quote(&quote.id).await.unwrap_or(quote);
+    let receipt = FillReceipt::new(
+        current.clone(),
+        current.constraints.clone(),
+        fill_attempt,
+        fill_result,
+    );
+    tracing::info!(
+        "Benchmark settlement for quote {}: {}",
+        current.id,
+        if receipt.is_accepted() {
+            "ACCEPTED"
+        } else {
+            "REJECTED"
+        }
+    );
+    state.domain.add_receipt(current.id, receipt).await;
+}
+
+/// Check that a taker's vault holds at least `required` for their leg
+async fn check_taker_balance(
+    state: &AppState,
+    taker_owner_id: &str,
+    taker_shard: u64,
+    required: u64,
+) -> Result<(), RejectionReason> {
+    let address = Address::new(parse_or_derive_owner_id(taker_owner_id), taker_shard);
+    let vault = state.runtime.read().await.domain_view().vault(&address);
+    match vault {
+        Ok(vault) => {
+            let available = vault.map_or(0, |v| v.balance());
+            if available < required {
+                return Err(RejectionReason::InsufficientBalance { required, available });
+            }
+            Ok(())
+        }
+        Err(e) => {
+            tracing::warn!("Failed to read taker vault {}: {}", address, e);
+            Err(RejectionReason::ValidationError {
+                message: format!("Could not verify taker balance: {}", e),
+            })
+        }
+    }
+}
 
 /// Convert an owner ID string to an OwnerId
 /// 
@@ -540,34 +1726,187 @@ fn parse_or_derive_owner_id(id_str: &str) -> delta_domain_sdk::base::crypto::Own
 /// Submit a fill to Delta Runtime for SDL creation and proof
 ///
 /// This creates the actual transfer verifiables:
-/// 1. Maker sends currency to taker (fill_price)
-/// 2. Taker sends asset to maker (fill_size)
+/// 1. Maker's leg to the taker (`maker_pays`: the price of a buy quote,
+///    the size of a sell)
+/// 2. Taker's leg to the maker (`taker_pays`)
+///
+/// Every step is journaled so the settlement resumes after a crash.
+/// `max_wait` is the taker's budget: a proof not ready within it is
+/// reported as pending and settled in the background.
 async fn submit_fill_to_delta(
-    state: &AppState,
+    state: &Arc<AppState>,
     local_laws_input: &rfq_local_laws::RfqLocalLawsInput,
     fill_ctx: &FillContext,
+    max_wait: Option<std::time::Duration>,
 ) -> String {
-    use delta_serializers::bytes::BytesSerializer;
-    use delta_serializers::serializer::Serializer;
+    let started = std::time::Instant::now();
+
+    // The zkVM program reads its input as JSON, so serde defaults apply
+    let fill_id = match state.journal.begin(fill_ctx.clone(), local_laws_input.to_json()) {
+        Ok(fill_id) => fill_id,
+        Err(e) => {
+            tracing::error!("{:#}; fill on quote {} not settled", e, fill_ctx.quote_id);
+            return format!("error_journal_{}", uuid::Uuid::new_v4());
+        }
+    };
+
+    // Run the settlement in a task that can outlive the request if the
+    // proof is late and compensation is on, or the taker's budget runs out
+    let settlement = &state.config.settlement;
+    let proof_timeout = std::time::Duration::from_secs(settlement.proof_timeout_secs);
+    let wait_limit = if settlement.compensate_timeouts {
+        proof_timeout + std::time::Duration::from_secs(settlement.compensation_timeout_secs)
+    } else {
+        proof_timeout
+    };
+    let (submitted_tx, submitted_rx) = tokio::sync::oneshot::channel();
+    let mut proof_wait = tokio::spawn(run_fill_pipeline(
+        state.clone(),
+        fill_id,
+        wait_limit,
+        submitted_tx,
+    ));
+    let sdl_hash = match submitted_rx.await {
+        Ok(Ok(sdl_hash)) => sdl_hash,
+        Ok(Err(placeholder)) => return placeholder,
+        Err(_) => return format!("error_pipeline_{}", uuid::Uuid::new_v4()),
+    };
+
+    // How long this request waits before handing the proof to the background
+    let budget = max_wait.map(|wait| wait.saturating_sub(started.elapsed()));
+    let response_wait = match (budget, settlement.compensate_timeouts) {
+        (Some(budget), true) => Some(budget.min(proof_timeout)),
+        (Some(budget), false) => Some(budget),
+        (None, true) => Some(proof_timeout),
+        (None, false) => None,
+    };
+
+    let status = if let Some(response_wait) = response_wait {
+        match tokio::time::timeout(response_wait, &mut proof_wait).await {
+            Ok(joined) => joined.unwrap_or(ProofStatus::Failed),
+            Err(_) => {
+                tracing::warn!(
+                    "Proof for SDL {} not ready after {}ms; settling in the background",
+                    sdl_hash,
+                    response_wait.as_millis()
+                );
+                tokio::spawn(compensate_late_proof(
+                    state.clone(),
+                    fill_ctx.clone(),
+                    sdl_hash.clone(),
+                    proof_wait,
+                ));
+                ProofStatus::Pending
+            }
+        }
+    } else {
+        proof_wait.await.unwrap_or(ProofStatus::Failed)
+    };
 
+    state
+        .domain
+        .set_proof_status(fill_ctx.quote_id, sdl_hash.clone(), status)
+        .await;
+    book_settlement(state, fill_ctx, &sdl_hash, status).await;
+    sdl_hash
+}
+
+/// Drive a journaled fill from its last durable step to a proof status
+///
+/// `submitted` receives the SDL hash once the transfers are submitted, or
+/// a placeholder hash if they couldn't be.
+async fn run_fill_pipeline(
+    state: Arc<AppState>,
+    fill_id: Uuid,
+    wait_limit: std::time::Duration,
+    submitted: tokio::sync::oneshot::Sender<Result<String, String>>,
+) -> ProofStatus {
+    let Some(entry) = state.journal.get(fill_id) else {
+        let _ = submitted.send(Err(format!("error_journal_{}", uuid::Uuid::new_v4())));
+        return ProofStatus::Failed;
+    };
+    let sdl_hash = match entry.sdl_hash_display {
+        Some(sdl_hash) => sdl_hash,
+        None => match submit_fill_sdl(&state, &entry).await {
+            Ok(sdl_hash) => sdl_hash,
+            Err(placeholder) => {
+                // Nothing was settled; a new fill attempt starts over
+                finish_journaled(&state, fill_id);
+                let _ = submitted.send(Err(placeholder));
+                return ProofStatus::Failed;
+            }
+        },
+    };
+    let _ = submitted.send(Ok(sdl_hash));
+
+    settle_fill_proof(&state, fill_id, wait_limit).await
+}
+
+/// Advance a journaled fill, logging a failure to persist the step
+///
+/// The fill keeps its last durable stage, so a crash redoes the step.
+fn advance_journaled(state: &AppState, fill_id: Uuid, stage: FillStage) {
+    if let Err(e) = state.journal.advance(fill_id, stage) {
+        tracing::error!("{:#}; fill {} stays journaled before {:?}", e, fill_id, stage);
+    }
+}
+
+/// Drop a settled fill from the journal, logging a failure to persist it
+fn finish_journaled(state: &AppState, fill_id: Uuid) {
+    if let Err(e) = state.journal.finish(fill_id) {
+        tracing::error!("{:#}; fill {} stays journaled and resumes on restart", e, fill_id);
+    }
+}
+
+/// Submit a fill's transfers in an SDL and journal the SDL hash
+///
+/// Returns the SDL hash, or a placeholder hash if nothing was submitted.
+async fn submit_fill_sdl(state: &AppState, entry: &JournalEntry) -> Result<String, String> {
     let runtime: tokio::sync::RwLockReadGuard<'_, DeltaRuntime> = state.runtime.read().await;
     let shard = state.config.shard;
+    let fill_ctx = &entry.fill;
 
     // Parse or derive owner IDs
     let maker_owner = parse_or_derive_owner_id(&fill_ctx.maker_owner_id);
     let taker_owner = parse_or_derive_owner_id(&fill_ctx.taker_owner_id);
-    let domain_owner = state.keypair.pub_key().owner();
+    let domain_owner = state.keys.active().pub_key().owner();
 
     let maker_address = Address::new(maker_owner, shard);
     let taker_address = Address::new(taker_owner, shard);
     let domain_address = Address::new(domain_owner, shard);
 
     // Get the next nonce for domain vault (both transfers debit from domain)
-    let base_nonce = match runtime.domain_view().next_nonce(&domain_owner) {
+    let next_nonce = match runtime.domain_view().next_nonce(&domain_owner) {
         Ok(nonce) => nonce,
         Err(e) => {
             tracing::error!("Failed to get domain nonce: {}", e);
-            return format!("error_nonce_{}", uuid::Uuid::new_v4());
+            state.watchdog.record(RuntimeOutcome::SubmitError);
+            return Err(format!("error_nonce_{}", uuid::Uuid::new_v4()));
+        }
+    };
+    // A resumed fill reuses its reserved nonce, so transfers that already
+    // landed before the crash can't be applied a second time
+    let base_nonce = match entry.base_nonce {
+        Some(reserved) if reserved != next_nonce => {
+            tracing::error!(
+                "Fill {} reserved domain nonce {} but the next nonce is {}; its transfers may \
+                 have landed before the crash and need manual reconciliation",
+                entry.fill_id,
+                reserved,
+                next_nonce
+            );
+            return Err(format!("error_nonce_{}", uuid::Uuid::new_v4()));
+        }
+        Some(reserved) => reserved,
+        None => {
+            let reserved = state
+                .journal
+                .update(entry.fill_id, |e| e.base_nonce = Some(next_nonce));
+            if let Err(e) = reserved {
+                tracing::error!("{:#}; fill {} not submitted", e, entry.fill_id);
+                return Err(format!("error_journal_{}", uuid::Uuid::new_v4()));
+            }
+            next_nonce
         }
     };
 
@@ -578,12 +1917,12 @@ async fn submit_fill_to_delta(
 
     // Create the transfer verifiables for atomic DvP (Delivery vs Payment)
     // For simplicity in this demo, the domain acts as intermediary:
-    // - Domain credits taker with maker's payment (currency)
-    // - Domain credits maker with taker's asset (simulated as native token)
+    // - Domain credits taker with the maker's leg (currency for a buy quote)
+    // - Domain credits maker with the taker's leg (simulated as native token)
     //
     // In a real implementation, you'd have proper asset tokens and direct transfers.
 
-    // Transfer 1: Domain -> Taker (the currency/payment from maker)
+    // Transfer 1: Domain -> Taker (the maker's leg)
     // Uses base_nonce for the first transfer
     let domain_to_taker = DebitAllowance {
         credited: taker_address,
@@ -595,15 +1934,16 @@ async fn submit_fill_to_delta(
         debited_shard: shard,
     };
 
-    let v1 = match SignedDebitAllowance::sign(domain_to_taker, state.keypair.as_ref()) {
+    let v1 = match SignedDebitAllowance::sign(domain_to_taker, state.keys.active()) {
         Ok(signed) => VerifiableType::DebitAllowance(signed),
         Err(e) => {
             tracing::error!("Failed to sign domain->taker transfer: {}", e);
-            return format!("error_sign_{}", uuid::Uuid::new_v4());
+            state.watchdog.record(RuntimeOutcome::SubmitError);
+            return Err(format!("error_sign_{}", uuid::Uuid::new_v4()));
         }
     };
 
-    // Transfer 2: Domain -> Maker (the asset from taker, simulated as native token)
+    // Transfer 2: Domain -> Maker (the taker's leg, simulated as native token)
     // Uses base_nonce + 1 for the second transfer
     let domain_to_maker = DebitAllowance {
         credited: maker_address,
@@ -615,11 +1955,12 @@ async fn submit_fill_to_delta(
         debited_shard: shard,
     };
 
-    let v2 = match SignedDebitAllowance::sign(domain_to_maker, state.keypair.as_ref()) {
+    let v2 = match SignedDebitAllowance::sign(domain_to_maker, state.keys.active()) {
         Ok(signed) => VerifiableType::DebitAllowance(signed),
         Err(e) => {
             tracing::error!("Failed to sign domain->maker transfer: {}", e);
-            return format!("error_sign_{}", uuid::Uuid::new_v4());
+            state.watchdog.record(RuntimeOutcome::SubmitError);
+            return Err(format!("error_sign_{}", uuid::Uuid::new_v4()));
         }
     };
 
@@ -629,7 +1970,8 @@ async fn submit_fill_to_delta(
     // Apply verifiables (creates state diffs)
     if let Err(e) = runtime.apply(default_execute(verifiables)).await {
         tracing::error!("Failed to apply verifiables: {}", e);
-        return format!("error_apply_{}", uuid::Uuid::new_v4());
+        state.watchdog.record(RuntimeOutcome::SubmitError);
+        return Err(format!("error_apply_{}", uuid::Uuid::new_v4()));
     }
 
     // Submit to get SDL hash
@@ -637,95 +1979,1143 @@ async fn submit_fill_to_delta(
         Ok(Some(hash)) => hash,
         Ok(None) => {
             tracing::info!("No state changes to submit");
-            return format!("no_changes_{}", uuid::Uuid::new_v4());
+            return Err(format!("no_changes_{}", uuid::Uuid::new_v4()));
         }
         Err(e) => {
             tracing::error!("Failed to submit SDL: {}", e);
-            return format!("error_submit_{}", uuid::Uuid::new_v4());
+            state.watchdog.record(RuntimeOutcome::SubmitError);
+            return Err(format!("error_submit_{}", uuid::Uuid::new_v4()));
         }
     };
 
     tracing::info!("SDL submitted: {:?}", sdl_hash);
 
-    // Serialize local laws input for proof
-    let input_bytes = match BytesSerializer::serialize(local_laws_input) {
-        Ok(bytes) => bytes,
+    let hash_string = format!("{:?}", sdl_hash);
+    let journaled = match serde_json::to_value(sdl_hash) {
+        Ok(journaled) => journaled,
         Err(e) => {
-            tracing::error!("Failed to serialize local laws input: {}", e);
-            return format!("{:?}", sdl_hash);
+            tracing::error!("Failed to journal SDL hash {}: {}", hash_string, e);
+            return Ok(hash_string);
+        }
+    };
+    let submitted = state.journal.update(entry.fill_id, |e| {
+        e.stage = FillStage::SdlSubmitted;
+        e.sdl_hash = Some(journaled);
+        e.sdl_hash_display = Some(hash_string.clone());
+    });
+    if let Err(e) = submitted {
+        tracing::error!("{:#}; SDL {} of fill {} can't be proven", e, hash_string, entry.fill_id);
+    }
+    Ok(hash_string)
+}
+
+/// Prove a fill's SDL and submit the proof, resuming from its journaled step
+async fn settle_fill_proof(
+    state: &AppState,
+    fill_id: Uuid,
+    wait_limit: std::time::Duration,
+) -> ProofStatus {
+    let Some(entry) = state.journal.get(fill_id) else {
+        return ProofStatus::Failed;
+    };
+    let sdl_hash = match entry.sdl_hash.map(serde_json::from_value) {
+        Some(Ok(sdl_hash)) => sdl_hash,
+        Some(Err(e)) => {
+            tracing::error!("Unreadable SDL hash in fill journal for {}: {}", fill_id, e);
+            finish_journaled(state, fill_id);
+            return ProofStatus::Failed;
+        }
+        None => {
+            tracing::error!("Fill {} has no journaled SDL hash", fill_id);
+            finish_journaled(state, fill_id);
+            return ProofStatus::Failed;
         }
     };
 
-    // Subscribe to updates BEFORE starting prove (to not miss the Proven event)
-    let mut updates = runtime.updates();
+    if entry.stage < FillStage::Proven {
+        let runtime = state.runtime.read().await;
 
-    // Start proof generation (async task)
-    if let Err(e) = runtime.prove_with_local_laws_input(sdl_hash, input_bytes).await {
-        tracing::error!("Failed to start proving: {}", e);
-        return format!("{:?}", sdl_hash);
-    }
+        // Subscribe to updates BEFORE starting prove (to not miss the Proven event)
+        let mut updates = runtime.updates();
 
-    tracing::info!("Proving started for SDL: {:?}", sdl_hash);
+        // A simulated backend decides the proof's latency and outcome up front
+        let simulated = state.proving_sim.as_ref().map(ProvingSimulator::next_proof);
+        let simulated_failure = simulated.as_ref().and_then(|sim| sim.failure.clone());
 
-    // Wait for proof to complete (SdlState::Proven)
-    let proof_timeout = tokio::time::Duration::from_secs(60);
-    let proven = tokio::time::timeout(proof_timeout, async {
-        loop {
-            match updates.recv().await {
-                Ok(update) => {
-                    if update.sdl_hash == sdl_hash {
-                        tracing::debug!("SDL update: {:?} -> {:?}", sdl_hash, update.new_state);
-                        match update.new_state {
-                            SdlState::Proven => {
-                                return Ok(());
-                            }
-                            SdlState::ProvingFailed(err) => {
-                                return Err(format!("Proving failed: {}", err));
+        // Start proof generation (async task); proving is restarted from
+        // scratch for a resumed fill, since the prover doesn't survive a crash
+        let started = match simulated_failure {
+            Some(_) => Ok(()),
+            None => runtime.prove_with_local_laws_input(sdl_hash, entry.input).await,
+        };
+        if let Err(e) = started {
+            tracing::error!("Failed to start proving: {}", e);
+            state.watchdog.record(RuntimeOutcome::ProvingFailure);
+            finish_journaled(state, fill_id);
+            return ProofStatus::Failed;
+        }
+        drop(runtime);
+        advance_journaled(state, fill_id, FillStage::Proving);
+        tracing::info!("Proving started for SDL: {:?}", sdl_hash);
+
+        // Wait for the proof (SdlState::Proven)
+        let proven = tokio::time::timeout(wait_limit, async {
+            if let Some(sim) = &simulated {
+                tokio::time::sleep(sim.latency).await;
+            }
+            if let Some(failure) = simulated_failure {
+                return Err(format!("Proving failed: {}", failure));
+            }
+            loop {
+                match updates.recv().await {
+                    Ok(update) => {
+                        if update.sdl_hash == sdl_hash {
+                            tracing::debug!("SDL update: {:?} -> {:?}", sdl_hash, update.new_state);
+                            match update.new_state {
+                                SdlState::Proven => {
+                                    return Ok(());
+                                }
+                                SdlState::ProvingFailed(err) => {
+                                    return Err(format!("Proving failed: {}", err));
+                                }
+                                _ => continue,
                             }
-                            _ => continue,
                         }
                     }
-                }
-                Err(e) => {
-                    tracing::warn!("Updates channel error: {:?}", e);
-                    // Channel lagged, try again
-                    continue;
+                    Err(e) => {
+                        tracing::warn!("Updates channel error: {:?}", e);
+                        // Channel lagged, try again
+                        continue;
+                    }
                 }
             }
-        }
-    })
-    .await;
+        })
+        .await;
 
-    match proven {
-        Ok(Ok(())) => {
-            tracing::info!("Proof generated for SDL: {:?}", sdl_hash);
-        }
-        Ok(Err(e)) => {
-            tracing::error!("Proving failed: {}", e);
-            return format!("{:?}", sdl_hash);
-        }
-        Err(_) => {
-            tracing::error!("Proof generation timed out for SDL: {:?}", sdl_hash);
-            return format!("{:?}", sdl_hash);
+        match proven {
+            Ok(Ok(())) => {
+                tracing::info!("Proof generated for SDL: {:?}", sdl_hash);
+                advance_journaled(state, fill_id, FillStage::Proven);
+            }
+            Ok(Err(e)) => {
+                tracing::error!("Proving failed: {}", e);
+                state.watchdog.record(RuntimeOutcome::ProvingFailure);
+                finish_journaled(state, fill_id);
+                return ProofStatus::Failed;
+            }
+            Err(_) => {
+                tracing::error!("Proof generation timed out for SDL: {:?}", sdl_hash);
+                state.watchdog.record(RuntimeOutcome::ProvingFailure);
+                finish_journaled(state, fill_id);
+                return ProofStatus::TimedOut;
+            }
         }
     }
 
-    // NOW submit proof to base layer (proof is stored)
+    // NOW submit proof to base layer (proof is stored). A failed submission
+    // stays journaled as proven and is retried on restart.
+    let runtime = state.runtime.read().await;
     if let Err(e) = runtime.submit_proof(sdl_hash).await {
         tracing::error!("Failed to submit proof: {}", e);
-        return format!("{:?}", sdl_hash);
+        state.watchdog.record(RuntimeOutcome::SubmitError);
+        return ProofStatus::Failed;
     }
 
     tracing::info!("Proof submitted for SDL: {:?}", sdl_hash);
-    format!("{:?}", sdl_hash)
+    state.watchdog.record(RuntimeOutcome::Success);
+    advance_journaled(state, fill_id, FillStage::ProofSubmitted);
+    finish_journaled(state, fill_id);
+    ProofStatus::Verified
+}
+
+/// Finish waiting for a late proof, upgrade its status and notify both parties
+async fn compensate_late_proof(
+    state: Arc<AppState>,
+    fill_ctx: FillContext,
+    sdl_hash: String,
+    proof_wait: tokio::task::JoinHandle<ProofStatus>,
+) {
+    let status = proof_wait.await.unwrap_or(ProofStatus::Failed);
+    tracing::info!("Late proof for SDL {} settled as {}", sdl_hash, status.as_str());
+    record_settled_proof(&state, fill_ctx, sdl_hash, status).await;
+}
+
+/// Record a proof status decided after the fill response and notify both parties
+async fn record_settled_proof(
+    state: &AppState,
+    fill_ctx: FillContext,
+    sdl_hash: String,
+    status: ProofStatus,
+) {
+    let recorded = state
+        .domain
+        .set_proof_status(fill_ctx.quote_id, sdl_hash.clone(), status)
+        .await;
+    if !recorded {
+        tracing::info!(
+            "Quote {} of SDL {} predates the restart; proof status only booked and notified",
+            fill_ctx.quote_id,
+            sdl_hash
+        );
+    }
+    book_settlement(state, &fill_ctx, &sdl_hash, status).await;
+    state.webhooks.notify_proof(&ProofNotification {
+        event: "proof_status_changed",
+        quote_id: fill_ctx.quote_id,
+        sdl_hash,
+        status,
+        maker_owner_id: fill_ctx.maker_owner_id,
+        taker_owner_id: fill_ctx.taker_owner_id,
+        at: chrono::Utc::now(),
+    });
+}
+
+/// Send a verified fill's settlement record to the booking webhooks
+async fn book_settlement(
+    state: &AppState,
+    fill_ctx: &FillContext,
+    sdl_hash: &str,
+    status: ProofStatus,
+) {
+    if status != ProofStatus::Verified {
+        return;
+    }
+    // Entries journaled before the terms were recorded fall back to the quote
+    let terms = match &fill_ctx.terms {
+        Some(terms) => terms.clone(),
+        None => match state.domain.get_quote(&fill_ctx.quote_id).await {
+            Some(quote) => FillTerms {
+                side: quote.constraints.side,
+                asset: quote.spec.asset,
+                currency: quote.spec.currency,
+            },
+            None => {
+                tracing::error!(
+                    "Quote {} of settled SDL {} is gone; settlement not booked",
+                    fill_ctx.quote_id,
+                    sdl_hash
+                );
+                return;
+            }
+        },
+    };
+    let shard = state.config.shard;
+    let party = |owner_id: &str| SettlementParty {
+        owner_id: owner_id.to_string(),
+        vault_address: Address::new(parse_or_derive_owner_id(owner_id), shard).to_string(),
+    };
+    let (maker, taker) = (&fill_ctx.maker_owner_id, &fill_ctx.taker_owner_id);
+    // The maker of a buy quote pays the currency, of a sell quote the asset
+    let (maker_symbol, taker_symbol) = match terms.side {
+        Side::Buy => (&terms.currency, &terms.asset),
+        Side::Sell => (&terms.asset, &terms.currency),
+    };
+    // Both legs settle in the native token (see `submit_fill_sdl`)
+    let leg = |from: &str, to: &str, symbol: &str, amount| SettlementLeg {
+        from: from.to_string(),
+        to: to.to_string(),
+        symbol: symbol.to_string(),
+        token_id: "native".to_string(),
+        amount,
+    };
+    state.webhooks.book_settlement(&SettlementRecord {
+        schema_version: SETTLEMENT_SCHEMA_VERSION,
+        event: "fill.settled",
+        settlement_id: settlement_id(&fill_ctx.quote_id, sdl_hash),
+        quote_id: fill_ctx.quote_id,
+        settled_at: chrono::Utc::now(),
+        maker: party(maker),
+        taker: party(taker),
+        side: terms.side,
+        asset: terms.asset.clone(),
+        currency: terms.currency.clone(),
+        legs: vec![
+            leg(maker, taker, maker_symbol, fill_ctx.maker_pays),
+            leg(taker, maker, taker_symbol, fill_ctx.taker_pays),
+        ],
+        proof: ProofReference {
+            sdl_hash: sdl_hash.to_string(),
+            shard,
+            status,
+        },
+    });
+}
+
+/// Resume the settlement of fills left unfinished by a crash
+async fn resume_fills(state: Arc<AppState>) {
+    let settlement = &state.config.settlement;
+    let wait_limit = std::time::Duration::from_secs(
+        settlement.proof_timeout_secs + settlement.compensation_timeout_secs,
+    );
+    for entry in state.journal.unfinished() {
+        tracing::info!(
+            "Resuming fill {} on quote {} from stage {:?}",
+            entry.fill_id,
+            entry.fill.quote_id,
+            entry.stage
+        );
+        let state = state.clone();
+        tokio::spawn(async move {
+            let (submitted_tx, submitted_rx) = tokio::sync::oneshot::channel();
+            let status =
+                run_fill_pipeline(state.clone(), entry.fill_id, wait_limit, submitted_tx).await;
+            // Fills whose transfers never made it into an SDL have no proof status
+            if let Ok(Ok(sdl_hash)) = submitted_rx.await {
+                tracing::info!("Resumed fill {} settled as {}", entry.fill_id, status.as_str());
+                record_settled_proof(&state, entry.fill, sdl_hash, status).await;
+            }
+        });
+    }
+}
+
+/// Check a fill request's signature against its taker's owner ID
+///
+/// Owner IDs are base58 ed25519 public keys, so a taker proves the request
+/// is theirs by signing [`signing::fill_message`]. Unsigned requests pass
+/// unless `require_taker_signatures` is set.
+fn check_taker_signature(
+    state: &AppState,
+    quote_id: &Uuid,
+    request: &FillRequest,
+) -> Result<(), String> {
+    let Some(signature) = &request.taker_signature else {
+        return if state.config.require_taker_signatures {
+            Err("Fill requests must be signed by the taker".to_string())
+        } else {
+            Ok(())
+        };
+    };
+    let key = bs58::decode(&request.taker_owner_id)
+        .into_vec()
+        .ok()
+        .filter(|key| key.len() == 32)
+        .ok_or_else(|| format!("Taker {} isn't an ed25519 owner ID", request.taker_owner_id))?;
+    let key = signing::PublicKey {
+        scheme: signing::SchemeKind::Ed25519,
+        key: signing::to_hex(&key),
+    };
+    let message = signing::fill_message(
+        &quote_id.to_string(),
+        &request.taker_owner_id,
+        request.taker_nonce,
+        request.size,
+        request.price,
+    );
+    key.verify(message.as_bytes(), signature)
+        .map_err(|e| format!("Invalid taker signature: {}", e))
+}
+
+/// Get the last accepted fill request nonce for a taker
+async fn get_taker_nonce(
+    State(state): State<Arc<AppState>>,
+    Path(taker): Path<String>,
+) -> Json<serde_json::Value> {
+    let last = state.domain.taker_nonce(&taker).await;
+    Json(serde_json::json!({
+        "taker_owner_id": taker,
+        "last_nonce": last,
+        "next_nonce": last.map_or(1, |n| n.saturating_add(1)),
+    }))
+}
+
+/// Request to cancel or approve a quote
+#[derive(Debug, serde::Deserialize)]
+struct MakerActionRequest {
+    /// Owner ID of the quote's maker
+    maker_owner_id: String,
+}
+
+/// Cancel a quote before it is posted (scheduled or awaiting approval)
+async fn cancel_quote(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<MakerActionRequest>,
+) -> Result<Json<ApiQuote>, (StatusCode, String)> {
+    let quote = state
+        .domain
+        .cancel_unposted_quote(&id, &request.maker_owner_id)
+        .await
+        .map_err(|e| maker_action_error(e, "cancelled", "scheduled or awaiting approval"))?;
+    tracing::info!("Quote {} cancelled before posting", id);
+    Ok(Json(ApiQuote::from(&quote)))
+}
+
+/// Approve a quote held for approval of low-confidence fields
+///
+/// The quote is posted (or scheduled, if its activation time is ahead).
+async fn approve_quote(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<MakerActionRequest>,
+) -> Result<Json<ApiQuote>, (StatusCode, String)> {
+    let quote = state
+        .domain
+        .approve_quote(&id, &request.maker_owner_id)
+        .await
+        .map_err(|e| maker_action_error(e, "approved", "awaiting approval"))?;
+    tracing::info!("Quote {} approved ({:?})", id, quote.status);
+    Ok(Json(ApiQuote::from(&quote)))
+}
+
+/// Request to amend an open quote
+#[derive(Debug, serde::Deserialize)]
+struct AmendQuoteRequest {
+    /// Owner ID of the quote's maker
+    maker_owner_id: String,
+    /// The amendment, e.g. "extend expiry to 30 minutes and restrict to taker_bob"
+    text: String,
+}
+
+/// Amend an open quote's constraints from English, keeping its ID
+///
+/// Changes are recorded in the quote's audit trail; Local Laws enforce the
+/// amended constraints on every later fill.
+async fn amend_quote(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    headers: HeaderMap,
+    Json(request): Json<AmendQuoteRequest>,
+) -> Result<Json<serde_json::Value>, Response> {
+    let limits = state
+        .config
+        .api_limits
+        .limits_for(limits::api_key(&headers))
+        .map_err(|e| (e.status(), e.to_string()).into_response())?;
+    let quote = state
+        .domain
+        .get_quote(&id)
+        .await
+        .ok_or_else(|| (StatusCode::NOT_FOUND, "Quote not found").into_response())?;
+    // Check before paying for a compilation; the state re-checks under its lock
+    let refused = |e| maker_action_error(e, "amended", "that are still open").into_response();
+    if quote.maker_owner_id != request.maker_owner_id {
+        return Err(refused(MakerActionError::WrongMaker));
+    }
+    if !quote.is_open() {
+        return Err(refused(MakerActionError::InvalidStatus(quote.status)));
+    }
+
+    let diff = state
+        .compiler
+        .compile_amendment(&quote.constraints, &request.text, &CancellationToken::new())
+        .await
+        .map_err(|e| match e {
+            CompilerError::NeedsClarification { questions } => clarification_response(
+                &request.text,
+                "Amendment is ambiguous; answer the questions and send a revised amendment",
+                questions,
+            ),
+            e @ CompilerError::BudgetExceeded(_) => {
+                (StatusCode::TOO_MANY_REQUESTS, e.to_string()).into_response()
+            }
+            e @ CompilerError::Timeout { .. } => {
+                (StatusCode::GATEWAY_TIMEOUT, e.to_string()).into_response()
+            }
+            e => (StatusCode::BAD_REQUEST, format!("Failed to compile amendment: {}", e))
+                .into_response(),
+        })?;
+    if diff.is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "Amendment changes no terms").into_response());
+    }
+    if let Some(max_fills) = diff.max_fills.filter(|_| quote.constraints.iceberg.is_none()) {
+        limits
+            .check_quote(quote.spec.size, max_fills, quote.spec.limit_price)
+            .map_err(|e| (e.status(), e.to_string()).into_response())?;
+    }
+
+    let now = chrono::Utc::now();
+    let mut changes = vec![];
+    let quote = state
+        .domain
+        .amend_quote(&id, &request.maker_owner_id, |quote| {
+            for (field, previous, applied) in diff.changes(&quote.constraints) {
+                changes.push(serde_json::json!({
+                    "field": field,
+                    "previous": previous,
+                    "applied": applied,
+                }));
+                quote.audit_trail.push(AuditEntry {
+                    at: now,
+                    event: AuditEvent::Amendment {
+                        text: request.text.clone(),
+                        field: field.to_string(),
+                        previous,
+                        applied,
+                    },
+                });
+            }
+            diff.apply(&mut quote.constraints);
+            if diff.allowed_sources.is_some() {
+                let sources = &quote.constraints.allowed_sources;
+                quote.constraints.feed_keys = state.config.feed_keys_for(sources);
+            }
+            quote.expires_at = quote.constraints.expiry_datetime();
+        })
+        .await
+        .map_err(refused)?;
+    tracing::info!("Quote {} amended ({} changes)", id, changes.len());
+
+    Ok(Json(serde_json::json!({
+        "quote": ApiQuote::from(&quote),
+        "changes": changes,
+        "explanation": state.compiler.explain(&quote),
+    })))
+}
+
+fn maker_action_error(e: MakerActionError, action: &str, allowed: &str) -> (StatusCode, String) {
+    match e {
+        MakerActionError::UnknownQuote => (StatusCode::NOT_FOUND, "Quote not found".to_string()),
+        MakerActionError::WrongMaker => (
+            StatusCode::FORBIDDEN,
+            format!("Only the quote's maker can have it {}", action),
+        ),
+        MakerActionError::InvalidStatus(status) => (
+            StatusCode::CONFLICT,
+            format!(
+                "Only quotes {} can be {}; quote is {}",
+                allowed,
+                action,
+                api_types::status_to_string(status)
+            ),
+        ),
+    }
+}
+
+/// Request to fund a vault from the faucet
+#[derive(Debug, serde::Deserialize)]
+struct FaucetRequest {
+    /// Owner ID to credit (base58, or any string for a derived demo owner)
+    owner_id: String,
+    /// Vault shard (defaults to the domain's shard)
+    shard: Option<u64>,
+    /// Plancks to credit
+    amount: u64,
+}
+
+/// Credit a vault with test plancks (mock mode only)
+///
+/// Lets demos fund makers and takers with their own vaults instead of
+/// everything settling out of the pre-funded domain vault.
+async fn faucet(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<FaucetRequest>,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    if !state.config.mock_mode {
+        return Err((
+            StatusCode::NOT_FOUND,
+            "The faucet is only available in mock mode".to_string(),
+        ));
+    }
+    let max = state.config.faucet_max_plancks;
+    if request.amount == 0 || request.amount > max {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("Amount must be between 1 and {} plancks", max),
+        ));
+    }
+
+    let shard = request.shard.unwrap_or(state.config.shard);
+    let address = Address::new(parse_or_derive_owner_id(&request.owner_id), shard);
+    let sdl_hash = submit_domain_transfer(&state, address, request.amount)
+        .await
+        .map_err(|e| {
+            tracing::error!("Faucet transfer to {} failed: {:#}", address, e);
+            (StatusCode::INTERNAL_SERVER_ERROR, format!("Faucet transfer failed: {:#}", e))
+        })?;
+    tracing::info!("Faucet credited {} with {} plancks", address, request.amount);
+
+    let balance = state
+        .runtime
+        .read()
+        .await
+        .domain_view()
+        .vault(&address)
+        .ok()
+        .flatten()
+        .map(|v| v.balance());
+    Ok(Json(serde_json::json!({
+        "owner_id": request.owner_id,
+        "address": address.to_string(),
+        "amount": request.amount,
+        "balance": balance,
+        "sdl_hash": sdl_hash,
+    })))
+}
+
+/// Default page size for receipt listings
+const DEFAULT_RECEIPT_PAGE: usize = 100;
+
+/// Maximum page size for receipt listings
+const MAX_RECEIPT_PAGE: usize = 1000;
+
+/// Query parameters for receipt listings
+#[derive(Debug, serde::Deserialize)]
+struct ReceiptQuery {
+    /// Return receipts after this receipt ID
+    cursor: Option<Uuid>,
+    /// Page size (default 100, max 1000); when streaming, the most receipts
+    /// to send (unlimited by default)
+    limit: Option<usize>,
+    /// "accepted" or "rejected"
+    status: Option<String>,
+    /// Rejection reason code (e.g. "STALE_FEED")
+    reason: Option<String>,
+    /// "json" (default) or "ndjson" to stream one receipt per line
+    format: Option<String>,
 }
 
 /// Get receipts for a quote
+///
+/// JSON responses are paginated; the cursor for the next page is returned
+/// in the `X-Next-Cursor` header. NDJSON responses stream every matching
+/// receipt after the cursor (up to `limit`), reading the store a page of
+/// `MAX_RECEIPT_PAGE` at a time as the body is sent.
 async fn get_receipts(
     State(state): State<Arc<AppState>>,
     Path(id): Path<Uuid>,
-) -> Json<Vec<ApiReceiptSummary>> {
+    Query(query): Query<ReceiptQuery>,
+) -> Result<Response, (StatusCode, String)> {
+    let filter = ReceiptFilter {
+        accepted: match query.status.as_deref() {
+            None => None,
+            Some("accepted") => Some(true),
+            Some("rejected") => Some(false),
+            Some(other) => {
+                return Err((
+                    StatusCode::BAD_REQUEST,
+                    format!("Invalid status '{}': expected accepted or rejected", other),
+                ))
+            }
+        },
+        reason_code: query.reason,
+    };
+
+    let streaming = match query.format.as_deref() {
+        None | Some("json") => false,
+        Some("ndjson") => true,
+        Some(other) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                format!("Invalid format '{}': expected json or ndjson", other),
+            ))
+        }
+    };
+
+    // A stream's `limit` caps the whole response, not each page
+    let page_size = move |limit: Option<usize>| match (streaming, limit) {
+        (true, limit) => limit.unwrap_or(usize::MAX).min(MAX_RECEIPT_PAGE),
+        (false, limit) => limit.unwrap_or(DEFAULT_RECEIPT_PAGE).min(MAX_RECEIPT_PAGE),
+    };
+
+    let (receipts, next_cursor) = state
+        .domain
+        .get_receipts_page(&id, &filter, query.cursor, Some(page_size(query.limit)))
+        .await
+        .ok_or((StatusCode::BAD_REQUEST, "Unknown cursor".to_string()))?;
+
+    if streaming {
+        // Each step sends a page and fetches the next, so the store lock is
+        // only held for one page at a time
+        let first = (Some((receipts, next_cursor)), query.limit);
+        let pages = futures::stream::unfold(first, move |(fetched, remaining)| {
+            let (state, filter) = (state.clone(), filter.clone());
+            async move {
+                let (page, next_cursor) = fetched?;
+                let remaining = remaining.map(|n| n.saturating_sub(page.len()));
+                let mut lines = Vec::new();
+                for receipt in receipt_summaries(&state, &page).await {
+                    if let Err(e) = serde_json::to_writer(&mut lines, &receipt) {
+                        return Some((Err(e), (None, remaining)));
+                    }
+                    lines.push(b'\n');
+                }
+                let fetched = match next_cursor {
+                    Some(cursor) if remaining != Some(0) => {
+                        let size = Some(page_size(remaining));
+                        state
+                            .domain
+                            .get_receipts_page(&id, &filter, Some(cursor), size)
+                            .await
+                    }
+                    _ => None,
+                };
+                Some((Ok(lines), (fetched, remaining)))
+            }
+        });
+        return Ok((
+            [(header::CONTENT_TYPE, "application/x-ndjson")],
+            Body::from_stream(pages),
+        )
+            .into_response());
+    }
+
+    let api_receipts = receipt_summaries(&state, &receipts).await;
+    let mut response = Json(api_receipts).into_response();
+    if let Some(cursor) = next_cursor {
+        if let Ok(value) = HeaderValue::from_str(&cursor.to_string()) {
+            response.headers_mut().insert("x-next-cursor", value);
+        }
+    }
+    Ok(response)
+}
+
+/// Receipt summaries with the proof status of each accepted fill's SDL
+async fn receipt_summaries(state: &AppState, receipts: &[FillReceipt]) -> Vec<ApiReceiptSummary> {
+    let mut summaries = Vec::with_capacity(receipts.len());
+    for receipt in receipts {
+        let mut summary = ApiReceiptSummary::from(receipt);
+        if let Some(sdl_hash) = receipt.sdl_hash() {
+            summary.proof_status = state
+                .domain
+                .proof_status(sdl_hash)
+                .await
+                .map(|s| s.as_str().to_string());
+        }
+        summaries.push(summary);
+    }
+    summaries
+}
+
+/// Get a receipt in full, exactly as hashed into the receipt log
+async fn get_receipt(
+    State(state): State<Arc<AppState>>,
+    Path(receipt_id): Path<Uuid>,
+) -> Result<Json<FillReceipt>, (StatusCode, String)> {
+    state
+        .domain
+        .get_receipt(receipt_id)
+        .await
+        .map(Json)
+        .ok_or((StatusCode::NOT_FOUND, format!("Receipt {} not found", receipt_id)))
+}
+
+/// Get the Merkle inclusion proof of a receipt against its day's anchor
+async fn get_inclusion_proof(
+    State(state): State<Arc<AppState>>,
+    Path(receipt_id): Path<Uuid>,
+) -> Result<Json<notary::InclusionProof>, (StatusCode, String)> {
+    state
+        .domain
+        .inclusion_proof(receipt_id)
+        .await
+        .map(Json)
+        .map_err(|e| match e {
+            InclusionError::UnknownReceipt => {
+                (StatusCode::NOT_FOUND, format!("Receipt {} not found", receipt_id))
+            }
+            InclusionError::NotAnchored => (
+                StatusCode::CONFLICT,
+                format!("Receipt {} is not anchored yet", receipt_id),
+            ),
+        })
+}
+
+/// List anchored receipt roots
+async fn list_anchors(State(state): State<Arc<AppState>>) -> Json<Vec<Anchor>> {
+    Json(state.domain.anchors().await)
+}
+
+/// Get every event concerning a quote, oldest first
+async fn get_quote_timeline(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    Query(viewer): Query<ViewerQuery>,
+) -> Result<Json<Vec<ApiTimelineEntry>>, StatusCode> {
+    let quote = state.domain.get_quote(&id).await.ok_or(StatusCode::NOT_FOUND)?;
+    if !viewer.can_see(&quote) {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    let events = state.domain.quote_timeline(&id).await;
+    if events.is_empty() {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    Ok(Json(events.iter().map(ApiTimelineEntry::from).collect()))
+}
+
+/// Report how often each of a quote's constraints has been checked and has rejected
+async fn get_quote_coverage(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    Query(viewer): Query<ViewerQuery>,
+) -> Result<Json<CoverageReport>, StatusCode> {
+    let quote = state
+        .domain
+        .get_quote(&id)
+        .await
+        .filter(|quote| viewer.can_see(quote))
+        .ok_or(StatusCode::NOT_FOUND)?;
     let receipts = state.domain.get_receipts(&id).await;
-    let api_receipts: Vec<ApiReceiptSummary> = receipts.iter().map(ApiReceiptSummary::from).collect();
-    Json(api_receipts)
+    Ok(Json(CoverageReport::build(&quote, &receipts)))
+}
+
+/// Return the stored evidence of a quote's compilation, for disputes
+async fn get_quote_compilation(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    Query(viewer): Query<ViewerQuery>,
+) -> Result<Json<ApiCompilationResponse>, StatusCode> {
+    let quote = state
+        .domain
+        .get_quote(&id)
+        .await
+        .filter(|quote| viewer.can_see(quote))
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let compilation = state
+        .domain
+        .get_compilation(&id)
+        .await
+        .ok_or(StatusCode::NOT_FOUND)?;
+    Ok(Json(ApiCompilationResponse {
+        quote_id: quote.id.to_string(),
+        compiled_by: quote.constraints.compiled_by,
+        compilation,
+    }))
+}
+
+/// Query parameters for tailing the event log
+#[derive(Debug, serde::Deserialize)]
+struct EventQuery {
+    /// Only events after this sequence number
+    #[serde(default)]
+    after: u64,
+    limit: Option<usize>,
+}
+
+/// Page through the domain event log, e.g. to audit it (admin only)
+///
+/// The events carry full quote snapshots and compilation records, so they
+/// show embargoed quotes, sealed auction responses and iceberg totals that
+/// the quote endpoints hide from other viewers.
+async fn list_events(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<EventQuery>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<EventEnvelope>>, MarketError> {
+    markets::authorize_admin(state.config.admin_key.as_deref(), &headers)?;
+    let limit = query.limit.unwrap_or(DEFAULT_RECEIPT_PAGE).min(MAX_RECEIPT_PAGE);
+    Ok(Json(state.domain.events(query.after, limit).await))
+}
+
+/// List the supported markets with their decimals, grid and trading status
+async fn list_markets(State(state): State<Arc<AppState>>) -> Json<Vec<Market>> {
+    Json(state.markets.list())
+}
+
+/// Request to halt a market
+#[derive(Debug, Default, serde::Deserialize)]
+struct HaltMarketRequest {
+    /// Shown to makers and takers while the market is halted
+    reason: Option<String>,
+}
+
+/// Halt a market: new quotes and fills on it are refused (admin only)
+async fn halt_market(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    headers: HeaderMap,
+    request: Option<Json<HaltMarketRequest>>,
+) -> Result<Json<Market>, MarketError> {
+    markets::authorize_admin(state.config.admin_key.as_deref(), &headers)?;
+    let request = request.map(|Json(r)| r).unwrap_or_default();
+    let market = state.markets.set_halted(&id, true, request.reason)?;
+    tracing::warn!(
+        "Market {} halted: {}",
+        market.id,
+        market.halt_reason.as_deref().unwrap_or("no reason given")
+    );
+    Ok(Json(market))
+}
+
+/// Resume trading on a halted market (admin only)
+async fn resume_market(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    headers: HeaderMap,
+) -> Result<Json<Market>, MarketError> {
+    markets::authorize_admin(state.config.admin_key.as_deref(), &headers)?;
+    let market = state.markets.set_halted(&id, false, None)?;
+    tracing::info!("Market {} resumed", market.id);
+    Ok(Json(market))
+}
+
+/// Report a maker's fill outcomes, constraint rejection rates and tuning suggestions
+async fn get_maker_report(
+    State(state): State<Arc<AppState>>,
+    Path(maker): Path<String>,
+) -> Json<MakerReport> {
+    let quotes = state
+        .domain
+        .get_all_quotes()
+        .await
+        .into_iter()
+        .filter(|q| q.maker_owner_id == maker)
+        .count();
+    let receipts: Vec<FillReceipt> = state
+        .domain
+        .all_receipts()
+        .await
+        .into_iter()
+        .filter(|r| r.quote.maker_owner_id == maker)
+        .collect();
+
+    let analytics = state.analytics.read().await;
+    Json(MakerReport::build(&maker, quotes, &receipts, &analytics))
+}
+
+/// Request to open an RFQ auction
+///
+/// The terms are given either as fields or as English `text`.
+#[derive(Debug, serde::Deserialize)]
+struct OpenAuctionRequest {
+    taker_owner_id: String,
+    /// The request in English, e.g. "looking to buy 10 dETH, settle USDD,
+    /// need 2 feeds" (the term fields below are then ignored)
+    #[serde(default)]
+    text: Option<String>,
+    /// The taker's side of the trade
+    #[serde(default)]
+    side: Option<Side>,
+    #[serde(default)]
+    asset: Option<String>,
+    #[serde(default)]
+    currency: Option<String>,
+    #[serde(default)]
+    size: Option<f64>,
+    #[serde(default)]
+    limit_price: Option<f64>,
+    #[serde(default)]
+    sources: Vec<String>,
+    #[serde(default)]
+    min_sources: Option<u32>,
+    /// How long makers have to respond (overrides a window in `text`)
+    #[serde(default)]
+    window_secs: Option<u64>,
+}
+
+impl OpenAuctionRequest {
+    /// The terms given as fields, if all the essential ones are
+    fn rfq_request(&self) -> Option<RfqRequest> {
+        Some(RfqRequest {
+            side: self.side?,
+            asset: self.asset.clone()?,
+            size: self.size?,
+            currency: self.currency.clone()?,
+            limit_price: self.limit_price,
+            sources: self.sources.clone(),
+            min_sources: self.min_sources,
+            response_window_secs: None,
+        })
+    }
+}
+
+/// An auction as seen by the API: responses are hidden until revealed
+fn auction_json(auction: &Auction, ranked: &[RankedResponse]) -> serde_json::Value {
+    let status = auction.status(chrono::Utc::now());
+    serde_json::json!({
+        "id": auction.id,
+        "status": status,
+        "taker_owner_id": auction.taker_owner_id,
+        "side": auction.side,
+        "asset": auction.asset,
+        "currency": auction.currency,
+        "size": auction.size,
+        "limit_price": auction.limit_price,
+        "sources": auction.sources,
+        "min_sources": auction.min_sources,
+        "opened_at": auction.opened_at,
+        "closes_at": auction.closes_at,
+        "response_count": auction.responses.len(),
+        "ranking": (status != AuctionStatus::Open).then_some(ranked),
+        "award": auction.award,
+    })
+}
+
+/// JSON error response for a refused auction request
+fn auction_error_response(e: AuctionError) -> Response {
+    let status = match e {
+        AuctionError::UnknownAuction | AuctionError::UnknownQuote => StatusCode::NOT_FOUND,
+        AuctionError::WrongMaker | AuctionError::WrongTaker => StatusCode::FORBIDDEN,
+        AuctionError::NotSealed | AuctionError::Mismatch(_) => StatusCode::UNPROCESSABLE_ENTITY,
+        _ => StatusCode::CONFLICT,
+    };
+    let body = serde_json::json!({
+        "success": false,
+        "error": { "code": e.code(), "message": e.to_string() },
+    });
+    (status, Json(body)).into_response()
+}
+
+/// Open an auction for makers to respond to with sealed quotes
+async fn open_auction(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<OpenAuctionRequest>,
+) -> Result<Json<serde_json::Value>, Response> {
+    let bad_request =
+        |message: &str| (StatusCode::BAD_REQUEST, message.to_string()).into_response();
+    let rfq = match &request.text {
+        Some(text) => match state
+            .compiler
+            .compile_rfq_request(text, &CancellationToken::new())
+            .await
+        {
+            Ok(rfq) => rfq,
+            Err(CompilerError::NeedsClarification { questions }) => {
+                return Err(clarification_response(
+                    text,
+                    "Request is ambiguous; answer the questions and send a revised request",
+                    questions,
+                ))
+            }
+            Err(e) => return Err(compile_error_response(text, e)),
+        },
+        None => request.rfq_request().ok_or_else(|| {
+            bad_request("Give the request as text, or its side, asset, currency and size")
+        })?,
+    };
+    if !(rfq.size.is_finite() && rfq.size > 0.0) {
+        return Err(bad_request("Size must be positive"));
+    }
+    let window_secs = request.window_secs.or(rfq.response_window_secs).unwrap_or(0);
+    if window_secs == 0 {
+        return Err(bad_request("Response window must be positive"));
+    }
+    let opened_at = chrono::Utc::now();
+    let auction = Auction {
+        id: Uuid::new_v4(),
+        taker_owner_id: request.taker_owner_id,
+        side: rfq.side,
+        asset: rfq.asset,
+        currency: rfq.currency,
+        size: rfq.size,
+        limit_price: rfq.limit_price,
+        sources: rfq.sources,
+        min_sources: rfq.min_sources,
+        opened_at,
+        closes_at: opened_at + chrono::Duration::seconds(window_secs as i64),
+        responses: vec![],
+        award: None,
+    };
+    tracing::info!("Auction {} opened (closes at {})", auction.id, auction.closes_at);
+    state.domain.open_auction(auction.clone()).await;
+    Ok(Json(auction_json(&auction, &[])))
+}
+
+/// List auctions, oldest first
+async fn list_auctions(State(state): State<Arc<AppState>>) -> Json<Vec<serde_json::Value>> {
+    let mut auctions = vec![];
+    for auction in state.domain.get_all_auctions().await {
+        if let Some((auction, ranked)) = state.domain.ranked_auction(&auction.id).await {
+            auctions.push(auction_json(&auction, &ranked));
+        }
+    }
+    Json(auctions)
+}
+
+/// Get an auction; once the window closes, its responses ranked best first
+async fn get_auction(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>, Response> {
+    let (auction, ranked) = state
+        .domain
+        .ranked_auction(&id)
+        .await
+        .ok_or_else(|| auction_error_response(AuctionError::UnknownAuction))?;
+    Ok(Json(auction_json(&auction, &ranked)))
+}
+
+/// Request to submit a quote as an auction response
+#[derive(Debug, serde::Deserialize)]
+struct AuctionResponseRequest {
+    maker_owner_id: String,
+    /// A quote created with `activate_at` no earlier than the auction's close
+    quote_id: QuoteId,
+}
+
+/// Submit a maker's scheduled quote as a sealed response
+async fn respond_to_auction(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<AuctionResponseRequest>,
+) -> Result<Json<serde_json::Value>, Response> {
+    let auction = state
+        .domain
+        .respond_to_auction(&id, &request.quote_id, &request.maker_owner_id)
+        .await
+        .map_err(auction_error_response)?;
+    tracing::info!("Auction {} response from {}", id, request.maker_owner_id);
+    Ok(Json(auction_json(&auction, &[])))
+}
+
+/// Accept an auction: fill the best response and issue regret receipts
+///
+/// The body is the fill request for the winning quote; the fill runs
+/// through the usual Local Laws and settlement path. The award is claimed
+/// before the fill, so concurrent accepts can't both fill a winner, and is
+/// only recorded (and the losing quotes withdrawn) if the fill succeeds.
+async fn accept_auction(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    headers: HeaderMap,
+    Json(request): Json<FillRequest>,
+) -> Result<Json<serde_json::Value>, Response> {
+    let (auction, ranked) = state
+        .domain
+        .ranked_auction(&id)
+        .await
+        .ok_or_else(|| auction_error_response(AuctionError::UnknownAuction))?;
+    let refusal = match auction.status(chrono::Utc::now()) {
+        AuctionStatus::Open => Some(AuctionError::StillOpen),
+        AuctionStatus::Awarded => Some(AuctionError::AlreadyAwarded),
+        AuctionStatus::Revealed if request.taker_owner_id != auction.taker_owner_id => {
+            Some(AuctionError::WrongTaker)
+        }
+        AuctionStatus::Revealed => None,
+    };
+    if let Some(e) = refusal {
+        return Err(auction_error_response(e));
+    }
+    let award = auction
+        .award(&ranked, chrono::Utc::now())
+        .ok_or_else(|| auction_error_response(AuctionError::NoResponses))?;
+
+    state
+        .domain
+        .claim_auction(&id)
+        .await
+        .map_err(auction_error_response)?;
+
+    // The sweeper may not have posted the winner yet
+    activate_due_quote(&state, &award.winner).await;
+    let fill = fill_quote(State(state.clone()), Path(award.winner), headers, Json(request)).await;
+    let fill = match fill {
+        Ok(Json(fill)) => fill,
+        Err(response) => {
+            state.domain.release_auction(&id);
+            return Err(response);
+        }
+    };
+    if !fill.success {
+        state.domain.release_auction(&id);
+        return Ok(Json(serde_json::json!({
+            "fill": fill,
+            "auction": auction_json(&auction, &ranked),
+        })));
+    }
+
+    let auction = state
+        .domain
+        .award_auction(&id, award)
+        .await
+        .map_err(auction_error_response)?;
+    tracing::info!(
+        "Auction {} awarded to quote {} ({} regret receipts)",
+        id,
+        fill.quote_id,
+        ranked.len().saturating_sub(1)
+    );
+    Ok(Json(serde_json::json!({
+        "fill": fill,
+        "auction": auction_json(&auction, &ranked),
+    })))
 }
@@ -0,0 +1,298 @@
+//! Supported markets
+//!
+//! The asset/currency pairs the domain trades, with their tick and lot
+//! sizes and the feeds quotes use when their text names none. When markets
+//! are configured, quotes for unlisted pairs are refused and prices and
+//! sizes must sit on the market's tick and lot grid. An operator can halt a
+//! market (`POST /markets/:id/halt` with the `X-Admin-Key` header), which
+//! refuses new quotes and fills on it until it is resumed.
+//!
+//! ```yaml
+//! admin_key: change-me
+//! markets:
+//!   - asset: dETH
+//!     currency: USDD
+//!     tick_size: 0.01
+//!     lot_size: 0.001
+//!     default_sources: [FeedA, FeedB]
+//! ```
+
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use chrono::{DateTime, Utc};
+use rfq_compiler::AssetRegistry;
+use serde::{Deserialize, Serialize};
+use std::sync::RwLock;
+use thiserror::Error;
+
+/// Header carrying the operator's admin key
+pub const ADMIN_KEY_HEADER: &str = "x-admin-key";
+
+/// A configured market
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarketConfig {
+    /// Asset traded
+    pub asset: String,
+    /// Currency it is priced in
+    pub currency: String,
+    /// Smallest price increment (any price if unset)
+    #[serde(default)]
+    pub tick_size: Option<f64>,
+    /// Smallest size increment (any size if unset)
+    #[serde(default)]
+    pub lot_size: Option<f64>,
+    /// Feeds used by quotes whose text names none
+    #[serde(default)]
+    pub default_sources: Vec<String>,
+    /// Start the market halted
+    #[serde(default)]
+    pub halted: bool,
+}
+
+/// Whether a market is trading
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MarketStatus {
+    Enabled,
+    Halted,
+}
+
+/// A market as listed by GET /markets
+#[derive(Debug, Clone, Serialize)]
+pub struct Market {
+    /// `<asset>-<currency>`, as used in admin paths
+    pub id: String,
+    pub asset: String,
+    pub currency: String,
+    pub asset_decimals: u8,
+    pub currency_decimals: u8,
+    pub tick_size: Option<f64>,
+    pub lot_size: Option<f64>,
+    pub default_sources: Vec<String>,
+    pub status: MarketStatus,
+    /// Why and since when the market is halted
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub halt_reason: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub halted_at: Option<DateTime<Utc>>,
+}
+
+impl Market {
+    fn matches(&self, asset: &str, currency: &str) -> bool {
+        self.asset.eq_ignore_ascii_case(asset) && self.currency.eq_ignore_ascii_case(currency)
+    }
+}
+
+/// Why a quote or fill was refused for its market
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum MarketError {
+    #[error("{asset}/{currency} is not a listed market (see GET /markets)")]
+    Unlisted { asset: String, currency: String },
+    #[error("Market {market} is halted: {reason}")]
+    Halted { market: String, reason: String },
+    #[error("Price {price} is not a multiple of {market}'s tick size {tick_size}")]
+    OffTick {
+        market: String,
+        price: f64,
+        tick_size: f64,
+    },
+    #[error("Size {size} is not a multiple of {market}'s lot size {lot_size}")]
+    OffLot {
+        market: String,
+        size: f64,
+        lot_size: f64,
+    },
+    #[error("Unknown market {0}")]
+    NotFound(String),
+    #[error("Admin endpoints are disabled (no admin_key configured)")]
+    AdminDisabled,
+    #[error("A valid admin key is required (X-Admin-Key header)")]
+    Unauthorized,
+}
+
+impl MarketError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Unlisted { .. } => "MARKET_UNLISTED",
+            Self::Halted { .. } => "MARKET_HALTED",
+            Self::OffTick { .. } => "OFF_TICK",
+            Self::OffLot { .. } => "OFF_LOT",
+            Self::NotFound(_) => "MARKET_NOT_FOUND",
+            Self::AdminDisabled | Self::Unauthorized => "UNAUTHORIZED",
+        }
+    }
+
+    pub fn status(&self) -> StatusCode {
+        match self {
+            Self::Unlisted { .. } | Self::OffTick { .. } | Self::OffLot { .. } => {
+                StatusCode::UNPROCESSABLE_ENTITY
+            }
+            Self::Halted { .. } => StatusCode::SERVICE_UNAVAILABLE,
+            Self::NotFound(_) => StatusCode::NOT_FOUND,
+            Self::AdminDisabled => StatusCode::FORBIDDEN,
+            Self::Unauthorized => StatusCode::UNAUTHORIZED,
+        }
+    }
+}
+
+impl IntoResponse for MarketError {
+    fn into_response(self) -> Response {
+        let body = serde_json::json!({
+            "success": false,
+            "error": {
+                "code": self.code(),
+                "message": self.to_string(),
+            },
+        });
+        (self.status(), Json(body)).into_response()
+    }
+}
+
+/// Check a request's admin key against the configured one
+pub fn authorize_admin(configured: Option<&str>, headers: &HeaderMap) -> Result<(), MarketError> {
+    let configured = configured
+        .filter(|key| !key.is_empty())
+        .ok_or(MarketError::AdminDisabled)?;
+    let given = headers.get(ADMIN_KEY_HEADER).and_then(|v| v.to_str().ok());
+    if given == Some(configured) {
+        Ok(())
+    } else {
+        Err(MarketError::Unauthorized)
+    }
+}
+
+/// Whether `value` is a whole number of `step`s
+fn on_grid(value: f64, step: f64) -> bool {
+    if step <= 0.0 {
+        return true;
+    }
+    let steps = value / step;
+    (steps - steps.round()).abs() <= 1e-6
+}
+
+/// The market list and each market's trading status
+pub struct Markets {
+    markets: RwLock<Vec<Market>>,
+}
+
+impl Markets {
+    pub fn new(configs: &[MarketConfig], assets: &AssetRegistry) -> Self {
+        let markets = configs
+            .iter()
+            .map(|config| Market {
+                id: format!("{}-{}", config.asset, config.currency),
+                asset: config.asset.clone(),
+                currency: config.currency.clone(),
+                asset_decimals: assets.get(&config.asset).decimals,
+                currency_decimals: assets.get(&config.currency).decimals,
+                tick_size: config.tick_size,
+                lot_size: config.lot_size,
+                default_sources: config.default_sources.clone(),
+                status: if config.halted {
+                    MarketStatus::Halted
+                } else {
+                    MarketStatus::Enabled
+                },
+                halt_reason: None,
+                halted_at: None,
+            })
+            .collect();
+        Self {
+            markets: RwLock::new(markets),
+        }
+    }
+
+    /// All markets, in configuration order
+    pub fn list(&self) -> Vec<Market> {
+        self.markets.read().unwrap().clone()
+    }
+
+    /// Halt or resume a market by ID, returning its new state
+    pub fn set_halted(
+        &self,
+        id: &str,
+        halted: bool,
+        reason: Option<String>,
+    ) -> Result<Market, MarketError> {
+        let mut markets = self.markets.write().unwrap();
+        let market = markets
+            .iter_mut()
+            .find(|m| m.id.eq_ignore_ascii_case(id))
+            .ok_or_else(|| MarketError::NotFound(id.to_string()))?;
+        if halted {
+            market.status = MarketStatus::Halted;
+            market.halt_reason = reason;
+            market.halted_at = Some(Utc::now());
+        } else {
+            market.status = MarketStatus::Enabled;
+            market.halt_reason = None;
+            market.halted_at = None;
+        }
+        Ok(market.clone())
+    }
+
+    /// Check a new quote against its market
+    ///
+    /// Returns the market, or `None` if no markets are configured (every
+    /// pair is then accepted).
+    pub fn check_quote(
+        &self,
+        asset: &str,
+        currency: &str,
+        size: f64,
+        limit_price: Option<f64>,
+    ) -> Result<Option<Market>, MarketError> {
+        let Some(market) = self.check_trading(asset, currency)? else {
+            return Ok(None);
+        };
+        if let (Some(price), Some(tick_size)) = (limit_price, market.tick_size) {
+            if !on_grid(price, tick_size) {
+                return Err(MarketError::OffTick {
+                    market: market.id,
+                    price,
+                    tick_size,
+                });
+            }
+        }
+        if let Some(lot_size) = market.lot_size {
+            if !on_grid(size, lot_size) {
+                return Err(MarketError::OffLot {
+                    market: market.id,
+                    size,
+                    lot_size,
+                });
+            }
+        }
+        Ok(Some(market))
+    }
+
+    /// Check that a pair is listed (if markets are configured) and not halted
+    pub fn check_trading(
+        &self,
+        asset: &str,
+        currency: &str,
+    ) -> Result<Option<Market>, MarketError> {
+        let markets = self.markets.read().unwrap();
+        if markets.is_empty() {
+            return Ok(None);
+        }
+        let market = markets
+            .iter()
+            .find(|m| m.matches(asset, currency))
+            .cloned()
+            .ok_or_else(|| MarketError::Unlisted {
+                asset: asset.to_string(),
+                currency: currency.to_string(),
+            })?;
+        if market.status == MarketStatus::Halted {
+            return Err(MarketError::Halted {
+                market: market.id,
+                reason: market
+                    .halt_reason
+                    .unwrap_or_else(|| "halted by the operator".to_string()),
+            });
+        }
+        Ok(Some(market))
+    }
+}
@@ -0,0 +1,326 @@
+//! Receipt notarization
+//!
+//! Every receipt is appended to a hash-chained log
+//! (`entry_hash = SHA-256(prev_hash || receipt_hash)`). Periodically the
+//! Merkle root of each UTC day's receipts is anchored on Delta: the domain
+//! signs a 1-plank transfer to a vault address derived from the root, so the
+//! root is committed in a domain-signed SDL. Inclusion proofs then tie any
+//! receipt to an anchored root.
+//!
+//! Hashing:
+//! - `receipt_hash = SHA-256(JSON of the receipt)`
+//! - `leaf = SHA-256(0x00 || receipt_hash)`
+//! - `node = SHA-256(0x01 || left || right)`; an unpaired node is promoted
+//!   to the next level unchanged
+
+use chrono::{DateTime, NaiveDate, Utc};
+use rfq_models::FillReceipt;
+use serde::{Deserialize, Serialize, Serializer};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+/// A SHA-256 digest
+pub type Hash = [u8; 32];
+
+/// Notarization settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotaryConfig {
+    /// Whether receipt roots are anchored on Delta
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// How often new receipts are anchored, in seconds
+    #[serde(default = "default_interval_secs")]
+    pub interval_secs: u64,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+fn default_interval_secs() -> u64 {
+    3600
+}
+
+impl Default for NotaryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_enabled(),
+            interval_secs: default_interval_secs(),
+        }
+    }
+}
+
+/// An entry in the hash-chained receipt log
+#[derive(Debug, Clone, Serialize)]
+pub struct LogEntry {
+    /// Position in the log (0-based)
+    pub index: u64,
+    /// The logged receipt
+    pub receipt_id: Uuid,
+    /// UTC day the receipt was generated
+    pub day: NaiveDate,
+    #[serde(serialize_with = "hex")]
+    pub receipt_hash: Hash,
+    #[serde(serialize_with = "hex")]
+    pub prev_hash: Hash,
+    #[serde(serialize_with = "hex")]
+    pub entry_hash: Hash,
+}
+
+/// A day's receipt root committed on Delta
+#[derive(Debug, Clone, Serialize)]
+pub struct Anchor {
+    /// UTC day whose receipts the root covers
+    pub day: NaiveDate,
+    /// Merkle root over the first `receipt_count` receipts of the day
+    #[serde(serialize_with = "hex")]
+    pub root: Hash,
+    /// Number of receipts covered
+    pub receipt_count: usize,
+    /// Log head when the anchor was taken
+    #[serde(serialize_with = "hex")]
+    pub chain_head: Hash,
+    /// SDL carrying the anchor transfer
+    pub sdl_hash: String,
+    /// Vault credited by the anchor transfer (owner ID = root, base58)
+    pub anchor_owner_id: String,
+    /// When the anchor was submitted
+    pub anchored_at: DateTime<Utc>,
+}
+
+/// A day root that has receipts not yet covered by an anchor
+#[derive(Debug, Clone)]
+pub struct PendingAnchor {
+    pub day: NaiveDate,
+    pub root: Hash,
+    pub receipt_count: usize,
+    pub chain_head: Hash,
+}
+
+/// Which side of the running hash a proof sibling sits on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Side {
+    Left,
+    Right,
+}
+
+/// One step of a Merkle inclusion proof
+#[derive(Debug, Clone, Serialize)]
+pub struct ProofStep {
+    #[serde(serialize_with = "hex")]
+    pub sibling: Hash,
+    pub side: Side,
+}
+
+/// Proof that a receipt is included in an anchored day root
+#[derive(Debug, Clone, Serialize)]
+pub struct InclusionProof {
+    pub receipt_id: Uuid,
+    pub day: NaiveDate,
+    /// Position of the receipt among the day's receipts
+    pub leaf_index: usize,
+    #[serde(serialize_with = "hex")]
+    pub receipt_hash: Hash,
+    /// Siblings from the leaf up to the root
+    pub proof: Vec<ProofStep>,
+    /// The anchor the proof verifies against
+    pub anchor: Anchor,
+    /// The receipt's entry in the hash-chained log
+    pub entry: LogEntry,
+}
+
+/// Why an inclusion proof is unavailable
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InclusionError {
+    /// The receipt is not in the log
+    UnknownReceipt,
+    /// The receipt is logged but no anchor covers it yet
+    NotAnchored,
+}
+
+/// Append-only, hash-chained receipt log plus its anchors
+#[derive(Debug, Default)]
+pub struct ReceiptLog {
+    entries: Vec<LogEntry>,
+    /// Anchors per day, oldest first
+    anchors: BTreeMap<NaiveDate, Vec<Anchor>>,
+}
+
+impl ReceiptLog {
+    /// Append a receipt to the chain
+    pub fn append(&mut self, receipt: &FillReceipt) -> &LogEntry {
+        let receipt_hash = receipt_hash(receipt);
+        let prev_hash = self.head();
+        let entry_hash: Hash = Sha256::new()
+            .chain_update(prev_hash)
+            .chain_update(receipt_hash)
+            .finalize()
+            .into();
+
+        self.entries.push(LogEntry {
+            index: self.entries.len() as u64,
+            receipt_id: receipt.receipt_id,
+            day: receipt.generated_at.date_naive(),
+            receipt_hash,
+            prev_hash,
+            entry_hash,
+        });
+        self.entries.last().expect("just pushed")
+    }
+
+    /// Hash of the latest entry (zero for an empty log)
+    pub fn head(&self) -> Hash {
+        self.entries.last().map_or([0u8; 32], |e| e.entry_hash)
+    }
+
+    /// Day roots with receipts not covered by their latest anchor
+    pub fn pending_anchors(&self) -> Vec<PendingAnchor> {
+        let mut days: BTreeMap<NaiveDate, Vec<Hash>> = BTreeMap::new();
+        for entry in &self.entries {
+            days.entry(entry.day).or_default().push(entry.receipt_hash);
+        }
+
+        days.into_iter()
+            .filter(|(day, hashes)| self.latest_anchor(day).map_or(0, |a| a.receipt_count) < hashes.len())
+            .map(|(day, hashes)| PendingAnchor {
+                day,
+                root: merkle_root(&hashes),
+                receipt_count: hashes.len(),
+                chain_head: self.head(),
+            })
+            .collect()
+    }
+
+    /// Record a submitted anchor
+    pub fn record_anchor(&mut self, anchor: Anchor) {
+        self.anchors.entry(anchor.day).or_default().push(anchor);
+    }
+
+    /// All anchors, oldest day first
+    pub fn anchors(&self) -> Vec<Anchor> {
+        self.anchors.values().flatten().cloned().collect()
+    }
+
+    /// Prove a receipt's inclusion in the latest anchor of its day
+    pub fn inclusion_proof(&self, receipt_id: Uuid) -> Result<InclusionProof, InclusionError> {
+        let entry = self
+            .entries
+            .iter()
+            .find(|e| e.receipt_id == receipt_id)
+            .ok_or(InclusionError::UnknownReceipt)?;
+
+        let day_hashes: Vec<Hash> = self
+            .entries
+            .iter()
+            .filter(|e| e.day == entry.day)
+            .map(|e| e.receipt_hash)
+            .collect();
+        let leaf_index = day_hashes
+            .iter()
+            .position(|h| *h == entry.receipt_hash)
+            .ok_or(InclusionError::UnknownReceipt)?;
+
+        let anchor = self
+            .latest_anchor(&entry.day)
+            .filter(|a| leaf_index < a.receipt_count)
+            .ok_or(InclusionError::NotAnchored)?;
+
+        let proof = merkle_proof(&day_hashes[..anchor.receipt_count], leaf_index);
+        debug_assert!(verify_inclusion(&entry.receipt_hash, &proof, &anchor.root));
+
+        Ok(InclusionProof {
+            receipt_id,
+            day: entry.day,
+            leaf_index,
+            receipt_hash: entry.receipt_hash,
+            proof,
+            anchor: anchor.clone(),
+            entry: entry.clone(),
+        })
+    }
+
+    fn latest_anchor(&self, day: &NaiveDate) -> Option<&Anchor> {
+        self.anchors.get(day).and_then(|a| a.last())
+    }
+}
+
+/// Hash of a receipt's JSON encoding
+pub fn receipt_hash(receipt: &FillReceipt) -> Hash {
+    let bytes = serde_json::to_vec(receipt).expect("receipts serialize to JSON");
+    Sha256::digest(bytes).into()
+}
+
+fn leaf_hash(receipt_hash: &Hash) -> Hash {
+    Sha256::new()
+        .chain_update([0x00])
+        .chain_update(receipt_hash)
+        .finalize()
+        .into()
+}
+
+fn node_hash(left: &Hash, right: &Hash) -> Hash {
+    Sha256::new()
+        .chain_update([0x01])
+        .chain_update(left)
+        .chain_update(right)
+        .finalize()
+        .into()
+}
+
+/// Hash one level of the tree into the next
+fn next_level(level: &[Hash]) -> Vec<Hash> {
+    level
+        .chunks(2)
+        .map(|pair| match pair {
+            [left, right] => node_hash(left, right),
+            [single] => *single,
+            _ => unreachable!(),
+        })
+        .collect()
+}
+
+/// Merkle root over receipt hashes (zero for no receipts)
+pub fn merkle_root(receipt_hashes: &[Hash]) -> Hash {
+    let mut level: Vec<Hash> = receipt_hashes.iter().map(leaf_hash).collect();
+    if level.is_empty() {
+        return [0u8; 32];
+    }
+    while level.len() > 1 {
+        level = next_level(&level);
+    }
+    level[0]
+}
+
+/// Sibling path from a leaf to the root
+fn merkle_proof(receipt_hashes: &[Hash], mut index: usize) -> Vec<ProofStep> {
+    let mut level: Vec<Hash> = receipt_hashes.iter().map(leaf_hash).collect();
+    let mut proof = Vec::new();
+    while level.len() > 1 {
+        let sibling = index ^ 1;
+        if let Some(hash) = level.get(sibling) {
+            proof.push(ProofStep {
+                sibling: *hash,
+                side: if sibling < index { Side::Left } else { Side::Right },
+            });
+        }
+        level = next_level(&level);
+        index /= 2;
+    }
+    proof
+}
+
+/// Recompute a root from a receipt hash and its proof
+pub fn verify_inclusion(receipt_hash: &Hash, proof: &[ProofStep], root: &Hash) -> bool {
+    let computed = proof.iter().fold(leaf_hash(receipt_hash), |acc, step| match step.side {
+        Side::Left => node_hash(&step.sibling, &acc),
+        Side::Right => node_hash(&acc, &step.sibling),
+    });
+    computed == *root
+}
+
+fn hex<S: Serializer>(hash: &Hash, serializer: S) -> Result<S::Ok, S::Error> {
+    let encoded: String = hash.iter().map(|b| format!("{:02x}", b)).collect();
+    serializer.serialize_str(&encoded)
+}
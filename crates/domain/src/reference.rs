@@ -0,0 +1,150 @@
+//! Reference price snapshots
+//!
+//! Queries the configured price feeds and reduces their answers to a single
+//! consensus price (the median), which is stored on quotes at creation time.
+
+use rfq_models::{from_fixed_price, EvidenceProvenance, FeedEvidence, PriceUpdate, ReferencePrice};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tokio::task::JoinSet;
+
+/// How long to wait for each feed before leaving it out of the consensus
+const FEED_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Client for the configured price feed endpoints
+pub struct FeedClient {
+    client: reqwest::Client,
+    /// Feed source name -> base URL (e.g. "FeedA" -> "http://localhost:9001")
+    endpoints: HashMap<String, String>,
+}
+
+impl FeedClient {
+    pub fn new(endpoints: HashMap<String, String>) -> Self {
+        Self {
+            client: reqwest::Client::builder()
+                .timeout(FEED_TIMEOUT)
+                .build()
+                .expect("Failed to build reqwest client"),
+            endpoints,
+        }
+    }
+
+    /// Snapshot the consensus price for an asset in `currency`
+    ///
+    /// Only queries feeds in `sources` (all configured feeds if empty).
+    /// Returns None if no feed answered.
+    pub async fn snapshot(
+        &self,
+        asset: &str,
+        currency: &str,
+        sources: &[String],
+    ) -> Option<ReferencePrice> {
+        consensus(self.fetch(asset, currency, sources).await)
+    }
+
+    /// Fetch the current price in `currency` from each feed in `sources`
+    /// (all if empty)
+    ///
+    /// Each answer carries its retrieval provenance. Feeds that don't answer
+    /// in time, or can't price the asset in the currency, are left out.
+    pub async fn fetch(
+        &self,
+        asset: &str,
+        currency: &str,
+        sources: &[String],
+    ) -> Vec<FeedEvidence> {
+        let mut requests = JoinSet::new();
+        for (name, base_url) in &self.endpoints {
+            if !sources.is_empty() && !sources.contains(name) {
+                continue;
+            }
+            let client = self.client.clone();
+            let url = format!(
+                "{}/price?asset={}&currency={}",
+                base_url.trim_end_matches('/'),
+                asset,
+                currency
+            );
+            let name = name.clone();
+            requests.spawn(async move {
+                let result = fetch_evidence(&client, &url).await;
+                (name, result)
+            });
+        }
+
+        let mut evidence = Vec::new();
+        while let Some(joined) = requests.join_next().await {
+            match joined {
+                Ok((_, Ok(e))) => evidence.push(e),
+                Ok((name, Err(e))) => tracing::warn!("Feed {} unavailable: {:#}", name, e),
+                Err(e) => tracing::warn!("Feed fetch task failed: {}", e),
+            }
+        }
+        evidence
+    }
+}
+
+/// Fetch one feed's price and record how it was retrieved
+async fn fetch_evidence(client: &reqwest::Client, url: &str) -> anyhow::Result<FeedEvidence> {
+    let started = Instant::now();
+    let response = client.get(url).send().await?;
+    let http_status = response.status().as_u16();
+    let body = response.error_for_status()?.bytes().await?;
+    let latency_ms = started.elapsed().as_millis() as u64;
+
+    let update: PriceUpdate = serde_json::from_slice(&body)?;
+    let response_hash = Sha256::digest(&body)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+
+    let price = update.fixed_price();
+    Ok(FeedEvidence {
+        source: update.source,
+        asset: update.asset,
+        price,
+        timestamp: update.timestamp,
+        signature: update.signature,
+        provenance: Some(EvidenceProvenance {
+            response_hash,
+            url: url.to_string(),
+            http_status,
+            latency_ms,
+            retrieved_at: chrono::Utc::now().timestamp() as u64,
+        }),
+    })
+}
+
+/// Median of the finite, positive prices
+pub fn median_price(prices: impl IntoIterator<Item = f64>) -> Option<f64> {
+    let mut prices: Vec<f64> = prices
+        .into_iter()
+        .filter(|p| p.is_finite() && *p > 0.0)
+        .collect();
+    prices.sort_by(f64::total_cmp);
+    let mid = prices.len() / 2;
+    match prices.len() {
+        0 => None,
+        len if len % 2 == 0 => Some((prices[mid - 1] + prices[mid]) / 2.0),
+        _ => Some(prices[mid]),
+    }
+}
+
+/// Reduce feed answers to their median price
+fn consensus(mut updates: Vec<FeedEvidence>) -> Option<ReferencePrice> {
+    updates.retain(|u| u.price > 0);
+    if updates.is_empty() {
+        return None;
+    }
+
+    let price = median_price(updates.iter().map(|u| from_fixed_price(u.price)))?;
+    let mut sources: Vec<String> = updates.into_iter().map(|u| u.source).collect();
+    sources.sort();
+
+    Some(ReferencePrice {
+        price,
+        sources,
+        captured_at: chrono::Utc::now(),
+    })
+}
@@ -0,0 +1,107 @@
+//! Reject-fast fill screening
+//!
+//! Cheap synchronous checks run at the top of `fill_quote`, before the
+//! domain state lock is taken or the Local Laws input is built: the taker
+//! deny list, expiry, size bounds, the taker allowlist, the taker's shard
+//! and the evidence count. A flood of hopeless fills is answered in
+//! microseconds without a receipt and never reaches the expensive pipeline.
+//! Local Laws still check everything for fills that pass.
+
+use chrono::{DateTime, Utc};
+use rfq_models::{FillRequest, Quote, RejectionReason};
+
+/// Most feed evidence entries a fill may carry
+const MAX_FEED_EVIDENCE: usize = 16;
+
+/// The terms of an open quote that fills are screened against
+#[derive(Debug, Clone)]
+pub struct FillScreen {
+    expires_at: DateTime<Utc>,
+    /// Units per whole asset, as used in Local Laws input
+    scale: f64,
+    /// Largest fill, in units
+    max_fill_size: u64,
+    allowed_takers: Vec<String>,
+    denied_takers: Vec<String>,
+    allowed_taker_shards: Vec<u64>,
+    quorum_count: u32,
+    quorum_tolerance_bps: u32,
+}
+
+impl FillScreen {
+    /// Screen for `quote`, whose asset has `scale` units per whole asset
+    pub fn new(quote: &Quote, scale: f64) -> Self {
+        Self {
+            expires_at: quote.expires_at,
+            scale,
+            max_fill_size: quote.constraints.max_fill_size,
+            allowed_takers: quote.constraints.allowed_takers.clone(),
+            denied_takers: quote.constraints.denied_takers.clone(),
+            allowed_taker_shards: quote.constraints.allowed_taker_shards.clone(),
+            quorum_count: quote.constraints.quorum_count,
+            quorum_tolerance_bps: quote.constraints.quorum_tolerance_bps,
+        }
+    }
+
+    /// Screen a fill request, returning why it can't succeed
+    pub fn check(&self, request: &FillRequest, now: DateTime<Utc>) -> Option<RejectionReason> {
+        if self.denied_takers.contains(&request.taker_owner_id) {
+            return Some(RejectionReason::DeniedTaker {
+                taker: request.taker_owner_id.clone(),
+            });
+        }
+        if now > self.expires_at {
+            return Some(RejectionReason::QuoteExpired {
+                expired_at: self.expires_at,
+                attempted_at: now,
+            });
+        }
+        let positive = |value: f64| value > 0.0 && value.is_finite();
+        if !positive(request.size) || !positive(request.price) {
+            return Some(RejectionReason::ValidationError {
+                message: "Fill size and price must be positive".to_string(),
+            });
+        }
+        let size = request.size * self.scale;
+        if size > self.max_fill_size as f64 {
+            return Some(RejectionReason::SizeExceedsMax {
+                offered_size: size,
+                max_size: self.max_fill_size as f64,
+            });
+        }
+        if !self.allowed_takers.is_empty() && !self.allowed_takers.contains(&request.taker_owner_id)
+        {
+            return Some(RejectionReason::UnauthorizedTaker {
+                taker: request.taker_owner_id.clone(),
+                allowed_takers: self.allowed_takers.clone(),
+            });
+        }
+        if !self.allowed_taker_shards.is_empty()
+            && !self.allowed_taker_shards.contains(&request.taker_shard)
+        {
+            return Some(RejectionReason::UnauthorizedShard {
+                taker: request.taker_owner_id.clone(),
+                taker_shard: request.taker_shard,
+                allowed_shards: self.allowed_taker_shards.clone(),
+            });
+        }
+        let evidence = request.feed_evidence.len();
+        if evidence < self.quorum_count as usize {
+            return Some(RejectionReason::QuorumNotMet {
+                sources_provided: evidence,
+                quorum_required: self.quorum_count,
+                price_spread_bps: None,
+                max_tolerance_bps: self.quorum_tolerance_bps,
+            });
+        }
+        if evidence > MAX_FEED_EVIDENCE {
+            return Some(RejectionReason::ValidationError {
+                message: format!(
+                    "{} feed evidence entries exceed the maximum of {}",
+                    evidence, MAX_FEED_EVIDENCE
+                ),
+            });
+        }
+        None
+    }
+}
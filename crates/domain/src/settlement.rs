@@ -0,0 +1,321 @@
+//! Proof settlement and compensation
+//!
+//! After a fill's SDL is submitted the domain waits up to
+//! `proof_timeout_secs` for its proof. If the proof is late and
+//! compensation is enabled, the fill's proof is marked pending and a
+//! background task keeps waiting up to `compensation_timeout_secs` longer.
+//! When the proof lands (or finally fails) the proof status is upgraded and
+//! both parties are notified through their webhooks.
+//!
+//! Once a fill's proof is verified, a [`SettlementRecord`] is posted to each
+//! configured booking webhook, signed with the webhook's shared secret, so
+//! treasury and back-office systems can book the trade. Unavailable
+//! webhooks are retried with backoff; every delivery of a record carries
+//! the same settlement ID, so receivers can drop duplicates.
+//!
+//! Settlement steps are recorded in the fill journal (see `journal.rs`) so
+//! they resume after a crash.
+
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use rfq_compiler::RetryConfig;
+use rfq_models::{QuoteId, Side};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// Proof settlement settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SettlementConfig {
+    /// How long a fill waits for its proof, in seconds
+    #[serde(default = "default_proof_timeout_secs")]
+    pub proof_timeout_secs: u64,
+    /// Keep waiting in the background after a timeout and upgrade the
+    /// proof status when the proof lands
+    #[serde(default = "default_compensate_timeouts")]
+    pub compensate_timeouts: bool,
+    /// How much longer the background wait lasts, in seconds
+    #[serde(default = "default_compensation_timeout_secs")]
+    pub compensation_timeout_secs: u64,
+    /// Webhook URLs notified of proof status changes, by owner ID
+    #[serde(default)]
+    pub webhooks: HashMap<String, String>,
+    /// Back-office webhooks sent a signed record of every settled fill
+    #[serde(default)]
+    pub booking_webhooks: Vec<BookingWebhook>,
+    /// Retry policy for booking webhooks that are unavailable (429, 5xx,
+    /// network errors)
+    #[serde(default)]
+    pub booking_retry: RetryConfig,
+    /// File the fill journal is persisted to (memory only if unset, so
+    /// settlements interrupted by a crash aren't resumed)
+    #[serde(default)]
+    pub journal_path: Option<PathBuf>,
+}
+
+fn default_proof_timeout_secs() -> u64 {
+    60
+}
+
+fn default_compensate_timeouts() -> bool {
+    true
+}
+
+fn default_compensation_timeout_secs() -> u64 {
+    900
+}
+
+impl Default for SettlementConfig {
+    fn default() -> Self {
+        Self {
+            proof_timeout_secs: default_proof_timeout_secs(),
+            compensate_timeouts: default_compensate_timeouts(),
+            compensation_timeout_secs: default_compensation_timeout_secs(),
+            webhooks: HashMap::new(),
+            booking_webhooks: Vec::new(),
+            booking_retry: RetryConfig::default(),
+            journal_path: None,
+        }
+    }
+}
+
+/// A back-office endpoint booking settled fills
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BookingWebhook {
+    pub url: String,
+    /// Shared secret the record's HMAC-SHA256 signature is keyed with
+    pub secret: String,
+}
+
+/// Proof state of an accepted fill's SDL
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProofStatus {
+    /// Proven and the proof submitted
+    Verified,
+    /// Timed out; still waiting for the proof in the background
+    Pending,
+    /// No proof arrived in time
+    TimedOut,
+    /// Proving or proof submission failed
+    Failed,
+}
+
+impl ProofStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Verified => "verified",
+            Self::Pending => "pending",
+            Self::TimedOut => "timed_out",
+            Self::Failed => "failed",
+        }
+    }
+}
+
+/// Webhook payload sent when a fill's proof status changes
+#[derive(Debug, Clone, Serialize)]
+pub struct ProofNotification {
+    pub event: &'static str,
+    pub quote_id: QuoteId,
+    pub sdl_hash: String,
+    pub status: ProofStatus,
+    pub maker_owner_id: String,
+    pub taker_owner_id: String,
+    pub at: DateTime<Utc>,
+}
+
+/// Version of the [`SettlementRecord`] schema; bumped on breaking changes
+pub const SETTLEMENT_SCHEMA_VERSION: u32 = 1;
+
+/// Record of a settled fill, posted to booking webhooks
+///
+/// Amounts are in plancks. The schema is stable within a
+/// `schema_version`: fields may be added, never renamed or removed.
+#[derive(Debug, Clone, Serialize)]
+pub struct SettlementRecord {
+    pub schema_version: u32,
+    pub event: &'static str,
+    /// Stable ID of the settlement (see [`settlement_id`]), also sent as
+    /// the `Idempotency-Key` header
+    pub settlement_id: String,
+    pub quote_id: QuoteId,
+    pub settled_at: DateTime<Utc>,
+    pub maker: SettlementParty,
+    pub taker: SettlementParty,
+    /// The maker's side of the trade
+    pub side: Side,
+    pub asset: String,
+    pub currency: String,
+    /// Transfers that settled the fill
+    pub legs: Vec<SettlementLeg>,
+    pub proof: ProofReference,
+}
+
+/// A party to a settled fill
+#[derive(Debug, Clone, Serialize)]
+pub struct SettlementParty {
+    pub owner_id: String,
+    pub vault_address: String,
+}
+
+/// One transfer of a settled fill
+#[derive(Debug, Clone, Serialize)]
+pub struct SettlementLeg {
+    /// Owner ID of the paying party
+    pub from: String,
+    /// Owner ID of the receiving party
+    pub to: String,
+    /// Symbol the leg is denominated in
+    pub symbol: String,
+    /// Delta token the leg was transferred in
+    pub token_id: String,
+    pub amount: u64,
+}
+
+/// Where a settled fill's proof can be found
+#[derive(Debug, Clone, Serialize)]
+pub struct ProofReference {
+    pub sdl_hash: String,
+    pub shard: u64,
+    pub status: ProofStatus,
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// ID of a fill's settlement: hex SHA-256 of `"{quote_id}:{sdl_hash}"`
+///
+/// Each fill settles in its own SDL, so the ID is the same however often
+/// the record is delivered, including after a restart.
+pub fn settlement_id(quote_id: &QuoteId, sdl_hash: &str) -> String {
+    to_hex(&Sha256::digest(format!("{}:{}", quote_id, sdl_hash)))
+}
+
+/// Signature of a booking webhook body: hex HMAC-SHA256 of
+/// `"{timestamp}.{body}"` keyed with the webhook's secret
+pub fn sign_booking(secret: &str, timestamp: i64, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    to_hex(&mac.finalize().into_bytes())
+}
+
+/// Delivers notifications to parties' webhooks
+#[derive(Debug)]
+pub struct Webhooks {
+    urls: HashMap<String, String>,
+    booking: Vec<BookingWebhook>,
+    booking_retry: RetryConfig,
+    client: reqwest::Client,
+}
+
+impl Webhooks {
+    pub fn new(
+        urls: HashMap<String, String>,
+        booking: Vec<BookingWebhook>,
+        booking_retry: RetryConfig,
+    ) -> Self {
+        Self {
+            urls,
+            booking,
+            booking_retry,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Post a signed settlement record to every booking webhook (in the
+    /// background)
+    ///
+    /// Each request carries `Idempotency-Key` (the record's settlement
+    /// ID), `X-RFQ-Timestamp` (unix seconds) and
+    /// `X-RFQ-Signature: sha256=<hex>` (see [`sign_booking`]). Unavailable
+    /// webhooks are retried with backoff, re-signed at each attempt, up to
+    /// the retry policy's attempts; other failures aren't retried.
+    pub fn book_settlement(&self, record: &SettlementRecord) {
+        if self.booking.is_empty() {
+            return;
+        }
+        let body = match serde_json::to_vec(record) {
+            Ok(body) => body,
+            Err(e) => {
+                tracing::error!("Failed to serialize settlement record: {}", e);
+                return;
+            }
+        };
+        for hook in &self.booking {
+            let client = self.client.clone();
+            let retry = self.booking_retry.clone();
+            let hook = hook.clone();
+            let body = body.clone();
+            let settlement_id = record.settlement_id.clone();
+            let sdl_hash = record.proof.sdl_hash.clone();
+            tokio::spawn(async move {
+                let mut attempt = 1;
+                loop {
+                    let timestamp = Utc::now().timestamp();
+                    let signature = sign_booking(&hook.secret, timestamp, &body);
+                    let sent = client
+                        .post(&hook.url)
+                        .header(reqwest::header::CONTENT_TYPE, "application/json")
+                        .header("Idempotency-Key", &settlement_id)
+                        .header("X-RFQ-Timestamp", timestamp.to_string())
+                        .header("X-RFQ-Signature", format!("sha256={}", signature))
+                        .body(body.clone())
+                        .send()
+                        .await
+                        .and_then(|r| r.error_for_status());
+                    let Err(e) = sent else {
+                        return;
+                    };
+                    let unavailable = match e.status() {
+                        Some(status) => {
+                            status.is_server_error()
+                                || status == reqwest::StatusCode::TOO_MANY_REQUESTS
+                        }
+                        None => true,
+                    };
+                    if !unavailable || attempt >= retry.max_attempts {
+                        tracing::error!(
+                            "Failed to book settlement {} of SDL {} at {} (attempt {}): {}",
+                            settlement_id,
+                            sdl_hash,
+                            hook.url,
+                            attempt,
+                            e
+                        );
+                        return;
+                    }
+                    let delay = retry.backoff(attempt);
+                    tracing::warn!(
+                        "Booking webhook {} unavailable ({}); retrying in {}ms",
+                        hook.url,
+                        e,
+                        delay.as_millis()
+                    );
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+            });
+        }
+    }
+
+    /// Notify maker and taker of a proof status change (in the background)
+    pub fn notify_proof(&self, notification: &ProofNotification) {
+        for party in [&notification.maker_owner_id, &notification.taker_owner_id] {
+            let Some(url) = self.urls.get(party) else {
+                continue;
+            };
+            let request = self.client.post(url).json(notification);
+            let party = party.clone();
+            tokio::spawn(async move {
+                if let Err(e) = request.send().await.and_then(|r| r.error_for_status()) {
+                    tracing::warn!("Failed to notify {} of proof status: {}", party, e);
+                }
+            });
+        }
+    }
+}
@@ -0,0 +1,103 @@
+//! Proving backend simulation
+//!
+//! The mock proving client proves every SDL at once and never fails, so
+//! proof timeouts, proving failures, watchdog suspensions and crash
+//! recovery can't be exercised against it. In mock mode a
+//! [`ProvingSimulation`] puts a configurable latency distribution and
+//! failure rate in front of it. Outcomes come from a seeded generator, so
+//! the same seed and fill order always give the same latencies and
+//! failures.
+//!
+//! ```yaml
+//! proving_simulation:
+//!   latency: { distribution: exponential, mean_ms: 2000 }
+//!   failure_rate: 0.1
+//!   seed: 42
+//! ```
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// How long simulated proofs take
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "distribution", rename_all = "snake_case")]
+pub enum LatencyDistribution {
+    /// Always the same
+    Fixed { ms: u64 },
+    /// Uniform between the bounds
+    Uniform { min_ms: u64, max_ms: u64 },
+    /// Exponential with the given mean (mostly quick, with a long tail)
+    Exponential { mean_ms: u64 },
+}
+
+impl Default for LatencyDistribution {
+    fn default() -> Self {
+        Self::Fixed { ms: 0 }
+    }
+}
+
+impl LatencyDistribution {
+    fn sample(&self, rng: &mut StdRng) -> Duration {
+        let ms = match *self {
+            Self::Fixed { ms } => ms,
+            Self::Uniform { min_ms, max_ms } => rng.gen_range(min_ms..=max_ms.max(min_ms)),
+            Self::Exponential { mean_ms } => {
+                let u: f64 = rng.gen();
+                (-(1.0 - u).ln() * mean_ms as f64) as u64
+            }
+        };
+        Duration::from_millis(ms)
+    }
+}
+
+/// Simulated proving backend behaviour (mock mode only)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProvingSimulation {
+    #[serde(default)]
+    pub latency: LatencyDistribution,
+    /// Share of proofs (0.0-1.0) that fail after their latency
+    #[serde(default)]
+    pub failure_rate: f64,
+    /// Seed of the outcome generator
+    #[serde(default)]
+    pub seed: u64,
+}
+
+/// The simulated outcome of one proof
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimulatedProof {
+    pub latency: Duration,
+    /// Why the proof fails, if it does
+    pub failure: Option<String>,
+}
+
+/// Draws simulated proof outcomes
+#[derive(Debug)]
+pub struct ProvingSimulator {
+    config: ProvingSimulation,
+    rng: Mutex<StdRng>,
+}
+
+impl ProvingSimulator {
+    pub fn new(config: ProvingSimulation) -> Self {
+        let rng = StdRng::seed_from_u64(config.seed);
+        Self {
+            config,
+            rng: Mutex::new(rng),
+        }
+    }
+
+    /// Draw the outcome of the next proof
+    pub fn next_proof(&self) -> SimulatedProof {
+        let mut rng = self.rng.lock().unwrap_or_else(|e| e.into_inner());
+        let latency = self.config.latency.sample(&mut rng);
+        let fails = rng.gen::<f64>() < self.config.failure_rate;
+        SimulatedProof {
+            latency,
+            failure: fails.then(|| "simulated proving failure".to_string()),
+        }
+    }
+}
@@ -1,63 +1,774 @@
 //! Domain state management
 
-use rfq_models::{Quote, QuoteId, FillReceipt};
-use std::collections::HashMap;
+use rfq_models::{
+    DivergenceAction, FillReceipt, FillRecord, FillRequest, Quote, QuoteId, QuoteStatus,
+    ReferencePrice, RejectionReason,
+};
+use rfq_compiler::{AssetRegistry, CompilationRecord};
+use crate::auction::{Auction, AuctionAward, AuctionError, AuctionResponse, RankedResponse};
+use crate::events::{DivergenceTrigger, DomainEvent, EventEnvelope, EventStore, MakerStats};
+use crate::notary::{Anchor, InclusionError, InclusionProof, PendingAnchor};
+use crate::screening::FillScreen;
+use crate::settlement::ProofStatus;
+use uuid::Uuid;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
+/// Filter for receipt queries
+#[derive(Debug, Default, Clone)]
+pub struct ReceiptFilter {
+    /// Only accepted or pending (true) or only rejected (false) receipts
+    pub accepted: Option<bool>,
+    /// Only rejections with this reason code (e.g. "STALE_FEED")
+    pub reason_code: Option<String>,
+}
+
+impl ReceiptFilter {
+    /// Check if a receipt passes the filter
+    pub fn matches(&self, receipt: &FillReceipt) -> bool {
+        let rejected = receipt.rejection_reason().is_some();
+        if self.accepted.is_some_and(|accepted| accepted == rejected) {
+            return false;
+        }
+        match &self.reason_code {
+            Some(code) => receipt
+                .rejection_reason()
+                .is_some_and(|r| r.code().eq_ignore_ascii_case(code)),
+            None => true,
+        }
+    }
+}
+
+/// Why a maker couldn't cancel or approve a quote
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MakerActionError {
+    UnknownQuote,
+    /// The caller isn't the quote's maker
+    WrongMaker,
+    /// The quote's status doesn't allow the action
+    InvalidStatus(QuoteStatus),
+}
+
+/// Furthest one fill may move a taker's request nonce past the last one
+/// accepted, so a single request can't use up the nonce space
+pub const MAX_TAKER_NONCE_STEP: u64 = 1_000;
+
+/// Why a taker's fill request nonce can't be used
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TakerNonceError {
+    /// Not above the last accepted nonce
+    Stale { last: u64 },
+    /// More than [`MAX_TAKER_NONCE_STEP`] past the last accepted nonce (0
+    /// before the taker's first fill)
+    TooFar { last: u64 },
+}
+
+impl TakerNonceError {
+    fn check(last: Option<&u64>, nonce: u64) -> Result<(), Self> {
+        match last.copied() {
+            Some(last) if nonce <= last => Err(Self::Stale { last }),
+            last => {
+                let last = last.unwrap_or(0);
+                if nonce - last > MAX_TAKER_NONCE_STEP {
+                    Err(Self::TooFar { last })
+                } else {
+                    Ok(())
+                }
+            }
+        }
+    }
+
+    /// Why `nonce` was refused, as answered to the taker
+    pub fn message(&self, nonce: u64) -> String {
+        match self {
+            Self::Stale { last } => {
+                format!("Stale taker nonce {}: last accepted nonce is {}", nonce, last)
+            }
+            Self::TooFar { last } => format!(
+                "Taker nonce {} is more than {} past the last accepted nonce {}",
+                nonce, MAX_TAKER_NONCE_STEP, last
+            ),
+        }
+    }
+}
+
 /// In-memory state for the RFQ domain
+///
+/// Backed by an append-only [`EventStore`]: every mutation appends a
+/// [`DomainEvent`] and all reads come from the projections folded from it.
 #[derive(Debug, Default)]
 pub struct DomainState {
-    /// Active quotes indexed by ID
-    quotes: RwLock<HashMap<QuoteId, Quote>>,
-    /// Fill receipts indexed by quote ID
-    receipts: RwLock<HashMap<QuoteId, Vec<FillReceipt>>>,
+    store: RwLock<EventStore>,
+    /// Screens of open quotes, readable without waiting on the store
+    screens: std::sync::RwLock<HashMap<QuoteId, FillScreen>>,
+    /// Decimals of quoted assets, for screening fill sizes
+    assets: AssetRegistry,
+    /// Auctions whose winning fill is in flight
+    awarding: std::sync::Mutex<HashSet<Uuid>>,
 }
 
 impl DomainState {
-    pub fn new() -> Arc<Self> {
-        Arc::new(Self::default())
+    pub fn new(assets: AssetRegistry) -> Arc<Self> {
+        Arc::new(Self {
+            assets,
+            ..Self::default()
+        })
     }
 
-    /// Add a quote
-    pub async fn add_quote(&self, quote: Quote) {
-        let mut quotes = self.quotes.write().await;
-        quotes.insert(quote.id, quote);
+    /// Add a quote, with the record of its compilation
+    pub async fn add_quote(&self, quote: Quote, compilation: CompilationRecord) {
+        self.update_screen(&quote);
+        let mut store = self.store.write().await;
+        store.append(DomainEvent::QuoteCompiled {
+            quote_id: quote.id,
+            record: Box::new(compilation),
+        });
+        store.append(DomainEvent::QuoteCreated {
+            quote: Box::new(quote),
+        });
     }
 
     /// Get a quote by ID
     pub async fn get_quote(&self, id: &QuoteId) -> Option<Quote> {
-        let quotes = self.quotes.read().await;
-        quotes.get(id).cloned()
+        let store = self.store.read().await;
+        store.projections().quotes.get(id).cloned()
+    }
+
+    /// Get the record of how a quote was compiled
+    pub async fn get_compilation(&self, id: &QuoteId) -> Option<CompilationRecord> {
+        let store = self.store.read().await;
+        store.projections().compilations.get(id).cloned()
     }
 
     /// Get all active quotes
     pub async fn get_active_quotes(&self) -> Vec<Quote> {
-        let quotes = self.quotes.read().await;
-        quotes.values().filter(|q| q.is_active()).cloned().collect()
+        let store = self.store.read().await;
+        store
+            .projections()
+            .quotes
+            .values()
+            .filter(|q| q.is_active())
+            .cloned()
+            .collect()
     }
 
     /// Get all quotes (including filled and expired)
     pub async fn get_all_quotes(&self) -> Vec<Quote> {
-        let quotes = self.quotes.read().await;
-        quotes.values().cloned().collect()
+        let store = self.store.read().await;
+        store.projections().quotes.values().cloned().collect()
     }
 
     /// Update a quote
     pub async fn update_quote(&self, quote: Quote) {
-        let mut quotes = self.quotes.write().await;
-        quotes.insert(quote.id, quote);
+        self.update_screen(&quote);
+        let mut store = self.store.write().await;
+        store.append(DomainEvent::QuoteUpdated {
+            quote: Box::new(quote),
+        });
+    }
+
+    /// Refresh a quote's status from the clock, recording a change
+    ///
+    /// Reads and writes the quote under one lock, so a fill recorded
+    /// meanwhile isn't overwritten by a stale copy.
+    pub async fn refresh_quote(&self, id: &QuoteId) -> Option<Quote> {
+        let mut store = self.store.write().await;
+        let mut quote = store.projections().quotes.get(id)?.clone();
+        if quote.refresh_status() {
+            self.update_screen(&quote);
+            store.append(DomainEvent::QuoteUpdated {
+                quote: Box::new(quote.clone()),
+            });
+        }
+        Some(quote)
+    }
+
+    /// Check a fill against a quote as it stands and record it, in one step
+    ///
+    /// `check` sees the quote with every fill recorded so far, including
+    /// those accepted while the caller validated this one, so concurrent
+    /// fills can't both take the last of a cap. Returns the quote with the
+    /// fill recorded, closed once exhausted, and what `check` returned.
+    ///
+    /// The taker's request nonce is consumed with the fill; a request that
+    /// lost a race for it is rejected.
+    pub async fn reserve_fill<T>(
+        &self,
+        id: &QuoteId,
+        fill: FillRecord,
+        taker_nonce: u64,
+        check: impl FnOnce(&Quote) -> Result<T, RejectionReason>,
+    ) -> Result<(Quote, T), RejectionReason> {
+        let mut store = self.store.write().await;
+        let last_nonce = store.projections().taker_nonces.get(&fill.taker_owner_id);
+        if let Err(e) = TakerNonceError::check(last_nonce, taker_nonce) {
+            return Err(RejectionReason::ValidationError {
+                message: e.message(taker_nonce),
+            });
+        }
+        let mut quote = store
+            .projections()
+            .quotes
+            .get(id)
+            .cloned()
+            .ok_or_else(|| RejectionReason::ValidationError {
+                message: "Quote not found".to_string(),
+            })?;
+        quote.refresh_status();
+        // Preferred takers fill embargoed quotes before they go active
+        if !quote.is_active() && !quote.is_embargoed() {
+            return Err(if quote.is_expired() {
+                RejectionReason::QuoteExpired {
+                    expired_at: quote.expires_at,
+                    attempted_at: chrono::Utc::now(),
+                }
+            } else {
+                RejectionReason::AlreadyFilled {
+                    filled_at: chrono::Utc::now(),
+                }
+            });
+        }
+        let checked = check(&quote)?;
+
+        let taker_owner_id = fill.taker_owner_id.clone();
+        quote.fill_history.push(fill);
+        if quote.is_exhausted() {
+            quote.status = QuoteStatus::Filled;
+        }
+        self.update_screen(&quote);
+        store.append(DomainEvent::QuoteUpdated {
+            quote: Box::new(quote.clone()),
+        });
+        store.append(DomainEvent::TakerNonceUsed {
+            taker_owner_id,
+            nonce: taker_nonce,
+        });
+        Ok((quote, checked))
+    }
+
+    /// Release a fill reserved by [`Self::reserve_fill`] that failed to
+    /// settle, reopening its quote if the fill had closed it
+    pub async fn release_fill(&self, id: &QuoteId, fill: &FillRecord) -> Option<Quote> {
+        let mut store = self.store.write().await;
+        let mut quote = store.projections().quotes.get(id)?.clone();
+        let pos = quote.fill_history.iter().rposition(|f| {
+            f.taker_owner_id == fill.taker_owner_id && f.fill_size == fill.fill_size
+        })?;
+        quote.fill_history.remove(pos);
+        if quote.status == QuoteStatus::Filled {
+            quote.status = QuoteStatus::Active;
+            quote.refresh_status();
+        }
+        self.update_screen(&quote);
+        store.append(DomainEvent::QuoteUpdated {
+            quote: Box::new(quote.clone()),
+        });
+        Some(quote)
+    }
+
+    /// IDs of scheduled quotes whose activation time has come
+    pub async fn due_quotes(&self) -> Vec<QuoteId> {
+        let store = self.store.read().await;
+        store
+            .projections()
+            .quotes
+            .values()
+            .filter(|q| q.is_due())
+            .map(|q| q.id)
+            .collect()
+    }
+
+    /// Post a due scheduled quote, unless it was cancelled meanwhile
+    pub async fn activate_quote(
+        &self,
+        id: &QuoteId,
+        reference_price: Option<ReferencePrice>,
+    ) -> Option<Quote> {
+        let mut store = self.store.write().await;
+        let mut quote = store.projections().quotes.get(id).filter(|q| q.is_due())?.clone();
+        quote.activate();
+        if reference_price.is_some() {
+            quote.reference_price = reference_price;
+        }
+        self.update_screen(&quote);
+        store.append(DomainEvent::QuoteActivated {
+            quote: Box::new(quote.clone()),
+        });
+        Some(quote)
+    }
+
+    /// Posted, open quotes whose divergence guard has a reference price to watch
+    pub async fn guarded_quotes(&self) -> Vec<Quote> {
+        let store = self.store.read().await;
+        store
+            .projections()
+            .quotes
+            .values()
+            .filter(|q| q.divergence_guard.is_some() && q.reference_price.is_some())
+            .filter(|q| q.is_posted() && q.is_open())
+            .cloned()
+            .collect()
+    }
+
+    /// Apply a quote's divergence guard to the current feed consensus
+    ///
+    /// Cancels or suspends a quote whose market has moved past the guard's
+    /// threshold and resumes a suspended one that is back within it.
+    /// Returns the quote if its status changed.
+    pub async fn guard_divergence(&self, id: &QuoteId, market_price: f64) -> Option<Quote> {
+        let mut store = self.store.write().await;
+        let quotes = &store.projections().quotes;
+        let mut quote = quotes.get(id).filter(|q| q.is_posted() && q.is_open())?.clone();
+        let guard = quote.divergence_guard?;
+        let reference_price = quote.reference_price.as_ref()?.price;
+        let suspended = quote.status == QuoteStatus::Suspended;
+
+        let event = match guard.breach(reference_price, market_price) {
+            Some(move_percent) if !suspended => {
+                let trigger = DivergenceTrigger {
+                    reference_price,
+                    market_price,
+                    move_percent,
+                    max_move_percent: guard.max_move_percent,
+                };
+                match guard.action {
+                    DivergenceAction::Cancel => {
+                        quote.status = QuoteStatus::Cancelled;
+                        DomainEvent::ProtectiveCancel {
+                            quote: Box::new(quote.clone()),
+                            trigger,
+                        }
+                    }
+                    DivergenceAction::Suspend => {
+                        quote.status = QuoteStatus::Suspended;
+                        DomainEvent::ProtectiveSuspend {
+                            quote: Box::new(quote.clone()),
+                            trigger,
+                        }
+                    }
+                }
+            }
+            None if suspended => {
+                // Back to active, or under embargo if it hasn't lapsed
+                quote.activate();
+                DomainEvent::QuoteResumed {
+                    quote: Box::new(quote.clone()),
+                    market_price,
+                }
+            }
+            _ => return None,
+        };
+        self.update_screen(&quote);
+        store.append(event);
+        Some(quote)
+    }
+
+    /// Cancel a quote before it is posted (scheduled or awaiting approval)
+    pub async fn cancel_unposted_quote(
+        &self,
+        id: &QuoteId,
+        maker_owner_id: &str,
+    ) -> Result<Quote, MakerActionError> {
+        let mut store = self.store.write().await;
+        let mut quote = Self::maker_quote(&store, id, maker_owner_id)?;
+        if quote.is_posted() {
+            return Err(MakerActionError::InvalidStatus(quote.status));
+        }
+        quote.status = QuoteStatus::Cancelled;
+        store.append(DomainEvent::QuoteCancelled {
+            quote: Box::new(quote.clone()),
+        });
+        Ok(quote)
+    }
+
+    /// Approve a quote held for approval, posting (or scheduling) it
+    pub async fn approve_quote(
+        &self,
+        id: &QuoteId,
+        maker_owner_id: &str,
+    ) -> Result<Quote, MakerActionError> {
+        let mut store = self.store.write().await;
+        let mut quote = Self::maker_quote(&store, id, maker_owner_id)?;
+        // Quotes that expired while awaiting approval stay expired
+        quote.refresh_status();
+        if quote.status != QuoteStatus::PendingApproval {
+            return Err(MakerActionError::InvalidStatus(quote.status));
+        }
+        quote.approve();
+        self.update_screen(&quote);
+        store.append(DomainEvent::QuoteApproved {
+            quote: Box::new(quote.clone()),
+        });
+        Ok(quote)
+    }
+
+    /// Amend an open quote on behalf of its maker
+    pub async fn amend_quote(
+        &self,
+        id: &QuoteId,
+        maker_owner_id: &str,
+        amend: impl FnOnce(&mut Quote),
+    ) -> Result<Quote, MakerActionError> {
+        let mut store = self.store.write().await;
+        let mut quote = Self::maker_quote(&store, id, maker_owner_id)?;
+        quote.refresh_status();
+        if !quote.is_open() {
+            return Err(MakerActionError::InvalidStatus(quote.status));
+        }
+        amend(&mut quote);
+        self.update_screen(&quote);
+        store.append(DomainEvent::QuoteAmended {
+            quote: Box::new(quote.clone()),
+        });
+        Ok(quote)
+    }
+
+    /// Open an RFQ auction
+    pub async fn open_auction(&self, auction: Auction) {
+        let mut store = self.store.write().await;
+        store.append(DomainEvent::AuctionOpened {
+            auction: Box::new(auction),
+        });
+    }
+
+    /// Get an auction by ID
+    pub async fn get_auction(&self, id: &Uuid) -> Option<Auction> {
+        let store = self.store.read().await;
+        store.projections().auctions.get(id).cloned()
+    }
+
+    /// All auctions, oldest first
+    pub async fn get_all_auctions(&self) -> Vec<Auction> {
+        let store = self.store.read().await;
+        let mut auctions: Vec<Auction> = store.projections().auctions.values().cloned().collect();
+        auctions.sort_by_key(|a| a.opened_at);
+        auctions
+    }
+
+    /// Submit a maker's scheduled quote as a sealed auction response
+    pub async fn respond_to_auction(
+        &self,
+        auction_id: &Uuid,
+        quote_id: &QuoteId,
+        maker_owner_id: &str,
+    ) -> Result<Auction, AuctionError> {
+        let mut store = self.store.write().await;
+        let projections = store.projections();
+        let auction = projections
+            .auctions
+            .get(auction_id)
+            .ok_or(AuctionError::UnknownAuction)?;
+        let quote = projections
+            .quotes
+            .get(quote_id)
+            .ok_or(AuctionError::UnknownQuote)?;
+        let now = chrono::Utc::now();
+        auction.check_response(quote, maker_owner_id, now)?;
+        store.append(DomainEvent::AuctionResponded {
+            auction_id: *auction_id,
+            response: AuctionResponse {
+                quote_id: *quote_id,
+                maker_owner_id: maker_owner_id.to_string(),
+                submitted_at: now,
+            },
+        });
+        Ok(store.projections().auctions[auction_id].clone())
+    }
+
+    /// An auction with its revealed responses ranked best first
+    ///
+    /// Responses stay sealed (an empty ranking) until the window closes.
+    pub async fn ranked_auction(
+        &self,
+        id: &Uuid,
+    ) -> Option<(Auction, Vec<RankedResponse>)> {
+        let store = self.store.read().await;
+        let projections = store.projections();
+        let auction = projections.auctions.get(id)?.clone();
+        let ranked = if chrono::Utc::now() < auction.closes_at {
+            vec![]
+        } else {
+            auction.rank(&projections.quotes, &projections.maker_reputations)
+        };
+        Some((auction, ranked))
+    }
+
+    /// Claim an auction's award before filling its winner
+    ///
+    /// Taken under the store lock so concurrent accepts can't both fill a
+    /// winner; the claim is held until [`Self::award_auction`] or
+    /// [`Self::release_auction`].
+    pub async fn claim_auction(&self, id: &Uuid) -> Result<(), AuctionError> {
+        let store = self.store.write().await;
+        let auction = store
+            .projections()
+            .auctions
+            .get(id)
+            .ok_or(AuctionError::UnknownAuction)?;
+        let mut awarding = self.awarding.lock().unwrap();
+        if auction.award.is_some() || !awarding.insert(*id) {
+            return Err(AuctionError::AlreadyAwarded);
+        }
+        Ok(())
+    }
+
+    /// Drop the claim on an auction whose winning fill was rejected
+    pub fn release_auction(&self, id: &Uuid) {
+        self.awarding.lock().unwrap().remove(id);
+    }
+
+    /// Record an auction's award and withdraw the losing quotes
+    pub async fn award_auction(
+        &self,
+        id: &Uuid,
+        award: AuctionAward,
+    ) -> Result<Auction, AuctionError> {
+        let mut store = self.store.write().await;
+        let auction = store
+            .projections()
+            .auctions
+            .get(id)
+            .ok_or(AuctionError::UnknownAuction)?;
+        if auction.award.is_some() {
+            return Err(AuctionError::AlreadyAwarded);
+        }
+        for regret in &award.regrets {
+            let quotes = &store.projections().quotes;
+            let Some(quote) = quotes.get(&regret.quote_id).filter(|q| q.is_open()) else {
+                continue;
+            };
+            let mut quote = quote.clone();
+            quote.status = QuoteStatus::Cancelled;
+            self.update_screen(&quote);
+            store.append(DomainEvent::QuoteCancelled {
+                quote: Box::new(quote),
+            });
+        }
+        store.append(DomainEvent::AuctionAwarded {
+            auction_id: *id,
+            award: Box::new(award),
+        });
+        self.release_auction(id);
+        Ok(store.projections().auctions[id].clone())
+    }
+
+    /// Look up a quote on behalf of its maker
+    fn maker_quote(
+        store: &EventStore,
+        id: &QuoteId,
+        maker_owner_id: &str,
+    ) -> Result<Quote, MakerActionError> {
+        let quote = store
+            .projections()
+            .quotes
+            .get(id)
+            .ok_or(MakerActionError::UnknownQuote)?;
+        if quote.maker_owner_id != maker_owner_id {
+            return Err(MakerActionError::WrongMaker);
+        }
+        Ok(quote.clone())
+    }
+
+    /// Screen a fill against its quote without taking the store lock
+    ///
+    /// Returns None for quotes that aren't open; the full fill path
+    /// answers those.
+    pub fn screen_fill(
+        &self,
+        quote_id: &QuoteId,
+        request: &FillRequest,
+    ) -> Option<RejectionReason> {
+        let screens = self.screens.read().expect("screen lock poisoned");
+        screens.get(quote_id)?.check(request, chrono::Utc::now())
+    }
+
+    fn update_screen(&self, quote: &Quote) {
+        let mut screens = self.screens.write().expect("screen lock poisoned");
+        if quote.is_active() {
+            let scale = self.assets.scale(&quote.spec.asset);
+            screens.insert(quote.id, FillScreen::new(quote, scale));
+        } else {
+            screens.remove(&quote.id);
+        }
     }
 
-    /// Add a receipt
+    /// Add a receipt (and append it to the receipt log)
     pub async fn add_receipt(&self, quote_id: QuoteId, receipt: FillReceipt) {
-        let mut receipts = self.receipts.write().await;
-        receipts.entry(quote_id).or_default().push(receipt);
+        let mut store = self.store.write().await;
+        store.append(DomainEvent::FillRecorded {
+            quote_id,
+            receipt: Box::new(receipt),
+        });
     }
 
     /// Get receipts for a quote
     pub async fn get_receipts(&self, quote_id: &QuoteId) -> Vec<FillReceipt> {
-        let receipts = self.receipts.read().await;
-        receipts.get(quote_id).cloned().unwrap_or_default()
+        let store = self.store.read().await;
+        store.projections().receipts.get(quote_id).cloned().unwrap_or_default()
+    }
+
+    /// All receipts across all quotes
+    pub async fn all_receipts(&self) -> Vec<FillReceipt> {
+        let store = self.store.read().await;
+        store.projections().receipts.values().flatten().cloned().collect()
+    }
+
+    /// A receipt by ID
+    pub async fn get_receipt(&self, receipt_id: Uuid) -> Option<FillReceipt> {
+        let store = self.store.read().await;
+        store
+            .projections()
+            .receipts
+            .values()
+            .flatten()
+            .find(|r| r.receipt_id == receipt_id)
+            .cloned()
+    }
+
+    /// A taker's share of accepted fills across all quotes
+    ///
+    /// Pending benchmark fills count as neither. Returns None if the taker
+    /// has no decided fills.
+    pub async fn taker_reputation(&self, taker_owner_id: &str) -> Option<f64> {
+        let store = self.store.read().await;
+        store
+            .projections()
+            .reputations
+            .get(taker_owner_id)
+            .filter(|stats| stats.decided > 0)
+            .map(|stats| stats.accepted as f64 / stats.decided as f64)
+    }
+
+    /// A maker's reliability record, None if it never posted a quote
+    pub async fn maker_reputation(&self, maker_owner_id: &str) -> Option<MakerStats> {
+        let store = self.store.read().await;
+        store.projections().maker_reputations.get(maker_owner_id).copied()
+    }
+
+    /// Every maker's reliability record, by owner ID
+    pub async fn maker_reputations(&self) -> HashMap<String, MakerStats> {
+        let store = self.store.read().await;
+        store.projections().maker_reputations.clone()
+    }
+
+    /// Get a page of matching receipts for a quote
+    ///
+    /// Receipts are returned in insertion order starting after the receipt
+    /// with ID `after`. Returns the page plus the cursor for the next page
+    /// (None when exhausted), or None if the cursor is unknown.
+    pub async fn get_receipts_page(
+        &self,
+        quote_id: &QuoteId,
+        filter: &ReceiptFilter,
+        after: Option<Uuid>,
+        limit: Option<usize>,
+    ) -> Option<(Vec<FillReceipt>, Option<Uuid>)> {
+        let store = self.store.read().await;
+        let all = store
+            .projections()
+            .receipts
+            .get(quote_id)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+
+        let start = match after {
+            Some(cursor) => all.iter().position(|r| r.receipt_id == cursor)? + 1,
+            None => 0,
+        };
+        let limit = limit.unwrap_or(usize::MAX);
+
+        let mut matching = all[start..].iter().filter(|r| filter.matches(r));
+        let page: Vec<FillReceipt> = matching.by_ref().take(limit).cloned().collect();
+        let next_cursor = match (page.last(), matching.next()) {
+            (Some(last), Some(_)) => Some(last.receipt_id),
+            _ => None,
+        };
+
+        Some((page, next_cursor))
+    }
+
+    /// Record the proof status of an accepted fill's SDL
+    ///
+    /// Returns false, recording nothing, if the quote isn't known: a fill
+    /// resumed after a restart outlives the in-memory quotes.
+    pub async fn set_proof_status(
+        &self,
+        quote_id: QuoteId,
+        sdl_hash: String,
+        status: ProofStatus,
+    ) -> bool {
+        let mut store = self.store.write().await;
+        if !store.projections().quotes.contains_key(&quote_id) {
+            return false;
+        }
+        store.append(DomainEvent::ProofStatusChanged {
+            quote_id,
+            sdl_hash,
+            status,
+        });
+        true
+    }
+
+    /// Proof status of an SDL, if it reached proving
+    pub async fn proof_status(&self, sdl_hash: &str) -> Option<ProofStatus> {
+        let store = self.store.read().await;
+        store.projections().proofs.get(sdl_hash).copied()
+    }
+
+    /// Last accepted fill request nonce for a taker
+    pub async fn taker_nonce(&self, taker_owner_id: &str) -> Option<u64> {
+        let store = self.store.read().await;
+        store.projections().taker_nonces.get(taker_owner_id).copied()
+    }
+
+    /// Check a taker's fill request nonce without consuming it
+    ///
+    /// The nonce is consumed by [`Self::reserve_fill`] once the fill is
+    /// accepted, so a rejected or failed request leaves it unused.
+    pub async fn check_taker_nonce(
+        &self,
+        taker_owner_id: &str,
+        nonce: u64,
+    ) -> Result<(), TakerNonceError> {
+        let store = self.store.read().await;
+        TakerNonceError::check(store.projections().taker_nonces.get(taker_owner_id), nonce)
+    }
+
+    /// Day roots with receipts not yet anchored
+    pub async fn pending_anchors(&self) -> Vec<PendingAnchor> {
+        self.store.read().await.projections().receipt_log.pending_anchors()
+    }
+
+    /// Record a submitted anchor
+    pub async fn record_anchor(&self, anchor: Anchor) {
+        let mut store = self.store.write().await;
+        store.append(DomainEvent::AnchorRecorded { anchor });
+    }
+
+    /// All anchors, oldest day first
+    pub async fn anchors(&self) -> Vec<Anchor> {
+        self.store.read().await.projections().receipt_log.anchors()
+    }
+
+    /// Prove a receipt's inclusion in its day's latest anchor
+    pub async fn inclusion_proof(&self, receipt_id: Uuid) -> Result<InclusionProof, InclusionError> {
+        self.store
+            .read()
+            .await
+            .projections()
+            .receipt_log
+            .inclusion_proof(receipt_id)
+    }
+
+    /// A page of the event log after sequence number `after`
+    pub async fn events(&self, after: u64, limit: usize) -> Vec<EventEnvelope> {
+        let store = self.store.read().await;
+        store.events_after(after).iter().take(limit).cloned().collect()
+    }
+
+    /// Every event concerning a quote, oldest first
+    pub async fn quote_timeline(&self, quote_id: &QuoteId) -> Vec<EventEnvelope> {
+        self.store.read().await.quote_events(quote_id)
     }
 }
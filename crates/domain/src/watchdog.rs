@@ -0,0 +1,224 @@
+//! Runtime health watchdog
+//!
+//! Tracks the outcome of recent SDL submissions and proofs. When the
+//! submission error rate or the number of proving failures in the window
+//! crosses its threshold, new fills are suspended (answered with a typed
+//! 503) and an operator alert is raised. A periodic probe resumes fills
+//! once the runtime answers again.
+
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Watchdog thresholds
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatchdogConfig {
+    /// How far back outcomes are considered, in seconds
+    #[serde(default = "default_window_secs")]
+    pub window_secs: u64,
+    /// Outcomes needed in the window before the error rate is judged
+    #[serde(default = "default_min_samples")]
+    pub min_samples: usize,
+    /// Submission error rate (0.0-1.0) that suspends fills
+    #[serde(default = "default_max_error_rate")]
+    pub max_error_rate: f64,
+    /// Proving failures in the window that suspend fills
+    #[serde(default = "default_max_proving_failures")]
+    pub max_proving_failures: usize,
+    /// How often a suspended runtime is probed for recovery, in seconds
+    #[serde(default = "default_probe_interval_secs")]
+    pub probe_interval_secs: u64,
+    /// URL that receives operator alerts as JSON (optional)
+    #[serde(default)]
+    pub alert_webhook_url: Option<String>,
+}
+
+fn default_window_secs() -> u64 {
+    300
+}
+
+fn default_min_samples() -> usize {
+    5
+}
+
+fn default_max_error_rate() -> f64 {
+    0.5
+}
+
+fn default_max_proving_failures() -> usize {
+    3
+}
+
+fn default_probe_interval_secs() -> u64 {
+    30
+}
+
+impl Default for WatchdogConfig {
+    fn default() -> Self {
+        Self {
+            window_secs: default_window_secs(),
+            min_samples: default_min_samples(),
+            max_error_rate: default_max_error_rate(),
+            max_proving_failures: default_max_proving_failures(),
+            probe_interval_secs: default_probe_interval_secs(),
+            alert_webhook_url: None,
+        }
+    }
+}
+
+/// Outcome of submitting a fill to the runtime
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RuntimeOutcome {
+    /// SDL submitted and proven
+    Success,
+    /// Building, applying or submitting the SDL failed
+    SubmitError,
+    /// Proof generation failed or timed out
+    ProvingFailure,
+}
+
+/// Why fills are suspended
+#[derive(Debug, Clone, Serialize)]
+pub struct Suspension {
+    /// Which threshold was breached
+    pub reason: String,
+    /// When fills were suspended
+    pub since: DateTime<Utc>,
+    /// Seconds until the next recovery probe
+    pub retry_after_secs: u64,
+}
+
+impl IntoResponse for Suspension {
+    fn into_response(self) -> Response {
+        let body = serde_json::json!({
+            "success": false,
+            "error": {
+                "code": "RUNTIME_SUSPENDED",
+                "message": format!("Fills are suspended: {}", self.reason),
+                "details": self,
+            },
+        });
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            [(header::RETRY_AFTER, self.retry_after_secs.to_string())],
+            Json(body),
+        )
+            .into_response()
+    }
+}
+
+/// Runtime health watchdog
+pub struct Watchdog {
+    config: WatchdogConfig,
+    outcomes: Mutex<VecDeque<(Instant, RuntimeOutcome)>>,
+    suspension: Mutex<Option<Suspension>>,
+    client: reqwest::Client,
+}
+
+impl Watchdog {
+    pub fn new(config: WatchdogConfig) -> Self {
+        Self {
+            config,
+            outcomes: Mutex::new(VecDeque::new()),
+            suspension: Mutex::new(None),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// The active suspension, if fills are suspended
+    pub fn suspension(&self) -> Option<Suspension> {
+        self.suspension.lock().unwrap().clone()
+    }
+
+    /// How often to probe a suspended runtime
+    pub fn probe_interval(&self) -> Duration {
+        Duration::from_secs(self.config.probe_interval_secs.max(1))
+    }
+
+    /// Record a runtime outcome, suspending fills if a threshold is breached
+    pub fn record(&self, outcome: RuntimeOutcome) {
+        let breach = {
+            let mut outcomes = self.outcomes.lock().unwrap();
+            let now = Instant::now();
+            outcomes.push_back((now, outcome));
+            let window = Duration::from_secs(self.config.window_secs);
+            while outcomes
+                .front()
+                .is_some_and(|(at, _)| now.duration_since(*at) > window)
+            {
+                outcomes.pop_front();
+            }
+            self.breach(&outcomes)
+        };
+
+        if let Some(reason) = breach {
+            let mut suspension = self.suspension.lock().unwrap();
+            if suspension.is_none() {
+                *suspension = Some(Suspension {
+                    reason: reason.clone(),
+                    since: Utc::now(),
+                    retry_after_secs: self.config.probe_interval_secs,
+                });
+                drop(suspension);
+                self.alert("suspended", &reason);
+            }
+        }
+    }
+
+    /// Resume fills after a successful recovery probe
+    pub fn resume(&self) {
+        let previous = self.suspension.lock().unwrap().take();
+        if let Some(previous) = previous {
+            self.outcomes.lock().unwrap().clear();
+            self.alert("resumed", &format!("Runtime recovered after: {}", previous.reason));
+        }
+    }
+
+    /// Check the thresholds against the outcomes in the window
+    fn breach(&self, outcomes: &VecDeque<(Instant, RuntimeOutcome)>) -> Option<String> {
+        let count = |kind| outcomes.iter().filter(|(_, o)| *o == kind).count();
+
+        let proving_failures = count(RuntimeOutcome::ProvingFailure);
+        if proving_failures >= self.config.max_proving_failures {
+            return Some(format!(
+                "{} proving failures in the last {}s",
+                proving_failures, self.config.window_secs
+            ));
+        }
+
+        let submit_errors = count(RuntimeOutcome::SubmitError);
+        let error_rate = submit_errors as f64 / outcomes.len() as f64;
+        if outcomes.len() >= self.config.min_samples && error_rate >= self.config.max_error_rate {
+            return Some(format!(
+                "SDL submission error rate {:.0}% over the last {}s",
+                error_rate * 100.0,
+                self.config.window_secs
+            ));
+        }
+
+        None
+    }
+
+    /// Log an operator alert and forward it to the alert webhook
+    fn alert(&self, event: &str, message: &str) {
+        tracing::error!(target: "operator_alert", "Fills {}: {}", event, message);
+
+        if let Some(url) = self.config.alert_webhook_url.clone() {
+            let request = self.client.post(url).json(&serde_json::json!({
+                "event": event,
+                "message": message,
+                "at": Utc::now(),
+            }));
+            tokio::spawn(async move {
+                if let Err(e) = request.send().await {
+                    tracing::warn!("Failed to deliver operator alert: {}", e);
+                }
+            });
+        }
+    }
+}
@@ -2,72 +2,236 @@
 //!
 //! Provides controllable HTTP endpoints that simulate price feeds
 //! with configurable behavior (good, stale, malicious).
+//!
+//! Assets can have their own price process in their own currency, and each
+//! feed serves FX rates (`GET /fx`, `GET /fx/:base/:quote`), which also
+//! price any asset in another currency (`/price?asset=dETH&currency=EUR`).
+//!
+//! A feed configured with a signing key signs each update with it (ed25519
+//! or secp256k1) and publishes the public key at `GET /key`; other feeds
+//! sign with the mock `sig_<name>_<timestamp>`.
+//!
+//! Each feed also exposes `POST /admin/pin` to queue exact responses
+//! (and `DELETE /admin/pin` to clear them) for deterministic tests.
 
 use axum::{
-    extract::{Query, State},
-    routing::get,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    routing::{get, post},
     Json, Router,
 };
 use chrono::Utc;
-use rfq_models::{FeedConfig, PriceUpdate};
-use serde::Deserialize;
+use rfq_models::signing::{self, PublicKey};
+use rfq_models::{FeedConfig, PriceProcess, PriceUpdate};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use tokio::sync::{Mutex, RwLock};
 
 /// State for a mock feed server
 #[derive(Debug)]
 pub struct FeedState {
     pub config: RwLock<FeedConfig>,
+    /// Exact responses to serve before falling back to the configured behavior
+    pub pinned: Mutex<VecDeque<PinnedPrice>>,
 }
 
 impl FeedState {
     pub fn new(config: FeedConfig) -> Self {
         Self {
             config: RwLock::new(config),
+            pinned: Mutex::new(VecDeque::new()),
         }
     }
 }
 
+/// A pinned (price, timestamp) response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PinnedPrice {
+    /// The exact price to report
+    pub price: f64,
+    /// The exact timestamp to report (defaults to the time of the request)
+    pub timestamp: Option<u64>,
+}
+
+/// Request to pin the next responses of a feed
+#[derive(Debug, Deserialize)]
+pub struct PinRequest {
+    /// Responses served in order, one per price request
+    pub responses: Vec<PinnedPrice>,
+}
+
+/// Number of pinned responses still queued
+#[derive(Debug, Serialize)]
+pub struct PinStatus {
+    pub pinned: usize,
+}
+
+/// Currency assets without their own price process are priced in
+const DEFAULT_CURRENCY: &str = "USDD";
+
 /// Query parameters for price requests
 #[derive(Debug, Deserialize)]
 pub struct PriceQuery {
     pub asset: Option<String>,
+    /// Currency to price the asset in (its process's currency if unset)
+    pub currency: Option<String>,
 }
 
-/// Get the current price from a feed
-pub async fn get_price(
-    State(state): State<Arc<FeedState>>,
-    Query(query): Query<PriceQuery>,
-) -> Json<PriceUpdate> {
-    let config = state.config.read().await;
-    let asset = query.asset.unwrap_or_else(|| "dETH".to_string());
+/// A fair price with small random variance
+fn sample(base_price: f64, variance_percent: f64) -> f64 {
+    base_price + rand_variance() * base_price * (variance_percent / 100.0)
+}
 
+/// The current FX rate of `base` in `quote`, from a configured pair or its inverse
+fn fx_rate(config: &FeedConfig, base: &str, quote: &str) -> Option<f64> {
+    if base.eq_ignore_ascii_case(quote) {
+        return Some(1.0);
+    }
+    let rate = |pair: &PriceProcess| sample(pair.base_price, pair.variance_percent);
+    if let Some(pair) = config.fx.iter().find(|pair| pair.prices(base, quote)) {
+        return Some(rate(pair));
+    }
+    let inverse = config.fx.iter().find(|pair| pair.prices(quote, base))?;
+    Some(1.0 / rate(inverse))
+}
+
+/// Report a fair price as this feed would: pinned, stale or manipulated
+/// according to its configuration
+fn report(
+    config: &FeedConfig,
+    pinned: Option<PinnedPrice>,
+    asset: String,
+    currency: String,
+    fair_price: f64,
+) -> PriceUpdate {
     let now = Utc::now();
-    let timestamp = if config.force_stale {
+    let timestamp = if let Some(ts) = pinned.as_ref().and_then(|p| p.timestamp) {
+        ts
+    } else if config.force_stale {
         (now.timestamp() as u64).saturating_sub(config.stale_seconds)
     } else {
         now.timestamp() as u64
     };
 
-    let price = if config.is_malicious {
-        config.base_price * config.manipulation_factor
+    let price = if let Some(pinned) = &pinned {
+        pinned.price
+    } else if config.is_malicious {
+        fair_price * config.manipulation_factor
     } else {
-        // Add small random variance
-        let variance = config.base_price * (config.variance_percent / 100.0);
-        config.base_price + (rand_variance() * variance)
+        fair_price
     };
 
-    Json(PriceUpdate {
+    let mut update = PriceUpdate {
         source: config.name.clone(),
         asset,
         price,
-        currency: "USDD".to_string(),
+        currency,
         timestamp,
         datetime: chrono::DateTime::from_timestamp(timestamp as i64, 0).unwrap_or(now),
-        signature: format!("sig_{}_{}", config.name, timestamp),
+        signature: String::new(),
+    };
+    update.signature = match config.signing_key.as_ref().and_then(|key| key.signer()) {
+        Some(signer) => signing::sign_hex(signer.as_ref(), update.signed_message().as_bytes()),
+        None => format!("sig_{}_{}", config.name, timestamp),
+    };
+    update
+}
+
+/// Get the current price from a feed
+///
+/// An asset in a currency other than its process's is converted at the
+/// feed's FX rate; 404 if there is none.
+pub async fn get_price(
+    State(state): State<Arc<FeedState>>,
+    Query(query): Query<PriceQuery>,
+) -> Result<Json<PriceUpdate>, StatusCode> {
+    let config = state.config.read().await;
+    let asset = query.asset.unwrap_or_else(|| "dETH".to_string());
+
+    // Prefer a process in the requested currency over converting another
+    let process = config
+        .prices
+        .iter()
+        .find(|p| query.currency.as_deref().is_some_and(|c| p.prices(&asset, c)))
+        .or_else(|| {
+            config
+                .prices
+                .iter()
+                .find(|p| p.asset.eq_ignore_ascii_case(&asset))
+        });
+    let (base_price, variance_percent, native_currency) = match process {
+        Some(p) => (p.base_price, p.variance_percent, p.currency.as_str()),
+        None => (config.base_price, config.variance_percent, DEFAULT_CURRENCY),
+    };
+    let currency = query.currency.unwrap_or_else(|| native_currency.to_string());
+    let rate = fx_rate(&config, native_currency, &currency).ok_or(StatusCode::NOT_FOUND)?;
+    let fair_price = sample(base_price, variance_percent) * rate;
+
+    let pinned = state.pinned.lock().await.pop_front();
+    Ok(Json(report(&config, pinned, asset, currency, fair_price)))
+}
+
+/// Get the current rate of every FX pair the feed serves
+pub async fn list_fx(State(state): State<Arc<FeedState>>) -> Json<Vec<PriceUpdate>> {
+    let config = state.config.read().await;
+    let rates = config
+        .fx
+        .iter()
+        .map(|pair| {
+            let fair_rate = sample(pair.base_price, pair.variance_percent);
+            report(&config, None, pair.asset.clone(), pair.currency.clone(), fair_rate)
+        })
+        .collect();
+    Json(rates)
+}
+
+/// Get the current FX rate of `base` in `quote` (e.g. `/fx/USDD/EUR`)
+///
+/// Inverse pairs are served too; 404 for pairs the feed doesn't know.
+pub async fn get_fx(
+    State(state): State<Arc<FeedState>>,
+    Path((base, quote)): Path<(String, String)>,
+) -> Result<Json<PriceUpdate>, StatusCode> {
+    let config = state.config.read().await;
+    let fair_rate = fx_rate(&config, &base, &quote).ok_or(StatusCode::NOT_FOUND)?;
+    let pinned = state.pinned.lock().await.pop_front();
+    Ok(Json(report(&config, pinned, base, quote, fair_rate)))
+}
+
+/// Get the public key the feed signs with (404 for mock signatures)
+pub async fn get_key(State(state): State<Arc<FeedState>>) -> Result<Json<PublicKey>, StatusCode> {
+    let config = state.config.read().await;
+    config
+        .signing_key
+        .as_ref()
+        .and_then(|key| key.public_key())
+        .map(Json)
+        .ok_or(StatusCode::NOT_FOUND)
+}
+
+/// Pin the next K responses to exact (price, timestamp) tuples
+///
+/// Lets integration tests build precise quorum-tolerance and staleness
+/// edge cases without fighting the variance jitter. Pins are appended
+/// to any responses still queued.
+pub async fn pin_prices(
+    State(state): State<Arc<FeedState>>,
+    Json(request): Json<PinRequest>,
+) -> Json<PinStatus> {
+    let mut pinned = state.pinned.lock().await;
+    pinned.extend(request.responses);
+    Json(PinStatus {
+        pinned: pinned.len(),
     })
 }
 
+/// Drop all queued pinned responses
+pub async fn clear_pins(State(state): State<Arc<FeedState>>) -> Json<PinStatus> {
+    state.pinned.lock().await.clear();
+    Json(PinStatus { pinned: 0 })
+}
+
 /// Simple deterministic "random" for demo purposes
 fn rand_variance() -> f64 {
     let t = std::time::SystemTime::now()
@@ -81,6 +245,10 @@ fn rand_variance() -> f64 {
 pub fn feed_router(state: Arc<FeedState>) -> Router {
     Router::new()
         .route("/price", get(get_price))
+        .route("/fx", get(list_fx))
+        .route("/fx/:base/:quote", get(get_fx))
+        .route("/key", get(get_key))
+        .route("/admin/pin", post(pin_prices).delete(clear_pins))
         .with_state(state)
 }
 
@@ -0,0 +1,4 @@
+fn main() {
+    // Build the local laws program so `include_elf!` picks up the current code
+    sp1_build::build_program("..");
+}
@@ -0,0 +1,212 @@
+//! Cycle budget check for the local laws ELF
+//!
+//! Executes the ELF in the SP1 executor (no proving) for a set of
+//! representative fill inputs, and the batch ELF for a batch of fills, and
+//! compares each cycle count against the
+//! budget in `cycle-budget.toml`. Exits non-zero if any case is over budget,
+//! has no budget, or fails validation, so CI catches constraints that make
+//! proofs disproportionately slower.
+//!
+//! ```bash
+//! cd crates/local-laws-elf/script && cargo run --release
+//! # or with a different budget file
+//! cargo run --release -- path/to/budget.toml
+//! ```
+
+use anyhow::{bail, Context, Result};
+use rfq_local_laws::{
+    benchmark_amount, twap, BatchOutputs, BenchmarkSettlement, RfqLocalLawsInput,
+};
+use rfq_models::{FeedEvidence, FillRecord, QuoteConstraints, TwapBenchmark};
+use serde::Deserialize;
+use sp1_sdk::{include_elf, ProverClient, SP1Stdin};
+use std::collections::BTreeMap;
+
+/// The local laws program, built by `build.rs`
+const LOCAL_LAWS_ELF: &[u8] = include_elf!("rfq-local-laws-elf");
+
+/// The batched local laws program, built by `build.rs`
+const LOCAL_LAWS_BATCH_ELF: &[u8] = include_elf!("rfq-local-laws-batch-elf");
+
+/// Budget file used when no path is given
+const DEFAULT_BUDGET_PATH: &str = "cycle-budget.toml";
+
+/// Timestamp all cases are validated at
+const NOW: u64 = 1_737_500_000;
+
+#[derive(Debug, Deserialize)]
+struct BudgetFile {
+    /// Case name -> maximum cycles
+    budgets: BTreeMap<String, u64>,
+}
+
+fn main() -> Result<()> {
+    sp1_sdk::utils::setup_logger();
+
+    let budget_path = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| DEFAULT_BUDGET_PATH.to_string());
+    let contents = std::fs::read_to_string(&budget_path)
+        .with_context(|| format!("Failed to read budget file {}", budget_path))?;
+    let budgets: BudgetFile =
+        toml::from_str(&contents).with_context(|| format!("Failed to parse {}", budget_path))?;
+
+    let client = ProverClient::from_env();
+    let mut failures = Vec::new();
+
+    println!("{:<20} {:>12} {:>12} {:>7}", "case", "cycles", "budget", "used");
+    for (name, elf, stdin) in cases() {
+        let cycles = match client.execute(elf, &stdin).run() {
+            Ok((public_values, report)) => {
+                // The batch program commits rejections instead of failing
+                let batch = BatchOutputs::from_bytes(public_values.as_slice());
+                if batch.is_some_and(|batch| batch.fills.iter().any(|fill| !fill.accepted)) {
+                    println!("{:<20} {:>12}", name, "REJECTED");
+                    failures.push(format!("{}: a fill in the batch was rejected", name));
+                    continue;
+                }
+                report.total_instruction_count()
+            }
+            Err(e) => {
+                println!("{:<20} {:>12}", name, "FAILED");
+                failures.push(format!("{}: execution failed: {}", name, e));
+                continue;
+            }
+        };
+
+        let Some(&budget) = budgets.budgets.get(name) else {
+            println!("{:<20} {:>12} {:>12}", name, cycles, "-");
+            failures.push(format!("{}: no budget in {}", name, budget_path));
+            continue;
+        };
+
+        let used = cycles as f64 / budget as f64 * 100.0;
+        println!("{:<20} {:>12} {:>12} {:>6.1}%", name, cycles, budget, used);
+        if cycles > budget {
+            failures.push(format!("{}: {} cycles exceeds budget of {}", name, cycles, budget));
+        }
+    }
+
+    if !failures.is_empty() {
+        for failure in &failures {
+            eprintln!("error: {}", failure);
+        }
+        bail!("{} cycle budget check(s) failed", failures.len());
+    }
+    Ok(())
+}
+
+/// Representative inputs with the ELF that runs them, all of which must
+/// pass validation
+fn cases() -> Vec<(&'static str, &'static [u8], SP1Stdin)> {
+    let mut fill_history = fill_input(2);
+    fill_history.constraints.max_fills = 101;
+    fill_history.constraints.max_fills_per_taker = Some(10);
+    fill_history.fill_history = (0..100)
+        .map(|i| FillRecord {
+            taker_owner_id: format!("taker_{}", i % 20),
+            fill_size: 10_000_000,
+            fill_price: 19_500_000,
+        })
+        .collect();
+
+    let batch: Vec<RfqLocalLawsInput> = (0..8).map(|_| fill_input(2)).collect();
+
+    vec![
+        ("minimal", LOCAL_LAWS_ELF, stdin(fill_input(1).to_json())),
+        ("quorum_2", LOCAL_LAWS_ELF, stdin(fill_input(2).to_json())),
+        ("quorum_8", LOCAL_LAWS_ELF, stdin(fill_input(8).to_json())),
+        ("quorum_32", LOCAL_LAWS_ELF, stdin(fill_input(32).to_json())),
+        ("fill_history_100", LOCAL_LAWS_ELF, stdin(fill_history.to_json())),
+        ("benchmark_60", LOCAL_LAWS_ELF, stdin(benchmark_input(60).to_json())),
+        ("batch_8", LOCAL_LAWS_BATCH_ELF, stdin(RfqLocalLawsInput::batch_to_json(&batch))),
+    ]
+}
+
+/// Stdin carrying an encoded input, the way the programs read it
+fn stdin(input: Vec<u8>) -> SP1Stdin {
+    let mut stdin = SP1Stdin::new();
+    stdin.write_vec(input);
+    stdin
+}
+
+/// A valid spot fill backed by `sources` agreeing feeds
+fn fill_input(sources: usize) -> RfqLocalLawsInput {
+    let names: Vec<String> = (0..sources).map(|i| format!("Feed{}", i)).collect();
+
+    let mut constraints = QuoteConstraints::new([7u8; 32]);
+    constraints.max_debit = 2_000_000_000;
+    constraints.expiry_timestamp = NOW + 300;
+    constraints.allowed_sources = names.clone();
+    constraints.max_staleness_secs = 5;
+    constraints.quorum_count = sources as u32;
+    constraints.quorum_tolerance_bps = 50;
+    constraints.allowed_assets = vec!["dETH".into()];
+    constraints.max_fill_size = 1_000_000_000;
+
+    RfqLocalLawsInput {
+        constraints,
+        taker_owner_id: "taker_cycles".into(),
+        taker_shard: 1,
+        fill_size: 1_000_000_000,
+        fill_price: 1_950_000_000,
+        feed_evidence: names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| {
+                let price = 1_950_000_000_000 + (i % 5) as u64 * 500_000_000;
+                evidence(name, price, NOW - 1)
+            })
+            .collect(),
+        current_timestamp: NOW,
+        clock_skew_secs: 0,
+        transfer_leg_count: 2,
+        has_extra_transfers: false,
+        fill_history: vec![],
+        benchmark: None,
+        leg_fill_sizes: Default::default(),
+        filled_so_far: 0,
+        taker_filled_notional: 0,
+        counterparties: None,
+        extensions: Default::default(),
+    }
+}
+
+/// A benchmark fill settled from `samples` feed samples
+fn benchmark_input(samples: u32) -> RfqLocalLawsInput {
+    let window_secs = 15 * samples as u64;
+    let mut input = fill_input(2);
+    input.constraints.benchmark = Some(TwapBenchmark {
+        window_secs,
+        min_samples: samples,
+    });
+
+    let accepted_at = NOW;
+    let samples: Vec<FeedEvidence> = (0..samples as u64)
+        .map(|i| {
+            let source = &input.constraints.allowed_sources[(i % 2) as usize];
+            evidence(source, 1_940_000_000_000 + (i % 7) * 1_000_000_000, accepted_at + i * 15)
+        })
+        .collect();
+    let price = twap(&samples, accepted_at, accepted_at + window_secs).expect("samples");
+
+    input.fill_size = 1_000_000;
+    input.fill_price = benchmark_amount(&input.constraints, price, input.fill_size);
+    input.current_timestamp = accepted_at + window_secs;
+    input.benchmark = Some(BenchmarkSettlement {
+        accepted_at,
+        samples,
+    });
+    input
+}
+
+fn evidence(source: &str, price: u64, timestamp: u64) -> FeedEvidence {
+    FeedEvidence {
+        source: source.to_string(),
+        asset: "dETH".into(),
+        price,
+        timestamp,
+        signature: "sig".into(),
+        provenance: None,
+    }
+}
@@ -0,0 +1,33 @@
+//! RFQ Local Laws batch ELF Program
+//!
+//! Validates many fill attempts against their quote constraints in one
+//! SP1 zkVM execution, so a single proof settles the whole batch instead
+//! of one proof per fill.
+//!
+//! Unlike the single-fill program, a failing fill doesn't fail the proof:
+//! each fill's outcome is committed, and only accepted fills settle.
+
+#![no_main]
+sp1_zkvm::entrypoint!(main);
+
+use rfq_local_laws::{BatchOutputs, RfqLocalLawsInput};
+
+/// Main entry point for the ZK prover
+///
+/// This function:
+/// 1. Reads the batch of RfqLocalLawsInputs from the prover, as a JSON
+///    array
+/// 2. Validates each fill against its constraints
+/// 3. Commits the [`BatchOutputs`]: every fill's public outputs and
+///    whether it passed
+pub fn main() {
+    // Read the local laws inputs
+    let inputs = RfqLocalLawsInput::batch_from_json(&sp1_zkvm::io::read_vec())
+        .expect("Invalid local laws inputs");
+
+    // Validate each fill on its own, keeping every result
+    let results = rfq_local_laws::validate_fills(&inputs);
+
+    // Commit the outcome of each fill, in order
+    sp1_zkvm::io::commit_slice(&BatchOutputs::new(&inputs, &results).to_bytes());
+}
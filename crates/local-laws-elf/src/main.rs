@@ -10,27 +10,27 @@
 #![no_main]
 sp1_zkvm::entrypoint!(main);
 
-use rfq_local_laws::RfqLocalLawsInput;
+use rfq_local_laws::{PublicOutputs, RfqLocalLawsInput};
 
 /// Main entry point for the ZK prover
 ///
 /// This function:
-/// 1. Reads the RfqLocalLawsInput from the prover
+/// 1. Reads the RfqLocalLawsInput from the prover, as JSON so inputs
+///    written before a constraint field was added still decode
 /// 2. Validates the fill against constraints
-/// 3. Commits the quote_id as public output
+/// 3. Commits the [`PublicOutputs`] of the validated fill
 pub fn main() {
     // Read the local laws input
-    let input: RfqLocalLawsInput = sp1_zkvm::io::read();
+    let input = RfqLocalLawsInput::from_json(&sp1_zkvm::io::read_vec())
+        .expect("Invalid local laws input");
 
     // Validate using our standalone validation function
     // This will panic if validation fails, causing the proof to fail
     rfq_local_laws::validate_fill(&input)
         .expect("Local laws validation failed");
 
-    // Commit the quote_id as public output
-    // This allows verifiers to know which quote was validated
-    sp1_zkvm::io::commit_slice(&input.constraints.quote_id);
-    
-    // Commit success
-    sp1_zkvm::io::commit(&1u8);
+    // Commit what was validated: the quote, a hash of its constraints, the
+    // fill, the taker and the number of feed sources
+    // This allows verifiers to check what was proven against a receipt
+    sp1_zkvm::io::commit_slice(&PublicOutputs::of(&input).to_bytes());
 }
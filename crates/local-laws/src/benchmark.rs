@@ -0,0 +1,144 @@
+//! Benchmark (TWAP) settlement
+//!
+//! Benchmark quotes accept fills immediately and settle once the averaging
+//! window has elapsed. The settlement amount is the fill size times the
+//! time-weighted average price of the feed samples collected in the window;
+//! Local Laws check each sample's signature like spot evidence and recompute
+//! the price from the samples at settlement time.
+
+use alloc::format;
+use alloc::vec::Vec;
+use rfq_models::{FeedEvidence, QuoteConstraints, RejectionReason, TwapBenchmark};
+use serde::{Deserialize, Serialize};
+
+use crate::rules::{check_feed_signature, overflow};
+use crate::RfqLocalLawsInput;
+
+/// Evidence for settling a benchmark fill
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BenchmarkSettlement {
+    /// When the fill was accepted (start of the averaging window)
+    pub accepted_at: u64,
+    /// Feed samples collected during the window
+    pub samples: Vec<FeedEvidence>,
+}
+
+/// Time-weighted average price of samples over `[start, end]`, in fixed
+/// point like the samples
+///
+/// Samples sharing a timestamp are averaged; each observation is weighted by
+/// the time until the next one (the last until `end`). Returns None if there
+/// are no samples.
+pub fn twap(samples: &[FeedEvidence], start: u64, end: u64) -> Option<u64> {
+    let mut sorted: Vec<&FeedEvidence> = samples.iter().collect();
+    sorted.sort_by_key(|s| s.timestamp);
+
+    // Collapse samples with equal timestamps into one observation
+    let mut observations: Vec<(u64, u128)> = Vec::new();
+    let mut i = 0;
+    while i < sorted.len() {
+        let timestamp = sorted[i].timestamp;
+        let group: Vec<u128> = sorted[i..]
+            .iter()
+            .take_while(|s| s.timestamp == timestamp)
+            .map(|s| u128::from(s.price))
+            .collect();
+        i += group.len();
+        let average = group.iter().sum::<u128>() / group.len() as u128;
+        observations.push((timestamp.max(start), average));
+    }
+
+    let first = observations.first()?;
+    let mut weighted = 0u128;
+    let mut total_weight = 0u128;
+    for (idx, (timestamp, price)) in observations.iter().enumerate() {
+        let next = observations.get(idx + 1).map_or(end, |o| o.0).min(end);
+        let weight = u128::from(next.saturating_sub(*timestamp));
+        weighted += price * weight;
+        total_weight += weight;
+    }
+
+    // All samples at the window end: fall back to their plain average
+    let price = weighted.checked_div(total_weight).unwrap_or(first.1);
+    u64::try_from(price).ok()
+}
+
+/// Settlement amount (in the currency's smallest units) for a fill at the
+/// benchmark price
+pub fn benchmark_amount(
+    constraints: &QuoteConstraints,
+    benchmark_price: u64,
+    fill_size: u64,
+) -> u64 {
+    constraints.amount_at(benchmark_price, fill_size).unwrap_or(u64::MAX)
+}
+
+/// Validate a benchmark fill's settlement amount against its evidence
+pub(crate) fn validate_benchmark(
+    input: &RfqLocalLawsInput,
+    benchmark: &TwapBenchmark,
+) -> Result<(), RejectionReason> {
+    let constraints = &input.constraints;
+    let invalid = |reason| Err(RejectionReason::InvalidBenchmarkEvidence { reason });
+
+    let Some(settlement) = &input.benchmark else {
+        return invalid("benchmark quote settled without benchmark samples".into());
+    };
+
+    let window_end = settlement
+        .accepted_at
+        .checked_add(benchmark.window_secs)
+        .ok_or_else(|| overflow("benchmark window end"))?;
+    if input.current_timestamp < window_end {
+        return invalid(format!(
+            "settled at {} before the benchmark window closes at {}",
+            input.current_timestamp, window_end
+        ));
+    }
+
+    if settlement.samples.len() < benchmark.min_samples as usize {
+        return invalid(format!(
+            "{} samples provided, {} required",
+            settlement.samples.len(),
+            benchmark.min_samples
+        ));
+    }
+
+    for sample in &settlement.samples {
+        if !constraints.allows_source(&sample.source) {
+            return Err(RejectionReason::UnauthorizedSource {
+                source: sample.source.clone(),
+                allowed_sources: constraints.allowed_sources.clone(),
+            });
+        }
+        if !constraints.allows_asset(&sample.asset) {
+            return invalid(format!("sample for unexpected asset '{}'", sample.asset));
+        }
+        // The samples set the settlement amount, so they're held to the
+        // same signatures as spot evidence
+        check_feed_signature(constraints, sample)?;
+        if sample.timestamp < settlement.accepted_at || sample.timestamp > window_end {
+            return invalid(format!(
+                "sample from '{}' at {} is outside the window [{}, {}]",
+                sample.source, sample.timestamp, settlement.accepted_at, window_end
+            ));
+        }
+    }
+
+    let benchmark_price = twap(&settlement.samples, settlement.accepted_at, window_end)
+        .ok_or_else(|| RejectionReason::InvalidBenchmarkEvidence {
+            reason: "no samples".into(),
+        })?;
+    let expected_amount = constraints
+        .amount_at(benchmark_price, input.fill_size)
+        .ok_or_else(|| overflow("benchmark settlement amount"))?;
+    if expected_amount.abs_diff(input.fill_price) > 1 {
+        return Err(RejectionReason::BenchmarkMismatch {
+            benchmark_price,
+            expected_amount,
+            actual_amount: input.fill_price,
+        });
+    }
+
+    Ok(())
+}
@@ -14,17 +14,30 @@
 
 extern crate alloc;
 
+mod benchmark;
+mod outputs;
+mod rules;
+mod transfers;
+
+pub use benchmark::{benchmark_amount, twap, BenchmarkSettlement};
+pub use outputs::{constraints_hash, taker_hash, BatchOutputs, FillOutcome, PublicOutputs};
+pub use rules::{BuiltinRule, FillRule, RuleSet, BUILTIN_RULES};
+pub use transfers::{validate_transfers, Counterparties, Transfer};
+
 #[cfg(feature = "delta-sdk")]
 use delta_local_laws::{LocalLaws, LocalLawsError};
 #[cfg(feature = "delta-sdk")]
 use delta_verifiable::types::{VerifiableWithDiffs, VerificationContext};
 
-use alloc::format;
+use alloc::collections::BTreeMap;
 use alloc::string::String;
 use alloc::vec::Vec;
-use rfq_models::{FeedEvidence, QuoteConstraints, RejectionReason};
+use rfq_models::{Extensions, FeedEvidence, FillRecord, QuoteConstraints, RejectionReason};
 use serde::{Deserialize, Serialize};
 
+#[cfg(feature = "delta-sdk")]
+use rules::{Stage, Transfers};
+
 /// Input to the RFQ Local Laws
 ///
 /// This is passed during proof generation and contains all the
@@ -35,18 +48,93 @@ pub struct RfqLocalLawsInput {
     pub constraints: QuoteConstraints,
     /// The taker's owner ID
     pub taker_owner_id: String,
+    /// The shard of the taker's vault, checked against
+    /// `allowed_taker_shards` (0 if unknown)
+    #[serde(default)]
+    pub taker_shard: u64,
     /// The fill size
     pub fill_size: u64,
+    /// Size already filled by earlier fills of a partially fillable quote
+    #[serde(default)]
+    pub filled_so_far: u64,
+    /// Amount the taker already paid in earlier fills (in the currency's
+    /// smallest units), of this quote or of the series it belongs to
+    ///
+    /// Never less than the taker's fills in `fill_history` add up to.
+    #[serde(default)]
+    pub taker_filled_notional: u64,
+    /// Sizes of a basket's further legs in this fill, by asset (in
+    /// smallest units)
+    #[serde(default)]
+    pub leg_fill_sizes: BTreeMap<String, u64>,
     /// The fill price (in smallest units)
     pub fill_price: u64,
     /// Price feed evidence
     pub feed_evidence: Vec<FeedEvidence>,
     /// Current timestamp (for expiry and staleness checks)
     pub current_timestamp: u64,
-    /// Number of transfer legs in the transaction
+    /// How far feed clocks may disagree with `current_timestamp`, in
+    /// seconds: evidence may be this much older than its staleness limit,
+    /// or this much in the future
+    #[serde(default)]
+    pub clock_skew_secs: u64,
+    /// Number of transfer legs in the transaction, counting a fee leg the
+    /// quote allows
     pub transfer_leg_count: usize,
     /// Whether there are any transfers outside the expected pattern
     pub has_extra_transfers: bool,
+    /// Owners the settlement must credit (any, if None)
+    ///
+    /// Only checked against the actual transfers, which the Delta runtime
+    /// passes to [`RfqLocalLaws`]; `transfer_leg_count` and
+    /// `has_extra_transfers` are ignored there.
+    #[serde(default)]
+    pub counterparties: Option<Counterparties>,
+    /// Fills of this quote accepted before this one
+    #[serde(default)]
+    pub fill_history: Vec<FillRecord>,
+    /// Benchmark samples (only when settling a benchmark quote)
+    #[serde(default)]
+    pub benchmark: Option<BenchmarkSettlement>,
+    /// Deployment-specific data about this fill, consumed by custom rules
+    #[serde(default)]
+    pub extensions: Extensions,
+}
+
+impl RfqLocalLawsInput {
+    /// Decode an input the prover wrote with [`Self::to_json`]
+    ///
+    /// The zkVM programs read JSON rather than bincode so serde defaults
+    /// apply: an input written before a constraint field was added still
+    /// decodes, with the field at its default.
+    pub fn from_json(bytes: &[u8]) -> Result<Self, serde_json::Error> {
+        serde_json::from_slice(bytes)
+    }
+
+    /// Decode a batch of inputs written with [`Self::batch_to_json`]
+    pub fn batch_from_json(bytes: &[u8]) -> Result<Vec<Self>, serde_json::Error> {
+        serde_json::from_slice(bytes)
+    }
+
+    /// The input as the zkVM programs read it
+    pub fn to_json(&self) -> Vec<u8> {
+        serde_json::to_vec(self).expect("input serializes to JSON")
+    }
+
+    /// A batch of inputs as the batch zkVM program reads it
+    pub fn batch_to_json(inputs: &[Self]) -> Vec<u8> {
+        serde_json::to_vec(inputs).expect("inputs serialize to JSON")
+    }
+
+    /// When the fill was decided
+    ///
+    /// Benchmark fills settle after their window, so expiry and spot
+    /// evidence freshness are judged at acceptance instead.
+    pub fn decision_timestamp(&self) -> u64 {
+        self.benchmark
+            .as_ref()
+            .map_or(self.current_timestamp, |b| b.accepted_at)
+    }
 }
 
 /// RFQ Local Laws implementation
@@ -61,192 +149,201 @@ impl LocalLaws for RfqLocalLaws {
     type Input<'a> = RfqLocalLawsInput;
 
     fn validate<'a>(
-        _verifiables: &[VerifiableWithDiffs],
-        _verification_context: &VerificationContext,
+        verifiables: &[VerifiableWithDiffs],
+        verification_context: &VerificationContext,
         input: &RfqLocalLawsInput,
     ) -> Result<(), LocalLawsError> {
-        validate_fill_internal(input).map_err(|e| LocalLawsError::new(e.message()))
+        RuleSet::builtin().validate_verifiables(verifiables, verification_context, input)
     }
 }
 
-/// Validate a fill attempt and return a detailed rejection reason if invalid
-///
-/// This is the core validation logic that works in both std and zkVM environments.
-pub fn validate_fill(input: &RfqLocalLawsInput) -> Result<(), RejectionReason> {
-    validate_fill_internal(input)
-}
-
-/// Internal validation logic
-fn validate_fill_internal(input: &RfqLocalLawsInput) -> Result<(), RejectionReason> {
-    let constraints = &input.constraints;
-
-    // 1. Check expiry
-    if input.current_timestamp > constraints.expiry_timestamp {
-        return Err(RejectionReason::QuoteExpired {
-            expired_at: constraints.expiry_datetime(),
-            attempted_at: chrono::Utc::now(),
-        });
-    }
-
-    // 2. Check taker allowlist
-    if !constraints.allowed_takers.is_empty()
-        && !constraints.allowed_takers.contains(&input.taker_owner_id)
-    {
-        return Err(RejectionReason::UnauthorizedTaker {
-            taker: input.taker_owner_id.clone(),
-            allowed_takers: constraints.allowed_takers.clone(),
-        });
-    }
-
-    // 3. Check fill size
-    if input.fill_size > constraints.max_fill_size {
-        return Err(RejectionReason::SizeExceedsMax {
-            offered_size: input.fill_size as f64,
-            max_size: constraints.max_fill_size as f64,
-        });
-    }
-
-    // 4. Check max debit
-    if input.fill_price > constraints.max_debit {
-        return Err(RejectionReason::PriceExceedsLimit {
-            offered_price: input.fill_price as f64,
-            limit_price: constraints.max_debit as f64,
-        });
-    }
-
-    // 5. Validate feed evidence
-    validate_feed_evidence_detailed(input)?;
-
-    // 6. Check transfer pattern
-    if constraints.require_atomic_dvp && input.transfer_leg_count != 2 {
-        return Err(RejectionReason::InvalidTransferPattern {
-            expected: String::from("2 legs (atomic DvP)"),
-            actual: format!("{} legs", input.transfer_leg_count),
-        });
-    }
-
-    // 7. Check for side-payments
-    if constraints.no_side_payments && input.has_extra_transfers {
-        return Err(RejectionReason::SidePaymentDetected {
-            description: String::from("Extra transfers detected outside expected pattern"),
-        });
+#[cfg(feature = "delta-sdk")]
+impl RuleSet {
+    /// Validate a fill against the transfers its verifiables make, as
+    /// [`RfqLocalLaws`] does with the built-in rules
+    ///
+    /// For a domain's own `LocalLaws` implementation with custom rules.
+    pub fn validate_verifiables(
+        &self,
+        verifiables: &[VerifiableWithDiffs],
+        verification_context: &VerificationContext,
+        input: &RfqLocalLawsInput,
+    ) -> Result<(), LocalLawsError> {
+        // The transfer pattern comes from the verifiables, not the caller
+        let transfers = transfers_of(verifiables);
+        let vault_nonces = verification_context
+            .vaults
+            .iter()
+            .map(|(address, vault)| (address.owner().into(), vault.nonce()))
+            .collect();
+        let observed = Transfers::Observed {
+            transfers: &transfers,
+            vault_nonces: &vault_nonces,
+        };
+        self.run(input, Stage::Settlement(observed))
+            .map_err(rejection_error)
     }
-
-    Ok(())
 }
 
-/// Validate feed evidence with detailed rejection reasons
-fn validate_feed_evidence_detailed(input: &RfqLocalLawsInput) -> Result<(), RejectionReason> {
-    let constraints = &input.constraints;
-
-    // Check quorum count
-    if input.feed_evidence.len() < constraints.quorum_count as usize {
-        return Err(RejectionReason::QuorumNotMet {
-            sources_provided: input.feed_evidence.len(),
-            quorum_required: constraints.quorum_count,
-            price_spread_percent: None,
-            max_tolerance_percent: constraints.quorum_tolerance_percent,
-        });
-    }
-
-    let mut valid_prices: Vec<f64> = Vec::new();
+/// A rejection as the Delta runtime reports it
+#[cfg(feature = "delta-sdk")]
+fn rejection_error(reason: RejectionReason) -> LocalLawsError {
+    LocalLawsError::new(reason.message())
+}
 
-    for evidence in &input.feed_evidence {
-        // Check source allowlist
-        if !constraints.allowed_sources.is_empty()
-            && !constraints.allowed_sources.contains(&evidence.source)
-        {
-            return Err(RejectionReason::UnauthorizedSource {
-                source: evidence.source.clone(),
-                allowed_sources: constraints.allowed_sources.clone(),
-            });
-        }
+/// The credits made by a fill's debit allowances, with the nonces they
+/// use (other verifiables move nothing)
+#[cfg(feature = "delta-sdk")]
+fn transfers_of(verifiables: &[VerifiableWithDiffs]) -> Vec<Transfer> {
+    use delta_verifiable::types::debit_allowance::AllowanceAmount;
+    use delta_verifiable::types::VerifiableType;
 
-        // Check freshness
-        let age = input.current_timestamp.saturating_sub(evidence.timestamp);
-        if age > constraints.max_staleness_secs {
-            return Err(RejectionReason::StaleFeed {
-                source: evidence.source.clone(),
-                feed_timestamp: evidence.timestamp,
-                current_timestamp: input.current_timestamp,
-                max_staleness_secs: constraints.max_staleness_secs,
+    let mut transfers = Vec::new();
+    for verifiable in verifiables {
+        let VerifiableType::DebitAllowance(signed) = &verifiable.verifiable else {
+            continue;
+        };
+        let allowance = &signed.payload;
+        for amount in allowance.allowances.values() {
+            let amount = match amount {
+                AllowanceAmount::Fungible(amount) => *amount,
+                // Anything but a fungible amount is never a DvP leg
+                _ => u64::MAX,
+            };
+            transfers.push(Transfer {
+                debited: signed.debited.owner().into(),
+                credited: allowance.credited.owner().into(),
+                amount,
+                nonce: allowance.new_nonce,
             });
         }
-
-        valid_prices.push(evidence.price);
     }
+    transfers
+}
 
-    // Check price quorum
-    if valid_prices.len() >= 2 {
-        let min_price = valid_prices.iter().cloned().fold(f64::INFINITY, f64::min);
-        let max_price = valid_prices.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+/// Validate a fill attempt and return a detailed rejection reason if invalid
+///
+/// This is the core validation logic that works in both std and zkVM environments.
+/// Runs the built-in rules; see [`RuleSet`] to add custom ones.
+pub fn validate_fill(input: &RfqLocalLawsInput) -> Result<(), RejectionReason> {
+    RuleSet::builtin().validate_fill(input)
+}
 
-        if min_price > 0.0 {
-            let spread_percent = ((max_price - min_price) / min_price) * 100.0;
-            if spread_percent > constraints.quorum_tolerance_percent {
-                return Err(RejectionReason::QuorumNotMet {
-                    sources_provided: valid_prices.len(),
-                    quorum_required: constraints.quorum_count,
-                    price_spread_percent: Some(spread_percent),
-                    max_tolerance_percent: constraints.quorum_tolerance_percent,
-                });
-            }
-        }
-    }
+/// Validate a batch of fill attempts, returning one result per input in order
+///
+/// Each fill is validated on its own, as by [`validate_fill`], so fills of
+/// the same quote must carry the earlier ones in their `fill_history`.
+/// Used by the batched zkVM program to prove many fills in one execution.
+pub fn validate_fills(inputs: &[RfqLocalLawsInput]) -> Vec<Result<(), RejectionReason>> {
+    RuleSet::builtin().validate_fills(inputs)
+}
 
-    Ok(())
+/// Validate acceptance of a benchmark fill, before its settlement price is known
+///
+/// Runs every check except the price limit, the benchmark and the transfer
+/// pattern, which are validated at settlement by [`validate_fill`].
+pub fn validate_acceptance(input: &RfqLocalLawsInput) -> Result<(), RejectionReason> {
+    RuleSet::builtin().validate_acceptance(input)
 }
 
-#[cfg(all(test, feature = "delta-sdk"))]
+#[cfg(test)]
 mod tests {
     use super::*;
+    use rfq_models::{Iceberg, QuorumPolicy, Side, CONSTRAINTS_VERSION};
 
     fn test_constraints() -> QuoteConstraints {
         QuoteConstraints {
+            version: CONSTRAINTS_VERSION,
             quote_id: [0u8; 32],
+            side: Side::Buy,
             max_debit: 2_000_000_000, // 2000 USDD
             min_credit: None,
             expiry_timestamp: 1737500000,
+            valid_from_timestamp: None,
+            trading_window: None,
             allowed_sources: alloc::vec!["FeedA".into(), "FeedB".into()],
+            feed_keys: Default::default(),
+            // The rules under test aren't about signatures
+            allow_unsigned_feeds: true,
             max_staleness_secs: 5,
+            source_staleness_secs: Default::default(),
             quorum_count: 2,
-            quorum_tolerance_percent: 0.5,
+            quorum_tolerance_bps: 50,
+            quorum_policy: QuorumPolicy::Spread,
             allowed_takers: alloc::vec![],
+            denied_takers: alloc::vec![],
+            allowed_taker_shards: alloc::vec![],
             allowed_assets: alloc::vec!["dETH".into()],
             require_atomic_dvp: true,
             no_side_payments: true,
-            nonce: 1,
+            allowed_fee: None,
             max_fill_size: 1_000_000_000, // 1 dETH
+            leg_max_fill_sizes: Default::default(),
+            max_fills: 1,
+            max_fills_per_taker: None,
+            max_notional_per_taker: None,
+            min_fill_size: None,
+            allow_partial_fills: false,
+            settlement_window_secs: None,
+            max_price_deviation_bps: None,
+            asset_decimals: 9,
+            currency_decimals: 6,
+            max_fee_bps: None,
+            benchmark: None,
+            iceberg: None,
+            extensions: Default::default(),
+            compiled_by: None,
         }
     }
 
-    #[test]
-    fn test_valid_fill() {
-        let input = RfqLocalLawsInput {
+    /// Feed evidence for the test quote's asset at 1950 USDD, a second old
+    fn evidence(source: &str) -> FeedEvidence {
+        FeedEvidence {
+            source: source.into(),
+            asset: "dETH".into(),
+            price: 1_950_000_000_000,
+            timestamp: 1737499999,
+            signature: "sig".into(),
+            provenance: None,
+        }
+    }
+
+    /// A fill of 1 dETH for 1950 USDD against [`test_constraints`] that
+    /// passes every rule, given evidence meeting the quorum
+    fn test_input(feed_evidence: Vec<FeedEvidence>) -> RfqLocalLawsInput {
+        RfqLocalLawsInput {
             constraints: test_constraints(),
             taker_owner_id: "some_taker".into(),
+            taker_shard: 1,
             fill_size: 1_000_000_000,
             fill_price: 1_950_000_000,
-            feed_evidence: alloc::vec![
-                FeedEvidence {
-                    source: "FeedA".into(),
-                    asset: "dETH".into(),
-                    price: 1950.0,
-                    timestamp: 1737499998,
-                    signature: "sig".into(),
-                },
-                FeedEvidence {
-                    source: "FeedB".into(),
-                    asset: "dETH".into(),
-                    price: 1951.0,
-                    timestamp: 1737499999,
-                    signature: "sig".into(),
-                },
-            ],
+            feed_evidence,
             current_timestamp: 1737500000,
+            clock_skew_secs: 0,
             transfer_leg_count: 2,
             has_extra_transfers: false,
-        };
+            fill_history: alloc::vec![],
+            benchmark: None,
+            leg_fill_sizes: Default::default(),
+            filled_so_far: 0,
+            taker_filled_notional: 0,
+            counterparties: None,
+            extensions: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_valid_fill() {
+        let input = test_input(alloc::vec![
+            FeedEvidence {
+                timestamp: 1737499998,
+                ..evidence("FeedA")
+            },
+            FeedEvidence {
+                price: 1_951_000_000_000,
+                ..evidence("FeedB")
+            },
+        ]);
 
         let result = validate_fill(&input);
         assert!(result.is_ok());
@@ -254,65 +351,907 @@ mod tests {
 
     #[test]
     fn test_stale_feed_rejection() {
-        let input = RfqLocalLawsInput {
-            constraints: test_constraints(),
-            taker_owner_id: "some_taker".into(),
-            fill_size: 1_000_000_000,
-            fill_price: 1_950_000_000,
-            feed_evidence: alloc::vec![
-                FeedEvidence {
-                    source: "FeedA".into(),
-                    asset: "dETH".into(),
-                    price: 1950.0,
-                    timestamp: 1737499990, // 10 seconds old
-                    signature: "sig".into(),
-                },
-                FeedEvidence {
-                    source: "FeedB".into(),
-                    asset: "dETH".into(),
-                    price: 1951.0,
-                    timestamp: 1737499999,
-                    signature: "sig".into(),
-                },
-            ],
-            current_timestamp: 1737500000,
-            transfer_leg_count: 2,
-            has_extra_transfers: false,
-        };
+        let input = test_input(alloc::vec![
+            FeedEvidence {
+                timestamp: 1737499990, // 10 seconds old
+                ..evidence("FeedA")
+            },
+            evidence("FeedB"),
+        ]);
 
         let result = validate_fill(&input);
         assert!(matches!(result, Err(RejectionReason::StaleFeed { .. })));
     }
 
+    #[test]
+    fn test_source_staleness() {
+        // A slow reference feed 10s old, a fast feed 2s old
+        let mut input = test_input(alloc::vec![
+            FeedEvidence {
+                timestamp: 1737499998,
+                ..evidence("FeedA")
+            },
+            FeedEvidence {
+                timestamp: 1737499990,
+                ..evidence("FeedB")
+            },
+        ]);
+        assert!(validate_fill(&input).is_err());
+
+        input.constraints.source_staleness_secs.insert("FeedB".into(), 60);
+        assert!(validate_fill(&input).is_ok());
+
+        // Overrides tighten a fast feed's limit too
+        input.constraints.source_staleness_secs.insert("FeedA".into(), 1);
+        assert!(matches!(
+            validate_fill(&input),
+            Err(RejectionReason::StaleFeed { ref source, max_staleness_secs: 1, .. })
+                if source == "FeedA"
+        ));
+    }
+
+    #[test]
+    fn test_clock_skew_grace() {
+        let mut input = test_input(alloc::vec![
+            FeedEvidence {
+                timestamp: 1737499993, // 7 seconds old, limit is 5
+                ..evidence("FeedA")
+            },
+            FeedEvidence {
+                timestamp: 1737500002, // 2 seconds in the future
+                ..evidence("FeedB")
+            },
+        ]);
+        assert!(matches!(validate_fill(&input), Err(RejectionReason::StaleFeed { .. })));
+
+        input.feed_evidence[0].timestamp = 1737499999;
+        assert!(matches!(validate_fill(&input), Err(RejectionReason::FutureFeed { .. })));
+
+        input.feed_evidence[0].timestamp = 1737499993;
+        input.clock_skew_secs = 2;
+        assert!(validate_fill(&input).is_ok());
+    }
+
     #[test]
     fn test_unauthorized_source_rejection() {
+        // FeedMallory isn't in the allowlist
+        let input = test_input(alloc::vec![evidence("FeedMallory"), evidence("FeedB")]);
+
+        let result = validate_fill(&input);
+        assert!(matches!(result, Err(RejectionReason::UnauthorizedSource { .. })));
+    }
+
+    #[test]
+    fn test_feed_asset_mismatch() {
+        // FeedB's price is for dSOL, not the quote's dETH
+        let input = test_input(alloc::vec![
+            evidence("FeedA"),
+            FeedEvidence {
+                asset: "dSOL".into(),
+                ..evidence("FeedB")
+            },
+        ]);
+
+        let result = validate_fill(&input);
+        assert!(matches!(result, Err(RejectionReason::FeedAssetMismatch { .. })));
+    }
+
+    #[test]
+    fn test_duplicate_feed_source() {
+        // FeedA three times is one source, short of a quorum of 2
+        let mut input = test_input(alloc::vec![evidence("FeedA"); 3]);
+        assert!(matches!(
+            validate_fill(&input),
+            Err(RejectionReason::QuorumNotMet {
+                sources_provided: 1,
+                ..
+            })
+        ));
+
+        // With the quorum met, the repeated evidence is still rejected
+        input.feed_evidence.push(evidence("FeedB"));
+        assert!(matches!(
+            validate_fill(&input),
+            Err(RejectionReason::DuplicateFeedSource { .. })
+        ));
+    }
+
+    #[test]
+    fn test_feed_signatures() {
+        use rfq_models::signing::{sign_hex, to_hex, Ed25519Signer, PublicKey, Signer};
+
+        let signers = [("FeedA", [1u8; 32]), ("FeedB", [2u8; 32])]
+            .map(|(source, seed)| (source, Ed25519Signer::from_seed(&seed)));
+        let mut signed: Vec<FeedEvidence> = signers
+            .iter()
+            .map(|(source, signer)| {
+                let mut signed = evidence(source);
+                signed.signature = sign_hex(signer, signed.signed_message().as_bytes());
+                signed
+            })
+            .collect();
+        let mut input = test_input(signed.clone());
+        for (source, signer) in &signers {
+            let key = PublicKey {
+                scheme: signer.kind(),
+                key: to_hex(&signer.public_key()),
+            };
+            input.constraints.feed_keys.insert((*source).into(), key);
+        }
+        input.constraints.allow_unsigned_feeds = false;
+        assert!(validate_fill(&input).is_ok());
+
+        // A price changed after signing no longer verifies
+        signed[1].price = 1_900_000_000_000;
+        input.feed_evidence = signed.clone();
+        assert!(matches!(
+            validate_fill(&input),
+            Err(RejectionReason::InvalidFeedSignature { source, .. }) if source == "FeedB"
+        ));
+
+        // Evidence from a source without a key is refused, signed or not
+        signed[1].price = 1_950_000_000_000;
+        input.feed_evidence = signed;
+        input.constraints.feed_keys.remove("FeedB");
+        assert!(matches!(
+            validate_fill(&input),
+            Err(RejectionReason::UnkeyedFeedSource { source }) if source == "FeedB"
+        ));
+
+        // ...unless the quote explicitly allows unsigned feeds
+        input.constraints.allow_unsigned_feeds = true;
+        assert!(validate_fill(&input).is_ok());
+    }
+
+    #[test]
+    fn test_taker_fill_cap_rejection() {
+        let mut input = RfqLocalLawsInput {
+            fill_history: alloc::vec![FillRecord {
+                taker_owner_id: "some_taker".into(),
+                fill_size: 1_000_000_000,
+                fill_price: 1_950_000_000,
+            }],
+            ..test_input(alloc::vec![evidence("FeedA"), evidence("FeedB")])
+        };
+        input.constraints.max_fills = 5;
+        input.constraints.max_fills_per_taker = Some(1);
+
+        let result = validate_fill(&input);
+        assert!(matches!(result, Err(RejectionReason::TakerFillLimitReached { .. })));
+
+        // Another taker can still fill
         let input = RfqLocalLawsInput {
-            constraints: test_constraints(),
+            taker_owner_id: "other_taker".into(),
+            ..input
+        };
+        assert!(validate_fill(&input).is_ok());
+    }
+
+    #[test]
+    fn test_iceberg_totals() {
+        // 1 dETH on display, 2.5 dETH in total
+        let filled = FillRecord {
             taker_owner_id: "some_taker".into(),
             fill_size: 1_000_000_000,
             fill_price: 1_950_000_000,
-            feed_evidence: alloc::vec![
+        };
+        let mut input = RfqLocalLawsInput {
+            fill_history: alloc::vec![filled.clone(), filled],
+            ..test_input(alloc::vec![evidence("FeedA"), evidence("FeedB")])
+        };
+        input.constraints.max_fills = 10;
+        input.constraints.iceberg = Some(Iceberg {
+            total_size: 2_500_000_000,
+            total_debit: None,
+        });
+
+        // The display refills, but only 0.5 dETH of the total is left
+        assert!(matches!(
+            validate_fill(&input),
+            Err(RejectionReason::IcebergSizeExhausted { filled_size: 2_000_000_000, .. })
+        ));
+        input.fill_size = 500_000_000;
+        input.fill_price = 975_000_000;
+        assert!(validate_fill(&input).is_ok());
+
+        // The fills together may not debit more than the total either
+        input.constraints.iceberg = Some(Iceberg {
+            total_size: 2_500_000_000,
+            total_debit: Some(4_500_000_000),
+        });
+        assert!(matches!(
+            validate_fill(&input),
+            Err(RejectionReason::IcebergDebitExhausted { debited: 3_900_000_000, .. })
+        ));
+    }
+
+    #[test]
+    fn test_min_fill_size() {
+        let mut input = RfqLocalLawsInput {
+            fill_size: 100_000_000,
+            fill_price: 195_000_000,
+            ..test_input(alloc::vec![evidence("FeedA"), evidence("FeedB")])
+        };
+        input.constraints.min_fill_size = Some(250_000_000); // 0.25 dETH
+        assert!(matches!(
+            validate_fill(&input),
+            Err(RejectionReason::SizeBelowMin { .. })
+        ));
+
+        // The last 0.1 dETH of an iceberg can still be taken
+        input.constraints.max_fills = 10;
+        input.constraints.iceberg = Some(Iceberg {
+            total_size: 1_100_000_000,
+            total_debit: None,
+        });
+        input.fill_history = alloc::vec![FillRecord {
+            taker_owner_id: "other_taker".into(),
+            fill_size: 1_000_000_000,
+            fill_price: 1_950_000_000,
+        }];
+        assert!(validate_fill(&input).is_ok());
+    }
+
+    #[test]
+    fn test_partial_fills() {
+        // 1 dETH, taken in pieces
+        let mut input = RfqLocalLawsInput {
+            fill_size: 600_000_000,
+            fill_price: 1_170_000_000,
+            ..test_input(alloc::vec![evidence("FeedA"), evidence("FeedB")])
+        };
+        input.constraints.allow_partial_fills = true;
+        input.constraints.max_fills = u32::MAX;
+        assert!(validate_fill(&input).is_ok());
+
+        // After 0.6 dETH, only 0.4 dETH is left
+        input.filled_so_far = 600_000_000;
+        assert!(matches!(
+            validate_fill(&input),
+            Err(RejectionReason::RemainingSizeExceeded { filled_size: 600_000_000, .. })
+        ));
+        input.fill_size = 400_000_000;
+        input.fill_price = 780_000_000;
+        assert!(validate_fill(&input).is_ok());
+    }
+
+    #[test]
+    fn test_settlement_transfers() {
+        let (maker, taker, domain) = ([1u8; 32], [2u8; 32], [9u8; 32]);
+        let mut input = RfqLocalLawsInput {
+            counterparties: Some(Counterparties { maker, taker }),
+            ..test_input(alloc::vec![])
+        };
+        let payment = Transfer {
+            debited: domain,
+            credited: taker,
+            amount: 1_950_000_000,
+            nonce: 7,
+        };
+        let delivery = Transfer {
+            credited: maker,
+            amount: 1_000_000_000,
+            nonce: 8,
+            ..payment
+        };
+        let vault_nonces = BTreeMap::from([(domain, 6)]);
+        assert!(validate_transfers(&input, &[delivery, payment], &vault_nonces).is_ok());
+
+        // Once settled, the same transfers can't be replayed
+        let settled = BTreeMap::from([(domain, 8)]);
+        assert!(matches!(
+            validate_transfers(&input, &[delivery, payment], &settled),
+            Err(RejectionReason::ReplayDetected { nonce: 8, vault_nonce: 8 })
+        ));
+
+        // nor can transfers from a vault whose nonce is unknown
+        assert!(matches!(
+            validate_transfers(&input, &[delivery, payment], &BTreeMap::new()),
+            Err(RejectionReason::UnknownVaultNonce { .. })
+        ));
+
+        // The payment must reach the taker, in full
+        let diverted = Transfer {
+            credited: [3u8; 32],
+            ..payment
+        };
+        assert!(matches!(
+            validate_transfers(&input, &[diverted, delivery], &vault_nonces),
+            Err(RejectionReason::InvalidTransferPattern { .. })
+        ));
+
+        // and nothing else may move alongside the two legs
+        input.constraints.require_atomic_dvp = false;
+        assert!(matches!(
+            validate_transfers(&input, &[payment, delivery, diverted], &vault_nonces),
+            Err(RejectionReason::SidePaymentDetected { .. })
+        ));
+    }
+
+    #[test]
+    fn test_fee_leg() {
+        let (maker, taker, domain, treasury) = ([1u8; 32], [2u8; 32], [9u8; 32], [7u8; 32]);
+        let mut input = RfqLocalLawsInput {
+            transfer_leg_count: 3,
+            counterparties: Some(Counterparties { maker, taker }),
+            ..test_input(alloc::vec![])
+        };
+        let payment = Transfer {
+            debited: domain,
+            credited: taker,
+            amount: 1_950_000_000,
+            nonce: 7,
+        };
+        let delivery = Transfer {
+            credited: maker,
+            amount: 1_000_000_000,
+            ..payment
+        };
+        let fee = Transfer {
+            credited: treasury,
+            amount: 1_000_000,
+            ..payment
+        };
+        let nonces = BTreeMap::from([(domain, 6)]);
+
+        // Without an allowance the fee is one leg too many
+        assert!(validate_transfers(&input, &[payment, delivery, fee], &nonces).is_err());
+
+        input.constraints.allowed_fee = Some(rfq_models::AllowedFee {
+            recipient: treasury,
+            max_amount: 2_000_000,
+        });
+        assert!(validate_transfers(&input, &[payment, delivery, fee], &nonces).is_ok());
+        assert!(validate_transfers(&input, &[payment, delivery], &nonces).is_ok());
+
+        // The maker's maximum fee caps the leg too: 1 USDD of 1950 is ~5.1bps
+        input.constraints.max_fee_bps = Some(5);
+        assert!(matches!(
+            validate_transfers(&input, &[payment, delivery, fee], &nonces),
+            Err(RejectionReason::FeeExceedsLimit { fee: 1_000_000, max_fee: 975_000 })
+        ));
+        input.constraints.max_fee_bps = Some(10);
+        assert!(validate_transfers(&input, &[payment, delivery, fee], &nonces).is_ok());
+        input.constraints.quorum_count = 0;
+        assert!(validate_fill(&input).is_ok());
+
+        // Only one fee leg, within the allowance
+        let large = Transfer {
+            amount: 3_000_000,
+            ..fee
+        };
+        assert!(validate_transfers(&input, &[payment, delivery, large], &nonces).is_err());
+        assert!(validate_transfers(&input, &[payment, delivery, fee, fee], &nonces).is_err());
+        input.constraints.require_atomic_dvp = false;
+        assert!(matches!(
+            validate_transfers(&input, &[payment, delivery, fee, fee], &nonces),
+            Err(RejectionReason::SidePaymentDetected { .. })
+        ));
+    }
+
+    #[test]
+    fn test_basket_legs() {
+        // 1 dETH and 20 dSOL as a package
+        let mut input = RfqLocalLawsInput {
+            fill_size: 500_000_000,
+            fill_price: 975_000_000,
+            leg_fill_sizes: BTreeMap::from([("dSOL".into(), 10_000_000_000)]),
+            ..test_input(alloc::vec![evidence("FeedA"), evidence("FeedB")])
+        };
+        input.constraints.leg_max_fill_sizes.insert("dSOL".into(), 20_000_000_000);
+        assert!(validate_fill(&input).is_ok());
+
+        // Half the dETH must come with half the dSOL
+        input.leg_fill_sizes.insert("dSOL".into(), 20_000_000_000);
+        assert!(matches!(
+            validate_fill(&input),
+            Err(RejectionReason::BasketLegMismatch { expected_size: 10_000_000_000, .. })
+        ));
+
+        // and with no asset outside the basket
+        input.leg_fill_sizes = BTreeMap::from([
+            ("dSOL".into(), 10_000_000_000),
+            ("dBTC".into(), 1),
+        ]);
+        assert!(matches!(
+            validate_fill(&input),
+            Err(RejectionReason::BasketLegMismatch { expected_size: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn test_benchmark_settlement() {
+        let sample = |price: u64, timestamp: u64| FeedEvidence {
+            price,
+            timestamp,
+            ..evidence("FeedA")
+        };
+        // 1900 for 200s, then 1960 for 400s -> TWAP 1940
+        let samples = alloc::vec![
+            sample(1_900_000_000_000, 1737499400),
+            sample(1_960_000_000_000, 1737499600),
+        ];
+        assert_eq!(twap(&samples, 1737499400, 1737500000), Some(1_940_000_000_000));
+        let fill_price = benchmark_amount(&test_constraints(), 1_940_000_000_000, 1_000_000_000);
+        assert_eq!(fill_price, 1_940_000_000);
+
+        let spot = |source: &str, price: u64| FeedEvidence {
+            price,
+            timestamp: 1737499399,
+            ..evidence(source)
+        };
+        let mut input = RfqLocalLawsInput {
+            fill_price,
+            benchmark: Some(BenchmarkSettlement {
+                accepted_at: 1737499400,
+                samples,
+            }),
+            ..test_input(alloc::vec![
+                spot("FeedA", 1_930_000_000_000),
+                spot("FeedB", 1_931_000_000_000),
+            ])
+        };
+        input.constraints.benchmark = Some(rfq_models::TwapBenchmark {
+            window_secs: 600,
+            min_samples: 2,
+        });
+        assert!(validate_fill(&input).is_ok());
+
+        // Settling at the acceptance-time spot price instead of the TWAP
+        input.fill_price = 1_930_000_000;
+        assert!(matches!(
+            validate_fill(&input),
+            Err(RejectionReason::BenchmarkMismatch { .. })
+        ));
+
+        // Settling 600s after acceptance meets a 600s settlement window,
+        // but not a 300s one
+        input.fill_price = fill_price;
+        input.constraints.settlement_window_secs = Some(600);
+        assert!(validate_fill(&input).is_ok());
+        input.constraints.settlement_window_secs = Some(300);
+        assert!(matches!(
+            validate_fill(&input),
+            Err(RejectionReason::SettlementWindowExceeded { window_secs: 300, .. })
+        ));
+
+        // Samples are held to the feeds' keys like spot evidence
+        use rfq_models::signing::{sign_hex, to_hex, Ed25519Signer, PublicKey, Signer};
+        input.constraints.settlement_window_secs = None;
+        input.constraints.allow_unsigned_feeds = false;
+        let signers = [("FeedA", [1u8; 32]), ("FeedB", [2u8; 32])]
+            .map(|(source, seed)| (source, Ed25519Signer::from_seed(&seed)));
+        for (source, signer) in &signers {
+            let key = PublicKey {
+                scheme: signer.kind(),
+                key: to_hex(&signer.public_key()),
+            };
+            input.constraints.feed_keys.insert((*source).into(), key);
+        }
+        let sign = |evidence: &mut FeedEvidence| {
+            let (_, signer) = signers.iter().find(|(s, _)| *s == evidence.source).unwrap();
+            evidence.signature = sign_hex(signer, evidence.signed_message().as_bytes());
+        };
+        input.feed_evidence.iter_mut().for_each(sign);
+        let settlement = input.benchmark.as_mut().unwrap();
+        settlement.samples.iter_mut().for_each(sign);
+        assert!(validate_fill(&input).is_ok());
+
+        // A sample whose price was changed after signing no longer verifies
+        input.benchmark.as_mut().unwrap().samples[1].price = 1_990_000_000_000;
+        assert!(matches!(
+            validate_fill(&input),
+            Err(RejectionReason::InvalidFeedSignature { source, .. }) if source == "FeedA"
+        ));
+
+        // Nor does a sample from an allowed source without a key
+        input.constraints.allowed_sources.push("FeedC".into());
+        let sample = &mut input.benchmark.as_mut().unwrap().samples[1];
+        sample.price = 1_960_000_000_000;
+        sample.source = "FeedC".into();
+        assert!(matches!(
+            validate_fill(&input),
+            Err(RejectionReason::UnkeyedFeedSource { source }) if source == "FeedC"
+        ));
+    }
+
+    #[test]
+    fn test_numeric_overflow() {
+        // A sell has no upper limit on the price, but the band still applies
+        let mut input = RfqLocalLawsInput {
+            fill_size: 1,
+            fill_price: u64::MAX,
+            ..test_input(alloc::vec![evidence("FeedA"), evidence("FeedB")])
+        };
+        input.constraints.side = Side::Sell;
+        input.constraints.max_price_deviation_bps = Some(100);
+        assert!(matches!(
+            validate_fill(&input),
+            Err(RejectionReason::NumericOverflow { .. })
+        ));
+
+        // A taker's notional can't wrap around its cap
+        input.constraints.side = Side::Buy;
+        input.constraints.max_price_deviation_bps = None;
+        input.constraints.max_notional_per_taker = Some(1_000);
+        input.fill_price = 2;
+        input.taker_filled_notional = u64::MAX - 1;
+        assert!(matches!(
+            validate_fill(&input),
+            Err(RejectionReason::NumericOverflow { .. })
+        ));
+    }
+
+    #[test]
+    fn test_price_band() {
+        // Within max_debit, but 1500 per dETH against a median of 1950
+        let mut input = RfqLocalLawsInput {
+            fill_price: 1_500_000_000,
+            ..test_input(alloc::vec![
                 FeedEvidence {
-                    source: "FeedMallory".into(), // Not in allowlist
-                    asset: "dETH".into(),
-                    price: 1950.0,
-                    timestamp: 1737499999,
-                    signature: "sig".into(),
+                    price: 1_949_000_000_000,
+                    ..evidence("FeedA")
                 },
                 FeedEvidence {
-                    source: "FeedB".into(),
-                    asset: "dETH".into(),
-                    price: 1951.0,
-                    timestamp: 1737499999,
-                    signature: "sig".into(),
+                    price: 1_951_000_000_000,
+                    ..evidence("FeedB")
                 },
-            ],
-            current_timestamp: 1737500000,
-            transfer_leg_count: 2,
-            has_extra_transfers: false,
+            ])
         };
+        input.constraints.max_price_deviation_bps = Some(100);
+        assert!(matches!(
+            validate_fill(&input),
+            Err(RejectionReason::PriceDeviationExceeded { .. })
+        ));
 
-        let result = validate_fill(&input);
-        assert!(matches!(result, Err(RejectionReason::UnauthorizedSource { .. })));
+        // 0.5 dETH for 970 USDD is 1940 per dETH, about 0.5% off
+        input.fill_size = 500_000_000;
+        input.fill_price = 970_000_000;
+        assert!(validate_fill(&input).is_ok());
+    }
+
+    #[test]
+    fn test_quorum_policies() {
+        // FeedC is manipulated, about 7.7% above the others
+        let mut input = test_input(alloc::vec![
+            evidence("FeedA"),
+            FeedEvidence {
+                price: 1_951_000_000_000,
+                ..evidence("FeedB")
+            },
+            FeedEvidence {
+                price: 2_100_000_000_000,
+                ..evidence("FeedC")
+            },
+        ]);
+        input.constraints.allowed_sources.push("FeedC".into());
+        input.constraints.max_price_deviation_bps = Some(100);
+        let quorum_not_met = |input: &RfqLocalLawsInput| {
+            matches!(validate_fill(input), Err(RejectionReason::QuorumNotMet { .. }))
+        };
+        assert!(quorum_not_met(&input));
+
+        // The median and the trimmed mean leave FeedC out
+        input.constraints.quorum_policy = QuorumPolicy::Median;
+        assert!(validate_fill(&input).is_ok());
+        input.constraints.quorum_policy = QuorumPolicy::TrimmedMean { trim: 1 };
+        assert!(validate_fill(&input).is_ok());
+
+        // The fill price is held to the quorum price, not the spread's median
+        input.constraints.quorum_policy = QuorumPolicy::Median;
+        input.fill_price = 1_990_000_000;
+        assert!(matches!(
+            validate_fill(&input),
+            Err(RejectionReason::PriceDeviationExceeded { median_price: 1_951_000_000_000, .. })
+        ));
+        input.fill_price = 1_950_000_000;
+
+        // Too few sources agree with the median
+        input.constraints.quorum_count = 3;
+        assert!(quorum_not_met(&input));
+        input.constraints.quorum_count = 2;
+
+        // Weighted equally, FeedC drags the mean away from every source
+        let weights = |entries: &[(&str, u32)]| QuorumPolicy::Weighted {
+            weights: entries.iter().map(|(s, w)| (String::from(*s), *w)).collect(),
+        };
+        input.constraints.quorum_policy = weights(&[]);
+        assert!(quorum_not_met(&input));
+        input.constraints.quorum_policy = weights(&[("FeedC", 0)]);
+        assert!(validate_fill(&input).is_ok());
+    }
+
+    #[test]
+    fn test_trading_window() {
+        // current_timestamp is 22:53:20 UTC
+        let mut input = test_input(alloc::vec![]);
+        input.constraints.quorum_count = 0;
+
+        input.constraints.valid_from_timestamp = Some(1737500001);
+        assert!(matches!(
+            validate_fill(&input),
+            Err(RejectionReason::QuoteNotYetValid { .. })
+        ));
+        input.constraints.valid_from_timestamp = Some(1737500000);
+        assert!(validate_fill(&input).is_ok());
+
+        let window = |start_secs, end_secs| {
+            Some(rfq_models::TradingWindow {
+                start_secs,
+                end_secs,
+            })
+        };
+        input.constraints.trading_window = window(14 * 3600, 16 * 3600);
+        assert!(matches!(
+            validate_fill(&input),
+            Err(RejectionReason::OutsideTradingWindow { .. })
+        ));
+        input.constraints.trading_window = window(22 * 3600, 23 * 3600);
+        assert!(validate_fill(&input).is_ok());
+        // Past midnight
+        input.constraints.trading_window = window(23 * 3600, 3600);
+        assert!(validate_fill(&input).is_err());
+        input.constraints.trading_window = window(22 * 3600, 3600);
+        assert!(validate_fill(&input).is_ok());
+    }
+
+    #[test]
+    fn test_sell_quote_limits() {
+        let (maker, taker) = ([1u8; 32], [2u8; 32]);
+        let mut input = RfqLocalLawsInput {
+            counterparties: Some(Counterparties { maker, taker }),
+            ..test_input(alloc::vec![])
+        };
+        input.constraints.side = Side::Sell;
+        input.constraints.max_debit = u64::MAX;
+        input.constraints.min_credit = Some(1_900_000_000); // 1900 USDD for 1 dETH
+        input.constraints.allow_partial_fills = true;
+        input.constraints.quorum_count = 0;
+        assert!(validate_fill(&input).is_ok());
+
+        // Half the size must bring in half the credit
+        input.fill_size = 500_000_000;
+        input.fill_price = 940_000_000;
+        assert!(matches!(
+            validate_fill(&input),
+            Err(RejectionReason::PriceBelowLimit { .. })
+        ));
+        input.fill_price = 950_000_000;
+        assert!(validate_fill(&input).is_ok());
+
+        // The maker is paid, the taker delivered to
+        let payment = Transfer {
+            debited: taker,
+            credited: maker,
+            amount: 950_000_000,
+            nonce: 1,
+        };
+        let delivery = Transfer {
+            debited: maker,
+            credited: taker,
+            amount: 500_000_000,
+            nonce: 1,
+        };
+        let nonces = BTreeMap::from([(taker, 0), (maker, 0)]);
+        assert!(validate_transfers(&input, &[payment, delivery], &nonces).is_ok());
+        input.constraints.side = Side::Buy;
+        assert!(validate_transfers(&input, &[payment, delivery], &nonces).is_err());
+    }
+
+    #[test]
+    fn test_taker_notional_cap() {
+        let fill = |taker: &str| FillRecord {
+            taker_owner_id: taker.into(),
+            fill_size: 500_000_000,
+            fill_price: 1_000_000_000,
+        };
+        let mut input = RfqLocalLawsInput {
+            fill_history: alloc::vec![fill("some_taker"), fill("other_taker")],
+            ..test_input(alloc::vec![])
+        };
+        input.constraints.quorum_count = 0;
+        input.constraints.max_fills = 10;
+        input.constraints.max_notional_per_taker = Some(3_000_000_000); // 3000 USDD
+        // 1000 filled before, only the taker's own fills count
+        assert!(validate_fill(&input).is_ok());
+
+        // 1500 filled across the series is more than this quote shows
+        input.taker_filled_notional = 1_500_000_000;
+        assert!(matches!(
+            validate_fill(&input),
+            Err(RejectionReason::TakerNotionalExceeded { filled_notional: 1_500_000_000, .. })
+        ));
+
+        // Understating it doesn't hide the quote's own history
+        input.taker_filled_notional = 0;
+        input.fill_history.push(fill("some_taker"));
+        assert!(validate_fill(&input).is_err());
+    }
+
+    #[test]
+    fn test_custom_rules() {
+        use rfq_models::ExtensionValue;
+
+        /// Only takers booked in the quote's jurisdiction
+        struct Jurisdiction;
+
+        impl FillRule for Jurisdiction {
+            fn name(&self) -> &str {
+                "jurisdiction"
+            }
+
+            fn validate(&self, input: &RfqLocalLawsInput) -> Result<(), RejectionReason> {
+                let key = "jurisdiction";
+                if input.constraints.extensions.get(key) != input.extensions.get(key) {
+                    return Err(RejectionReason::ValidationError {
+                        message: "Taker is outside the quote's jurisdiction".into(),
+                    });
+                }
+                Ok(())
+            }
+        }
+
+        let mut input = test_input(alloc::vec![]);
+        input.constraints.quorum_count = 0;
+        input
+            .constraints
+            .extensions
+            .insert("jurisdiction".into(), ExtensionValue::Text("EU".into()));
+
+        assert!(validate_fill(&input).is_ok());
+        let rules = RuleSet::builtin().with(Jurisdiction);
+        assert!(matches!(
+            rules.validate_fill(&input),
+            Err(RejectionReason::ValidationError { .. })
+        ));
+        assert!(rules.validate_acceptance(&input).is_err());
+
+        input
+            .extensions
+            .insert("jurisdiction".into(), ExtensionValue::Text("EU".into()));
+        assert!(rules.validate_fill(&input).is_ok());
+
+        // Dropping a built-in rule lets a second fill through
+        input.fill_history.push(FillRecord {
+            taker_owner_id: "other_taker".into(),
+            fill_size: 1,
+            fill_price: 1,
+        });
+        assert!(rules.validate_fill(&input).is_err());
+        let rules = rules.without("fill_caps");
+        assert!(!rules.names().any(|name| name == "fill_caps"));
+        assert!(rules.validate_fill(&input).is_ok());
+    }
+
+    #[test]
+    fn test_denied_taker() {
+        let mut input = RfqLocalLawsInput {
+            taker_owner_id: "taker_mallory".into(),
+            ..test_input(alloc::vec![evidence("FeedA"), evidence("FeedB")])
+        };
+        assert!(validate_fill(&input).is_ok());
+
+        // Denied even when the allowlist names the taker
+        input.constraints.allowed_takers = alloc::vec!["taker_mallory".into()];
+        input.constraints.denied_takers = alloc::vec!["taker_mallory".into()];
+        assert!(matches!(
+            validate_fill(&input),
+            Err(RejectionReason::DeniedTaker { .. })
+        ));
+
+        // The deny list is checked before anything else
+        input.current_timestamp = input.constraints.expiry_timestamp + 1;
+        assert!(matches!(
+            validate_fill(&input),
+            Err(RejectionReason::DeniedTaker { .. })
+        ));
+        assert!(validate_acceptance(&input).is_err());
+    }
+
+    #[test]
+    fn test_taker_shards() {
+        let mut input = RfqLocalLawsInput {
+            taker_owner_id: "taker_bob".into(),
+            taker_shard: 2,
+            ..test_input(alloc::vec![evidence("FeedA"), evidence("FeedB")])
+        };
+
+        input.constraints.allowed_taker_shards = alloc::vec![1, 2];
+        assert!(validate_fill(&input).is_ok());
+
+        input.taker_shard = 3;
+        match validate_fill(&input) {
+            Err(RejectionReason::UnauthorizedShard { taker_shard, allowed_shards, .. }) => {
+                assert_eq!(taker_shard, 3);
+                assert_eq!(allowed_shards, alloc::vec![1, 2]);
+            }
+            other => panic!("expected UnauthorizedShard, got {:?}", other),
+        }
+
+        // An unknown shard never matches
+        input.taker_shard = 0;
+        assert!(validate_fill(&input).is_err());
+    }
+
+    #[test]
+    fn test_public_outputs() {
+        let mut input =
+            test_input(alloc::vec![evidence("FeedA"), evidence("FeedB"), evidence("FeedA")]);
+
+        let outputs = PublicOutputs::of(&input);
+        assert_eq!(outputs.quote_id, input.constraints.quote_id);
+        assert_eq!(outputs.fill_size, 1_000_000_000);
+        assert_eq!(outputs.fill_price, 1_950_000_000);
+        assert_eq!(outputs.taker_hash, taker_hash("some_taker"));
+        assert_eq!(outputs.feed_sources, 2);
+
+        let bytes = outputs.to_bytes();
+        assert_eq!(bytes.len(), PublicOutputs::LEN);
+        assert_eq!(PublicOutputs::from_bytes(&bytes), Some(outputs));
+        assert_eq!(PublicOutputs::from_bytes(&bytes[1..]), None);
+
+        // Any change to the constraints changes the committed hash
+        input.constraints.max_staleness_secs += 1;
+        assert_ne!(PublicOutputs::of(&input).constraints_hash, outputs.constraints_hash);
+    }
+
+    #[test]
+    fn test_validate_fills() {
+        let input = test_input(alloc::vec![evidence("FeedA"), evidence("FeedB")]);
+        let over_limit = RfqLocalLawsInput {
+            fill_price: 2_100_000_000,
+            ..input.clone()
+        };
+        let inputs = alloc::vec![input.clone(), over_limit, input];
+
+        // A rejected fill doesn't stop the rest of the batch
+        let results = validate_fills(&inputs);
+        assert_eq!(results.len(), 3);
+        assert!(results[0].is_ok());
+        assert!(matches!(results[1], Err(RejectionReason::PriceExceedsLimit { .. })));
+        assert!(results[2].is_ok());
+
+        let batch = BatchOutputs::new(&inputs, &results);
+        let accepted: Vec<bool> = batch.fills.iter().map(|fill| fill.accepted).collect();
+        assert_eq!(accepted, alloc::vec![true, false, true]);
+        assert_eq!(batch.fills[1].outputs, PublicOutputs::of(&inputs[1]));
+
+        let bytes = batch.to_bytes();
+        assert_eq!(bytes.len(), 4 + 3 * FillOutcome::LEN);
+        assert_eq!(BatchOutputs::from_bytes(&bytes), Some(batch));
+        assert_eq!(BatchOutputs::from_bytes(&bytes[..bytes.len() - 1]), None);
+        assert_eq!(BatchOutputs::from_bytes(&[0; 4]), Some(BatchOutputs::default()));
+    }
+
+    #[test]
+    fn test_constraints_version() {
+        let input = test_input(alloc::vec![evidence("FeedA"), evidence("FeedB")]);
+
+        // An input written before versioning and the newer optional fields
+        // still decodes and validates
+        let mut json: serde_json::Value = serde_json::from_slice(&input.to_json()).unwrap();
+        let constraints = json["constraints"].as_object_mut().unwrap();
+        for field in ["version", "denied_takers", "allowed_taker_shards", "quorum_policy"] {
+            constraints.remove(field);
+        }
+        json.as_object_mut().unwrap().remove("taker_shard");
+        let legacy = RfqLocalLawsInput::from_json(&serde_json::to_vec(&json).unwrap()).unwrap();
+        assert_eq!(legacy.constraints.version, 0);
+        assert!(legacy.constraints.denied_takers.is_empty());
+        assert!(validate_fill(&legacy).is_ok());
+
+        let batch = serde_json::to_vec(&alloc::vec![json.clone(), json]).unwrap();
+        assert_eq!(RfqLocalLawsInput::batch_from_json(&batch).unwrap().len(), 2);
+        let batch = RfqLocalLawsInput::batch_to_json(&[input.clone(), input.clone()]);
+        assert_eq!(RfqLocalLawsInput::batch_from_json(&batch).unwrap().len(), 2);
+
+        // A newer layout than the rules know is refused
+        let mut newer = input;
+        newer.constraints.version = CONSTRAINTS_VERSION + 1;
+        assert!(matches!(
+            validate_fill(&newer),
+            Err(RejectionReason::UnsupportedConstraintsVersion { version, .. })
+                if version == CONSTRAINTS_VERSION + 1
+        ));
     }
 }
@@ -0,0 +1,188 @@
+//! Public outputs of the Local Laws zkVM program
+//!
+//! After a fill passes, the program commits what it validated: the quote,
+//! a hash of its full constraints, the fill's size and price, the taker and
+//! how many feed sources priced it. A verifier holding the receipt can
+//! recompute each of them without re-running validation.
+//!
+//! Encoding (integers little-endian, 116 bytes):
+//! `quote_id (32) || constraints_hash (32) || fill_size (8) || fill_price (8)
+//! || taker_hash (32) || feed_sources (4)`
+//!
+//! The batched program validates many fills in one execution and commits
+//! [`BatchOutputs`] instead, with each fill's outputs and whether it passed:
+//! `fill_count (4) || (outputs (116) || accepted (1))...`
+
+use alloc::collections::BTreeSet;
+use alloc::vec::Vec;
+use rfq_models::{FeedEvidence, QuoteConstraints, RejectionReason};
+use sha3::{Digest, Keccak256};
+
+use crate::RfqLocalLawsInput;
+
+/// What a Local Laws proof commits about the fill it validated
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PublicOutputs {
+    /// The quote the fill is against
+    pub quote_id: [u8; 32],
+    /// [`constraints_hash`] of the quote's constraints
+    pub constraints_hash: [u8; 32],
+    /// The fill size (in smallest units)
+    pub fill_size: u64,
+    /// The fill price (in smallest units)
+    pub fill_price: u64,
+    /// [`taker_hash`] of the taker's owner ID
+    pub taker_hash: [u8; 32],
+    /// Number of distinct sources in the feed evidence
+    pub feed_sources: u32,
+}
+
+impl PublicOutputs {
+    /// Length of the encoded outputs in bytes
+    pub const LEN: usize = 32 + 32 + 8 + 8 + 32 + 4;
+
+    /// The outputs of a validated input
+    pub fn of(input: &RfqLocalLawsInput) -> Self {
+        Self::new(
+            &input.constraints,
+            &input.taker_owner_id,
+            input.fill_size,
+            input.fill_price,
+            &input.feed_evidence,
+        )
+    }
+
+    /// The outputs of a fill of `constraints` by `taker_owner_id`
+    pub fn new(
+        constraints: &QuoteConstraints,
+        taker_owner_id: &str,
+        fill_size: u64,
+        fill_price: u64,
+        feed_evidence: &[FeedEvidence],
+    ) -> Self {
+        let sources: BTreeSet<&str> = feed_evidence.iter().map(|e| e.source.as_str()).collect();
+        Self {
+            quote_id: constraints.quote_id,
+            constraints_hash: constraints_hash(constraints),
+            fill_size,
+            fill_price,
+            taker_hash: taker_hash(taker_owner_id),
+            feed_sources: sources.len() as u32,
+        }
+    }
+
+    /// The committed bytes
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(Self::LEN);
+        bytes.extend_from_slice(&self.quote_id);
+        bytes.extend_from_slice(&self.constraints_hash);
+        bytes.extend_from_slice(&self.fill_size.to_le_bytes());
+        bytes.extend_from_slice(&self.fill_price.to_le_bytes());
+        bytes.extend_from_slice(&self.taker_hash);
+        bytes.extend_from_slice(&self.feed_sources.to_le_bytes());
+        bytes
+    }
+
+    /// Decodes committed bytes (None if they aren't [`Self::LEN`] long)
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != Self::LEN {
+            return None;
+        }
+        let hash = |at: usize| -> [u8; 32] { bytes[at..at + 32].try_into().unwrap() };
+        let word = |at: usize| u64::from_le_bytes(bytes[at..at + 8].try_into().unwrap());
+        Some(Self {
+            quote_id: hash(0),
+            constraints_hash: hash(32),
+            fill_size: word(64),
+            fill_price: word(72),
+            taker_hash: hash(80),
+            feed_sources: u32::from_le_bytes(bytes[112..116].try_into().unwrap()),
+        })
+    }
+}
+
+/// What a batched Local Laws proof commits about one of its fills
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FillOutcome {
+    /// The fill's outputs, as a single-fill proof would commit them
+    pub outputs: PublicOutputs,
+    /// Whether the fill passed validation
+    pub accepted: bool,
+}
+
+impl FillOutcome {
+    /// Length of an encoded outcome in bytes
+    pub const LEN: usize = PublicOutputs::LEN + 1;
+}
+
+/// What a batched Local Laws proof commits, one outcome per fill in order
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BatchOutputs {
+    /// The outcomes, in the order the fills were given
+    pub fills: Vec<FillOutcome>,
+}
+
+impl BatchOutputs {
+    /// The outputs of `inputs`, given their validation `results`
+    pub fn new(inputs: &[RfqLocalLawsInput], results: &[Result<(), RejectionReason>]) -> Self {
+        let fills = inputs
+            .iter()
+            .zip(results)
+            .map(|(input, result)| FillOutcome {
+                outputs: PublicOutputs::of(input),
+                accepted: result.is_ok(),
+            })
+            .collect();
+        Self { fills }
+    }
+
+    /// The committed bytes
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(4 + self.fills.len() * FillOutcome::LEN);
+        bytes.extend_from_slice(&(self.fills.len() as u32).to_le_bytes());
+        for fill in &self.fills {
+            bytes.extend_from_slice(&fill.outputs.to_bytes());
+            bytes.push(fill.accepted as u8);
+        }
+        bytes
+    }
+
+    /// Decodes committed bytes (None if their length doesn't match the
+    /// fill count)
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let (count, rest) = bytes.split_first_chunk::<4>()?;
+        let count = u32::from_le_bytes(*count) as usize;
+        if rest.len() != count.checked_mul(FillOutcome::LEN)? {
+            return None;
+        }
+        let fills = rest
+            .chunks_exact(FillOutcome::LEN)
+            .map(|chunk| {
+                let (outputs, accepted) = chunk.split_at(PublicOutputs::LEN);
+                Some(FillOutcome {
+                    outputs: PublicOutputs::from_bytes(outputs)?,
+                    accepted: match accepted[0] {
+                        0 => false,
+                        1 => true,
+                        _ => return None,
+                    },
+                })
+            })
+            .collect::<Option<_>>()?;
+        Some(Self { fills })
+    }
+}
+
+/// keccak256 of the constraints' JSON
+///
+/// Map fields are ordered, so the same constraints always hash the same,
+/// including after a round trip through a receipt.
+pub fn constraints_hash(constraints: &QuoteConstraints) -> [u8; 32] {
+    let json = serde_json::to_vec(constraints).expect("constraints serialize to JSON");
+    Keccak256::digest(&json).into()
+}
+
+/// keccak256 of a taker's owner ID
+pub fn taker_hash(taker_owner_id: &str) -> [u8; 32] {
+    Keccak256::digest(taker_owner_id.as_bytes()).into()
+}
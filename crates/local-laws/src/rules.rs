@@ -0,0 +1,744 @@
+//! The rule pipeline
+//!
+//! Every entry point runs a [`RuleSet`], in order, and stops at the first
+//! rejection: [`validate_fill`](crate::validate_fill),
+//! [`validate_fills`](crate::validate_fills),
+//! [`validate_acceptance`](crate::validate_acceptance) and the Delta
+//! runtime's [`RfqLocalLaws`](crate::RfqLocalLaws), which adapts the
+//! rejection to a `LocalLawsError`. They differ only in what is known of
+//! the settlement, so a new constraint is one more rule here. Domains with
+//! bespoke checks implement [`FillRule`] and add it to the set.
+
+use alloc::boxed::Box;
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use rfq_models::{
+    deviation_bps, FeedEvidence, FillRecord, QuorumPolicy, QuoteConstraints, RejectionReason,
+    Side, CONSTRAINTS_VERSION,
+};
+
+use crate::transfers::{validate_transfers, Transfer};
+use crate::{benchmark, RfqLocalLawsInput};
+
+/// A check of a fill against its quote's constraints
+///
+/// Custom rules typically read the quote's and the fill's `extensions`.
+pub trait FillRule: Send + Sync {
+    /// The rule's name, unique within a [`RuleSet`]
+    fn name(&self) -> &str;
+
+    /// Accept the fill, or say why it's rejected
+    fn validate(&self, input: &RfqLocalLawsInput) -> Result<(), RejectionReason>;
+
+    /// Whether the rule can run when a benchmark fill is accepted, before
+    /// its settlement price and transfers are known
+    fn at_acceptance(&self) -> bool {
+        true
+    }
+}
+
+/// Where a fill's transfers come from
+pub(crate) enum Transfers<'a> {
+    /// As the caller declared them (`transfer_leg_count` and
+    /// `has_extra_transfers`)
+    Declared,
+    /// As observed in the verifiables, with the debited vaults' nonces
+    /// before the settlement
+    #[cfg_attr(not(feature = "delta-sdk"), allow(dead_code))]
+    Observed {
+        transfers: &'a [Transfer],
+        vault_nonces: &'a BTreeMap<[u8; 32], u64>,
+    },
+}
+
+/// How far a fill has got
+pub(crate) enum Stage<'a> {
+    /// Accepted, with the settlement still to come (a benchmark fill
+    /// waiting for its window): rules that need the price or the transfers
+    /// are skipped
+    Acceptance,
+    /// Settling with these transfers
+    Settlement(Transfers<'a>),
+}
+
+type Check = fn(&RfqLocalLawsInput, &Transfers) -> Result<(), RejectionReason>;
+
+/// One of the crate's own rules
+///
+/// Built-in rules also see the transfers observed by the Delta runtime;
+/// run on their own, they judge the declared transfer pattern.
+pub struct BuiltinRule {
+    name: &'static str,
+    check: Check,
+    at_acceptance: bool,
+}
+
+impl FillRule for BuiltinRule {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn validate(&self, input: &RfqLocalLawsInput) -> Result<(), RejectionReason> {
+        (self.check)(input, &Transfers::Declared)
+    }
+
+    fn at_acceptance(&self) -> bool {
+        self.at_acceptance
+    }
+}
+
+/// The built-in rules, in the order they run
+pub static BUILTIN_RULES: [BuiltinRule; 14] = [
+    BuiltinRule { name: "version", check: check_version, at_acceptance: true },
+    BuiltinRule { name: "denied_taker", check: check_denied_taker, at_acceptance: true },
+    BuiltinRule { name: "expiry", check: check_expiry, at_acceptance: true },
+    BuiltinRule { name: "trading_window", check: check_trading_window, at_acceptance: true },
+    BuiltinRule { name: "taker", check: check_taker, at_acceptance: true },
+    BuiltinRule { name: "fill_caps", check: check_fill_caps, at_acceptance: true },
+    BuiltinRule { name: "fill_size", check: check_fill_size, at_acceptance: true },
+    BuiltinRule { name: "basket_legs", check: check_basket_legs, at_acceptance: true },
+    BuiltinRule { name: "price_limit", check: check_price_limit, at_acceptance: false },
+    BuiltinRule { name: "taker_notional", check: check_taker_notional, at_acceptance: false },
+    BuiltinRule { name: "feed_evidence", check: check_feed_evidence, at_acceptance: true },
+    BuiltinRule { name: "benchmark", check: check_benchmark, at_acceptance: false },
+    BuiltinRule {
+        name: "settlement_window",
+        check: check_settlement_window,
+        at_acceptance: false,
+    },
+    BuiltinRule { name: "transfers", check: check_transfers, at_acceptance: false },
+];
+
+enum Entry {
+    Builtin(&'static BuiltinRule),
+    Custom(Box<dyn FillRule>),
+}
+
+impl Entry {
+    fn rule(&self) -> &dyn FillRule {
+        match self {
+            Entry::Builtin(rule) => *rule,
+            Entry::Custom(rule) => rule.as_ref(),
+        }
+    }
+}
+
+/// An ordered set of rules a fill must pass
+///
+/// The default set is [`BUILTIN_RULES`]; start from it and add or drop
+/// rules by name.
+pub struct RuleSet {
+    rules: Vec<Entry>,
+}
+
+impl RuleSet {
+    /// No rules at all
+    pub fn empty() -> Self {
+        Self { rules: Vec::new() }
+    }
+
+    /// The built-in rules
+    pub fn builtin() -> Self {
+        Self {
+            rules: BUILTIN_RULES.iter().map(Entry::Builtin).collect(),
+        }
+    }
+
+    /// Add a rule, to run after the others (replacing any rule of the
+    /// same name)
+    pub fn with(mut self, rule: impl FillRule + 'static) -> Self {
+        self = self.without(rule.name());
+        self.rules.push(Entry::Custom(Box::new(rule)));
+        self
+    }
+
+    /// Drop the rule with this name, if any
+    pub fn without(mut self, name: &str) -> Self {
+        self.rules.retain(|entry| entry.rule().name() != name);
+        self
+    }
+
+    /// The rules' names, in the order they run
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.rules.iter().map(|entry| entry.rule().name())
+    }
+
+    /// Validate a fill at settlement, against its declared transfers
+    pub fn validate_fill(&self, input: &RfqLocalLawsInput) -> Result<(), RejectionReason> {
+        self.run(input, Stage::Settlement(Transfers::Declared))
+    }
+
+    /// Validate a batch of fills at settlement, each on its own, with one
+    /// result per input
+    pub fn validate_fills(&self, inputs: &[RfqLocalLawsInput]) -> Vec<Result<(), RejectionReason>> {
+        inputs.iter().map(|input| self.validate_fill(input)).collect()
+    }
+
+    /// Validate acceptance of a benchmark fill, skipping the rules that
+    /// need its settlement
+    pub fn validate_acceptance(&self, input: &RfqLocalLawsInput) -> Result<(), RejectionReason> {
+        self.run(input, Stage::Acceptance)
+    }
+
+    /// Run every rule that applies at `stage`, stopping at the first
+    /// rejection
+    pub(crate) fn run(
+        &self,
+        input: &RfqLocalLawsInput,
+        stage: Stage,
+    ) -> Result<(), RejectionReason> {
+        let (transfers, acceptance) = match stage {
+            Stage::Acceptance => (Transfers::Declared, true),
+            Stage::Settlement(transfers) => (transfers, false),
+        };
+        self.rules
+            .iter()
+            .filter(|entry| !acceptance || entry.rule().at_acceptance())
+            .try_for_each(|entry| match entry {
+                Entry::Builtin(rule) => (rule.check)(input, &transfers),
+                Entry::Custom(rule) => rule.validate(input),
+            })
+    }
+}
+
+impl Default for RuleSet {
+    fn default() -> Self {
+        Self::builtin()
+    }
+}
+
+/// Reject constraints with a newer layout than these rules read, whose
+/// new fields would otherwise go unchecked
+fn check_version(input: &RfqLocalLawsInput, _: &Transfers) -> Result<(), RejectionReason> {
+    let version = input.constraints.version;
+    if version > CONSTRAINTS_VERSION {
+        return Err(RejectionReason::UnsupportedConstraintsVersion {
+            version,
+            supported: CONSTRAINTS_VERSION,
+        });
+    }
+    Ok(())
+}
+
+/// Reject takers on the deny list, before any other constraint is checked
+fn check_denied_taker(input: &RfqLocalLawsInput, _: &Transfers) -> Result<(), RejectionReason> {
+    if input.constraints.denies_taker(&input.taker_owner_id) {
+        return Err(RejectionReason::DeniedTaker {
+            taker: input.taker_owner_id.clone(),
+        });
+    }
+    Ok(())
+}
+
+/// Reject fills decided after the quote expired
+fn check_expiry(input: &RfqLocalLawsInput, _: &Transfers) -> Result<(), RejectionReason> {
+    let constraints = &input.constraints;
+    if input.decision_timestamp() > constraints.expiry_timestamp {
+        return Err(RejectionReason::QuoteExpired {
+            expired_at: constraints.expiry_datetime(),
+            attempted_at: chrono::Utc::now(),
+        });
+    }
+    Ok(())
+}
+
+/// Reject fills decided before the quote becomes valid or outside its
+/// daily trading window
+fn check_trading_window(input: &RfqLocalLawsInput, _: &Transfers) -> Result<(), RejectionReason> {
+    let constraints = &input.constraints;
+    let decided_at = input.decision_timestamp();
+    let attempted_at = || {
+        chrono::DateTime::from_timestamp(decided_at as i64, 0).unwrap_or_else(chrono::Utc::now)
+    };
+
+    if let Some(valid_from) = constraints.valid_from_timestamp {
+        if decided_at < valid_from {
+            return Err(RejectionReason::QuoteNotYetValid {
+                valid_from: constraints.valid_from_datetime().unwrap_or_else(chrono::Utc::now),
+                attempted_at: attempted_at(),
+            });
+        }
+    }
+    if let Some(window) = &constraints.trading_window {
+        if !window.contains(decided_at) {
+            return Err(RejectionReason::OutsideTradingWindow {
+                window: format!("{}", window),
+                attempted_at: attempted_at(),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Reject takers outside the allowlist or on a shard the quote excludes
+fn check_taker(input: &RfqLocalLawsInput, _: &Transfers) -> Result<(), RejectionReason> {
+    let constraints = &input.constraints;
+    if !constraints.allows_taker(&input.taker_owner_id) {
+        return Err(RejectionReason::UnauthorizedTaker {
+            taker: input.taker_owner_id.clone(),
+            allowed_takers: constraints.allowed_takers.clone(),
+        });
+    }
+    if !constraints.allows_taker_shard(input.taker_shard) {
+        return Err(RejectionReason::UnauthorizedShard {
+            taker: input.taker_owner_id.clone(),
+            taker_shard: input.taker_shard,
+            allowed_shards: constraints.allowed_taker_shards.clone(),
+        });
+    }
+    Ok(())
+}
+
+/// Validate the total and per-taker fill caps, and an iceberg's hidden
+/// total size, against the fill history
+fn check_fill_caps(input: &RfqLocalLawsInput, _: &Transfers) -> Result<(), RejectionReason> {
+    let constraints = &input.constraints;
+
+    let fills = u32::try_from(input.fill_history.len()).unwrap_or(u32::MAX);
+    if fills >= constraints.max_fills {
+        return Err(RejectionReason::FillLimitReached {
+            fills,
+            max_fills: constraints.max_fills,
+        });
+    }
+
+    if let Some(max_fills_per_taker) = constraints.max_fills_per_taker {
+        let taker_fills = input
+            .fill_history
+            .iter()
+            .filter(|f| f.taker_owner_id == input.taker_owner_id)
+            .count();
+        let taker_fills = u32::try_from(taker_fills).unwrap_or(u32::MAX);
+        if taker_fills >= max_fills_per_taker {
+            return Err(RejectionReason::TakerFillLimitReached {
+                taker: input.taker_owner_id.clone(),
+                taker_fills,
+                max_fills_per_taker,
+            });
+        }
+    }
+
+    if let Some(iceberg) = &constraints.iceberg {
+        let remaining = constraints
+            .iceberg_remaining(&input.fill_history)
+            .unwrap_or_default();
+        if input.fill_size > remaining {
+            return Err(RejectionReason::IcebergSizeExhausted {
+                filled_size: iceberg.total_size - remaining,
+                offered_size: input.fill_size,
+                total_size: iceberg.total_size,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Check the fill size against the maximum, cumulatively for partial
+/// fills, and the minimum
+fn check_fill_size(input: &RfqLocalLawsInput, _: &Transfers) -> Result<(), RejectionReason> {
+    let constraints = &input.constraints;
+
+    if input.fill_size > constraints.max_fill_size {
+        return Err(RejectionReason::SizeExceedsMax {
+            offered_size: input.fill_size as f64,
+            max_size: constraints.max_fill_size as f64,
+        });
+    }
+    let partial_remaining = constraints.partial_remaining(input.filled_so_far);
+    if partial_remaining.is_some_and(|remaining| input.fill_size > remaining) {
+        return Err(RejectionReason::RemainingSizeExceeded {
+            filled_size: input.filled_so_far,
+            offered_size: input.fill_size,
+            max_size: constraints.max_fill_size,
+        });
+    }
+    if let Some(min_fill_size) = constraints.min_fill_size {
+        // The last of an iceberg or a partially filled quote may be
+        // smaller than the minimum
+        let min_size = constraints
+            .iceberg_remaining(&input.fill_history)
+            .or(partial_remaining)
+            .map_or(min_fill_size, |remaining| remaining.min(min_fill_size));
+        if input.fill_size < min_size {
+            return Err(RejectionReason::SizeBelowMin {
+                offered_size: input.fill_size as f64,
+                min_size: min_size as f64,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Validate that a basket fill moves each further leg in proportion to its
+/// size, and no other asset
+fn check_basket_legs(input: &RfqLocalLawsInput, _: &Transfers) -> Result<(), RejectionReason> {
+    let constraints = &input.constraints;
+
+    for asset in constraints.leg_max_fill_sizes.keys() {
+        let expected_size = constraints
+            .leg_fill_size(asset, input.fill_size)
+            .unwrap_or_default();
+        let offered_size = input.leg_fill_sizes.get(asset).copied().unwrap_or_default();
+        if offered_size != expected_size {
+            return Err(RejectionReason::BasketLegMismatch {
+                asset: asset.clone(),
+                offered_size,
+                expected_size,
+            });
+        }
+    }
+
+    let unknown = input
+        .leg_fill_sizes
+        .iter()
+        .find(|(asset, _)| !constraints.leg_max_fill_sizes.contains_key(*asset));
+    if let Some((asset, &offered_size)) = unknown {
+        return Err(RejectionReason::BasketLegMismatch {
+            asset: asset.clone(),
+            offered_size,
+            expected_size: 0,
+        });
+    }
+
+    Ok(())
+}
+
+/// Check the price against the quote's limit: the max debit of a buy, per
+/// fill and across an iceberg's fills, or the min credit of a sell
+fn check_price_limit(input: &RfqLocalLawsInput, _: &Transfers) -> Result<(), RejectionReason> {
+    let constraints = &input.constraints;
+
+    if constraints.side == Side::Sell {
+        return match constraints.min_credit_for(input.fill_size) {
+            Some(min_credit) if input.fill_price < min_credit => {
+                Err(RejectionReason::PriceBelowLimit {
+                    offered_price: input.fill_price as f64,
+                    limit_price: min_credit as f64,
+                })
+            }
+            _ => Ok(()),
+        };
+    }
+
+    if input.fill_price > constraints.max_debit {
+        return Err(RejectionReason::PriceExceedsLimit {
+            offered_price: input.fill_price as f64,
+            limit_price: constraints.max_debit as f64,
+        });
+    }
+    if let Some(total_debit) = constraints.iceberg.and_then(|iceberg| iceberg.total_debit) {
+        let debited = input
+            .fill_history
+            .iter()
+            .map(|f| f.fill_price)
+            .fold(0u64, u64::saturating_add);
+        let total = debited
+            .checked_add(input.fill_price)
+            .ok_or_else(|| overflow("iceberg's total debit"))?;
+        if total > total_debit {
+            return Err(RejectionReason::IcebergDebitExhausted {
+                debited,
+                offered_price: input.fill_price,
+                total_debit,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Cap how much a single taker trades, across this fill and their earlier
+/// ones
+fn check_taker_notional(input: &RfqLocalLawsInput, _: &Transfers) -> Result<(), RejectionReason> {
+    let Some(max_notional_per_taker) = input.constraints.max_notional_per_taker else {
+        return Ok(());
+    };
+    let filled_notional = input
+        .taker_filled_notional
+        .max(FillRecord::taker_notional(&input.fill_history, &input.taker_owner_id));
+    let total = filled_notional
+        .checked_add(input.fill_price)
+        .ok_or_else(|| overflow("taker's notional"))?;
+    if total > max_notional_per_taker {
+        return Err(RejectionReason::TakerNotionalExceeded {
+            taker: input.taker_owner_id.clone(),
+            filled_notional,
+            offered_price: input.fill_price,
+            max_notional_per_taker,
+        });
+    }
+    Ok(())
+}
+
+/// Check the feed evidence: quorum, sources, signatures, freshness, the
+/// sources' agreement and the fill price's distance from the quorum price
+fn check_feed_evidence(input: &RfqLocalLawsInput, _: &Transfers) -> Result<(), RejectionReason> {
+    let constraints = &input.constraints;
+
+    // Check quorum count, counting each source once
+    let sources: BTreeSet<&str> = input.feed_evidence.iter().map(|e| e.source.as_str()).collect();
+    if sources.len() < constraints.quorum_count as usize {
+        return Err(RejectionReason::QuorumNotMet {
+            sources_provided: sources.len(),
+            quorum_required: constraints.quorum_count,
+            price_spread_bps: None,
+            max_tolerance_bps: constraints.quorum_tolerance_bps,
+        });
+    }
+
+    let mut valid_prices: Vec<(&str, u64)> = Vec::new();
+    let mut seen = BTreeSet::new();
+
+    for evidence in &input.feed_evidence {
+        // Check each source reports once
+        if !seen.insert(evidence.source.as_str()) {
+            return Err(RejectionReason::DuplicateFeedSource {
+                source: evidence.source.clone(),
+            });
+        }
+
+        // Check source allowlist
+        if !constraints.allowed_sources.is_empty()
+            && !constraints.allowed_sources.contains(&evidence.source)
+        {
+            return Err(RejectionReason::UnauthorizedSource {
+                source: evidence.source.clone(),
+                allowed_sources: constraints.allowed_sources.clone(),
+            });
+        }
+
+        // Check the evidence prices an asset the quote trades
+        if !constraints.allows_asset(&evidence.asset) {
+            return Err(RejectionReason::FeedAssetMismatch {
+                source: evidence.source.clone(),
+                asset: evidence.asset.clone(),
+                allowed_assets: constraints.allowed_assets.clone(),
+            });
+        }
+
+        check_feed_signature(constraints, evidence)?;
+
+        // Check freshness, allowing for clock skew either way
+        let decided_at = input.decision_timestamp();
+        if evidence.timestamp > decided_at.saturating_add(input.clock_skew_secs) {
+            return Err(RejectionReason::FutureFeed {
+                source: evidence.source.clone(),
+                feed_timestamp: evidence.timestamp,
+                current_timestamp: decided_at,
+                max_skew_secs: input.clock_skew_secs,
+            });
+        }
+        let age = decided_at.saturating_sub(evidence.timestamp);
+        let max_staleness_secs = constraints.max_staleness_for(&evidence.source);
+        if age > max_staleness_secs.saturating_add(input.clock_skew_secs) {
+            return Err(RejectionReason::StaleFeed {
+                source: evidence.source.clone(),
+                feed_timestamp: evidence.timestamp,
+                current_timestamp: decided_at,
+                max_staleness_secs,
+            });
+        }
+
+        valid_prices.push((evidence.source.as_str(), evidence.price));
+    }
+
+    // Check price quorum
+    let quorum_price = quorum_price(constraints, &valid_prices)?;
+
+    // Check the fill price against the feeds (a benchmark quote's price is
+    // checked against its TWAP instead)
+    let band = constraints.max_price_deviation_bps.filter(|_| constraints.benchmark.is_none());
+    let fill_price = constraints.unit_price(input.fill_size, input.fill_price);
+    // Only an empty fill has no unit price; otherwise it overflowed
+    if band.is_some() && fill_price.is_none() && input.fill_size > 0 {
+        return Err(overflow("fill's unit price"));
+    }
+    if let (Some(max_deviation), Some(fill_price)) = (band, fill_price) {
+        let deviation = quorum_price.and_then(|price| deviation_bps(fill_price, price));
+        if let (Some(quorum_price), Some(deviation)) = (quorum_price, deviation) {
+            if deviation > u64::from(max_deviation) {
+                return Err(RejectionReason::PriceDeviationExceeded {
+                    fill_price,
+                    median_price: quorum_price,
+                    deviation_bps: deviation,
+                    max_deviation_bps: max_deviation,
+                });
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Check a benchmark fill's settlement amount against its TWAP
+fn check_benchmark(input: &RfqLocalLawsInput, _: &Transfers) -> Result<(), RejectionReason> {
+    match &input.constraints.benchmark {
+        Some(benchmark) => benchmark::validate_benchmark(input, benchmark),
+        None => Ok(()),
+    }
+}
+
+/// Check the fill settles within its window after acceptance
+fn check_settlement_window(
+    input: &RfqLocalLawsInput,
+    _: &Transfers,
+) -> Result<(), RejectionReason> {
+    let Some(window_secs) = input.constraints.settlement_window_secs else {
+        return Ok(());
+    };
+    let accepted_at = input.decision_timestamp();
+    if input.current_timestamp > accepted_at.saturating_add(window_secs) {
+        return Err(RejectionReason::SettlementWindowExceeded {
+            accepted_at,
+            settled_at: input.current_timestamp,
+            window_secs,
+        });
+    }
+    Ok(())
+}
+
+/// Check the transfer pattern and side-payments, against the observed
+/// transfers when there are any
+fn check_transfers(
+    input: &RfqLocalLawsInput,
+    transfers: &Transfers,
+) -> Result<(), RejectionReason> {
+    let constraints = &input.constraints;
+
+    if let Transfers::Observed { transfers, vault_nonces } = transfers {
+        return validate_transfers(input, transfers, vault_nonces);
+    }
+
+    // A declared third leg is the fee, if the quote allows one
+    let fee_legs = usize::from(constraints.allowed_fee.is_some());
+    let legs = input.transfer_leg_count;
+    if constraints.require_atomic_dvp && !(2..=2 + fee_legs).contains(&legs) {
+        let expected = if fee_legs > 0 {
+            "2 legs (atomic DvP), or 3 with a fee"
+        } else {
+            "2 legs (atomic DvP)"
+        };
+        return Err(RejectionReason::InvalidTransferPattern {
+            expected: String::from(expected),
+            actual: format!("{} legs", legs),
+        });
+    }
+    if constraints.no_side_payments && input.has_extra_transfers {
+        return Err(RejectionReason::SidePaymentDetected {
+            description: String::from("Extra transfers detected outside expected pattern"),
+        });
+    }
+
+    Ok(())
+}
+
+/// The price the sources' quorum agrees on, under the quote's policy
+/// (None if there are no prices)
+fn quorum_price(
+    constraints: &QuoteConstraints,
+    priced: &[(&str, u64)],
+) -> Result<Option<u64>, RejectionReason> {
+    let mut prices: Vec<u64> = priced.iter().map(|(_, price)| *price).collect();
+    prices.sort_unstable();
+    let tolerance = u64::from(constraints.quorum_tolerance_bps);
+    let not_met = |sources_provided, price_spread_bps| RejectionReason::QuorumNotMet {
+        sources_provided,
+        quorum_required: constraints.quorum_count,
+        price_spread_bps,
+        max_tolerance_bps: constraints.quorum_tolerance_bps,
+    };
+
+    let policy = &constraints.quorum_policy;
+    let reference = match policy {
+        QuorumPolicy::Spread => {
+            if let (Some(&min_price), Some(&max_price)) = (prices.first(), prices.last()) {
+                let spread = deviation_bps(max_price, min_price).filter(|bps| *bps > tolerance);
+                if let Some(spread) = spread {
+                    return Err(not_met(prices.len(), Some(spread)));
+                }
+            }
+            return Ok(median(&prices));
+        }
+        QuorumPolicy::Median => median(&prices),
+        QuorumPolicy::TrimmedMean { trim } => {
+            let trim = *trim as usize;
+            let kept = prices.get(trim..prices.len().saturating_sub(trim)).unwrap_or_default();
+            weighted_mean(kept.iter().map(|price| (*price, 1)))
+        }
+        QuorumPolicy::Weighted { .. } => weighted_mean(
+            priced
+                .iter()
+                .map(|(source, price)| (*price, policy.weight(source))),
+        ),
+    };
+    let Some(reference) = reference else {
+        // Every price was trimmed, or the sources weigh nothing
+        return if prices.is_empty() { Ok(None) } else { Err(not_met(0, None)) };
+    };
+
+    // Sources too far from the reference price don't count towards quorum
+    let deviations: Vec<u64> = prices
+        .iter()
+        .map(|price| deviation_bps(*price, reference).unwrap_or(u64::MAX))
+        .collect();
+    let agreeing = deviations.iter().filter(|bps| **bps <= tolerance).count();
+    if agreeing < constraints.quorum_count as usize {
+        return Err(not_met(agreeing, deviations.iter().max().copied()));
+    }
+    Ok(Some(reference))
+}
+
+/// Mean of prices by weight (None if the weights add up to 0)
+fn weighted_mean(weighted: impl Iterator<Item = (u64, u32)>) -> Option<u64> {
+    let (sum, weight) = weighted.fold((0u128, 0u128), |(sum, total), (price, weight)| {
+        (sum + u128::from(price) * u128::from(weight), total + u128::from(weight))
+    });
+    (weight > 0).then(|| (sum / weight) as u64)
+}
+
+/// Check a feed report's signature against its source's key
+///
+/// A source without a key is only accepted, unverified, when the quote
+/// allows unsigned feeds.
+pub(crate) fn check_feed_signature(
+    constraints: &QuoteConstraints,
+    evidence: &FeedEvidence,
+) -> Result<(), RejectionReason> {
+    match constraints.feed_keys.get(&evidence.source) {
+        Some(key) => {
+            let message = evidence.signed_message();
+            key.verify(message.as_bytes(), &evidence.signature)
+                .map_err(|e| RejectionReason::InvalidFeedSignature {
+                    source: evidence.source.clone(),
+                    reason: format!("{}", e),
+                })
+        }
+        None if constraints.allow_unsigned_feeds => Ok(()),
+        None => Err(RejectionReason::UnkeyedFeedSource {
+            source: evidence.source.clone(),
+        }),
+    }
+}
+
+/// Rejection for an amount too large to compute with
+pub(crate) fn overflow(quantity: &str) -> RejectionReason {
+    RejectionReason::NumericOverflow {
+        quantity: String::from(quantity),
+    }
+}
+
+/// Median of sorted prices (None if there are none)
+fn median(sorted: &[u64]) -> Option<u64> {
+    let mid = sorted.len() / 2;
+    match sorted.len() {
+        0 => None,
+        n if n % 2 == 0 => {
+            Some(((u128::from(sorted[mid - 1]) + u128::from(sorted[mid])) / 2) as u64)
+        }
+        _ => Some(sorted[mid]),
+    }
+}
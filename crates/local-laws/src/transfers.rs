@@ -0,0 +1,155 @@
+//! Settlement transfers
+//!
+//! The Delta runtime hands Local Laws the verifiables a fill settles with.
+//! A [`Transfer`] is the part of each that the guardrails look at, so the
+//! checks here run the same with or without the SDK: whoever sells the
+//! asset must be credited the fill price, whoever buys it the fill size,
+//! and nothing else may move but the one fee leg a quote may allow. Each
+//! transfer must also use a fresh nonce of the vault it debits, so a
+//! settlement that was already proven can't be replayed.
+
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use rfq_models::signing::to_hex;
+use rfq_models::RejectionReason;
+use serde::{Deserialize, Serialize};
+
+use crate::RfqLocalLawsInput;
+
+/// One credit in a fill's settlement
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Transfer {
+    /// Owner ID of the debited vault
+    pub debited: [u8; 32],
+    /// Owner ID of the credited vault
+    pub credited: [u8; 32],
+    /// Amount credited (in smallest units)
+    pub amount: u64,
+    /// The debited vault's nonce after the transfer
+    pub nonce: u64,
+}
+
+/// The owners a fill's transfers must credit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Counterparties {
+    /// Credited with the fill size of a buy quote, the fill price of a sell
+    pub maker: [u8; 32],
+    /// Credited with the other leg
+    pub taker: [u8; 32],
+}
+
+/// Validate the transfers a fill actually settles with against its terms
+///
+/// The maker of a buy quote must be credited `fill_size` and the taker
+/// `fill_price`; a sell quote's legs run the other way (either owner may be
+/// credited, if the input names no counterparties). One transfer the quote's
+/// `allowed_fee` covers may come with them, at most `max_fee_bps` of the
+/// fill price; any other transfer is a side-payment. `vault_nonces` are the
+/// debited vaults' nonces before the settlement, by owner ID; each
+/// transfer's nonce must be above its vault's, and a transfer from a vault
+/// missing there is rejected.
+pub fn validate_transfers(
+    input: &RfqLocalLawsInput,
+    transfers: &[Transfer],
+    vault_nonces: &BTreeMap<[u8; 32], u64>,
+) -> Result<(), RejectionReason> {
+    let constraints = &input.constraints;
+    let is_fee = |t: &Transfer| {
+        constraints
+            .allowed_fee
+            .is_some_and(|fee| fee.covers(&t.credited, t.amount))
+    };
+
+    if constraints.require_atomic_dvp {
+        let (legs, expected) = if transfers.iter().any(is_fee) {
+            (3, "3 legs (atomic DvP and a fee)")
+        } else {
+            (2, "2 legs (atomic DvP)")
+        };
+        if transfers.len() != legs {
+            return Err(RejectionReason::InvalidTransferPattern {
+                expected: String::from(expected),
+                actual: format!("{} legs", transfers.len()),
+            });
+        }
+    }
+
+    let parties = input.counterparties;
+    // Each party is credited what the other pays
+    let (taker_credit, maker_credit) = constraints.side.legs(input.fill_size, input.fill_price);
+    let legs = [
+        ("taker", parties.map(|p| p.taker), taker_credit),
+        ("maker", parties.map(|p| p.maker), maker_credit),
+    ];
+    let mut unmatched: Vec<&Transfer> = transfers.iter().collect();
+    for (party, owner, amount) in legs {
+        let leg = unmatched
+            .iter()
+            .position(|t| t.amount == amount && owner.is_none_or(|o| o == t.credited));
+        match leg {
+            Some(i) => {
+                unmatched.remove(i);
+            }
+            None => {
+                return Err(RejectionReason::InvalidTransferPattern {
+                    expected: format!("{} credited {}", party, amount),
+                    actual: describe(transfers),
+                })
+            }
+        }
+    }
+
+    // At most one fee leg, within the maker's maximum fee
+    if let Some(i) = unmatched.iter().position(|t| is_fee(t)) {
+        let fee = unmatched.remove(i).amount;
+        if let Some(max_fee) = constraints.max_fee(input.fill_price).filter(|max| fee > *max) {
+            return Err(RejectionReason::FeeExceedsLimit { fee, max_fee });
+        }
+    }
+
+    if let Some(extra) = unmatched.first().filter(|_| constraints.no_side_payments) {
+        return Err(RejectionReason::SidePaymentDetected {
+            description: format!(
+                "Transfer of {} to {} outside the DvP legs",
+                extra.amount,
+                owner_prefix(&extra.credited)
+            ),
+        });
+    }
+
+    for transfer in transfers {
+        // A vault whose nonce is unknown can't be shown not to be replayed
+        let Some(&vault_nonce) = vault_nonces.get(&transfer.debited) else {
+            return Err(RejectionReason::UnknownVaultNonce {
+                vault: owner_prefix(&transfer.debited),
+            });
+        };
+        if transfer.nonce <= vault_nonce {
+            return Err(RejectionReason::ReplayDetected {
+                nonce: transfer.nonce,
+                vault_nonce,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Transfers as displayed in a rejection, e.g. "1950 to 3f2a9c01, 5 to 77b0e4d2"
+fn describe(transfers: &[Transfer]) -> String {
+    if transfers.is_empty() {
+        return String::from("no transfers");
+    }
+    transfers
+        .iter()
+        .map(|t| format!("{} to {}", t.amount, owner_prefix(&t.credited)))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// First bytes of an owner ID in hex, enough to tell vaults apart
+fn owner_prefix(owner: &[u8; 32]) -> String {
+    to_hex(&owner[..4])
+}
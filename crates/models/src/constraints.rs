@@ -2,21 +2,41 @@
 //!
 //! These constraints are enforced by Local Laws during settlement.
 
+use alloc::collections::BTreeMap;
 use alloc::string::String;
 use alloc::vec;
 use alloc::vec::Vec;
 use chrono::{DateTime, Utc};
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::signing::PublicKey;
+use crate::Side;
 
 /// The constraints (guardrails) for a quote
 ///
 /// These are compiled from the maker's English quote text
 /// and enforced at settlement time by Local Laws.
+///
+/// Constraints written with an older layout are migrated as they are read.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(remote = "Self")]
 pub struct QuoteConstraints {
+    /// Layout version the constraints were written with (0 if written
+    /// before versioning)
+    ///
+    /// Local Laws refuse versions newer than [`CONSTRAINTS_VERSION`], whose
+    /// fields they would silently ignore.
+    #[serde(default)]
+    pub version: u32,
+
     /// Unique identifier linking to the quote
     pub quote_id: [u8; 32],
 
+    /// Whether the maker buys or sells the asset, which decides the
+    /// direction of the price checks
+    #[serde(default = "default_side")]
+    pub side: Side,
+
     /// Maximum amount that can be debited (in smallest units)
     /// For a buy order, this is the max price * size
     pub max_debit: u64,
@@ -28,21 +48,65 @@ pub struct QuoteConstraints {
     /// When the quote expires (Unix timestamp)
     pub expiry_timestamp: u64,
 
+    /// When the quote becomes fillable (Unix timestamp; None = at once)
+    #[serde(default)]
+    pub valid_from_timestamp: Option<u64>,
+
+    /// Daily hours in which the quote can be filled (None = any time)
+    #[serde(default)]
+    pub trading_window: Option<TradingWindow>,
+
     /// Allowed price feed sources (e.g., ["FeedA", "FeedB"])
     pub allowed_sources: Vec<String>,
 
+    /// Public keys of feed sources, by source name
+    ///
+    /// All feed evidence must be signed by its source's key; evidence from
+    /// a source without one is rejected unless `allow_unsigned_feeds` is set.
+    #[serde(default)]
+    pub feed_keys: BTreeMap<String, PublicKey>,
+
+    /// Accept feed evidence from sources without a key in `feed_keys`,
+    /// unverified (for development against mock feeds only)
+    ///
+    /// Evidence from sources with a key is still checked against it.
+    #[serde(default)]
+    pub allow_unsigned_feeds: bool,
+
     /// Maximum age of price feed data in seconds
     pub max_staleness_secs: u64,
 
+    /// Maximum age of a source's feed data in seconds, overriding
+    /// `max_staleness_secs` for the sources listed
+    #[serde(default)]
+    pub source_staleness_secs: BTreeMap<String, u64>,
+
     /// Minimum number of sources that must agree (quorum)
     pub quorum_count: u32,
 
-    /// Maximum percentage difference between sources for quorum
-    pub quorum_tolerance_percent: f64,
+    /// Maximum difference between sources' prices for quorum, in basis points
+    ///
+    /// Read by `LegacyLayout`, which also accepts the percentage older
+    /// layouts wrote.
+    #[serde(default, skip_deserializing)]
+    pub quorum_tolerance_bps: u32,
+
+    /// How sources' prices are combined into the quorum price
+    #[serde(default)]
+    pub quorum_policy: QuorumPolicy,
 
     /// Allowed taker owner IDs (empty = any taker allowed)
     pub allowed_takers: Vec<String>,
 
+    /// Taker owner IDs that may never fill, even if allowed above (e.g. a
+    /// sanctions list)
+    #[serde(default)]
+    pub denied_takers: Vec<String>,
+
+    /// Shards the taker's vault must be on (empty = any shard)
+    #[serde(default)]
+    pub allowed_taker_shards: Vec<u64>,
+
     /// Allowed asset token IDs that can be transferred
     pub allowed_assets: Vec<String>,
 
@@ -54,38 +118,383 @@ pub struct QuoteConstraints {
     /// If true, only the main asset exchange is allowed
     pub no_side_payments: bool,
 
-    /// The nonce for replay protection (quote can only be filled once)
-    pub nonce: u64,
+    /// A fee transfer the settlement may make besides the DvP legs (None =
+    /// no fee)
+    ///
+    /// Neither an atomic DvP nor `no_side_payments` rejects one such leg.
+    #[serde(default)]
+    pub allowed_fee: Option<AllowedFee>,
 
     /// Maximum size that can be filled
     pub max_fill_size: u64,
+
+    /// Maximum size per fill of each further leg of a basket, by asset (in
+    /// smallest units; empty = single-asset quote)
+    ///
+    /// A fill delivers every leg in proportion to its share of `max_fill_size`.
+    #[serde(default)]
+    pub leg_max_fill_sizes: BTreeMap<String, u64>,
+
+    /// Maximum number of fills across all takers
+    #[serde(default = "default_max_fills")]
+    pub max_fills: u32,
+
+    /// Maximum number of fills per taker (None = only the total cap applies)
+    #[serde(default)]
+    pub max_fills_per_taker: Option<u32>,
+
+    /// Largest amount one taker may trade across their fills (in the
+    /// currency's smallest units; None = uncapped)
+    #[serde(default)]
+    pub max_notional_per_taker: Option<u64>,
+
+    /// Smallest size a single fill may take (None = no minimum)
+    #[serde(default)]
+    pub min_fill_size: Option<u64>,
+
+    /// Whether a fill may take less than the full size, leaving the rest open
+    #[serde(default)]
+    pub allow_partial_fills: bool,
+
+    /// Seconds after acceptance within which a fill must settle (None = no
+    /// deadline)
+    ///
+    /// A benchmark fill settles when its window closes; a spot fill settles
+    /// as it is accepted.
+    #[serde(default)]
+    pub settlement_window_secs: Option<u64>,
+
+    /// Maximum distance of the fill price from the quorum price (the feed
+    /// median by default), in basis points
+    #[serde(default)]
+    pub max_price_deviation_bps: Option<u32>,
+
+    /// Decimals of the asset and of the currency, to read a fill's unit
+    /// price from its smallest-unit amounts
+    #[serde(default = "default_decimals")]
+    pub asset_decimals: u8,
+    #[serde(default = "default_decimals")]
+    pub currency_decimals: u8,
+
+    /// Largest fee leg the maker tolerates, in basis points of the fill
+    /// price
+    #[serde(default)]
+    pub max_fee_bps: Option<u32>,
+
+    /// Settle at a benchmark price instead of spot (None = spot settlement)
+    #[serde(default)]
+    pub benchmark: Option<TwapBenchmark>,
+
+    /// Hidden total of an iceberg quote, which shows `max_fill_size` at a
+    /// time (None = not an iceberg)
+    #[serde(default)]
+    pub iceberg: Option<Iceberg>,
+
+    /// Deployment-specific rule data, consumed by custom rules
+    #[serde(default)]
+    pub extensions: Extensions,
+
+    /// Which compiler produced these constraints (None if built by hand)
+    #[serde(default)]
+    pub compiled_by: Option<CompilerStamp>,
+}
+
+impl Serialize for QuoteConstraints {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        // The derived impl, generated as an inherent function by
+        // `remote = "Self"`
+        QuoteConstraints::serialize(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for QuoteConstraints {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        LegacyLayout::deserialize(deserializer)?.migrate()
+    }
+}
+
+/// [`QuoteConstraints`] as any layout version wrote them
+///
+/// Fields whose unit changed are read under every name they had and
+/// converted: version 0 wrote the quorum tolerance as
+/// `quorum_tolerance_percent`, and versions 1 to 3 the fee cap as
+/// `max_fee_percent`. The current names win if both are present.
+#[derive(Deserialize)]
+struct LegacyLayout {
+    #[serde(flatten, with = "QuoteConstraints")]
+    constraints: QuoteConstraints,
+    quorum_tolerance_bps: Option<u32>,
+    quorum_tolerance_percent: Option<f64>,
+    max_fee_percent: Option<f64>,
+}
+
+impl LegacyLayout {
+    fn migrate<E: serde::de::Error>(self) -> Result<QuoteConstraints, E> {
+        let mut constraints = self.constraints;
+        constraints.quorum_tolerance_bps = self
+            .quorum_tolerance_bps
+            .or(self.quorum_tolerance_percent.map(percent_to_bps))
+            .ok_or_else(|| E::missing_field("quorum_tolerance_bps"))?;
+        if constraints.max_fee_bps.is_none() {
+            constraints.max_fee_bps = self.max_fee_percent.map(percent_to_bps);
+        }
+        Ok(constraints)
+    }
+}
+
+/// Which compiler produced a set of constraints
+///
+/// Part of the constraints, and so of every receipt over them, so a dispute
+/// can establish how a quote's guardrails were compiled even after
+/// compilation behavior changes between releases.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CompilerStamp {
+    /// Version of the rfq-compiler crate
+    pub compiler_version: String,
+    /// How the text was compiled ("grammar", "dsl", "cache" or "llm")
+    pub method: String,
+    /// Provider and model whose answer was used, as `provider/model`
+    #[serde(default)]
+    pub model: Option<String>,
+    /// SHA-256 of the prompt template, hex (LLM and cached compilations)
+    #[serde(default)]
+    pub prompt_hash: Option<String>,
+}
+
+/// Deployment-specific rule data, by name
+///
+/// A BTreeMap so entries always serialize in key order, keeping the
+/// serialized constraints (and proofs over them) canonical.
+pub type Extensions = BTreeMap<String, ExtensionValue>;
+
+/// A value in [`Extensions`]
+///
+/// There are no floats: fractional amounts should be fixed-point integers,
+/// so equal values always serialize to the same bytes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExtensionValue {
+    Bool(bool),
+    Int(i64),
+    Text(String),
+    Bytes(Vec<u8>),
+    List(Vec<ExtensionValue>),
+    Map(BTreeMap<String, ExtensionValue>),
+}
+
+/// A time-weighted average price benchmark
+///
+/// Fills are accepted immediately and settle once the window after
+/// acceptance has elapsed, at the TWAP observed over that window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TwapBenchmark {
+    /// Length of the averaging window after acceptance, in seconds
+    pub window_secs: u64,
+    /// Minimum number of feed samples the window must contain
+    pub min_samples: u32,
+}
+
+/// The hidden size behind an iceberg quote
+///
+/// Only `max_fill_size` is on display. After each fill the display
+/// refills, until the fills together reach these totals.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Iceberg {
+    /// Largest size filled across all fills (in smallest units)
+    pub total_size: u64,
+    /// Largest amount debited across all fills (None = unbounded)
+    #[serde(default)]
+    pub total_debit: Option<u64>,
+}
+
+/// A fee leg a settlement may pay
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AllowedFee {
+    /// Owner ID of the vault the fee is credited to
+    pub recipient: [u8; 32],
+    /// Largest fee (in smallest units)
+    pub max_amount: u64,
+}
+
+impl AllowedFee {
+    /// Whether crediting `amount` to `credited` is this fee
+    pub fn covers(&self, credited: &[u8; 32], amount: u64) -> bool {
+        *credited == self.recipient && amount <= self.max_amount
+    }
+}
+
+/// A daily trading window, in UTC
+///
+/// Times are seconds after midnight; a window that closes before it opens
+/// runs past midnight (e.g. 22:00 to 02:00).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TradingWindow {
+    /// When the window opens
+    pub start_secs: u32,
+    /// When the window closes (exclusive)
+    pub end_secs: u32,
+}
+
+impl TradingWindow {
+    /// Seconds in a day
+    pub const DAY_SECS: u32 = 86_400;
+
+    /// Whether a Unix timestamp falls in the window
+    pub fn contains(&self, timestamp: u64) -> bool {
+        let time = (timestamp % u64::from(Self::DAY_SECS)) as u32;
+        if self.start_secs <= self.end_secs {
+            (self.start_secs..self.end_secs).contains(&time)
+        } else {
+            time >= self.start_secs || time < self.end_secs
+        }
+    }
+}
+
+impl core::fmt::Display for TradingWindow {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let hhmm = |secs: u32| (secs / 3600, secs % 3600 / 60);
+        let (start_h, start_m) = hhmm(self.start_secs);
+        let (end_h, end_m) = hhmm(self.end_secs);
+        write!(f, "{:02}:{:02}-{:02}:{:02} UTC", start_h, start_m, end_h, end_m)
+    }
+}
+
+/// How a quorum combines its sources' prices
+///
+/// Every policy yields a reference price the fill price is held to. Apart
+/// from `Spread`, sources further than the tolerance from the reference
+/// are left out, and `quorum_count` sources must remain.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QuorumPolicy {
+    /// All prices within the tolerance of each other, around their median
+    #[default]
+    Spread,
+    /// The median price
+    Median,
+    /// The mean price without the `trim` lowest and `trim` highest
+    TrimmedMean { trim: u32 },
+    /// The mean price weighted by source (unlisted sources weigh 1)
+    Weighted { weights: BTreeMap<String, u32> },
+}
+
+impl QuorumPolicy {
+    /// A source's weight in a weighted mean
+    pub fn weight(&self, source: &str) -> u32 {
+        match self {
+            Self::Weighted { weights } => weights.get(source).copied().unwrap_or(1),
+            _ => 1,
+        }
+    }
+}
+
+impl core::fmt::Display for QuorumPolicy {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Spread => write!(f, "spread"),
+            Self::Median => write!(f, "median"),
+            Self::TrimmedMean { trim } => write!(f, "trimmed {}", trim),
+            Self::Weighted { weights } => {
+                write!(f, "weighted ")?;
+                for (i, (source, weight)) in weights.iter().enumerate() {
+                    let separator = if i == 0 { "" } else { "+" };
+                    write!(f, "{}{}:{}", separator, source, weight)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Constraints from before sides were recorded only bound the maker's debit
+fn default_side() -> Side {
+    Side::Buy
+}
+
+fn default_max_fills() -> u32 {
+    1
+}
+
+fn default_decimals() -> u8 {
+    9
 }
 
 impl QuoteConstraints {
     /// Create a new QuoteConstraints with sensible defaults
     pub fn new(quote_id: [u8; 32]) -> Self {
         Self {
+            version: CONSTRAINTS_VERSION,
             quote_id,
+            side: default_side(),
             max_debit: 0,
             min_credit: None,
             expiry_timestamp: 0,
+            valid_from_timestamp: None,
+            trading_window: None,
             allowed_sources: vec![],
+            feed_keys: BTreeMap::new(),
+            allow_unsigned_feeds: false,
             max_staleness_secs: 60, // 1 minute default
+            source_staleness_secs: BTreeMap::new(),
             quorum_count: 1,
-            quorum_tolerance_percent: 1.0,
+            quorum_tolerance_bps: 100,
+            quorum_policy: QuorumPolicy::default(),
             allowed_takers: vec![],
+            denied_takers: vec![],
+            allowed_taker_shards: vec![],
             allowed_assets: vec![],
             require_atomic_dvp: true,
             no_side_payments: true,
-            nonce: 0,
+            allowed_fee: None,
             max_fill_size: 0,
+            leg_max_fill_sizes: BTreeMap::new(),
+            max_fills: default_max_fills(),
+            max_fills_per_taker: None,
+            max_notional_per_taker: None,
+            min_fill_size: None,
+            allow_partial_fills: false,
+            settlement_window_secs: None,
+            max_price_deviation_bps: None,
+            asset_decimals: default_decimals(),
+            currency_decimals: default_decimals(),
+            max_fee_bps: None,
+            benchmark: None,
+            iceberg: None,
+            extensions: Extensions::new(),
+            compiled_by: None,
+        }
+    }
+
+    /// Size of a basket leg in a fill of `fill_size`, in proportion to the
+    /// leg's share of `max_fill_size` (None if the asset isn't a leg)
+    pub fn leg_fill_size(&self, asset: &str, fill_size: u64) -> Option<u64> {
+        let max = *self.leg_max_fill_sizes.get(asset)?;
+        if self.max_fill_size == 0 {
+            return Some(0);
         }
+        let size = max as u128 * fill_size as u128 / self.max_fill_size as u128;
+        Some(u64::try_from(size).unwrap_or(u64::MAX))
+    }
+
+    /// Sizes of every basket leg in a fill of `fill_size`
+    pub fn leg_fill_sizes(&self, fill_size: u64) -> BTreeMap<String, u64> {
+        self.leg_max_fill_sizes
+            .keys()
+            .filter_map(|asset| Some((asset.clone(), self.leg_fill_size(asset, fill_size)?)))
+            .collect()
     }
 
     /// Convert expiry timestamp to DateTime
     pub fn expiry_datetime(&self) -> DateTime<Utc> {
         DateTime::from_timestamp(self.expiry_timestamp as i64, 0)
-            .unwrap_or_else(|| Utc::now())
+            .unwrap_or_else(Utc::now)
+    }
+
+    /// Convert the start of the validity period to DateTime (None if the
+    /// quote is valid at once)
+    pub fn valid_from_datetime(&self) -> Option<DateTime<Utc>> {
+        let valid_from = self.valid_from_timestamp?;
+        Some(DateTime::from_timestamp(valid_from as i64, 0).unwrap_or_else(Utc::now))
     }
 
     /// Check if the constraints allow a specific taker
@@ -93,10 +502,140 @@ impl QuoteConstraints {
         self.allowed_takers.is_empty() || self.allowed_takers.iter().any(|t| t == taker_owner_id)
     }
 
+    /// Check if the constraints deny a specific taker
+    pub fn denies_taker(&self, taker_owner_id: &str) -> bool {
+        self.denied_takers.iter().any(|t| t == taker_owner_id)
+    }
+
+    /// Check if the constraints allow a taker on a specific shard
+    pub fn allows_taker_shard(&self, taker_shard: u64) -> bool {
+        self.allowed_taker_shards.is_empty() || self.allowed_taker_shards.contains(&taker_shard)
+    }
+
     /// Check if the constraints allow a specific source
     pub fn allows_source(&self, source: &str) -> bool {
         self.allowed_sources.is_empty() || self.allowed_sources.iter().any(|s| s == source)
     }
+
+    /// Check if the constraints allow a specific asset
+    pub fn allows_asset(&self, asset: &str) -> bool {
+        self.allowed_assets.is_empty() || self.allowed_assets.iter().any(|a| a == asset)
+    }
+
+    /// Maximum age of a source's feed data in seconds
+    pub fn max_staleness_for(&self, source: &str) -> u64 {
+        self.source_staleness_secs
+            .get(source)
+            .copied()
+            .unwrap_or(self.max_staleness_secs)
+    }
+
+    /// Least a sell quote's maker must be credited for a fill of
+    /// `fill_size`: `min_credit` in proportion to the fill's share of
+    /// `max_fill_size`, rounded up (None without a `min_credit`)
+    pub fn min_credit_for(&self, fill_size: u64) -> Option<u64> {
+        let min_credit = self.min_credit?;
+        if self.max_fill_size == 0 {
+            return Some(min_credit);
+        }
+        let credit = (min_credit as u128 * fill_size as u128).div_ceil(self.max_fill_size as u128);
+        Some(u64::try_from(credit).unwrap_or(u64::MAX))
+    }
+
+    /// Size left of an iceberg's hidden total after `fills` (None if not
+    /// an iceberg)
+    pub fn iceberg_remaining(&self, fills: &[FillRecord]) -> Option<u64> {
+        let iceberg = self.iceberg.as_ref()?;
+        Some(iceberg.total_size.saturating_sub(FillRecord::total_size(fills)))
+    }
+
+    /// Quorum tolerance in percent, as displayed
+    pub fn quorum_tolerance_percent(&self) -> f64 {
+        bps_to_percent(self.quorum_tolerance_bps)
+    }
+
+    /// Maximum price deviation in percent, as displayed
+    pub fn max_price_deviation_percent(&self) -> Option<f64> {
+        self.max_price_deviation_bps.map(bps_to_percent)
+    }
+
+    /// Maximum fee in percent, as displayed
+    pub fn max_fee_percent(&self) -> Option<f64> {
+        self.max_fee_bps.map(bps_to_percent)
+    }
+
+    /// Largest fee leg a fill for `fill_price` may carry (None = uncapped)
+    pub fn max_fee(&self, fill_price: u64) -> Option<u64> {
+        let bps = self.max_fee_bps?;
+        let fee = u128::from(fill_price) * u128::from(bps) / 10_000;
+        Some(u64::try_from(fee).unwrap_or(u64::MAX))
+    }
+
+    /// Price per whole unit of the asset of a fill of `fill_size` for
+    /// `fill_price`, in fixed point like feed prices (None for an empty fill)
+    pub fn unit_price(&self, fill_size: u64, fill_price: u64) -> Option<u64> {
+        let scale = |decimals: u32| 10u128.checked_pow(decimals);
+        let numerator = (fill_price as u128)
+            .checked_mul(scale(u32::from(self.asset_decimals))?)?
+            .checked_mul(scale(PRICE_DECIMALS)?)?;
+        let denominator =
+            (fill_size as u128).checked_mul(scale(u32::from(self.currency_decimals))?)?;
+        if denominator == 0 {
+            return None;
+        }
+        u64::try_from(numerator / denominator).ok()
+    }
+
+    /// Amount (in the currency's smallest units) of a fill of `fill_size` at
+    /// the fixed-point unit price `unit_price`; the inverse of [`Self::unit_price`]
+    pub fn amount_at(&self, unit_price: u64, fill_size: u64) -> Option<u64> {
+        let scale = |decimals: u32| 10u128.checked_pow(decimals);
+        let numerator = (unit_price as u128)
+            .checked_mul(fill_size as u128)?
+            .checked_mul(scale(u32::from(self.currency_decimals))?)?;
+        let denominator =
+            scale(u32::from(self.asset_decimals))?.checked_mul(scale(PRICE_DECIMALS)?)?;
+        u64::try_from(numerator / denominator).ok()
+    }
+
+    /// Size left of a partially fillable quote after `filled_so_far` (None
+    /// unless partial fills are allowed; icebergs track their total instead)
+    pub fn partial_remaining(&self, filled_so_far: u64) -> Option<u64> {
+        (self.allow_partial_fills && self.iceberg.is_none())
+            .then(|| self.max_fill_size.saturating_sub(filled_so_far))
+    }
+}
+
+/// Decimals of fixed-point feed prices: 1950.25 is 1_950_250_000_000
+pub const PRICE_DECIMALS: u32 = 9;
+
+/// Current [`QuoteConstraints`] layout version
+///
+/// Bump it when adding a field, and give the field a serde default so
+/// constraints written with an older layout still deserialize. A field that
+/// changes unit keeps its old name readable in `LegacyLayout`.
+pub const CONSTRAINTS_VERSION: u32 = 4;
+
+/// Distance of `price` from `reference` in basis points, rounded up so it
+/// only exceeds a limit in whole bps when the exact distance does (None if
+/// the reference is zero)
+pub fn deviation_bps(price: u64, reference: u64) -> Option<u64> {
+    if reference == 0 {
+        return None;
+    }
+    let bps = (price.abs_diff(reference) as u128 * 10_000).div_ceil(reference as u128);
+    Some(u64::try_from(bps).unwrap_or(u64::MAX))
+}
+
+fn bps_to_percent(bps: u32) -> f64 {
+    f64::from(bps) / 100.0
+}
+
+/// A percentage in whole basis points, rounded to the nearest (0.25 is 25)
+pub fn percent_to_bps(percent: f64) -> u32 {
+    // Without std there's no f64::round; the cast saturates negative
+    // percentages at 0
+    (percent * 100.0 + 0.5) as u32
 }
 
 /// Evidence from a price feed, included with a fill attempt
@@ -106,12 +645,33 @@ pub struct FeedEvidence {
     pub source: String,
     /// The asset being priced
     pub asset: String,
-    /// The price
-    pub price: f64,
+    /// The price, in fixed point with [`PRICE_DECIMALS`] decimals
+    pub price: u64,
     /// Unix timestamp when the price was fetched
     pub timestamp: u64,
     /// Signature from the feed (for verification)
     pub signature: String,
+    /// How the evidence was retrieved (None for taker-supplied evidence)
+    #[serde(default)]
+    pub provenance: Option<EvidenceProvenance>,
+}
+
+/// Retrieval metadata for feed evidence
+///
+/// Recorded when the domain fetches evidence itself, so receipts can tell
+/// genuinely retrieved data apart from evidence a taker assembled.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EvidenceProvenance {
+    /// SHA-256 of the raw upstream response body (hex)
+    pub response_hash: String,
+    /// URL the evidence was retrieved from
+    pub url: String,
+    /// HTTP status of the upstream response
+    pub http_status: u16,
+    /// Request round-trip time in milliseconds
+    pub latency_ms: u64,
+    /// Unix timestamp when the response was received
+    pub retrieved_at: u64,
 }
 
 impl FeedEvidence {
@@ -119,4 +679,113 @@ impl FeedEvidence {
     pub fn is_fresh(&self, max_staleness_secs: u64, current_time: u64) -> bool {
         current_time.saturating_sub(self.timestamp) <= max_staleness_secs
     }
+
+    /// The message the source signed for this evidence
+    pub fn signed_message(&self) -> String {
+        crate::signing::feed_message(&self.source, &self.asset, self.price, self.timestamp)
+    }
+}
+
+/// A prior accepted fill of a quote
+///
+/// Multi-fill quotes pass their fill history to Local Laws so the
+/// total and per-taker fill caps can be enforced at settlement.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FillRecord {
+    /// The taker's owner ID
+    pub taker_owner_id: String,
+    /// The filled size (in smallest units)
+    pub fill_size: u64,
+    /// The amount paid for it (in the currency's smallest units)
+    #[serde(default)]
+    pub fill_price: u64,
+}
+
+impl FillRecord {
+    /// Total size of `fills`
+    pub fn total_size(fills: &[FillRecord]) -> u64 {
+        fills.iter().map(|f| f.fill_size).fold(0, u64::saturating_add)
+    }
+
+    /// Total amount paid in `taker`'s fills among `fills`
+    pub fn taker_notional(fills: &[FillRecord], taker: &str) -> u64 {
+        fills
+            .iter()
+            .filter(|f| f.taker_owner_id == taker)
+            .map(|f| f.fill_price)
+            .fold(0, u64::saturating_add)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    /// Constraints as written before versioning, in the baseline layout
+    const BASELINE: &str = r#"{
+        "quote_id": [7,7,7,7,7,7,7,7,7,7,7,7,7,7,7,7,7,7,7,7,7,7,7,7,7,7,7,7,7,7,7,7],
+        "max_debit": 2000000000,
+        "min_credit": null,
+        "expiry_timestamp": 1737500000,
+        "allowed_sources": ["FeedA", "FeedB"],
+        "max_staleness_secs": 5,
+        "quorum_count": 2,
+        "quorum_tolerance_percent": 0.5,
+        "allowed_takers": [],
+        "allowed_assets": ["dETH"],
+        "require_atomic_dvp": true,
+        "no_side_payments": true,
+        "nonce": 0,
+        "max_fill_size": 1000000000
+    }"#;
+
+    #[test]
+    fn test_baseline_layout() {
+        let constraints: QuoteConstraints = serde_json::from_str(BASELINE).unwrap();
+        assert_eq!(constraints.version, 0);
+        assert_eq!(constraints.quote_id, [7; 32]);
+        assert_eq!(constraints.quorum_tolerance_bps, 50);
+        assert_eq!(constraints.side, Side::Buy);
+        assert_eq!(constraints.max_fills, 1);
+        assert_eq!(constraints.max_fee_bps, None);
+
+        // Written back in the current layout, it reads the same
+        let current = serde_json::to_value(&constraints).unwrap();
+        assert!(current.get("quorum_tolerance_percent").is_none());
+        let reread: QuoteConstraints = serde_json::from_value(current.clone()).unwrap();
+        assert_eq!(serde_json::to_value(&reread).unwrap(), current);
+    }
+
+    #[test]
+    fn test_percent_fee_layout() {
+        // Versions 1 to 3 wrote the fee cap in percent
+        let mut json: serde_json::Value = serde_json::from_str(BASELINE).unwrap();
+        json["version"] = 3.into();
+        json["max_fee_percent"] = 0.05.into();
+        let constraints: QuoteConstraints = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(constraints.max_fee_bps, Some(5));
+
+        // The current field wins over the legacy one
+        json["max_fee_bps"] = 8.into();
+        json["quorum_tolerance_bps"] = 25.into();
+        let constraints: QuoteConstraints = serde_json::from_value(json).unwrap();
+        assert_eq!(constraints.max_fee_bps, Some(8));
+        assert_eq!(constraints.quorum_tolerance_bps, 25);
+    }
+
+    #[test]
+    fn test_missing_tolerance() {
+        let mut json: serde_json::Value = serde_json::from_str(BASELINE).unwrap();
+        json.as_object_mut().unwrap().remove("quorum_tolerance_percent");
+        let error = serde_json::from_value::<QuoteConstraints>(json).unwrap_err();
+        assert!(error.to_string().contains("quorum_tolerance_bps"), "{}", error);
+    }
+
+    #[test]
+    fn test_percent_to_bps() {
+        assert_eq!(percent_to_bps(0.25), 25);
+        assert_eq!(percent_to_bps(0.005), 1);
+        assert_eq!(percent_to_bps(1.0), 100);
+        assert_eq!(percent_to_bps(-1.0), 0);
+    }
 }
@@ -5,6 +5,9 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+use crate::signing::{self, SchemeKind, SigningKey};
+use crate::PRICE_DECIMALS;
+
 /// A price update from a feed source
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PriceUpdate {
@@ -24,6 +27,42 @@ pub struct PriceUpdate {
     pub signature: String,
 }
 
+impl PriceUpdate {
+    /// The message the source signs for this update
+    pub fn signed_message(&self) -> String {
+        signing::feed_message(&self.source, &self.asset, self.fixed_price(), self.timestamp)
+    }
+
+    /// The price in fixed point, as signed and carried in feed evidence
+    pub fn fixed_price(&self) -> u64 {
+        to_fixed_price(self.price)
+    }
+}
+
+/// A price in fixed point with [`PRICE_DECIMALS`] decimals
+pub fn to_fixed_price(price: f64) -> u64 {
+    (price * 10f64.powi(PRICE_DECIMALS as i32)).round() as u64
+}
+
+/// A fixed-point price as a decimal number, for display
+pub fn from_fixed_price(price: u64) -> f64 {
+    price as f64 / 10f64.powi(PRICE_DECIMALS as i32)
+}
+
+/// Consensus feed price captured when a quote was created
+///
+/// Serves as the reference for slippage checks and for measuring
+/// the maker's spread at creation time.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReferencePrice {
+    /// Median of the prices reported by the responding sources
+    pub price: f64,
+    /// The sources that contributed to the consensus
+    pub sources: Vec<String>,
+    /// When the snapshot was taken
+    pub captured_at: DateTime<Utc>,
+}
+
 /// Configuration for a mock feed
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FeedConfig {
@@ -41,6 +80,66 @@ pub struct FeedConfig {
     pub is_malicious: bool,
     /// If malicious, price manipulation factor
     pub manipulation_factor: f64,
+    /// Assets priced by their own process; other assets are priced at
+    /// `base_price` in USDD
+    #[serde(default)]
+    pub prices: Vec<PriceProcess>,
+    /// Currency pairs served by `/fx`, also used to price assets in other
+    /// currencies
+    #[serde(default = "default_fx")]
+    pub fx: Vec<PriceProcess>,
+    /// Key the feed signs its updates with (mock `sig_<name>_<ts>`
+    /// signatures if unset)
+    #[serde(default)]
+    pub signing_key: Option<SigningKey>,
+}
+
+/// A mock price (or FX rate) for one pair
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PriceProcess {
+    /// Asset (or base currency) priced
+    pub asset: String,
+    /// Currency the price is in
+    pub currency: String,
+    /// Price around which the feed reports
+    pub base_price: f64,
+    /// Price variance (percentage)
+    #[serde(default = "default_variance_percent")]
+    pub variance_percent: f64,
+}
+
+impl PriceProcess {
+    pub fn new(asset: &str, currency: &str, base_price: f64) -> Self {
+        Self {
+            asset: asset.to_string(),
+            currency: currency.to_string(),
+            base_price,
+            variance_percent: default_variance_percent(),
+        }
+    }
+
+    /// Whether this process prices `asset` in `currency` (case-insensitively)
+    pub fn prices(&self, asset: &str, currency: &str) -> bool {
+        self.asset.eq_ignore_ascii_case(asset) && self.currency.eq_ignore_ascii_case(currency)
+    }
+}
+
+fn default_variance_percent() -> f64 {
+    0.1
+}
+
+/// The FX pairs every mock feed serves unless configured otherwise
+fn default_fx() -> Vec<PriceProcess> {
+    vec![
+        PriceProcess {
+            variance_percent: 0.02,
+            ..PriceProcess::new("USDD", "EUR", 0.92)
+        },
+        PriceProcess {
+            variance_percent: 0.01,
+            ..PriceProcess::new("USDD", "USDC", 1.0)
+        },
+    ]
 }
 
 impl Default for FeedConfig {
@@ -53,6 +152,9 @@ impl Default for FeedConfig {
             stale_seconds: 0,
             is_malicious: false,
             manipulation_factor: 1.0,
+            prices: vec![],
+            fx: default_fx(),
+            signing_key: None,
         }
     }
 }
@@ -67,6 +169,28 @@ impl FeedConfig {
         }
     }
 
+    /// Price `asset` in `currency` around `base_price`
+    pub fn with_price(mut self, asset: &str, currency: &str, base_price: f64) -> Self {
+        self.prices.push(PriceProcess::new(asset, currency, base_price));
+        self
+    }
+
+    /// Serve the FX rate of `base` in `quote` around `rate`
+    pub fn with_fx(mut self, base: &str, quote: &str, rate: f64) -> Self {
+        self.fx.retain(|pair| !pair.prices(base, quote));
+        self.fx.push(PriceProcess::new(base, quote, rate));
+        self
+    }
+
+    /// Sign updates with `scheme`, from a hex 32-byte secret
+    pub fn with_signing_key(mut self, scheme: SchemeKind, secret: &str) -> Self {
+        self.signing_key = Some(SigningKey {
+            scheme,
+            secret: secret.to_string(),
+        });
+        self
+    }
+
     /// Create a stale feed config
     pub fn stale(name: &str, base_price: f64, stale_seconds: u64) -> Self {
         Self {
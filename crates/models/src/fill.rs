@@ -13,7 +13,7 @@ use crate::FeedEvidence;
 use uuid::Uuid;
 
 #[cfg(feature = "std")]
-use crate::QuoteId;
+use crate::{Extensions, QuoteId, Side};
 
 /// Unique identifier for a fill attempt
 #[cfg(feature = "std")]
@@ -55,6 +55,13 @@ pub enum FillResult {
         /// Settlement details
         settlement: SettlementDetails,
     },
+    /// Fill was accepted and settles at a benchmark once its window closes
+    PendingSettlement {
+        /// The fill ID
+        fill_id: FillId,
+        /// When the benchmark window closes
+        settles_at: DateTime<Utc>,
+    },
     /// Fill was rejected
     Rejected {
         /// The fill ID
@@ -82,6 +89,83 @@ pub struct SettlementDetails {
     pub currency: String,
     /// Timestamp of settlement
     pub settled_at: DateTime<Utc>,
+    /// How the currency amount was derived from the price
+    #[serde(default)]
+    pub pricing: Option<PricingBreakdown>,
+}
+
+#[cfg(feature = "std")]
+impl SettlementDetails {
+    /// Settlement of a DvP fill of `fill_size` at `fill_price` against a
+    /// quote on `side`, settled now
+    pub fn dvp(
+        side: Side,
+        fill_size: u64,
+        fill_price: u64,
+        asset: String,
+        currency: String,
+        pricing: Option<PricingBreakdown>,
+    ) -> Self {
+        let (maker_pays, taker_pays) = side.legs(fill_size, fill_price);
+        Self {
+            maker_debit: maker_pays,
+            maker_credit: taker_pays,
+            taker_debit: taker_pays,
+            taker_credit: maker_pays,
+            asset,
+            currency,
+            settled_at: Utc::now(),
+            pricing,
+        }
+    }
+
+    /// The fill size and price the settlement carried, as `(size, price)`,
+    /// for a quote on `side`
+    pub fn fill_amounts(&self, side: Side) -> (u64, u64) {
+        match side {
+            Side::Buy => (self.taker_debit, self.maker_debit),
+            Side::Sell => (self.maker_debit, self.taker_debit),
+        }
+    }
+}
+
+/// How a fill's currency amount was derived from its price
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PricingBreakdown {
+    /// Price per unit of the asset the fill settled at (the taker's
+    /// price, or the benchmark for TWAP quotes)
+    pub price: f64,
+    /// Median price of the fill's feed evidence at validation
+    pub feed_median: Option<f64>,
+    /// Price relative to the feed median, in basis points (positive = above)
+    pub spread_to_median_bps: Option<f64>,
+    /// Fee taken from the currency leg, in currency units (the domain
+    /// charges no fees yet, so always 0)
+    pub fee: u64,
+    /// price × size in currency units, before rounding
+    pub exact_amount: f64,
+    /// Units the settled amount differs from `exact_amount` by rounding
+    pub rounding_adjustment: f64,
+}
+
+#[cfg(feature = "std")]
+impl PricingBreakdown {
+    /// Break down a currency amount of `amount` units for `size` of the
+    /// asset at `price`, with `scale` units per whole unit of currency
+    pub fn new(price: f64, size: f64, scale: f64, amount: u64, feed_median: Option<f64>) -> Self {
+        let exact_amount = price * size * scale;
+        Self {
+            price,
+            feed_median,
+            spread_to_median_bps: feed_median
+                .filter(|median| *median > 0.0)
+                .map(|median| (price - median) / median * 10_000.0),
+            fee: 0,
+            exact_amount,
+            rounding_adjustment: amount as f64 - exact_amount,
+        }
+    }
 }
 
 /// Reason for rejecting a fill
@@ -96,10 +180,41 @@ pub enum RejectionReason {
         expired_at: DateTime<Utc>,
         attempted_at: DateTime<Utc>,
     },
+    /// Quote isn't fillable yet
+    QuoteNotYetValid {
+        valid_from: DateTime<Utc>,
+        attempted_at: DateTime<Utc>,
+    },
+    /// Fill attempted outside the quote's daily trading window
+    OutsideTradingWindow {
+        window: String,
+        attempted_at: DateTime<Utc>,
+    },
     /// Quote was already filled
     AlreadyFilled {
         filled_at: DateTime<Utc>,
     },
+    /// Quote has reached its total fill cap
+    FillLimitReached {
+        fills: u32,
+        max_fills: u32,
+    },
+    /// Taker has reached the per-taker fill cap
+    TakerFillLimitReached {
+        taker: String,
+        taker_fills: u32,
+        max_fills_per_taker: u32,
+    },
+    /// Benchmark samples don't cover the settlement window
+    InvalidBenchmarkEvidence {
+        reason: String,
+    },
+    /// Settlement amount doesn't match the benchmark computed from evidence
+    BenchmarkMismatch {
+        benchmark_price: u64,
+        expected_amount: u64,
+        actual_amount: u64,
+    },
     /// Price feed data is stale
     StaleFeed {
         source: String,
@@ -107,47 +222,168 @@ pub enum RejectionReason {
         current_timestamp: u64,
         max_staleness_secs: u64,
     },
+    /// Price feed data is timestamped ahead of the domain's clock
+    FutureFeed {
+        source: String,
+        feed_timestamp: u64,
+        current_timestamp: u64,
+        max_skew_secs: u64,
+    },
     /// Price feed source not in allowlist
     UnauthorizedSource {
         source: String,
         allowed_sources: Vec<String>,
     },
+    /// The same source appears more than once in the feed evidence
+    DuplicateFeedSource {
+        source: String,
+    },
+    /// Feed evidence prices an asset the quote doesn't trade
+    FeedAssetMismatch {
+        source: String,
+        asset: String,
+        allowed_assets: Vec<String>,
+    },
+    /// Feed evidence isn't signed by its source's key
+    InvalidFeedSignature {
+        source: String,
+        reason: String,
+    },
+    /// Feed evidence comes from a source the quote has no key for
+    UnkeyedFeedSource {
+        source: String,
+    },
     /// Taker not in allowlist
     UnauthorizedTaker {
         taker: String,
         allowed_takers: Vec<String>,
     },
+    /// Constraints were written with a layout newer than Local Laws read
+    UnsupportedConstraintsVersion {
+        version: u32,
+        supported: u32,
+    },
+    /// Taker is on the quote's deny list
+    DeniedTaker {
+        taker: String,
+    },
+    /// Taker's vault is on a shard the quote doesn't allow
+    UnauthorizedShard {
+        taker: String,
+        taker_shard: u64,
+        allowed_shards: Vec<u64>,
+    },
     /// Price exceeds limit
     PriceExceedsLimit {
         offered_price: f64,
         limit_price: f64,
     },
+    /// Price falls short of a sell quote's limit
+    PriceBelowLimit {
+        offered_price: f64,
+        limit_price: f64,
+    },
     /// Size exceeds maximum
     SizeExceedsMax {
         offered_size: f64,
         max_size: f64,
     },
+    /// Fill would take a partially filled quote past its size
+    RemainingSizeExceeded {
+        filled_size: u64,
+        offered_size: u64,
+        max_size: u64,
+    },
+    /// Size below the quote's minimum fill
+    SizeBelowMin {
+        offered_size: f64,
+        min_size: f64,
+    },
+    /// Fill doesn't deliver a basket leg in proportion to its size
+    BasketLegMismatch {
+        asset: String,
+        offered_size: u64,
+        expected_size: u64,
+    },
+    /// Fill would take an iceberg quote past its hidden total size
+    IcebergSizeExhausted {
+        filled_size: u64,
+        offered_size: u64,
+        total_size: u64,
+    },
+    /// Fill would take an iceberg quote past its total debit
+    IcebergDebitExhausted {
+        debited: u64,
+        offered_price: u64,
+        total_debit: u64,
+    },
+    /// Fill would take a taker past their notional cap
+    TakerNotionalExceeded {
+        taker: String,
+        filled_notional: u64,
+        offered_price: u64,
+        max_notional_per_taker: u64,
+    },
     /// Quorum not met (not enough sources or too much disagreement)
     QuorumNotMet {
         sources_provided: usize,
         quorum_required: u32,
-        price_spread_percent: Option<f64>,
-        max_tolerance_percent: f64,
+        price_spread_bps: Option<u64>,
+        max_tolerance_bps: u32,
+    },
+    /// Fill price is too far from the feeds' quorum price, their median
+    /// unless the quote's quorum policy says otherwise (prices in fixed
+    /// point, see [`PRICE_DECIMALS`](crate::PRICE_DECIMALS))
+    PriceDeviationExceeded {
+        fill_price: u64,
+        median_price: u64,
+        deviation_bps: u64,
+        max_deviation_bps: u32,
     },
     /// Attempted side-payment detected
     SidePaymentDetected {
         description: String,
     },
+    /// The settlement's fee leg is above the maker's maximum fee
+    FeeExceedsLimit {
+        fee: u64,
+        max_fee: u64,
+    },
+    /// The fill settles later after acceptance than the quote allows
+    SettlementWindowExceeded {
+        accepted_at: u64,
+        settled_at: u64,
+        window_secs: u64,
+    },
     /// Transfer pattern invalid (not atomic DvP)
     InvalidTransferPattern {
         expected: String,
         actual: String,
     },
+    /// A transfer reuses a nonce its vault has already moved past
+    ReplayDetected {
+        nonce: u64,
+        vault_nonce: u64,
+    },
+    /// A transfer debits a vault whose current nonce wasn't provided
+    UnknownVaultNonce {
+        vault: String,
+    },
     /// Insufficient balance
     InsufficientBalance {
         required: u64,
         available: u64,
     },
+    /// Rejected by one of the maker's auto-reject rules
+    AutoRejected {
+        rule: String,
+        detail: String,
+    },
+    /// An amount in the fill is too large to compute with (e.g. a crafted
+    /// size or price), so it can't be checked against the guardrails
+    NumericOverflow {
+        quantity: String,
+    },
     /// Generic validation error
     ValidationError {
         message: String,
@@ -161,9 +397,36 @@ impl RejectionReason {
             Self::QuoteExpired { expired_at, attempted_at } => {
                 format!("Quote expired at {} (attempted at {})", expired_at, attempted_at)
             }
+            Self::QuoteNotYetValid { valid_from, attempted_at } => {
+                format!(
+                    "Quote isn't fillable until {} (attempted at {})",
+                    valid_from, attempted_at
+                )
+            }
+            Self::OutsideTradingWindow { window, attempted_at } => {
+                format!("Quote is only fillable {} (attempted at {})", window, attempted_at)
+            }
             Self::AlreadyFilled { filled_at } => {
                 format!("Quote was already filled at {}", filled_at)
             }
+            Self::FillLimitReached { fills, max_fills } => {
+                format!("Quote already filled {} of {} times", fills, max_fills)
+            }
+            Self::TakerFillLimitReached { taker, taker_fills, max_fills_per_taker } => {
+                format!(
+                    "Taker '{}' already filled {} times, max per taker is {}",
+                    taker, taker_fills, max_fills_per_taker
+                )
+            }
+            Self::InvalidBenchmarkEvidence { reason } => {
+                format!("Invalid benchmark evidence: {}", reason)
+            }
+            Self::BenchmarkMismatch { benchmark_price, expected_amount, actual_amount } => {
+                format!(
+                    "Settlement amount {} doesn't match benchmark price {} (expected {})",
+                    actual_amount, benchmark_price, expected_amount
+                )
+            }
             Self::StaleFeed { source, feed_timestamp, current_timestamp, max_staleness_secs } => {
                 let age = current_timestamp - feed_timestamp;
                 format!(
@@ -171,18 +434,59 @@ impl RejectionReason {
                     source, age, max_staleness_secs
                 )
             }
+            Self::FutureFeed { source, feed_timestamp, current_timestamp, max_skew_secs } => {
+                format!(
+                    "Feed data from '{}' is {}s in the future, max clock skew is {}s",
+                    source,
+                    feed_timestamp.saturating_sub(*current_timestamp),
+                    max_skew_secs
+                )
+            }
             Self::UnauthorizedSource { source, allowed_sources } => {
                 format!(
                     "Source '{}' not in allowlist. Allowed: {:?}",
                     source, allowed_sources
                 )
             }
+            Self::DuplicateFeedSource { source } => {
+                format!("Source '{}' appears more than once in the feed evidence", source)
+            }
+            Self::FeedAssetMismatch { source, asset, allowed_assets } => {
+                format!(
+                    "Evidence from source '{}' prices '{}', not one of {:?}",
+                    source, asset, allowed_assets
+                )
+            }
+            Self::InvalidFeedSignature { source, reason } => {
+                format!("Evidence from source '{}' failed signature check: {}", source, reason)
+            }
+            Self::UnkeyedFeedSource { source } => {
+                format!("Evidence from source '{}' can't be verified: it has no key", source)
+            }
+            Self::UnsupportedConstraintsVersion { version, supported } => {
+                format!(
+                    "Constraints version {} is newer than supported version {}",
+                    version, supported
+                )
+            }
+            Self::DeniedTaker { taker } => {
+                format!("Taker '{}' is on the deny list", taker)
+            }
+            Self::UnauthorizedShard { taker, taker_shard, allowed_shards } => {
+                format!(
+                    "Taker '{}' on shard {} not allowed. Allowed shards: {:?}",
+                    taker, taker_shard, allowed_shards
+                )
+            }
             Self::UnauthorizedTaker { taker, allowed_takers } => {
                 format!(
                     "Taker '{}' not in allowlist. Allowed: {:?}",
                     taker, allowed_takers
                 )
             }
+            Self::PriceBelowLimit { offered_price, limit_price } => {
+                format!("Offered price {} is below limit {}", offered_price, limit_price)
+            }
             Self::PriceExceedsLimit { offered_price, limit_price } => {
                 format!(
                     "Offered price {} exceeds limit {}",
@@ -195,34 +499,110 @@ impl RejectionReason {
                     offered_size, max_size
                 )
             }
-            Self::QuorumNotMet { sources_provided, quorum_required, price_spread_percent, max_tolerance_percent } => {
-                if let Some(spread) = price_spread_percent {
-                    format!(
-                        "Price spread {}% exceeds tolerance {}%",
-                        spread, max_tolerance_percent
-                    )
-                } else {
-                    format!(
+            Self::RemainingSizeExceeded { filled_size, offered_size, max_size } => {
+                format!(
+                    "Offered size {} exceeds the {} left of the quote's size {}",
+                    offered_size,
+                    max_size.saturating_sub(*filled_size),
+                    max_size
+                )
+            }
+            Self::SizeBelowMin { offered_size, min_size } => {
+                format!("Offered size {} is below the minimum fill {}", offered_size, min_size)
+            }
+            Self::BasketLegMismatch { asset, offered_size, expected_size } => {
+                format!(
+                    "Basket leg {} has size {}, expected {} for this fill",
+                    asset, offered_size, expected_size
+                )
+            }
+            Self::IcebergSizeExhausted { filled_size, offered_size, total_size } => {
+                format!(
+                    "Offered size {} exceeds the {} left of the quote's total {}",
+                    offered_size,
+                    total_size.saturating_sub(*filled_size),
+                    total_size
+                )
+            }
+            Self::TakerNotionalExceeded { taker, filled_notional, offered_price, max_notional_per_taker } => {
+                format!(
+                    "Offered price {} takes taker {} past their notional cap {} ({} filled)",
+                    offered_price, taker, max_notional_per_taker, filled_notional
+                )
+            }
+            Self::IcebergDebitExhausted { debited, offered_price, total_debit } => {
+                format!(
+                    "Offered price {} exceeds the {} left of the quote's total debit {}",
+                    offered_price,
+                    total_debit.saturating_sub(*debited),
+                    total_debit
+                )
+            }
+            Self::QuorumNotMet { sources_provided, quorum_required, price_spread_bps, max_tolerance_bps } => {
+                match price_spread_bps {
+                    // Too few sources agree with the quorum price
+                    Some(spread) if (*sources_provided as u64) < u64::from(*quorum_required) => {
+                        format!(
+                            "Only {} sources within {}bps of the quorum price, {} required (furthest is {}bps off)",
+                            sources_provided, max_tolerance_bps, quorum_required, spread
+                        )
+                    }
+                    Some(spread) => format!(
+                        "Price spread {}bps exceeds tolerance {}bps",
+                        spread, max_tolerance_bps
+                    ),
+                    None => format!(
                         "Only {} sources provided, {} required for quorum",
                         sources_provided, quorum_required
-                    )
+                    ),
                 }
             }
+            Self::PriceDeviationExceeded { fill_price, median_price, deviation_bps, max_deviation_bps } => {
+                format!(
+                    "Fill price {} is {}bps from the feed price {}, max allowed is {}bps",
+                    fill_price, deviation_bps, median_price, max_deviation_bps
+                )
+            }
             Self::SidePaymentDetected { description } => {
                 format!("Side-payment detected: {}", description)
             }
+            Self::FeeExceedsLimit { fee, max_fee } => {
+                format!("Fee of {} exceeds the maker's maximum fee of {}", fee, max_fee)
+            }
+            Self::SettlementWindowExceeded { accepted_at, settled_at, window_secs } => {
+                format!(
+                    "Fill settled {}s after acceptance, past the {}s settlement window",
+                    settled_at.saturating_sub(*accepted_at),
+                    window_secs
+                )
+            }
             Self::InvalidTransferPattern { expected, actual } => {
                 format!(
                     "Invalid transfer pattern. Expected: {}, got: {}",
                     expected, actual
                 )
             }
+            Self::ReplayDetected { nonce, vault_nonce } => {
+                format!(
+                    "Replay detected: transfer nonce {} is not above the vault's nonce {}",
+                    nonce, vault_nonce
+                )
+            }
+            Self::UnknownVaultNonce { vault } => {
+                format!("No nonce known for debited vault {}; replay can't be ruled out", vault)
+            }
             Self::InsufficientBalance { required, available } => {
                 format!(
                     "Insufficient balance: required {}, available {}",
                     required, available
                 )
             }
+            Self::AutoRejected { rule, detail } => {
+                format!("Rejected by maker rule '{}': {}", rule, detail)
+            }
+            Self::NumericOverflow { quantity } => {
+                format!("Numeric overflow computing the {}", quantity)
+            }
             Self::ValidationError { message } => message.clone(),
         }
     }
@@ -231,16 +611,44 @@ impl RejectionReason {
     pub fn code(&self) -> &'static str {
         match self {
             Self::QuoteExpired { .. } => "QUOTE_EXPIRED",
+            Self::QuoteNotYetValid { .. } => "QUOTE_NOT_YET_VALID",
+            Self::OutsideTradingWindow { .. } => "OUTSIDE_TRADING_WINDOW",
             Self::AlreadyFilled { .. } => "ALREADY_FILLED",
+            Self::FillLimitReached { .. } => "FILL_LIMIT_REACHED",
+            Self::TakerFillLimitReached { .. } => "TAKER_FILL_LIMIT_REACHED",
+            Self::InvalidBenchmarkEvidence { .. } => "INVALID_BENCHMARK_EVIDENCE",
+            Self::BenchmarkMismatch { .. } => "BENCHMARK_MISMATCH",
             Self::StaleFeed { .. } => "STALE_FEED",
+            Self::FutureFeed { .. } => "FUTURE_FEED",
             Self::UnauthorizedSource { .. } => "UNAUTHORIZED_SOURCE",
+            Self::DuplicateFeedSource { .. } => "DUPLICATE_FEED_SOURCE",
+            Self::FeedAssetMismatch { .. } => "FEED_ASSET_MISMATCH",
+            Self::InvalidFeedSignature { .. } => "INVALID_FEED_SIGNATURE",
+            Self::UnkeyedFeedSource { .. } => "UNKEYED_FEED_SOURCE",
             Self::UnauthorizedTaker { .. } => "UNAUTHORIZED_TAKER",
+            Self::UnsupportedConstraintsVersion { .. } => "UNSUPPORTED_CONSTRAINTS_VERSION",
+            Self::DeniedTaker { .. } => "DENIED_TAKER",
+            Self::UnauthorizedShard { .. } => "UNAUTHORIZED_SHARD",
             Self::PriceExceedsLimit { .. } => "PRICE_EXCEEDS_LIMIT",
+            Self::PriceBelowLimit { .. } => "PRICE_BELOW_LIMIT",
             Self::SizeExceedsMax { .. } => "SIZE_EXCEEDS_MAX",
+            Self::RemainingSizeExceeded { .. } => "REMAINING_SIZE_EXCEEDED",
+            Self::SizeBelowMin { .. } => "SIZE_BELOW_MIN",
+            Self::BasketLegMismatch { .. } => "BASKET_LEG_MISMATCH",
+            Self::IcebergSizeExhausted { .. } => "ICEBERG_SIZE_EXHAUSTED",
+            Self::IcebergDebitExhausted { .. } => "ICEBERG_DEBIT_EXHAUSTED",
+            Self::TakerNotionalExceeded { .. } => "TAKER_NOTIONAL_EXCEEDED",
             Self::QuorumNotMet { .. } => "QUORUM_NOT_MET",
+            Self::PriceDeviationExceeded { .. } => "PRICE_DEVIATION_EXCEEDED",
             Self::SidePaymentDetected { .. } => "SIDE_PAYMENT_DETECTED",
+            Self::FeeExceedsLimit { .. } => "FEE_EXCEEDS_LIMIT",
+            Self::SettlementWindowExceeded { .. } => "SETTLEMENT_WINDOW_EXCEEDED",
             Self::InvalidTransferPattern { .. } => "INVALID_TRANSFER_PATTERN",
+            Self::ReplayDetected { .. } => "REPLAY_DETECTED",
+            Self::UnknownVaultNonce { .. } => "UNKNOWN_VAULT_NONCE",
             Self::InsufficientBalance { .. } => "INSUFFICIENT_BALANCE",
+            Self::AutoRejected { .. } => "AUTO_REJECTED",
+            Self::NumericOverflow { .. } => "NUMERIC_OVERFLOW",
             Self::ValidationError { .. } => "VALIDATION_ERROR",
         }
     }
@@ -260,4 +668,18 @@ pub struct FillRequest {
     pub price: f64,
     /// Price feed evidence
     pub feed_evidence: Vec<FeedEvidence>,
+    /// Per-taker request nonce; must exceed the taker's last accepted nonce
+    pub taker_nonce: u64,
+    /// The taker's hex ed25519 signature over
+    /// [`fill_message`](crate::signing::fill_message), by the key its owner
+    /// ID encodes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub taker_signature: Option<String>,
+    /// Deployment-specific data about the fill, passed to Local Laws
+    #[serde(default)]
+    pub extensions: Extensions,
+    /// Longest the taker will wait for settlement, in milliseconds; past it
+    /// the fill is answered as pending and settles in the background
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_wait_ms: Option<u64>,
 }
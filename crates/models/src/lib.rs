@@ -5,6 +5,7 @@
 //! - Guardrails (constraints) compiled from English
 //! - Fill attempts and results
 //! - Price feed data
+//! - Signature schemes for feed evidence and receipts ([`signing`])
 //!
 //! ## Features
 //!
@@ -18,6 +19,7 @@ extern crate alloc;
 mod quote;
 mod constraints;
 mod fill;
+pub mod signing;
 
 #[cfg(feature = "std")]
 mod feed;
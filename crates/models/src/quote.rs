@@ -2,15 +2,19 @@
 
 #![allow(unused_imports)]
 
+use alloc::collections::BTreeMap;
 use alloc::string::String;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveTime, Utc};
 use serde::{Deserialize, Serialize};
 
 #[cfg(feature = "std")]
 use uuid::Uuid;
 
 #[cfg(feature = "std")]
-use crate::QuoteConstraints;
+use crate::{
+    percent_to_bps, AllowedFee, Extensions, FillRecord, QuoteConstraints, ReferencePrice,
+    RejectionReason,
+};
 
 /// Unique identifier for a quote (only available with std)
 #[cfg(feature = "std")]
@@ -24,10 +28,30 @@ pub enum Side {
     Sell,
 }
 
+impl Side {
+    /// What each party of a quote on this side pays in a fill of
+    /// `fill_size` at `fill_price`, as `(maker_pays, taker_pays)`
+    ///
+    /// The maker of a buy quote pays the price for the asset; the maker of
+    /// a sell quote delivers the asset for the price.
+    pub fn legs(self, fill_size: u64, fill_price: u64) -> (u64, u64) {
+        match self {
+            Side::Buy => (fill_price, fill_size),
+            Side::Sell => (fill_size, fill_price),
+        }
+    }
+}
+
 /// The status of a quote
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum QuoteStatus {
+    /// Quote awaits the maker's approval of terms the compiler was unsure of
+    PendingApproval,
+    /// Quote is scheduled: hidden and unfillable until it activates
+    Scheduled,
+    /// Quote is under embargo: visible and fillable only by preferred takers
+    Pending,
     /// Quote is active and can be filled
     Active,
     /// Quote has been filled
@@ -36,6 +60,8 @@ pub enum QuoteStatus {
     Expired,
     /// Quote was cancelled by maker
     Cancelled,
+    /// Quote is held by its divergence guard until the market comes back
+    Suspended,
 }
 
 /// The specification of a quote (what the maker wants to trade)
@@ -52,6 +78,50 @@ pub struct QuoteSpec {
     pub limit_price: Option<f64>,
     /// The currency for settlement (e.g., "USDD")
     pub currency: String,
+    /// Further assets traded in the same package (basket quotes), after
+    /// `asset`; `limit_price` is then per unit of `asset` with the other
+    /// legs included
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub legs: Vec<QuoteLeg>,
+}
+
+/// A further leg of a basket quote
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QuoteLeg {
+    /// The asset (e.g., "dSOL")
+    pub asset: String,
+    /// Its size per fill, alongside the quote's `size`
+    pub size: f64,
+}
+
+/// A taker's request for quotes ("looking to buy 10 dETH, settle USDD")
+///
+/// The taker-side counterpart of [`QuoteSpec`]: makers answer it with
+/// quotes on the other side.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RfqRequest {
+    /// The taker's side of the trade
+    pub side: Side,
+    /// The asset wanted (e.g., "dETH")
+    pub asset: String,
+    /// The size wanted
+    pub size: f64,
+    /// The currency to settle in (e.g., "USDD")
+    pub currency: String,
+    /// Worst price the taker accepts (max for buys, min for sells)
+    #[serde(default)]
+    pub limit_price: Option<f64>,
+    /// Feeds responses must price against (empty = any)
+    #[serde(default)]
+    pub sources: Vec<String>,
+    /// Fewest feeds a response's quorum may use
+    #[serde(default)]
+    pub min_sources: Option<u32>,
+    /// How long makers have to respond, in seconds
+    #[serde(default)]
+    pub response_window_secs: Option<u64>,
 }
 
 /// A complete quote posted by a maker
@@ -76,6 +146,208 @@ pub struct Quote {
     pub maker_vault_address: String,
     /// Original English text (for display)
     pub original_text: String,
+    /// When a scheduled quote is posted
+    #[serde(default)]
+    pub activates_at: Option<DateTime<Utc>>,
+    /// Critical fields the compiler was unsure of, with their confidence
+    #[serde(default)]
+    pub low_confidence_fields: BTreeMap<String, f64>,
+    /// When the embargo ends and the quote is publicly listed
+    #[serde(default)]
+    pub embargo_until: Option<DateTime<Utc>>,
+    /// Takers that can see and fill the quote during the embargo
+    #[serde(default)]
+    pub embargo_takers: Vec<String>,
+    /// Consensus feed price snapshot taken at creation
+    #[serde(default)]
+    pub reference_price: Option<ReferencePrice>,
+    /// Name of the constraint preset the quote text referenced, if any
+    #[serde(default)]
+    pub preset: Option<String>,
+    /// Accepted fills so far (multi-fill quotes can be filled several times)
+    #[serde(default)]
+    pub fill_history: Vec<FillRecord>,
+    /// Changes made to the quote after compilation
+    #[serde(default)]
+    pub audit_trail: Vec<AuditEntry>,
+    /// Maker filters checked before Local Laws
+    #[serde(default)]
+    pub auto_reject: Option<AutoRejectRules>,
+    /// Cancels or suspends the quote if the market moves away from its
+    /// reference price
+    #[serde(default)]
+    pub divergence_guard: Option<DivergenceGuard>,
+}
+
+/// Maker protection against the market moving away from a quote
+///
+/// The domain compares the feed consensus with the quote's reference
+/// price and acts once they are more than `max_move_percent` apart.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DivergenceGuard {
+    /// Largest tolerated move from the reference price, in percent
+    pub max_move_percent: f64,
+    /// What to do once it is exceeded
+    #[serde(default)]
+    pub action: DivergenceAction,
+}
+
+/// What a [`DivergenceGuard`] does to its quote
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DivergenceAction {
+    /// Cancel the quote for good
+    #[default]
+    Cancel,
+    /// Suspend fills until the market is back within the threshold
+    Suspend,
+}
+
+#[cfg(feature = "std")]
+impl DivergenceGuard {
+    /// How far `market` is from `reference`, in percent, if past the threshold
+    pub fn breach(&self, reference: f64, market: f64) -> Option<f64> {
+        if reference <= 0.0 {
+            return None;
+        }
+        let moved = (market - reference).abs() / reference * 100.0;
+        (moved > self.max_move_percent).then_some(moved)
+    }
+}
+
+/// Maker-configured filters for fill attempts
+///
+/// Evaluated before Local Laws; fills they reject are answered directly
+/// and never produce receipts.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AutoRejectRules {
+    /// Reject takers whose share of accepted fills is below this (0.0-1.0)
+    #[serde(default)]
+    pub min_taker_reputation: Option<f64>,
+    /// Accept takers without fill history despite `min_taker_reputation`
+    #[serde(default)]
+    pub allow_new_takers: bool,
+    /// Reject fills smaller than this size
+    #[serde(default)]
+    pub min_fill_size: Option<f64>,
+    /// Daily UTC windows during which all fills are rejected
+    #[serde(default)]
+    pub blackout_windows: Vec<BlackoutWindow>,
+}
+
+/// A daily UTC time window (wraps past midnight when `end` < `start`)
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlackoutWindow {
+    /// Window start (e.g. "22:00:00")
+    pub start: NaiveTime,
+    /// Window end, exclusive (e.g. "06:00:00")
+    pub end: NaiveTime,
+}
+
+#[cfg(feature = "std")]
+impl BlackoutWindow {
+    /// Check if a time falls in the window
+    pub fn contains(&self, at: DateTime<Utc>) -> bool {
+        let time = at.time();
+        if self.start <= self.end {
+            self.start <= time && time < self.end
+        } else {
+            time >= self.start || time < self.end
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl AutoRejectRules {
+    /// Check a fill attempt against the rules
+    ///
+    /// `taker_reputation` is the taker's share of accepted fills, None if
+    /// the taker has no history. Returns the first rule that rejects.
+    pub fn check(
+        &self,
+        size: f64,
+        taker_reputation: Option<f64>,
+        at: DateTime<Utc>,
+    ) -> Option<RejectionReason> {
+        if let Some(window) = self.blackout_windows.iter().find(|w| w.contains(at)) {
+            return Some(RejectionReason::AutoRejected {
+                rule: "blackout_window".to_string(),
+                detail: format!("Maker blackout from {} to {} UTC", window.start, window.end),
+            });
+        }
+
+        if let Some(min_size) = self.min_fill_size {
+            if size < min_size {
+                return Some(RejectionReason::AutoRejected {
+                    rule: "min_fill_size".to_string(),
+                    detail: format!("Fill size {} is below the maker minimum {}", size, min_size),
+                });
+            }
+        }
+
+        if let Some(min_reputation) = self.min_taker_reputation {
+            let rejected = match taker_reputation {
+                Some(reputation) => reputation < min_reputation,
+                None => !self.allow_new_takers,
+            };
+            if rejected {
+                return Some(RejectionReason::AutoRejected {
+                    rule: "min_taker_reputation".to_string(),
+                    detail: match taker_reputation {
+                        Some(reputation) => format!(
+                            "Taker reputation {:.2} is below the maker minimum {:.2}",
+                            reputation, min_reputation
+                        ),
+                        None => "Maker doesn't accept takers without fill history".to_string(),
+                    },
+                });
+            }
+        }
+
+        None
+    }
+}
+
+/// An entry in a quote's audit trail
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    /// When the change was made
+    pub at: DateTime<Utc>,
+    /// What changed
+    #[serde(flatten)]
+    pub event: AuditEvent,
+}
+
+/// A recorded change to a quote
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum AuditEvent {
+    /// A compiled constraint was replaced by an explicit request field
+    ConstraintOverride {
+        /// The constraint field (e.g. "quorum_count")
+        field: String,
+        /// The value the compiler extracted from the text
+        compiled: serde_json::Value,
+        /// The value that was applied
+        applied: serde_json::Value,
+    },
+    /// The maker amended a constraint of the open quote
+    Amendment {
+        /// The amendment text
+        text: String,
+        /// The constraint field (e.g. "allowed_takers")
+        field: String,
+        /// The value before the amendment
+        previous: serde_json::Value,
+        /// The value that was applied
+        applied: serde_json::Value,
+    },
 }
 
 #[cfg(feature = "std")]
@@ -89,18 +361,241 @@ impl Quote {
     pub fn is_expired(&self) -> bool {
         Utc::now() >= self.expires_at
     }
+
+    /// Check if the quote is still under its publication embargo
+    pub fn is_embargoed(&self) -> bool {
+        self.status == QuoteStatus::Pending
+            && !self.is_expired()
+            && self.embargo_until.is_some_and(|until| Utc::now() < until)
+    }
+
+    /// Check if a scheduled quote's activation time has come
+    pub fn is_due(&self) -> bool {
+        self.status == QuoteStatus::Scheduled
+            && self.activates_at.is_some_and(|at| Utc::now() >= at)
+    }
+
+    /// Check if the quote can still be filled, now or once posted
+    pub fn is_open(&self) -> bool {
+        !matches!(
+            self.status,
+            QuoteStatus::Filled | QuoteStatus::Expired | QuoteStatus::Cancelled
+        ) && !self.is_expired()
+    }
+
+    /// Check if the quote has been posted (isn't awaiting approval or activation)
+    pub fn is_posted(&self) -> bool {
+        !matches!(self.status, QuoteStatus::PendingApproval | QuoteStatus::Scheduled)
+    }
+
+    /// Approve a quote awaiting approval
+    ///
+    /// It is posted right away, or scheduled if its activation time is
+    /// still ahead.
+    pub fn approve(&mut self) {
+        if self.activates_at.is_some_and(|at| Utc::now() < at) {
+            self.status = QuoteStatus::Scheduled;
+        } else {
+            self.activate();
+        }
+    }
+
+    /// Post a scheduled quote, under embargo if it has one
+    pub fn activate(&mut self) {
+        self.status = QuoteStatus::Pending;
+        // Publishes the quote right away if there's no embargo
+        self.refresh_status();
+    }
+
+    /// Check if a taker can see the quote
+    ///
+    /// Quotes that aren't posted yet are hidden from all takers and
+    /// embargoed quotes are only visible to the embargo takers; everything
+    /// else is public.
+    pub fn is_visible_to(&self, taker_owner_id: Option<&str>) -> bool {
+        if !self.is_posted() {
+            return false;
+        }
+        !self.is_embargoed()
+            || taker_owner_id.is_some_and(|taker| self.embargo_takers.iter().any(|t| t == taker))
+    }
+
+    /// Size filled so far, in the asset's smallest units
+    pub fn filled_size(&self) -> u64 {
+        FillRecord::total_size(&self.fill_history)
+    }
+
+    /// Whether no fill can be accepted anymore: the fill cap is reached, or
+    /// an iceberg's total or a partially filled quote's size is used up
+    pub fn is_exhausted(&self) -> bool {
+        self.fill_history.len() >= self.constraints.max_fills as usize
+            || self.constraints.iceberg_remaining(&self.fill_history) == Some(0)
+            || self.constraints.partial_remaining(self.filled_size()) == Some(0)
+    }
+
+    /// Size takers can see on an iceberg quote (the display size, or what
+    /// is left of the hidden total if that is less) or on a partially
+    /// fillable one (what is left of it); None for other quotes
+    pub fn visible_size(&self) -> Option<f64> {
+        let remaining = self
+            .constraints
+            .iceberg_remaining(&self.fill_history)
+            .or_else(|| self.constraints.partial_remaining(self.filled_size()))?;
+        let display = self.constraints.max_fill_size;
+        let remaining = remaining.min(display);
+        Some(if display == 0 {
+            0.0
+        } else {
+            self.spec.size * remaining as f64 / display as f64
+        })
+    }
+
+    /// The maker's limit price relative to the creation-time reference, in bps
+    ///
+    /// Positive means the limit is above the reference price.
+    pub fn spread_to_reference_bps(&self) -> Option<f64> {
+        let reference = self.reference_price.as_ref()?.price;
+        let limit = self.spec.limit_price?;
+        (reference > 0.0).then(|| (limit - reference) / reference * 10_000.0)
+    }
+
+    /// Bring the status up to date with the clock
+    ///
+    /// Publishes quotes whose embargo has lapsed and expires quotes past
+    /// their expiry (unapproved ones included). Returns true if the status
+    /// changed. Scheduled quotes are left to the sweeper, which records
+    /// their activation.
+    pub fn refresh_status(&mut self) -> bool {
+        let next = match self.status {
+            QuoteStatus::Active
+            | QuoteStatus::Pending
+            | QuoteStatus::PendingApproval
+            | QuoteStatus::Suspended
+                if self.is_expired() =>
+            {
+                QuoteStatus::Expired
+            }
+            QuoteStatus::Pending if !self.is_embargoed() => QuoteStatus::Active,
+            status => status,
+        };
+        let changed = next != self.status;
+        self.status = next;
+        changed
+    }
 }
 
 /// Request to create a new quote
 #[cfg(feature = "std")]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CreateQuoteRequest {
-    /// The English text describing the quote
+    /// The English text describing the quote (or constraint DSL, see `dsl`)
     pub text: String,
+    /// Compile `text` as constraint DSL instead of English
+    #[serde(default)]
+    pub dsl: bool,
     /// The maker's owner ID
     pub maker_owner_id: String,
     /// The maker's shard
     pub maker_shard: u64,
+    /// Post the quote at this unix timestamp (seconds) instead of now
+    ///
+    /// The compiled expiry and any embargo count from activation.
+    #[serde(default)]
+    pub activate_at: Option<i64>,
+    /// Keep the quote unlisted for this many seconds after it is posted
+    #[serde(default)]
+    pub embargo_secs: Option<u64>,
+    /// Takers that can see the quote during the embargo
+    /// (defaults to the quote's allowed takers)
+    #[serde(default)]
+    pub embargo_takers: Vec<String>,
+    /// Expiry in seconds from creation (overrides the compiled expiry)
+    #[serde(default)]
+    pub expiry_secs: Option<u64>,
+    /// Allowed takers (overrides the compiled allowlist; empty = any taker)
+    #[serde(default)]
+    pub allowed_takers: Option<Vec<String>>,
+    /// Minimum number of agreeing feed sources (overrides the compiled quorum)
+    #[serde(default)]
+    pub quorum_count: Option<u32>,
+    /// Maximum spread between sources, in percent (overrides the compiled tolerance)
+    #[serde(default)]
+    pub quorum_tolerance_percent: Option<f64>,
+    /// Fee leg the settlement may pay besides the DvP legs
+    #[serde(default)]
+    pub allowed_fee: Option<AllowedFee>,
+    /// Filters for fill attempts, checked before Local Laws
+    #[serde(default)]
+    pub auto_reject: Option<AutoRejectRules>,
+    /// Cancel or suspend the quote if the market moves away from its
+    /// reference price
+    #[serde(default)]
+    pub divergence_guard: Option<DivergenceGuard>,
+    /// Create the quote even if it duplicates one the maker has open
+    #[serde(default)]
+    pub allow_duplicate: bool,
+    /// Compile with the LLM even if the text was compiled before
+    #[serde(default)]
+    pub bypass_cache: bool,
+    /// Post the quote even if the LLM was unsure of a critical field,
+    /// instead of holding it for approval
+    #[serde(default)]
+    pub accept_low_confidence: bool,
+    /// Deployment-specific rule data attached to the constraints
+    #[serde(default)]
+    pub extensions: Extensions,
+}
+
+#[cfg(feature = "std")]
+impl CreateQuoteRequest {
+    /// Apply the explicit constraint fields over the compiled constraints
+    ///
+    /// Returns an audit event for every field whose value changed.
+    pub fn apply_overrides(
+        &self,
+        constraints: &mut QuoteConstraints,
+        now: DateTime<Utc>,
+    ) -> Vec<AuditEvent> {
+        fn record<T: PartialEq + Serialize>(
+            events: &mut Vec<AuditEvent>,
+            field: &str,
+            target: &mut T,
+            value: T,
+        ) {
+            if *target != value {
+                events.push(AuditEvent::ConstraintOverride {
+                    field: field.to_string(),
+                    compiled: serde_json::json!(*target),
+                    applied: serde_json::json!(value),
+                });
+                *target = value;
+            }
+        }
+
+        let mut events = Vec::new();
+        if let Some(secs) = self.expiry_secs {
+            let expiry = now.timestamp() as u64 + secs;
+            record(&mut events, "expiry_timestamp", &mut constraints.expiry_timestamp, expiry);
+        }
+        if let Some(takers) = &self.allowed_takers {
+            record(&mut events, "allowed_takers", &mut constraints.allowed_takers, takers.clone());
+        }
+        if let Some(count) = self.quorum_count {
+            record(&mut events, "quorum_count", &mut constraints.quorum_count, count);
+        }
+        if let Some(tolerance) = self.quorum_tolerance_percent {
+            record(
+                &mut events,
+                "quorum_tolerance_bps",
+                &mut constraints.quorum_tolerance_bps,
+                percent_to_bps(tolerance),
+            );
+        }
+        if let Some(fee) = self.allowed_fee {
+            record(&mut events, "allowed_fee", &mut constraints.allowed_fee, Some(fee));
+        }
+        events
+    }
 }
 
 /// Response after creating a quote
@@ -57,6 +57,19 @@ impl FillReceipt {
         matches!(self.result, FillResult::Accepted { .. })
     }
 
+    /// Check if the fill is awaiting benchmark settlement
+    pub fn is_pending(&self) -> bool {
+        matches!(self.result, FillResult::PendingSettlement { .. })
+    }
+
+    /// SDL hash of an accepted fill
+    pub fn sdl_hash(&self) -> Option<&str> {
+        match &self.result {
+            FillResult::Accepted { sdl_hash, .. } => Some(sdl_hash),
+            _ => None,
+        }
+    }
+
     /// Get the rejection reason if rejected
     pub fn rejection_reason(&self) -> Option<&RejectionReason> {
         match &self.result {
@@ -70,7 +83,12 @@ impl FillReceipt {
         ReceiptSummary {
             receipt_id: self.receipt_id,
             quote_id: self.quote.id,
-            status: if self.is_accepted() { "ACCEPTED" } else { "REJECTED" }.to_string(),
+            status: match &self.result {
+                FillResult::Accepted { .. } => "ACCEPTED",
+                FillResult::PendingSettlement { .. } => "PENDING_SETTLEMENT",
+                FillResult::Rejected { .. } => "REJECTED",
+            }
+            .to_string(),
             reason: self.rejection_reason().map(|r| r.message()),
             reason_code: self.rejection_reason().map(|r| r.code().to_string()),
             taker: self.fill_attempt.taker_owner_id.clone(),
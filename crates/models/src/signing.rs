@@ -0,0 +1,442 @@
+//! Pluggable signature schemes
+//!
+//! Feed evidence and receipts are signed by parties with their own kinds
+//! of key: most oracles sign with ed25519, EVM-native counterparties with
+//! secp256k1. A [`PublicKey`] names its [`SchemeKind`] and verification
+//! dispatches to the matching [`SignatureScheme`], so each party signs in
+//! its native format:
+//!
+//! - `ed25519`: 32-byte public key, 64-byte signature over the message
+//! - `secp256k1`: EIP-191 `personal_sign`, a 65-byte `r || s || v`
+//!   signature over `keccak256("\x19Ethereum Signed Message:\n" || len ||
+//!   message)`. The key is the signer's 20-byte address, or a 33 or
+//!   65-byte SEC1 public key.
+//!
+//! Keys and signatures are written as hex, with or without `0x`.
+
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+use serde::{Deserialize, Serialize};
+
+/// A signature algorithm
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SchemeKind {
+    #[default]
+    Ed25519,
+    Secp256k1,
+}
+
+impl SchemeKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Ed25519 => "ed25519",
+            Self::Secp256k1 => "secp256k1",
+        }
+    }
+
+    /// The scheme that verifies signatures of this kind
+    pub fn scheme(&self) -> &'static dyn SignatureScheme {
+        match self {
+            Self::Ed25519 => &Ed25519,
+            Self::Secp256k1 => &Secp256k1,
+        }
+    }
+}
+
+/// Why a signature didn't verify
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignatureError {
+    /// The public key isn't a valid key of the scheme
+    InvalidKey,
+    /// The signature isn't well-formed for the scheme
+    Malformed,
+    /// The signature is well-formed but wasn't made by the key
+    Mismatch,
+}
+
+impl fmt::Display for SignatureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidKey => write!(f, "invalid public key"),
+            Self::Malformed => write!(f, "malformed signature"),
+            Self::Mismatch => write!(f, "signature doesn't match the key"),
+        }
+    }
+}
+
+/// Verification of one signature algorithm
+pub trait SignatureScheme: Send + Sync {
+    fn kind(&self) -> SchemeKind;
+
+    /// Check that `signature` over `message` was made by `public_key`
+    fn verify(&self, public_key: &[u8], message: &[u8], signature: &[u8])
+        -> Result<(), SignatureError>;
+}
+
+/// A private key that signs with one scheme
+pub trait Signer: Send + Sync {
+    fn kind(&self) -> SchemeKind;
+
+    /// The key signatures verify against (an address for secp256k1)
+    fn public_key(&self) -> Vec<u8>;
+
+    fn sign(&self, message: &[u8]) -> Vec<u8>;
+}
+
+/// Ed25519 (RFC 8032)
+pub struct Ed25519;
+
+impl SignatureScheme for Ed25519 {
+    fn kind(&self) -> SchemeKind {
+        SchemeKind::Ed25519
+    }
+
+    fn verify(
+        &self,
+        public_key: &[u8],
+        message: &[u8],
+        signature: &[u8],
+    ) -> Result<(), SignatureError> {
+        let key: [u8; 32] = public_key.try_into().map_err(|_| SignatureError::InvalidKey)?;
+        let key = ed25519_dalek::VerifyingKey::from_bytes(&key)
+            .map_err(|_| SignatureError::InvalidKey)?;
+        let signature = ed25519_dalek::Signature::from_slice(signature)
+            .map_err(|_| SignatureError::Malformed)?;
+        key.verify_strict(message, &signature)
+            .map_err(|_| SignatureError::Mismatch)
+    }
+}
+
+/// Signs with an ed25519 key
+pub struct Ed25519Signer(ed25519_dalek::SigningKey);
+
+impl Ed25519Signer {
+    pub fn from_seed(seed: &[u8; 32]) -> Self {
+        Self(ed25519_dalek::SigningKey::from_bytes(seed))
+    }
+}
+
+impl Signer for Ed25519Signer {
+    fn kind(&self) -> SchemeKind {
+        SchemeKind::Ed25519
+    }
+
+    fn public_key(&self) -> Vec<u8> {
+        self.0.verifying_key().to_bytes().to_vec()
+    }
+
+    fn sign(&self, message: &[u8]) -> Vec<u8> {
+        use ed25519_dalek::Signer as _;
+        self.0.sign(message).to_bytes().to_vec()
+    }
+}
+
+/// secp256k1 ECDSA over EIP-191 personal messages, as EVM wallets sign
+pub struct Secp256k1;
+
+/// keccak256 of `message` as an EIP-191 personal message
+fn personal_message_hash(message: &[u8]) -> [u8; 32] {
+    use sha3::{Digest, Keccak256};
+    let mut hasher = Keccak256::new();
+    hasher.update(format!("\x19Ethereum Signed Message:\n{}", message.len()).as_bytes());
+    hasher.update(message);
+    hasher.finalize().into()
+}
+
+/// The EVM address of a secp256k1 key: the last 20 bytes of the
+/// keccak256 of its uncompressed point
+fn evm_address(key: &k256::ecdsa::VerifyingKey) -> [u8; 20] {
+    use sha3::{Digest, Keccak256};
+    let point = key.to_encoded_point(false);
+    let hash = Keccak256::digest(&point.as_bytes()[1..]);
+    let mut address = [0u8; 20];
+    address.copy_from_slice(&hash[12..]);
+    address
+}
+
+impl SignatureScheme for Secp256k1 {
+    fn kind(&self) -> SchemeKind {
+        SchemeKind::Secp256k1
+    }
+
+    fn verify(
+        &self,
+        public_key: &[u8],
+        message: &[u8],
+        signature: &[u8],
+    ) -> Result<(), SignatureError> {
+        use k256::ecdsa::{RecoveryId, Signature, VerifyingKey};
+        let [rs @ .., v] = signature else {
+            return Err(SignatureError::Malformed);
+        };
+        if rs.len() != 64 {
+            return Err(SignatureError::Malformed);
+        }
+        // Wallets write v as 27/28, libraries as 0/1
+        let recovery = RecoveryId::from_byte(v.checked_sub(27).unwrap_or(*v))
+            .ok_or(SignatureError::Malformed)?;
+        let signature = Signature::from_slice(rs).map_err(|_| SignatureError::Malformed)?;
+        let hash = personal_message_hash(message);
+        let signer = VerifyingKey::recover_from_prehash(&hash, &signature, recovery)
+            .map_err(|_| SignatureError::Mismatch)?;
+
+        let matches = match public_key.len() {
+            20 => evm_address(&signer) == public_key,
+            33 | 65 => {
+                let key = VerifyingKey::from_sec1_bytes(public_key)
+                    .map_err(|_| SignatureError::InvalidKey)?;
+                key == signer
+            }
+            _ => return Err(SignatureError::InvalidKey),
+        };
+        if matches {
+            Ok(())
+        } else {
+            Err(SignatureError::Mismatch)
+        }
+    }
+}
+
+/// Signs EIP-191 personal messages with a secp256k1 key
+pub struct Secp256k1Signer(k256::ecdsa::SigningKey);
+
+impl Secp256k1Signer {
+    /// Returns None if `secret` isn't a valid secp256k1 scalar
+    pub fn from_bytes(secret: &[u8; 32]) -> Option<Self> {
+        k256::ecdsa::SigningKey::from_bytes(secret.into()).ok().map(Self)
+    }
+}
+
+impl Signer for Secp256k1Signer {
+    fn kind(&self) -> SchemeKind {
+        SchemeKind::Secp256k1
+    }
+
+    fn public_key(&self) -> Vec<u8> {
+        evm_address(self.0.verifying_key()).to_vec()
+    }
+
+    fn sign(&self, message: &[u8]) -> Vec<u8> {
+        let hash = personal_message_hash(message);
+        let (signature, recovery) = self
+            .0
+            .sign_prehash_recoverable(&hash)
+            .expect("a 32-byte prehash always signs");
+        let mut bytes = signature.to_bytes().to_vec();
+        bytes.push(27 + recovery.to_byte());
+        bytes
+    }
+}
+
+/// A party's public key and the scheme it signs with
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PublicKey {
+    #[serde(default)]
+    pub scheme: SchemeKind,
+    /// Hex public key (or EVM address for secp256k1)
+    pub key: String,
+}
+
+impl PublicKey {
+    /// Check a hex `signature` over `message`
+    pub fn verify(&self, message: &[u8], signature: &str) -> Result<(), SignatureError> {
+        let key = from_hex(&self.key).ok_or(SignatureError::InvalidKey)?;
+        let signature = from_hex(signature).ok_or(SignatureError::Malformed)?;
+        self.scheme.scheme().verify(&key, message, &signature)
+    }
+}
+
+/// A private key and the scheme it signs with
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SigningKey {
+    #[serde(default)]
+    pub scheme: SchemeKind,
+    /// Hex 32-byte secret: an ed25519 seed or a secp256k1 scalar
+    pub secret: String,
+}
+
+impl SigningKey {
+    /// Returns None if the secret isn't a valid key of the scheme
+    pub fn signer(&self) -> Option<Box<dyn Signer>> {
+        let secret: [u8; 32] = from_hex(&self.secret)?.try_into().ok()?;
+        Some(match self.scheme {
+            SchemeKind::Ed25519 => Box::new(Ed25519Signer::from_seed(&secret)),
+            SchemeKind::Secp256k1 => Box::new(Secp256k1Signer::from_bytes(&secret)?),
+        })
+    }
+
+    /// The public key others verify this key's signatures with
+    pub fn public_key(&self) -> Option<PublicKey> {
+        let signer = self.signer()?;
+        Some(PublicKey {
+            scheme: self.scheme,
+            key: to_hex(&signer.public_key()),
+        })
+    }
+}
+
+/// Sign `message` and encode the signature as hex
+pub fn sign_hex(signer: &dyn Signer, message: &[u8]) -> String {
+    to_hex(&signer.sign(message))
+}
+
+/// The message a feed signs for one price
+///
+/// The price is the fixed-point integer (see
+/// [`PRICE_DECIMALS`](crate::PRICE_DECIMALS)), so the same report always
+/// signs the same bytes on every platform.
+pub fn feed_message(source: &str, asset: &str, price: u64, timestamp: u64) -> String {
+    format!("rfq-feed:{}:{}:{}:{}", source, asset, price, timestamp)
+}
+
+/// The message a taker signs to request a fill, with the size and price
+/// as requested
+pub fn fill_message(
+    quote_id: &str,
+    taker_owner_id: &str,
+    taker_nonce: u64,
+    size: f64,
+    price: f64,
+) -> String {
+    format!("rfq-fill:{}:{}:{}:{}:{}", quote_id, taker_owner_id, taker_nonce, size, price)
+}
+
+/// The message a party signs to attest to a receipt, by its hash (hex)
+pub fn receipt_message(receipt_hash: &str) -> String {
+    format!("rfq-receipt:{}", receipt_hash)
+}
+
+/// Lowercase hex, without a prefix
+pub fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Decode hex, with or without a `0x` prefix
+pub fn from_hex(text: &str) -> Option<Vec<u8>> {
+    let text = text.trim();
+    let text = text.strip_prefix("0x").unwrap_or(text);
+    if text.len() % 2 != 0 {
+        return None;
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(text.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(scheme: SchemeKind, byte: u8) -> SigningKey {
+        SigningKey {
+            scheme,
+            secret: to_hex(&[byte; 32]),
+        }
+    }
+
+    fn message(price: u64) -> String {
+        feed_message("FeedA", "dETH", price, 1737400000)
+    }
+
+    #[test]
+    fn test_sign_verify_round_trip() {
+        for scheme in [SchemeKind::Ed25519, SchemeKind::Secp256k1] {
+            let key = key(scheme, 7);
+            let signature = sign_hex(key.signer().unwrap().as_ref(), message(1950).as_bytes());
+            let public_key = key.public_key().unwrap();
+            assert_eq!(public_key.verify(message(1950).as_bytes(), &signature), Ok(()));
+            assert_eq!(
+                public_key.verify(message(1950).as_bytes(), &format!("0x{}", signature)),
+                Ok(())
+            );
+        }
+    }
+
+    #[test]
+    fn test_tampered_price_rejected() {
+        for scheme in [SchemeKind::Ed25519, SchemeKind::Secp256k1] {
+            let key = key(scheme, 7);
+            let signature = sign_hex(key.signer().unwrap().as_ref(), message(1950).as_bytes());
+            assert_eq!(
+                key.public_key().unwrap().verify(message(1951).as_bytes(), &signature),
+                Err(SignatureError::Mismatch),
+                "{}",
+                scheme.as_str()
+            );
+        }
+    }
+
+    #[test]
+    fn test_wrong_key_rejected() {
+        for scheme in [SchemeKind::Ed25519, SchemeKind::Secp256k1] {
+            let signature = sign_hex(
+                key(scheme, 7).signer().unwrap().as_ref(),
+                message(1950).as_bytes(),
+            );
+            assert_eq!(
+                key(scheme, 8).public_key().unwrap().verify(message(1950).as_bytes(), &signature),
+                Err(SignatureError::Mismatch),
+                "{}",
+                scheme.as_str()
+            );
+        }
+
+        // A key of the other scheme never verifies
+        let signature = sign_hex(
+            key(SchemeKind::Ed25519, 7).signer().unwrap().as_ref(),
+            message(1950).as_bytes(),
+        );
+        let public_key = PublicKey {
+            scheme: SchemeKind::Secp256k1,
+            ..key(SchemeKind::Ed25519, 7).public_key().unwrap()
+        };
+        assert!(public_key.verify(message(1950).as_bytes(), &signature).is_err());
+    }
+
+    #[test]
+    fn test_secp256k1_recovery_byte() {
+        // Libraries write v as 0/1 rather than 27/28
+        let key = key(SchemeKind::Secp256k1, 7);
+        let mut signature = key.signer().unwrap().sign(message(1950).as_bytes());
+        signature[64] -= 27;
+        assert_eq!(
+            key.public_key().unwrap().verify(message(1950).as_bytes(), &to_hex(&signature)),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_malformed_input() {
+        let public_key = key(SchemeKind::Ed25519, 7).public_key().unwrap();
+        assert_eq!(
+            public_key.verify(message(1950).as_bytes(), "not hex"),
+            Err(SignatureError::Malformed)
+        );
+        assert_eq!(
+            public_key.verify(message(1950).as_bytes(), "abcd"),
+            Err(SignatureError::Malformed)
+        );
+        let short_key = PublicKey {
+            scheme: SchemeKind::Ed25519,
+            key: "abcd".into(),
+        };
+        assert_eq!(
+            short_key.verify(message(1950).as_bytes(), &to_hex(&[0; 64])),
+            Err(SignatureError::InvalidKey)
+        );
+        // The secp256k1 scalar must be non-zero
+        assert!(key(SchemeKind::Secp256k1, 0).signer().is_none());
+    }
+
+    #[test]
+    fn test_hex_round_trip() {
+        assert_eq!(from_hex(&to_hex(&[0, 1, 0xab, 0xff])), Some(vec![0, 1, 0xab, 0xff]));
+        assert_eq!(from_hex("0xABff"), Some(vec![0xab, 0xff]));
+        assert_eq!(from_hex("abc"), None);
+    }
+}
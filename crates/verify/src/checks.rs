@@ -0,0 +1,534 @@
+//! Receipt checks
+//!
+//! Each check re-derives one claim a receipt makes using only the receipt,
+//! the public hashing rules of the domain's receipt log and the Local Laws
+//! crate, so nothing the domain says has to be taken on trust.
+//!
+//! Hashing (as in the domain's notary):
+//! - `receipt_hash = SHA-256(JSON of the receipt)`
+//! - `entry_hash = SHA-256(prev_hash || receipt_hash)`
+//! - `leaf = SHA-256(0x00 || receipt_hash)`
+//! - `node = SHA-256(0x01 || left || right)`
+
+use rfq_compiler::AssetRegistry;
+use rfq_local_laws::{PublicOutputs, RfqLocalLawsInput};
+use rfq_models::signing::{self, PublicKey};
+use rfq_models::{FillReceipt, FillRecord, FillResult};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// A SHA-256 digest
+pub type Hash = [u8; 32];
+
+/// Result of one check
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Pass,
+    Fail,
+    /// Not enough material to check (e.g. no inclusion proof given)
+    Skip,
+}
+
+/// One line of the report
+#[derive(Debug, Clone)]
+pub struct Check {
+    pub name: &'static str,
+    pub outcome: Outcome,
+    pub detail: String,
+}
+
+impl Check {
+    fn new(name: &'static str, outcome: Outcome, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            outcome,
+            detail: detail.into(),
+        }
+    }
+}
+
+/// Public keys of feeds and makers, each with its signature scheme
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct TrustedKeys {
+    /// By feed source name
+    #[serde(default)]
+    pub feeds: HashMap<String, PublicKey>,
+    /// By maker owner ID
+    #[serde(default)]
+    pub makers: HashMap<String, PublicKey>,
+}
+
+/// Proof that a receipt is in an anchored day root (GET /receipts/:id/inclusion)
+#[derive(Debug, Clone, Deserialize)]
+pub struct InclusionProof {
+    pub receipt_id: Uuid,
+    pub receipt_hash: String,
+    pub proof: Vec<ProofStep>,
+    pub anchor: Anchor,
+    /// The receipt's entry in the hash-chained log (None from domains that
+    /// predate it)
+    #[serde(default)]
+    pub entry: Option<LogEntry>,
+}
+
+/// An entry in the domain's hash-chained receipt log
+#[derive(Debug, Clone, Deserialize)]
+pub struct LogEntry {
+    pub index: u64,
+    pub receipt_hash: String,
+    pub prev_hash: String,
+    pub entry_hash: String,
+}
+
+/// One step of a Merkle inclusion proof
+#[derive(Debug, Clone, Deserialize)]
+pub struct ProofStep {
+    pub sibling: String,
+    pub side: Side,
+}
+
+/// Which side of the running hash a proof sibling sits on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Side {
+    Left,
+    Right,
+}
+
+/// A day's receipt root committed on Delta
+#[derive(Debug, Clone, Deserialize)]
+pub struct Anchor {
+    pub day: String,
+    pub root: String,
+    pub sdl_hash: String,
+    pub anchor_owner_id: String,
+}
+
+/// Hash of a receipt's JSON encoding
+pub fn receipt_hash(receipt: &FillReceipt) -> Hash {
+    let bytes = serde_json::to_vec(receipt).expect("receipts serialize to JSON");
+    Sha256::digest(bytes).into()
+}
+
+pub fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+pub fn from_hex(text: &str) -> Option<Vec<u8>> {
+    let text = text.trim().trim_start_matches("0x");
+    if !text.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(text.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+fn hash_from_hex(text: &str) -> Option<Hash> {
+    from_hex(text)?.try_into().ok()
+}
+
+fn leaf_hash(receipt_hash: &Hash) -> Hash {
+    Sha256::new()
+        .chain_update([0x00])
+        .chain_update(receipt_hash)
+        .finalize()
+        .into()
+}
+
+fn entry_hash(prev_hash: &Hash, receipt_hash: &Hash) -> Hash {
+    Sha256::new()
+        .chain_update(prev_hash)
+        .chain_update(receipt_hash)
+        .finalize()
+        .into()
+}
+
+fn node_hash(left: &Hash, right: &Hash) -> Hash {
+    Sha256::new()
+        .chain_update([0x01])
+        .chain_update(left)
+        .chain_update(right)
+        .finalize()
+        .into()
+}
+
+/// The receipt is in a day root the domain anchored on Delta
+pub fn inclusion(receipt: &FillReceipt, hash: &Hash, proof: Option<&InclusionProof>) -> Check {
+    let check = |outcome, detail: String| Check::new("inclusion", outcome, detail);
+    let Some(proof) = proof else {
+        return check(Outcome::Skip, "no inclusion proof given".to_string());
+    };
+    if proof.receipt_id != receipt.receipt_id {
+        return check(
+            Outcome::Fail,
+            format!("the proof is for receipt {}", proof.receipt_id),
+        );
+    }
+    if hash_from_hex(&proof.receipt_hash) != Some(*hash) {
+        return check(
+            Outcome::Fail,
+            format!("the proof is for receipt hash {}", proof.receipt_hash),
+        );
+    }
+
+    let mut computed = leaf_hash(hash);
+    for step in &proof.proof {
+        let Some(sibling) = hash_from_hex(&step.sibling) else {
+            return check(Outcome::Fail, format!("invalid sibling hash {}", step.sibling));
+        };
+        computed = match step.side {
+            Side::Left => node_hash(&sibling, &computed),
+            Side::Right => node_hash(&computed, &sibling),
+        };
+    }
+    if hash_from_hex(&proof.anchor.root) != Some(computed) {
+        return check(
+            Outcome::Fail,
+            format!(
+                "the proof leads to root {}, not the anchored {}",
+                to_hex(&computed),
+                proof.anchor.root
+            ),
+        );
+    }
+    // The anchor transfer credits the vault whose owner ID is the root
+    if bs58::encode(computed).into_string() != proof.anchor.anchor_owner_id {
+        return check(
+            Outcome::Fail,
+            format!("anchor vault {} isn't derived from the root", proof.anchor.anchor_owner_id),
+        );
+    }
+    check(
+        Outcome::Pass,
+        format!(
+            "in the {} root anchored by SDL {} (vault {})",
+            proof.anchor.day, proof.anchor.sdl_hash, proof.anchor.anchor_owner_id
+        ),
+    )
+}
+
+/// The receipt's log entry links to the entry logged before it
+///
+/// The entry must hash correctly over the receipt; with the preceding
+/// receipt's inclusion proof (`previous`), that entry's hash must be the
+/// one `prev_hash` names, so receipts can't be dropped from or slipped into
+/// the log between them.
+pub fn chain(
+    hash: &Hash,
+    proof: Option<&InclusionProof>,
+    previous: Option<&InclusionProof>,
+) -> Check {
+    let check = |outcome, detail: String| Check::new("receipt chain", outcome, detail);
+    let Some(entry) = proof.and_then(|p| p.entry.as_ref()) else {
+        return check(Outcome::Skip, "no log entry given".to_string());
+    };
+    if hash_from_hex(&entry.receipt_hash) != Some(*hash) {
+        return check(
+            Outcome::Fail,
+            format!("entry {} is for receipt hash {}", entry.index, entry.receipt_hash),
+        );
+    }
+    let Some(prev_hash) = hash_from_hex(&entry.prev_hash) else {
+        return check(Outcome::Fail, format!("invalid prev hash {}", entry.prev_hash));
+    };
+    if hash_from_hex(&entry.entry_hash) != Some(entry_hash(&prev_hash, hash)) {
+        return check(
+            Outcome::Fail,
+            format!("entry {} hash isn't over its prev hash and receipt", entry.index),
+        );
+    }
+
+    // The first entry chains from zero
+    if entry.index == 0 {
+        return if prev_hash == [0u8; 32] {
+            check(Outcome::Pass, "first entry of the log".to_string())
+        } else {
+            check(Outcome::Fail, format!("entry 0 has prev hash {}", entry.prev_hash))
+        };
+    }
+    let Some(previous) = previous else {
+        return check(
+            Outcome::Skip,
+            format!(
+                "entry {} hashes correctly; its link to entry {} needs the previous receipt's \
+                 inclusion proof",
+                entry.index,
+                entry.index - 1
+            ),
+        );
+    };
+    let Some(before) = &previous.entry else {
+        return check(
+            Outcome::Fail,
+            "the previous inclusion proof has no log entry".to_string(),
+        );
+    };
+    let before_hash = match (
+        hash_from_hex(&before.prev_hash),
+        hash_from_hex(&before.receipt_hash),
+    ) {
+        (Some(prev), Some(receipt)) => entry_hash(&prev, &receipt),
+        _ => return check(Outcome::Fail, format!("invalid hashes in entry {}", before.index)),
+    };
+    if before.index + 1 != entry.index {
+        return check(
+            Outcome::Fail,
+            format!("the previous proof is for entry {}, not {}", before.index, entry.index - 1),
+        );
+    }
+    if hash_from_hex(&before.entry_hash) != Some(before_hash) || before_hash != prev_hash {
+        return check(
+            Outcome::Fail,
+            format!(
+                "prev hash {} isn't entry {}'s hash {}",
+                entry.prev_hash,
+                before.index,
+                to_hex(&before_hash)
+            ),
+        );
+    }
+    check(
+        Outcome::Pass,
+        format!("entry {} chains from entry {}", entry.index, before.index),
+    )
+}
+
+/// Every piece of feed evidence carries its source's signature
+///
+/// Evidence is verified with its source's key in `keys`, in the key's
+/// scheme. Evidence from a source without a key can't be verified and
+/// fails the check, whatever signature it carries.
+pub fn feed_signatures(receipt: &FillReceipt, keys: &TrustedKeys) -> Check {
+    let evidence = &receipt.fill_attempt.feed_evidence;
+    if evidence.is_empty() {
+        return Check::new("feed signatures", Outcome::Skip, "the fill carried no feed evidence");
+    }
+    let unsigned: Vec<String> = evidence
+        .iter()
+        .filter_map(|e| {
+            let error = match keys.feeds.get(&e.source) {
+                Some(key) => key
+                    .verify(e.signed_message().as_bytes(), &e.signature)
+                    .err()?
+                    .to_string(),
+                None => "no key for the source, unverified".to_string(),
+            };
+            Some(format!("{}@{} ({})", e.source, e.timestamp, error))
+        })
+        .collect();
+    if unsigned.is_empty() {
+        Check::new(
+            "feed signatures",
+            Outcome::Pass,
+            format!("{} evidence entries signed by their sources' keys", evidence.len()),
+        )
+    } else {
+        Check::new(
+            "feed signatures",
+            Outcome::Fail,
+            format!("unverified signature on {}", unsigned.join(", ")),
+        )
+    }
+}
+
+/// The quote's maker signed the receipt hash
+///
+/// The maker signs `rfq-receipt:<receipt hash hex>` with the scheme of its
+/// key in `keys`.
+pub fn maker_signature(
+    receipt: &FillReceipt,
+    hash: &Hash,
+    keys: &TrustedKeys,
+    signature: Option<&str>,
+) -> Check {
+    let check = |outcome, detail: String| Check::new("maker signature", outcome, detail);
+    let Some(signature) = signature else {
+        return check(Outcome::Skip, "no maker signature given".to_string());
+    };
+    let maker = &receipt.quote.maker_owner_id;
+    let Some(key) = keys.makers.get(maker) else {
+        return check(Outcome::Skip, format!("no key for maker {}", maker));
+    };
+    let message = signing::receipt_message(&to_hex(hash));
+    match key.verify(message.as_bytes(), signature) {
+        Ok(()) => check(
+            Outcome::Pass,
+            format!("signed by maker {} ({})", maker, key.scheme.as_str()),
+        ),
+        Err(e) => check(Outcome::Fail, format!("maker {}: {}", maker, e)),
+    }
+}
+
+/// The proof's public values commit to this receipt's quote and settled fill
+///
+/// The Local Laws program commits [`PublicOutputs`], which are recomputed
+/// from the receipt: its constraints, settled amounts, taker and evidence.
+pub fn public_outputs(receipt: &FillReceipt, public_values: Option<&[u8]>) -> Check {
+    let check = |outcome, detail: String| Check::new("public outputs", outcome, detail);
+    let Some(values) = public_values else {
+        return check(Outcome::Skip, "no public values given".to_string());
+    };
+    let FillResult::Accepted { settlement, .. } = &receipt.result else {
+        return check(
+            Outcome::Fail,
+            "public values were given, but the receipt records no settled fill".to_string(),
+        );
+    };
+    let Some(committed) = PublicOutputs::from_bytes(values) else {
+        return check(
+            Outcome::Fail,
+            format!(
+                "expected {} bytes of public values, got {}",
+                PublicOutputs::LEN,
+                values.len()
+            ),
+        );
+    };
+    let attempt = &receipt.fill_attempt;
+    // Settled amounts are what the proven fill carried
+    let (fill_size, fill_price) = settlement.fill_amounts(receipt.constraints.side);
+    let expected = PublicOutputs::new(
+        &receipt.constraints,
+        &attempt.taker_owner_id,
+        fill_size,
+        fill_price,
+        &attempt.feed_evidence,
+    );
+
+    let mut mismatches = Vec::new();
+    if committed.quote_id != expected.quote_id {
+        mismatches.push(format!("quote {}", to_hex(&committed.quote_id)));
+    }
+    if committed.constraints_hash != expected.constraints_hash {
+        mismatches.push("constraints hash".to_string());
+    }
+    if committed.fill_size != expected.fill_size {
+        mismatches.push(format!("fill size {}", committed.fill_size));
+    }
+    if committed.fill_price != expected.fill_price {
+        mismatches.push(format!("fill price {}", committed.fill_price));
+    }
+    if committed.taker_hash != expected.taker_hash {
+        mismatches.push("taker hash".to_string());
+    }
+    if committed.feed_sources != expected.feed_sources {
+        mismatches.push(format!("{} feed sources", committed.feed_sources));
+    }
+    if mismatches.is_empty() {
+        check(
+            Outcome::Pass,
+            format!(
+                "quote {} validated: size {} at {}, {} feed sources",
+                to_hex(&committed.quote_id),
+                committed.fill_size,
+                committed.fill_price,
+                committed.feed_sources
+            ),
+        )
+    } else {
+        check(
+            Outcome::Fail,
+            format!("the proof committed a different {}", mismatches.join(", ")),
+        )
+    }
+}
+
+/// Replaying the recorded fill through Local Laws gives the recorded outcome
+pub fn constraints(receipt: &FillReceipt, assets: &AssetRegistry, clock_skew_secs: u64) -> Check {
+    let check = |outcome, detail: String| Check::new("constraints", outcome, detail);
+    let recorded = serde_json::to_value(&receipt.constraints).ok();
+    if recorded != serde_json::to_value(&receipt.quote.constraints).ok() {
+        return check(
+            Outcome::Fail,
+            "the receipt's constraints differ from its quote's".to_string(),
+        );
+    }
+
+    let attempt = &receipt.fill_attempt;
+    let (fill_size, fill_price) = match &receipt.result {
+        // Settled amounts are exact
+        FillResult::Accepted { settlement, .. } => {
+            settlement.fill_amounts(receipt.constraints.side)
+        }
+        _ => {
+            let spec = &receipt.quote.spec;
+            let units = assets.to_units(&spec.asset, attempt.size).and_then(|size| {
+                Ok((size, assets.to_units(&spec.currency, attempt.price * attempt.size)?))
+            });
+            match units {
+                Ok(units) => units,
+                Err(e) => return check(Outcome::Fail, e.to_string()),
+            }
+        }
+    };
+
+    // An accepted fill is already in its quote's history
+    let mut fill_history = receipt.quote.fill_history.clone();
+    if !matches!(receipt.result, FillResult::Rejected { .. }) {
+        if let Some(pos) = fill_history
+            .iter()
+            .rposition(|f| f.taker_owner_id == attempt.taker_owner_id && f.fill_size == fill_size)
+        {
+            fill_history.remove(pos);
+        }
+    }
+
+    let filled_so_far = FillRecord::total_size(&fill_history);
+    let taker_filled_notional =
+        FillRecord::taker_notional(&fill_history, &attempt.taker_owner_id);
+    let input = RfqLocalLawsInput {
+        constraints: receipt.constraints.clone(),
+        taker_owner_id: attempt.taker_owner_id.clone(),
+        taker_shard: attempt.taker_shard,
+        fill_size,
+        fill_price,
+        feed_evidence: attempt.feed_evidence.clone(),
+        current_timestamp: attempt.attempted_at.timestamp() as u64,
+        clock_skew_secs,
+        transfer_leg_count: 2,
+        has_extra_transfers: false,
+        fill_history,
+        benchmark: None,
+        leg_fill_sizes: receipt.constraints.leg_fill_sizes(fill_size),
+        filled_so_far,
+        taker_filled_notional,
+        counterparties: None,
+        extensions: Default::default(),
+    };
+    // Benchmark samples aren't in the receipt, so only acceptance replays
+    let benchmark = receipt.constraints.benchmark.is_some();
+    let replayed = if benchmark {
+        rfq_local_laws::validate_acceptance(&input)
+    } else {
+        rfq_local_laws::validate_fill(&input)
+    };
+    let scope = if benchmark { "acceptance" } else { "fill" };
+
+    match (&receipt.result, replayed) {
+        (FillResult::Rejected { reason, .. }, Err(e)) if e.code() == reason.code() => {
+            let detail = format!("Local Laws reproduce the rejection ({})", reason.code());
+            check(Outcome::Pass, detail)
+        }
+        (FillResult::Rejected { reason, .. }, Err(replayed)) => check(
+            Outcome::Skip,
+            format!(
+                "rejected with {}, which the domain checked before Local Laws (they report {})",
+                reason.code(),
+                replayed.code()
+            ),
+        ),
+        (FillResult::Rejected { reason, .. }, Ok(())) => check(
+            Outcome::Skip,
+            format!("rejected with {} by a domain check outside Local Laws", reason.code()),
+        ),
+        (_, Ok(())) => check(Outcome::Pass, format!("Local Laws accept the recorded {}", scope)),
+        (_, Err(replayed)) => check(
+            Outcome::Fail,
+            format!("the fill was accepted, but Local Laws reject it: {}", replayed.message()),
+        ),
+    }
+}
@@ -0,0 +1,169 @@
+//! RFQ Receipt Verifier
+//!
+//! Checks a fill receipt without access to the domain, so a counterparty
+//! can confirm what the domain claims happened:
+//! - the receipt is in a day root the domain anchored on Delta
+//! - its entry in the receipt log chains from the entry before it (with
+//!   `--prev-inclusion`)
+//! - the proof's public values commit to the quote, its constraints and the fill
+//! - the feed evidence is signed by its sources
+//! - the maker signed the receipt (with `--keys` and `--maker-signature`)
+//! - replaying the fill through Local Laws gives the recorded outcome
+//!
+//! ## Usage
+//!
+//! ```bash
+//! curl -s $DOMAIN/receipts/$RECEIPT > receipt.json
+//! curl -s $DOMAIN/receipts/$RECEIPT/inclusion > inclusion.json
+//! rfq-verify receipt.json --inclusion inclusion.json --public-values public_values.bin
+//! ```
+//!
+//! Exits with status 1 if any check fails.
+
+mod checks;
+
+use anyhow::{Context, Result};
+use checks::{Check, InclusionProof, Outcome, TrustedKeys};
+use clap::Parser;
+use rfq_compiler::AssetRegistry;
+use rfq_models::FillReceipt;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+#[derive(Parser)]
+#[command(name = "rfq-verify")]
+#[command(about = "Verify an RFQ Arena fill receipt without the domain")]
+struct CliArgs {
+    /// Receipt JSON (from GET /receipts/:id)
+    receipt: PathBuf,
+
+    /// Inclusion proof JSON (from GET /receipts/:id/inclusion)
+    #[arg(long)]
+    inclusion: Option<PathBuf>,
+
+    /// Inclusion proof JSON of the receipt logged just before this one, to
+    /// check the receipt log's hash chain between them
+    #[arg(long)]
+    prev_inclusion: Option<PathBuf>,
+
+    /// Public values committed by the fill's proof (raw bytes or hex)
+    #[arg(long)]
+    public_values: Option<PathBuf>,
+
+    /// Asset decimals, as the `assets` section of the domain config (YAML)
+    #[arg(long)]
+    assets: Option<PathBuf>,
+
+    /// Feed and maker public keys (YAML, `feeds:` and `makers:` by name,
+    /// each a `scheme` and hex `key`)
+    #[arg(long)]
+    keys: Option<PathBuf>,
+
+    /// The maker's hex signature over the receipt hash
+    #[arg(long)]
+    maker_signature: Option<String>,
+
+    /// How far feed clocks may be off, as configured on the domain
+    #[arg(long, default_value_t = 2)]
+    clock_skew_secs: u64,
+}
+
+fn main() -> Result<ExitCode> {
+    let args = CliArgs::parse();
+
+    let receipt: FillReceipt = read_json(&args.receipt)?;
+    let inclusion: Option<InclusionProof> = args.inclusion.as_deref().map(read_json).transpose()?;
+    let prev_inclusion: Option<InclusionProof> =
+        args.prev_inclusion.as_deref().map(read_json).transpose()?;
+    let public_values = args
+        .public_values
+        .as_ref()
+        .map(|path| {
+            let bytes = std::fs::read(path)
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            // Hex text or raw bytes
+            let hex = std::str::from_utf8(&bytes).ok().and_then(checks::from_hex);
+            Ok::<_, anyhow::Error>(hex.unwrap_or(bytes))
+        })
+        .transpose()?;
+    let assets: AssetRegistry = match &args.assets {
+        Some(path) => {
+            let text = std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            serde_yaml::from_str(&text).with_context(|| format!("Invalid {}", path.display()))?
+        }
+        None => AssetRegistry::default(),
+    };
+    let keys: TrustedKeys = match &args.keys {
+        Some(path) => {
+            let text = std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            serde_yaml::from_str(&text).with_context(|| format!("Invalid {}", path.display()))?
+        }
+        None => TrustedKeys::default(),
+    };
+
+    let hash = checks::receipt_hash(&receipt);
+    let report = [
+        Check {
+            name: "receipt hash",
+            outcome: Outcome::Pass,
+            detail: checks::to_hex(&hash),
+        },
+        checks::inclusion(&receipt, &hash, inclusion.as_ref()),
+        checks::chain(&hash, inclusion.as_ref(), prev_inclusion.as_ref()),
+        checks::public_outputs(&receipt, public_values.as_deref()),
+        checks::feed_signatures(&receipt, &keys),
+        checks::maker_signature(&receipt, &hash, &keys, args.maker_signature.as_deref()),
+        checks::constraints(&receipt, &assets, args.clock_skew_secs),
+    ];
+
+    println!(
+        "Receipt {} for quote {} ({})",
+        receipt.receipt_id,
+        receipt.quote.id,
+        receipt.summary().status
+    );
+    if let Some(stamp) = &receipt.constraints.compiled_by {
+        println!(
+            "Compiled by rfq-compiler {} ({}{}{})",
+            stamp.compiler_version,
+            stamp.method,
+            stamp.model.as_ref().map(|m| format!(", {}", m)).unwrap_or_default(),
+            stamp
+                .prompt_hash
+                .as_ref()
+                .map(|h| format!(", prompt {}", h))
+                .unwrap_or_default()
+        );
+    }
+    for check in &report {
+        let outcome = match check.outcome {
+            Outcome::Pass => "PASS",
+            Outcome::Fail => "FAIL",
+            Outcome::Skip => "SKIP",
+        };
+        println!("  {}  {:<16} {}", outcome, check.name, check.detail);
+    }
+
+    let count = |outcome| report.iter().filter(|c| c.outcome == outcome).count();
+    let failed = count(Outcome::Fail);
+    println!(
+        "Result: {} ({} passed, {} failed, {} skipped)",
+        if failed == 0 { "PASS" } else { "FAIL" },
+        count(Outcome::Pass),
+        failed,
+        count(Outcome::Skip)
+    );
+    Ok(if failed == 0 {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    })
+}
+
+fn read_json<T: serde::de::DeserializeOwned>(path: &Path) -> Result<T> {
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    serde_json::from_str(&text).with_context(|| format!("Invalid {}", path.display()))
+}
@@ -165,7 +165,8 @@ FILL_RESPONSE=$(curl -s -X POST "$BASE_URL/quotes/$QUOTE_ID/fill" \
         \"taker_shard\": $TAKER_SHARD,
         \"size\": 1.0,
         \"price\": 1950.5,
-        \"feed_evidence\": $FEED_EVIDENCE
+        \"feed_evidence\": $FEED_EVIDENCE,
+        \"taker_nonce\": $(date +%s)
     }")
 
 echo "Fill response:"
@@ -38,6 +38,14 @@ export function useQuotes() {
       headers: { "Content-Type": "application/json" },
       body: JSON.stringify(request),
     });
+    if (res.status === 422) {
+      const { error } = await res.json();
+      throw new Error(
+        error.questions
+          ? `${error.message}:\n- ${error.questions.join("\n- ")}`
+          : error.message,
+      );
+    }
     if (!res.ok) {
       const text = await res.text();
       throw new Error(text || "Failed to create quote");
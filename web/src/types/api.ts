@@ -2,18 +2,53 @@
 
 export type Direction = "buy" | "sell";
 
-export type QuoteStatus = "active" | "filled" | "expired" | "cancelled";
+export type QuoteStatus =
+  | "pending_approval"
+  | "scheduled"
+  | "pending"
+  | "active"
+  | "filled"
+  | "expired"
+  | "cancelled"
+  | "suspended"; // held by its divergence guard
 
 // Local Law constraints from the backend
 export interface LocalLaw {
   max_debit: number;
   expiry_timestamp: number;
+  valid_from_timestamp?: number;
+  trading_window?: string; // e.g. "14:00-16:00 UTC"
   allowed_sources: string[];
   max_staleness_secs: number;
+  source_staleness_secs?: Record<string, number>; // per-source overrides
   quorum_count: number;
   quorum_tolerance_percent: number;
+  quorum_policy?: string; // e.g. "median", "trimmed 1"; absent = spread
   require_atomic_dvp: boolean;
   no_side_payments: boolean;
+  fee_recipient?: string; // owner ID (hex) of the one allowed fee leg
+  max_fee_amount?: number; // plancks
+  max_notional_per_taker?: number; // plancks of the currency
+  min_fill_size?: number; // plancks
+  allow_partial_fills: boolean;
+  settlement_window_secs?: number;
+  max_price_deviation_percent?: number;
+  max_fee_percent?: number;
+  compiled_by?: CompilerStamp;
+}
+
+// Which compiler produced a quote's guardrails
+export interface CompilerStamp {
+  compiler_version: string;
+  method: "grammar" | "dsl" | "cache" | "llm";
+  model: string | null; // "provider/model"
+  prompt_hash: string | null; // SHA-256 of the prompt template
+}
+
+// Further asset of a basket quote
+export interface QuoteLeg {
+  asset: string;
+  size: number;
 }
 
 // Quote as returned by GET /quotes and GET /quotes/:id
@@ -23,14 +58,35 @@ export interface Quote {
   status: QuoteStatus;
   asset: string;
   direction: Direction;
-  size: number;
+  size: number; // For icebergs, the size on display; for partially filled quotes, what is left
+  iceberg: boolean; // More size hidden behind `size`, refilled after each fill
+  legs?: QuoteLeg[]; // Further assets traded as a package, at their full sizes
   price_limit: number | null;
   currency: string;
   expires_at: number; // Unix timestamp
   created_at: number; // Unix timestamp
+  activates_at?: number; // Unix timestamp, for scheduled quotes
+  low_confidence_fields?: Record<string, number>; // Need approval when pending_approval
   maker_owner_id: string;
   maker_shard: number;
   local_law: LocalLaw;
+  divergence_guard?: DivergenceGuard;
+  maker_reputation?: MakerReputation; // On GET /quotes and GET /quotes/:id
+}
+
+// How reliably a maker settles and honours its quotes
+export interface MakerReputation {
+  score: number | null; // 0 to 1, null until the maker has a track record
+  settlement_failure_rate: number | null;
+  amendment_after_fill_rate: number;
+  settled_fills: number;
+  quotes: number;
+}
+
+// Cancels or suspends a quote when the market moves away from its reference price
+export interface DivergenceGuard {
+  max_move_percent: number;
+  action?: "cancel" | "suspend"; // default cancel
 }
 
 // Request to create a new quote
@@ -38,23 +94,121 @@ export interface CreateQuoteRequest {
   text: string;
   maker_owner_id: string;
   maker_shard: number;
+  dsl?: boolean;
+  activate_at?: number; // Unix timestamp; post the quote later
+  allow_duplicate?: boolean;
+  bypass_cache?: boolean;
+  accept_low_confidence?: boolean;
+  divergence_guard?: DivergenceGuard;
+  allowed_fee?: AllowedFee;
+  extensions?: Extensions;
+}
+
+// A fee leg the settlement may pay besides the DvP legs
+export interface AllowedFee {
+  recipient: number[]; // owner ID bytes
+  max_amount: number; // plancks
 }
 
 // Response from POST /quotes
 export interface CreateQuoteResponse extends Quote {
-  constraints_summary: string;
+  explanation: ConstraintExplanation;
   message: string;
+  warnings?: string[];
+  lint_warnings?: LintWarning[]; // most risky first
+}
+
+export type LintRisk = "low" | "medium" | "high";
+
+export interface LintWarning {
+  code: string;
+  risk: LintRisk;
+  field: string;
+  message: string;
+}
+
+// Response from POST /quotes/lint
+export interface LintResponse {
+  asset: string;
+  direction: Direction;
+  size: number;
+  price_limit: number | null;
+  currency: string;
+  local_law: LocalLaw;
+  explanation: ConstraintExplanation;
+  preset: string | null;
+  low_confidence_fields: Record<string, number>;
+  warnings: LintWarning[]; // most risky first
+  fields: FieldSource[];
+}
+
+// A quote drafting session (POST /quotes/drafts, POST /quotes/drafts/:id/messages)
+export interface DraftResponse {
+  draft_id: string;
+  messages: string[];
+  text: string; // post with POST /quotes once ready
+  terms: Record<string, unknown> | null; // compiled terms so far
+  questions: string[];
+  ready: boolean;
+  quote?: LintResponse;
+  created_at: number;
+  updated_at: number;
+}
+
+export type FieldOrigin = "text" | "preset" | "default" | "inferred" | "request" | "amendment";
+
+// An enforced field traced back to the quote text
+export interface FieldSource {
+  field: string;
+  value: string;
+  origin: FieldOrigin;
+  phrase?: string;
+  span?: [number, number]; // byte range in the text
+}
+
+// An enforced constraint in a sentence, and where its value came from
+export interface ExplainedConstraint {
+  field: string;
+  value: string;
+  sentence: string;
+  origin: FieldOrigin;
+  source: string; // e.g. "from text: 'expires in 10 minutes'", "default"
+  phrase?: string; // quote phrase or amendment text
+}
+
+export interface ConstraintExplanation {
+  constraints: ExplainedConstraint[];
 }
 
 // Feed evidence for fill requests
 export interface FeedEvidence {
   source: string;
   asset: string;
-  price: number;
+  price: number; // fixed point, 9 decimals
   timestamp: number;
   signature: string;
+  provenance?: EvidenceProvenance | null;
 }
 
+// How feed evidence was retrieved (absent for taker-assembled evidence)
+export interface EvidenceProvenance {
+  response_hash: string;
+  url: string;
+  http_status: number;
+  latency_ms: number;
+  retrieved_at: number;
+}
+
+// Deployment-specific rule data, e.g. { "desk": { "text": "emea" } }
+export type ExtensionValue =
+  | { bool: boolean }
+  | { int: number }
+  | { text: string }
+  | { bytes: number[] }
+  | { list: ExtensionValue[] }
+  | { map: Record<string, ExtensionValue> };
+export type Extensions = Record<string, ExtensionValue>;
+
 // Request to fill a quote
 export interface FillRequest {
   taker_owner_id: string;
@@ -62,6 +216,10 @@ export interface FillRequest {
   size: number;
   price: number;
   feed_evidence: FeedEvidence[];
+  taker_nonce: number;
+  taker_signature?: string; // hex ed25519 signature of the rfq-fill message
+  extensions?: Extensions;
+  max_wait_ms?: number; // Answer as pending if settlement takes longer
 }
 
 // Settlement details in fill response
@@ -72,6 +230,17 @@ export interface Settlement {
   taker_credit: number;
   asset: string;
   currency: string;
+  pricing?: PricingBreakdown;
+}
+
+// How a fill's currency amount was derived from its price
+export interface PricingBreakdown {
+  price: number;
+  feed_median: number | null;
+  spread_to_median_bps: number | null;
+  fee: number;
+  exact_amount: number; // price * size in currency units, before rounding
+  rounding_adjustment: number;
 }
 
 // Receipt in fill response
@@ -86,10 +255,12 @@ export interface FillResponseReceipt {
   settlement: Settlement;
 }
 
+export type ProofStatus = "verified" | "pending" | "timed_out" | "failed";
+
 // Proof info in fill response
 export interface Proof {
   sdl_hash: string;
-  status: string;
+  status: ProofStatus;
 }
 
 // Response from POST /quotes/:id/fill
@@ -100,6 +271,14 @@ export interface FillResponse {
   message: string;
   receipt: FillResponseReceipt;
   proof: Proof;
+  pending?: PendingSettlement;
+}
+
+// Settlement still running when the fill was answered
+export interface PendingSettlement {
+  fill_id: string;
+  sdl_hash: string;
+  receipts_url: string;
 }
 
 // Receipt as returned by GET /quotes/:id/receipts
@@ -118,4 +297,72 @@ export interface FillReceipt {
   error_code?: string;
   error_message?: string;
   settlement?: Settlement;
+  proof_status?: ProofStatus;
+}
+
+export type MarketStatus = "enabled" | "halted";
+
+// Market as returned by GET /markets
+export interface Market {
+  id: string; // "<asset>-<currency>"
+  asset: string;
+  currency: string;
+  asset_decimals: number;
+  currency_decimals: number;
+  tick_size: number | null;
+  lot_size: number | null;
+  default_sources: string[];
+  status: MarketStatus;
+  halt_reason?: string;
+  halted_at?: string;
+}
+
+export type AuctionStatus = "open" | "revealed" | "awarded";
+
+export interface RankedResponse {
+  rank: number; // 1 = best
+  quote_id: string;
+  maker_owner_id: string;
+  price: number;
+  maker_reputation: number | null; // Breaks price ties
+}
+
+export interface RegretReceipt {
+  auction_id: string;
+  quote_id: string;
+  maker_owner_id: string;
+  rank: number;
+  price: number;
+  winning_price: number;
+  issued_at: string;
+}
+
+// Auction as returned by GET /auctions/:id
+export interface Auction {
+  id: string;
+  status: AuctionStatus;
+  taker_owner_id: string;
+  side: Direction; // the taker's side
+  asset: string;
+  currency: string;
+  size: number;
+  limit_price: number | null; // worst price the taker accepts
+  sources: string[]; // feeds responses must price against (empty = any)
+  min_sources: number | null;
+  opened_at: string;
+  closes_at: string;
+  response_count: number;
+  ranking: RankedResponse[] | null; // null while responses are sealed
+  award: {
+    winner: string;
+    winning_price: number;
+    awarded_at: string;
+    regrets: RegretReceipt[];
+  } | null;
+}
+
+// Response from POST /auctions/:id/accept
+export interface AcceptAuctionResponse {
+  fill: FillResponse;
+  auction: Auction;
 }